@@ -7,5 +7,13 @@ async fn main() -> anyhow::Result<()> {
         observability::init_fmt(observability::Output::Log).ok();
     }
     let opt = hc::Opt::from_args();
-    opt.run().await
+    if let Err(e) = opt.run().await {
+        // A plugin reports its own errors, so only pass on its exit code.
+        if let Some(failed) = e.downcast_ref::<hc::external_subcommands::ExternalSubcommandFailed>()
+        {
+            std::process::exit(failed.code);
+        }
+        return Err(e);
+    }
+    Ok(())
 }
@@ -0,0 +1,108 @@
+//! Run `hc-<name>` executables from the `PATH` as `hc <name>` subcommands.
+
+use anyhow::anyhow;
+use holochain_cli_sandbox as hc_sandbox;
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Prefix of the executables that are run as `hc` subcommands.
+pub const PLUGIN_PREFIX: &str = "hc-";
+
+/// Path of the `.hc` file listing the sandboxes in the current directory.
+pub const HC_SANDBOX_FILE_ENV: &str = "HC_SANDBOX_FILE";
+
+/// Admin port of the first running sandbox.
+pub const HC_ADMIN_PORT_ENV: &str = "HC_ADMIN_PORT";
+
+/// Comma separated admin ports of the sandboxes, in the order of the `.hc`
+/// file. The port of a sandbox that isn't running is left empty.
+pub const HC_ADMIN_PORTS_ENV: &str = "HC_ADMIN_PORTS";
+
+/// The `hc-<name>` executable ran but exited unsuccessfully.
+/// The `hc` binary exits with the same code.
+#[derive(Debug)]
+pub struct ExternalSubcommandFailed {
+    /// The path of the executable.
+    pub exe: PathBuf,
+    /// Its exit code, or 1 if it was stopped by a signal.
+    pub code: i32,
+}
+
+impl std::fmt::Display for ExternalSubcommandFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} exited with code {}", self.exe.display(), self.code)
+    }
+}
+
+impl std::error::Error for ExternalSubcommandFailed {}
+
+/// Run the `hc-<name>` executable with the remaining arguments.
+/// Returns [`ExternalSubcommandFailed`] if it fails.
+pub fn run(args: Vec<OsString>) -> anyhow::Result<()> {
+    let mut args = args.into_iter();
+    let name = args
+        .next()
+        .ok_or_else(|| anyhow!("Missing external subcommand"))?;
+    let name = name.to_string_lossy();
+    let exe = find_plugin(&name).ok_or_else(|| {
+        anyhow!(
+            "no such subcommand: `{}`. No `{}{}` found on the PATH",
+            name,
+            PLUGIN_PREFIX,
+            name
+        )
+    })?;
+
+    let mut cmd = std::process::Command::new(&exe);
+    cmd.args(args);
+    let hc_dir = std::env::current_dir()?;
+    let hc_file = hc_dir.join(".hc");
+    if hc_file.exists() {
+        cmd.env(HC_SANDBOX_FILE_ENV, &hc_file);
+        let ports = hc_sandbox::save::load_ports(hc_dir)?;
+        if let Some(port) = ports.iter().flatten().next() {
+            // Keep a place for every sandbox so the positions match `hc list`
+            let ports: Vec<_> = ports
+                .iter()
+                .map(|port| port.map(|port| port.to_string()).unwrap_or_default())
+                .collect();
+            cmd.env(HC_ADMIN_PORT_ENV, port.to_string());
+            cmd.env(HC_ADMIN_PORTS_ENV, ports.join(","));
+        }
+    }
+
+    let status = cmd
+        .status()
+        .map_err(|e| anyhow!("Failed to run {}: {}", exe.display(), e))?;
+    if !status.success() {
+        return Err(ExternalSubcommandFailed {
+            exe,
+            code: status.code().unwrap_or(1),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Find the `hc-<name>` executable on the `PATH`.
+fn find_plugin(name: &str) -> Option<PathBuf> {
+    let file_name = format!("{}{}{}", PLUGIN_PREFIX, name, std::env::consts::EXE_SUFFIX);
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
@@ -99,6 +99,15 @@
 //! # Or clean all
 //! hc clean
 //! ```
+//! #### Plugins
+//! Any other subcommand runs the matching `hc-<name>` executable
+//! from the `PATH` with the remaining arguments, so `hc foo --bar`
+//! runs `hc-foo --bar`. When there is a `.hc` file in the current
+//! directory, plugins are given:
+//! - `HC_SANDBOX_FILE`: The path of the `.hc` file.
+//! - `HC_ADMIN_PORT`: The admin port of the first running sandbox.
+//! - `HC_ADMIN_PORTS`: The comma separated admin ports of the sandboxes, in the
+//!   order shown by `hc list`, with an empty place for each one that isn't running.
 //! ## Library
 //! This crate can also be used as a library so you can create more
 //! complex setups / admin calls.
@@ -113,6 +122,8 @@ pub use holochain_cli_bundle as hc_bundle;
 use holochain_cli_sandbox as hc_sandbox;
 use structopt::StructOpt;
 
+pub mod external_subcommands;
+
 /// Holochain CLI
 ///
 /// Work with DNA, hApp and web-hApp bundle files, set up sandbox environments for testing
@@ -130,6 +141,9 @@ pub enum Opt {
     WebApp(hc_bundle::HcWebAppBundle),
     /// Work with sandboxed environments for testing and development
     Sandbox(hc_sandbox::HcSandbox),
+    /// Run an `hc-<name>` executable from the `PATH`
+    #[structopt(external_subcommand)]
+    External(Vec<std::ffi::OsString>),
 }
 
 impl Opt {
@@ -140,6 +154,7 @@ impl Opt {
             Self::App(cmd) => cmd.run().await?,
             Self::WebApp(cmd) => cmd.run().await?,
             Self::Sandbox(cmd) => cmd.run().await?,
+            Self::External(args) => external_subcommands::run(args)?,
         }
         Ok(())
     }
@@ -0,0 +1,96 @@
+#![cfg(unix)]
+
+use assert_cmd::prelude::*;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Command;
+
+/// Put an `hc-foo` script in `bin` that prints the plugin env vars and
+/// its arguments, and fails with code 3 when its first argument is `fail`.
+fn write_plugin(bin: &Path) {
+    let script = bin.join("hc-foo");
+    std::fs::write(
+        &script,
+        r#"#!/bin/sh
+[ "$1" = "fail" ] && exit 3
+echo "$HC_SANDBOX_FILE|$HC_ADMIN_PORT|$HC_ADMIN_PORTS|$*"
+"#,
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// Write a `.hc` file listing three sandboxes where the first and the
+/// last are running.
+fn write_sandboxes(hc_dir: &Path) {
+    let mut hc_file = String::new();
+    for i in 0..3 {
+        let sandbox = hc_dir.join(format!("sandbox_{}", i));
+        std::fs::create_dir(&sandbox).unwrap();
+        std::fs::write(sandbox.join("conductor-config.yaml"), "").unwrap();
+        hc_file.push_str(&format!("{}\n", sandbox.display()));
+    }
+    std::fs::write(hc_dir.join(".hc"), hc_file).unwrap();
+    std::fs::write(hc_dir.join(".hc_live_0"), "1111\n").unwrap();
+    std::fs::write(hc_dir.join(".hc_live_2"), "3333\n").unwrap();
+}
+
+fn hc(bin: &Path, hc_dir: &Path) -> Command {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let path = std::env::join_paths(
+        std::iter::once(bin.to_path_buf()).chain(std::env::split_paths(&path)),
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("hc").unwrap();
+    cmd.current_dir(hc_dir)
+        .env("PATH", path)
+        .env_remove("HC_SANDBOX_FILE")
+        .env_remove("HC_ADMIN_PORT")
+        .env_remove("HC_ADMIN_PORTS");
+    cmd
+}
+
+#[test]
+fn runs_plugin_with_sandbox_env() {
+    let bin = tempfile::tempdir().unwrap();
+    let hc_dir = tempfile::tempdir().unwrap();
+    write_plugin(bin.path());
+    write_sandboxes(hc_dir.path());
+
+    let output = hc(bin.path(), hc_dir.path())
+        .args(&["foo", "--bar", "baz"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // - The ports keep the positions of the sandboxes in the `.hc` file
+    let expected = format!(
+        "{}|1111|1111,,3333|--bar baz\n",
+        hc_dir.path().canonicalize().unwrap().join(".hc").display()
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn plugin_without_sandboxes_gets_no_env() {
+    let bin = tempfile::tempdir().unwrap();
+    let hc_dir = tempfile::tempdir().unwrap();
+    write_plugin(bin.path());
+
+    let output = hc(bin.path(), hc_dir.path()).arg("foo").output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "|||\n");
+}
+
+#[test]
+fn hc_exits_with_plugin_exit_code() {
+    let bin = tempfile::tempdir().unwrap();
+    let hc_dir = tempfile::tempdir().unwrap();
+    write_plugin(bin.path());
+
+    let status = hc(bin.path(), hc_dir.path())
+        .args(&["foo", "fail"])
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(3));
+}
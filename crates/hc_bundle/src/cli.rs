@@ -51,6 +51,14 @@ pub enum HcDnaBundle {
         /// provided working directory.
         #[structopt(short = "o", long)]
         output: Option<PathBuf>,
+
+        /// Fail unless every resource in the manifest is bundled, so that the
+        /// bundle only depends on the contents of the working directory.
+        ///
+        /// Packing identical files always gives byte-identical bundles, which
+        /// lets anyone check a published bundle by packing it from source.
+        #[structopt(long)]
+        reproducible: bool,
     },
 
     /// Unpack parts of the `.dna` bundle file into a specific directory.
@@ -77,6 +85,37 @@ pub enum HcDnaBundle {
         #[structopt(short = "f", long)]
         force: bool,
     },
+
+    /// Print the manifest of a `.dna` bundle, the sizes of its resources,
+    /// its DNA hash with and without its uid and properties, and the wasm
+    /// hash, read-only functions and entry defs of every zome.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc dna info ./some/dir/my-dna.dna
+    Info {
+        /// The path to the bundle to inspect
+        path: std::path::PathBuf,
+    },
+
+    /// Check that every zome in a `.dna` bundle exports what the conductor
+    /// needs to call it, and that its callbacks and zome functions have the
+    /// signatures the conductor calls them with.
+    ///
+    /// Exits with an error if any zome has a problem.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc dna verify ./some/dir/my-dna.dna --require entry_defs
+    Verify {
+        /// The path to the bundle to verify
+        path: std::path::PathBuf,
+
+        /// A callback that every zome must export, e.g. `validate`.
+        /// May be given more than once.
+        #[structopt(short = "r", long = "require")]
+        required_callbacks: Vec<String>,
+    },
 }
 
 /// Work with Holochain hApp bundles
@@ -109,6 +148,14 @@ pub enum HcAppBundle {
         /// provided working directory.
         #[structopt(short = "o", long)]
         output: Option<PathBuf>,
+
+        /// Fail unless every resource in the manifest is bundled, so that the
+        /// bundle only depends on the contents of the working directory.
+        ///
+        /// Packing identical files always gives byte-identical bundles, which
+        /// lets anyone check a published bundle by packing it from source.
+        #[structopt(long)]
+        reproducible: bool,
     },
 
     /// Unpack parts of the `.happ` bundle file into a specific directory.
@@ -135,6 +182,32 @@ pub enum HcAppBundle {
         #[structopt(short = "f", long)]
         force: bool,
     },
+
+    /// Print the manifest of a `.happ` bundle, the sizes of its resources
+    /// and, for every role, what `hc dna info` prints about its DNA along with
+    /// the DNA hash once the uid and properties of the role are applied.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc app info ./some/dir/my-app.happ
+    Info {
+        /// The path to the bundle to inspect
+        path: std::path::PathBuf,
+    },
+
+    /// Explain why two `.happ` bundles would install cells with different
+    /// DNA hashes, role by role.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc app diff ./old/my-app.happ ./new/my-app.happ
+    Diff {
+        /// The path to the first bundle
+        a: std::path::PathBuf,
+
+        /// The path to the second bundle
+        b: std::path::PathBuf,
+    },
 }
 
 /// Work with Holochain Web-hApp bundles
@@ -167,6 +240,14 @@ pub enum HcWebAppBundle {
         /// provided working directory.
         #[structopt(short = "o", long)]
         output: Option<PathBuf>,
+
+        /// Fail unless every resource in the manifest is bundled, so that the
+        /// bundle only depends on the contents of the working directory.
+        ///
+        /// Packing identical files always gives byte-identical bundles, which
+        /// lets anyone check a published bundle by packing it from source.
+        #[structopt(long)]
+        reproducible: bool,
     },
 
     /// Unpack parts of the `.webhapp` bundle file into a specific directory.
@@ -202,10 +283,14 @@ impl HcDnaBundle {
             Self::Init { path } => {
                 crate::init::init_dna(path).await?;
             }
-            Self::Pack { path, output } => {
+            Self::Pack {
+                path,
+                output,
+                reproducible,
+            } => {
                 let name = get_dna_name(&path).await?;
                 let (bundle_path, _) =
-                    crate::packing::pack::<DnaManifest>(&path, output, name).await?;
+                    crate::packing::pack::<DnaManifest>(&path, output, name, reproducible).await?;
                 println!("Wrote bundle {}", bundle_path.to_string_lossy());
             }
             Self::Unpack {
@@ -218,6 +303,35 @@ impl HcDnaBundle {
                         .await?;
                 println!("Unpacked to directory {}", dir_path.to_string_lossy());
             }
+            Self::Info { path } => {
+                let info = crate::inspect::dna_info(&path).await?;
+                print!("{}", serde_yaml::to_string(&info)?);
+            }
+            Self::Verify {
+                path,
+                required_callbacks,
+            } => {
+                let zomes = crate::inspect::verify_dna(&path, &required_callbacks).await?;
+                let mut problem_count = 0;
+                for (zome_name, problems) in zomes {
+                    if problems.is_empty() {
+                        println!("{}: ok", zome_name);
+                    } else {
+                        println!("{}:", zome_name);
+                        for problem in problems.iter() {
+                            println!("  {}", problem);
+                        }
+                        problem_count += problems.len();
+                    }
+                }
+                if problem_count > 0 {
+                    anyhow::bail!(
+                        "Found {} problem(s) in {}",
+                        problem_count,
+                        path.to_string_lossy()
+                    );
+                }
+            }
         }
         Ok(())
     }
@@ -230,10 +344,14 @@ impl HcAppBundle {
             Self::Init { path } => {
                 crate::init::init_app(path).await?;
             }
-            Self::Pack { path, output } => {
+            Self::Pack {
+                path,
+                output,
+                reproducible,
+            } => {
                 let name = get_app_name(&path).await?;
                 let (bundle_path, _) =
-                    crate::packing::pack::<AppManifest>(&path, output, name).await?;
+                    crate::packing::pack::<AppManifest>(&path, output, name, reproducible).await?;
                 println!("Wrote bundle {}", bundle_path.to_string_lossy());
             }
             Self::Unpack {
@@ -246,6 +364,19 @@ impl HcAppBundle {
                         .await?;
                 println!("Unpacked to directory {}", dir_path.to_string_lossy());
             }
+            Self::Info { path } => {
+                let info = crate::inspect::app_info(&path).await?;
+                print!("{}", serde_yaml::to_string(&info)?);
+            }
+            Self::Diff { a, b } => {
+                let differences = crate::inspect::app_diff(&a, &b).await?;
+                if differences.is_empty() {
+                    println!("Both apps install cells with the same DNA hashes");
+                }
+                for difference in differences {
+                    println!("{}", difference);
+                }
+            }
         }
         Ok(())
     }
@@ -258,10 +389,15 @@ impl HcWebAppBundle {
             Self::Init { path } => {
                 crate::init::init_web_app(path).await?;
             }
-            Self::Pack { path, output } => {
+            Self::Pack {
+                path,
+                output,
+                reproducible,
+            } => {
                 let name = get_web_app_name(&path).await?;
                 let (bundle_path, _) =
-                    crate::packing::pack::<WebAppManifest>(&path, output, name).await?;
+                    crate::packing::pack::<WebAppManifest>(&path, output, name, reproducible)
+                        .await?;
                 println!("Wrote bundle {}", bundle_path.to_string_lossy());
             }
             Self::Unpack {
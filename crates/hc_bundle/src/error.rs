@@ -35,6 +35,12 @@ pub enum HcBundleError {
 
     #[error("This file should have a '.{0}' extension: {1}")]
     FileExtensionMissing(&'static str, PathBuf),
+
+    /// A reproducible bundle must not depend on files outside of it
+    #[error(
+        "A reproducible bundle must include all of its resources, but these are not bundled: {0:?}"
+    )]
+    UnbundledResources(Vec<mr_bundle::Location>),
 }
 
 /// HcBundle Result type.
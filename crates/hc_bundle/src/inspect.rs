@@ -0,0 +1,483 @@
+#![forbid(missing_docs)]
+
+//! Inspect the contents of DNA and hApp bundles without installing them.
+
+use crate::error::HcBundleResult;
+use holochain_types::dna::wasm_metering;
+use holochain_types::prelude::*;
+use holochain_wasmer_host::prelude::*;
+use mr_bundle::Bundle;
+use mr_bundle::ResourceMap;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use wasmer::ExternType;
+use wasmer::FunctionType;
+use wasmer::Type;
+
+/// The exports that every zome needs so the host can pass data in and out.
+const REQUIRED_EXPORTS: [&str; 3] = ["memory", "__allocate", "__deallocate"];
+
+/// The callbacks the host looks for, by the first component of their name.
+/// e.g. `validate_create_entry` is a `validate` callback.
+const KNOWN_CALLBACKS: [&str; 7] = [
+    "init",
+    "entry_defs",
+    "genesis_self_check",
+    "validate",
+    "validation_package",
+    "post_commit",
+    "migrate_agent",
+];
+
+/// What `hc dna info` prints about a DNA bundle.
+#[derive(Debug, serde::Serialize)]
+pub struct DnaInfo {
+    /// The manifest of the bundle.
+    pub manifest: DnaManifest,
+    /// The size in bytes of every resource in the bundle.
+    pub resources: BTreeMap<PathBuf, usize>,
+    /// The hash of the DNA as bundled.
+    pub dna_hash: DnaHashB64,
+    /// The hash of the DNA with an empty uid and no properties.
+    /// Bundles that only differ in their uid or properties share this hash.
+    pub dna_hash_without_uid_or_properties: DnaHashB64,
+    /// The zomes of the DNA, in order.
+    pub zomes: Vec<ZomeSummary>,
+}
+
+/// What `hc dna info` prints about a single zome.
+#[derive(Debug, serde::Serialize)]
+pub struct ZomeSummary {
+    /// The name of the zome.
+    pub name: ZomeName,
+    /// The hash of the zome's wasm.
+    pub wasm_hash: WasmHashB64,
+    /// The size in bytes of the zome's wasm.
+    pub wasm_size: usize,
+    /// The functions marked as read-only.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub read_only_fns: BTreeSet<FunctionName>,
+    /// The entry types defined by the zome's `entry_defs` callback.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_defs: Option<EntryDefs>,
+    /// Why the entry defs could not be read from the wasm.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_defs_error: Option<String>,
+}
+
+/// What `hc app info` prints about a hApp bundle.
+#[derive(Debug, serde::Serialize)]
+pub struct AppInfo {
+    /// The manifest of the bundle.
+    pub manifest: AppManifest,
+    /// The size in bytes of every resource in the bundle.
+    pub resources: BTreeMap<PathBuf, usize>,
+    /// The roles of the app, in order.
+    pub roles: Vec<RoleSummary>,
+}
+
+/// What `hc app info` prints about a single role.
+#[derive(Debug, serde::Serialize)]
+pub struct RoleSummary {
+    /// The id of the role.
+    pub id: AppRoleId,
+    /// The hash of the DNA once the uid and properties of the role are applied.
+    /// This is the hash of the cell the role provisions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installed_dna_hash: Option<DnaHashB64>,
+    /// The DNA filling the role, if it can be found from the bundle.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dna: Option<DnaInfo>,
+}
+
+/// Read a DNA bundle and describe its contents.
+pub async fn dna_info(path: &Path) -> HcBundleResult<DnaInfo> {
+    let bundle = DnaBundle::read_from_file(path).await?;
+    let (info, _) = inspect_dna(bundle, None, None).await?;
+    Ok(info)
+}
+
+/// Read a hApp bundle and describe its contents, including every DNA
+/// bundled inside it.
+pub async fn app_info(path: &Path) -> HcBundleResult<AppInfo> {
+    let bundle: Bundle<AppManifest> = Bundle::read_from_file(path).await?;
+    let mut roles = Vec::new();
+    for role in bundle.manifest().app_roles() {
+        let (installed_dna_hash, dna) = match resolve_role_dna(&bundle, &role).await? {
+            Some((info, dna_def)) => (Some(DnaHash::with_data_sync(&dna_def).into()), Some(info)),
+            None => (None, None),
+        };
+        roles.push(RoleSummary {
+            id: role.id,
+            installed_dna_hash,
+            dna,
+        });
+    }
+    Ok(AppInfo {
+        manifest: bundle.manifest().clone(),
+        resources: resource_sizes(bundle.bundled_resources()),
+        roles,
+    })
+}
+
+/// Compare the DNAs that two hApp bundles would install for each role,
+/// returning every difference that changes a DNA hash.
+/// No differences means both bundles install exactly the same cells.
+pub async fn app_diff(a: &Path, b: &Path) -> HcBundleResult<Vec<String>> {
+    let a = installed_dna_defs(a).await?;
+    let b = installed_dna_defs(b).await?;
+    let mut differences = Vec::new();
+    let role_ids: BTreeSet<_> = a.keys().chain(b.keys()).collect();
+    for role_id in role_ids {
+        match (a.get(role_id), b.get(role_id)) {
+            (Some(_), None) => {
+                differences.push(format!("role `{}` is only in the first app", role_id))
+            }
+            (None, Some(_)) => {
+                differences.push(format!("role `{}` is only in the second app", role_id))
+            }
+            (Some(Some(a_def)), Some(Some(b_def))) => {
+                let a_hash = DnaHash::with_data_sync(a_def);
+                let b_hash = DnaHash::with_data_sync(b_def);
+                if a_hash != b_hash {
+                    differences.push(format!(
+                        "role `{}`: DNA hash {} vs {}",
+                        role_id,
+                        DnaHashB64::from(a_hash),
+                        DnaHashB64::from(b_hash)
+                    ));
+                    differences.extend(
+                        dna_def_differences(a_def, b_def)
+                            .into_iter()
+                            .map(|d| format!("  {}", d)),
+                    );
+                }
+            }
+            (Some(a_def), Some(b_def)) => {
+                if a_def.is_some() != b_def.is_some() {
+                    differences.push(format!(
+                        "role `{}`: only one app provides a DNA for this role",
+                        role_id
+                    ));
+                }
+            }
+            (None, None) => unreachable!("role ids come from one of the apps"),
+        }
+    }
+    Ok(differences)
+}
+
+/// Check that every zome of a DNA bundle exports what the host needs to call
+/// it, and that every callback and zome function has the signature the host
+/// calls it with.
+///
+/// Every callback in `required_callbacks` must be exported by every zome.
+/// Returns the problems found in each zome, empty if there are none.
+pub async fn verify_dna(
+    path: &Path,
+    required_callbacks: &[String],
+) -> HcBundleResult<Vec<(ZomeName, Vec<String>)>> {
+    let bundle = DnaBundle::read_from_file(path).await?;
+    let (dna_file, _) = bundle.into_dna_file(None, None).await?;
+    let mut problems = Vec::new();
+    for (zome_name, _) in dna_file.dna_def().zomes.iter() {
+        let wasm = dna_file.get_wasm_for_zome(zome_name)?;
+        problems.push((
+            zome_name.clone(),
+            verify_zome(&**wasm.code, required_callbacks),
+        ));
+    }
+    Ok(problems)
+}
+
+async fn inspect_dna(
+    bundle: DnaBundle,
+    uid: Option<Uid>,
+    properties: Option<YamlProperties>,
+) -> HcBundleResult<(DnaInfo, DnaDef)> {
+    let manifest = bundle.manifest().clone();
+    let resources = resource_sizes(bundle.bundled_resources());
+    let (dna_file, original_hash) = bundle.into_dna_file(uid, properties).await?;
+
+    let mut bare_dna_def = dna_file.dna_def().clone();
+    bare_dna_def.uid = String::new();
+    bare_dna_def.properties = SerializedBytes::try_from(YamlProperties::empty())?;
+
+    let mut zomes = Vec::new();
+    for (zome_name, zome_def) in dna_file.dna_def().zomes.iter() {
+        let wasm_zome = match zome_def {
+            ZomeDef::Wasm(wasm_zome) => wasm_zome,
+            // Bundles can only contain wasm zomes.
+            ZomeDef::Inline(_) => continue,
+        };
+        let wasm = dna_file.get_wasm_for_zome(zome_name)?;
+        let (entry_defs, entry_defs_error) = match read_entry_defs(&**wasm.code) {
+            Ok(entry_defs) => (entry_defs, None),
+            Err(e) => (None, Some(e)),
+        };
+        zomes.push(ZomeSummary {
+            name: zome_name.clone(),
+            wasm_hash: wasm_zome.wasm_hash.clone().into(),
+            wasm_size: wasm.code.len(),
+            read_only_fns: wasm_zome.read_only_fns.clone(),
+            entry_defs,
+            entry_defs_error,
+        });
+    }
+
+    let info = DnaInfo {
+        manifest,
+        resources,
+        dna_hash: original_hash.into(),
+        dna_hash_without_uid_or_properties: DnaHash::with_data_sync(&bare_dna_def).into(),
+        zomes,
+    };
+    Ok((info, dna_file.dna_def().clone()))
+}
+
+/// Read the DNA filling a role, with the uid and properties of the role
+/// applied, if the DNA is bundled in or next to the app.
+async fn resolve_role_dna(
+    bundle: &Bundle<AppManifest>,
+    role: &AppRoleManifest,
+) -> HcBundleResult<Option<(DnaInfo, DnaDef)>> {
+    match &role.dna.location {
+        Some(location) => {
+            let bytes = bundle.resolve(location).await?;
+            let dna_bundle = DnaBundle::decode(&bytes)?;
+            Ok(Some(
+                inspect_dna(
+                    dna_bundle,
+                    role.dna.uid.clone(),
+                    role.dna.properties.clone(),
+                )
+                .await?,
+            ))
+        }
+        None => Ok(None),
+    }
+}
+
+async fn installed_dna_defs(path: &Path) -> HcBundleResult<BTreeMap<AppRoleId, Option<DnaDef>>> {
+    let bundle: Bundle<AppManifest> = Bundle::read_from_file(path).await?;
+    let mut dna_defs = BTreeMap::new();
+    for role in bundle.manifest().app_roles() {
+        let dna_def = resolve_role_dna(&bundle, &role)
+            .await?
+            .map(|(_, dna_def)| dna_def);
+        dna_defs.insert(role.id, dna_def);
+    }
+    Ok(dna_defs)
+}
+
+/// Explain which parts of two DnaDefs differ.
+/// Every part of a DnaDef goes into its hash.
+fn dna_def_differences(a: &DnaDef, b: &DnaDef) -> Vec<String> {
+    let mut differences = Vec::new();
+    if a.name != b.name {
+        differences.push(format!("name: {:?} vs {:?}", a.name, b.name));
+    }
+    if a.uid != b.uid {
+        differences.push(format!("uid: {:?} vs {:?}", a.uid, b.uid));
+    }
+    if a.properties != b.properties {
+        differences.push(format!(
+            "properties: {} vs {}",
+            properties_to_string(&a.properties),
+            properties_to_string(&b.properties)
+        ));
+    }
+    if a.origin_time != b.origin_time {
+        differences.push(format!(
+            "origin_time: {} vs {}",
+            a.origin_time, b.origin_time
+        ));
+    }
+
+    let a_names: Vec<_> = a.zomes.iter().map(|(name, _)| name.to_string()).collect();
+    let b_names: Vec<_> = b.zomes.iter().map(|(name, _)| name.to_string()).collect();
+    if a_names != b_names {
+        differences.push(format!(
+            "zomes: [{}] vs [{}]",
+            a_names.join(", "),
+            b_names.join(", ")
+        ));
+    }
+    for (name, a_zome) in a.zomes.iter() {
+        let b_zome = match b.zomes.iter().find(|(b_name, _)| b_name == name) {
+            Some((_, b_zome)) => b_zome,
+            None => continue,
+        };
+        match (a_zome, b_zome) {
+            (ZomeDef::Wasm(a_zome), ZomeDef::Wasm(b_zome)) => {
+                if a_zome.wasm_hash != b_zome.wasm_hash {
+                    differences.push(format!(
+                        "zome `{}`: wasm hash {} vs {}",
+                        name,
+                        WasmHashB64::from(a_zome.wasm_hash.clone()),
+                        WasmHashB64::from(b_zome.wasm_hash.clone())
+                    ));
+                }
+                if a_zome.read_only_fns != b_zome.read_only_fns {
+                    differences.push(format!(
+                        "zome `{}`: read-only functions {:?} vs {:?}",
+                        name, a_zome.read_only_fns, b_zome.read_only_fns
+                    ));
+                }
+            }
+            (a_zome, b_zome) => {
+                if a_zome != b_zome {
+                    differences.push(format!("zome `{}`: definitions differ", name));
+                }
+            }
+        }
+    }
+    differences
+}
+
+fn properties_to_string(properties: &SerializedBytes) -> String {
+    match YamlProperties::try_from(properties.clone()) {
+        Ok(properties) => serde_yaml::to_string(&properties)
+            .map(|s| s.trim_start_matches("---").trim().replace('\n', ", "))
+            .unwrap_or_else(|e| e.to_string()),
+        Err(_) => format!("{} bytes of non-YAML data", properties.bytes().len()),
+    }
+}
+
+fn resource_sizes(resources: &ResourceMap) -> BTreeMap<PathBuf, usize> {
+    resources
+        .iter()
+        .map(|(path, bytes)| (path.clone(), bytes.len()))
+        .collect()
+}
+
+/// Run the `entry_defs` callback of a zome to find out its entry types.
+///
+/// Every host function the wasm imports is stubbed with one that traps,
+/// which is fine for the entry defs of any well-behaved zome.
+/// The callback gets the same default fuel and memory limits as it would in
+/// a conductor, so a zome that loops forever can't hang `hc dna info`.
+/// Returns `None` if the zome has no `entry_defs` callback.
+fn read_entry_defs(code: &[u8]) -> Result<Option<EntryDefs>, String> {
+    let store = wasm_metering::metered_store(wasm_metering::DEFAULT_WASM_MEMORY_PAGES);
+    let module = Module::new(&store, code).map_err(|e| e.to_string())?;
+    if !module.exports().any(|export| export.name() == "entry_defs") {
+        return Ok(None);
+    }
+
+    let mut namespaces: BTreeMap<String, Exports> = BTreeMap::new();
+    for import in module.imports() {
+        if let ExternType::Function(ty) = import.ty() {
+            let name = import.name().to_string();
+            let stub = Function::new(&store, ty.clone(), move |_| {
+                Err(RuntimeError::new(format!(
+                    "host function {} is not available outside a conductor",
+                    name
+                )))
+            });
+            namespaces
+                .entry(import.module().to_string())
+                .or_insert_with(Exports::new)
+                .insert(import.name(), stub);
+        }
+    }
+    let mut imports = ImportObject::new();
+    for (namespace, exports) in namespaces {
+        imports.register(namespace, exports);
+    }
+
+    let instance = Instance::new(&module, &imports).map_err(|e| e.to_string())?;
+    wasm_metering::set_fuel(&instance, wasm_metering::DEFAULT_WASM_FUEL);
+    let instance = Arc::new(Mutex::new(instance));
+    let result: Result<ExternIO, _> = holochain_wasmer_host::guest::call(
+        instance.clone(),
+        "entry_defs",
+        ExternIO::encode(()).map_err(|e| e.to_string())?,
+    );
+    let result = match result {
+        Ok(result) => result,
+        Err(_) if wasm_metering::remaining_fuel(&instance.lock()).is_none() => {
+            return Err("the `entry_defs` callback ran out of fuel".to_string())
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    match result.decode().map_err(|e| e.to_string())? {
+        EntryDefsCallbackResult::Defs(entry_defs) => Ok(Some(entry_defs)),
+    }
+}
+
+fn verify_zome(code: &[u8], required_callbacks: &[String]) -> Vec<String> {
+    let store = wasm_metering::metered_store(wasm_metering::DEFAULT_WASM_MEMORY_PAGES);
+    let module = match Module::new(&store, code) {
+        Ok(module) => module,
+        Err(e) => return vec![format!("the wasm does not compile: {}", e)],
+    };
+    let exports: BTreeMap<String, ExternType> = module
+        .exports()
+        .map(|export| (export.name().to_string(), export.ty().clone()))
+        .collect();
+
+    let mut problems = Vec::new();
+    for name in REQUIRED_EXPORTS {
+        if !exports.contains_key(name) {
+            problems.push(format!("missing the `{}` export", name));
+        }
+    }
+    for name in required_callbacks {
+        if !exports.contains_key(name) {
+            problems.push(format!("missing the `{}` callback", name));
+        }
+    }
+
+    let extern_fn = FunctionType::new([Type::I32, Type::I32], [Type::I64]);
+    for (name, ty) in exports.iter() {
+        let expected = match name.as_str() {
+            "memory" => {
+                if !matches!(ty, ExternType::Memory(_)) {
+                    problems.push("`memory` is not a memory".to_string());
+                }
+                continue;
+            }
+            "__allocate" => FunctionType::new([Type::I32], [Type::I32]),
+            "__deallocate" => FunctionType::new([Type::I32, Type::I32], Vec::<Type>::new()),
+            _ => match name.strip_prefix(READ_ONLY_FN_MARKER_PREFIX) {
+                Some(fn_name) => {
+                    if !exports.contains_key(fn_name) {
+                        problems.push(format!(
+                            "`{}` is marked read-only but is not exported",
+                            fn_name
+                        ));
+                    }
+                    FunctionType::new(Vec::<Type>::new(), Vec::<Type>::new())
+                }
+                None => extern_fn.clone(),
+            },
+        };
+        match ty {
+            ExternType::Function(ty) if *ty != expected => {
+                let kind = if is_callback(name) {
+                    "callback"
+                } else {
+                    "function"
+                };
+                problems.push(format!(
+                    "{} `{}` has the signature {} but the host calls it as {}",
+                    kind, name, ty, expected
+                ));
+            }
+            _ => (),
+        }
+    }
+    problems
+}
+
+/// Whether the host calls this export as a callback rather than as a zome
+/// function.
+fn is_callback(name: &str) -> bool {
+    KNOWN_CALLBACKS
+        .iter()
+        .any(|callback| name == *callback || name.starts_with(&format!("{}_", callback)))
+}
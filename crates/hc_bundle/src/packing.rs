@@ -47,14 +47,25 @@ fn bundle_path_to_dir(path: &Path, extension: &'static str) -> HcBundleResult<Pa
 
 /// Pack a directory containing a DNA manifest into a DnaBundle, returning
 /// the path to which the bundle file was written
+///
+/// Packing the same files always gives the same bundle bytes. If `reproducible`
+/// is set, packing also fails unless every resource in the manifest is bundled,
+/// so that the bundle doesn't depend on anything outside of the directory.
 pub async fn pack<M: Manifest>(
     dir_path: &std::path::Path,
     target_path: Option<PathBuf>,
     name: String,
+    reproducible: bool,
 ) -> HcBundleResult<(PathBuf, Bundle<M>)> {
     let dir_path = ffs::canonicalize(dir_path).await?;
     let manifest_path = dir_path.join(&M::path());
     let bundle: Bundle<M> = Bundle::pack_yaml(&manifest_path).await?;
+    if reproducible {
+        let unbundled = bundle.unbundled_locations();
+        if !unbundled.is_empty() {
+            return Err(HcBundleError::UnbundledResources(unbundled));
+        }
+    }
     let target_path = match target_path {
         Some(target_path) => {
             if target_path.is_dir() {
@@ -117,7 +128,7 @@ zomes:
         // in the parent directory
         std::fs::write(tmpdir.path().join("zome-3.wasm"), &[7, 8, 9]).unwrap();
 
-        let (bundle_path, bundle) = pack::<DnaManifest>(&dir, None, "test_dna".to_string())
+        let (bundle_path, bundle) = pack::<DnaManifest>(&dir, None, "test_dna".to_string(), false)
             .await
             .unwrap();
 
@@ -155,6 +166,7 @@ zomes:
             &dir,
             Some(dir.parent().unwrap().to_path_buf()),
             "test_dna".to_string(),
+            false,
         )
         .await
         .unwrap();
@@ -173,7 +185,82 @@ zomes:
         assert_eq!(dir.read_dir().unwrap().collect::<Vec<_>>().len(), 3);
 
         // Ensure that we get the same bundle after the roundtrip
-        let (_, bundle2) = pack(&dir, None, "test_dna".to_string()).await.unwrap();
+        let (_, bundle2) = pack(&dir, None, "test_dna".to_string(), false)
+            .await
+            .unwrap();
         assert_eq!(bundle, bundle2);
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reproducible() {
+        let tmpdir = tempfile::Builder::new()
+            .prefix("hc-bundle-test")
+            .tempdir()
+            .unwrap();
+
+        let manifest_yaml = r#"
+---
+manifest_version: "1"
+name: test_dna
+uid: blablabla
+origin_time: 2022-02-11T23:29:00.789576Z
+properties:
+  some: 42
+  props: yay
+zomes:
+  - name: zome1
+    bundled: zome-1.wasm
+  - name: zome2
+    bundled: nested/zome-2.wasm
+  - name: zome3
+    bundled: zome-3.wasm
+        "#;
+        let files: Vec<(&str, &[u8])> = vec![
+            ("dna.yaml", manifest_yaml.as_bytes()),
+            ("zome-1.wasm", &[1, 2, 3]),
+            ("nested/zome-2.wasm", &[4, 5, 6]),
+            ("zome-3.wasm", &[7, 8, 9]),
+        ];
+
+        // Write the same files to two directories, in a different order and
+        // at different times
+        let dir_a = tmpdir.path().join("a");
+        let dir_b = tmpdir.path().join("some/other/b");
+        for (dir, files) in [
+            (&dir_a, files.clone()),
+            (&dir_b, files.into_iter().rev().collect()),
+        ] {
+            std::fs::create_dir_all(dir.join("nested")).unwrap();
+            for (path, bytes) in files {
+                std::fs::write(dir.join(path), bytes).unwrap();
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        let (path_a, _) = pack::<DnaManifest>(&dir_a, None, "test_dna".to_string(), true)
+            .await
+            .unwrap();
+        let (path_b, _) = pack::<DnaManifest>(&dir_b, None, "test_dna".to_string(), true)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read(path_a).unwrap(),
+            std::fs::read(path_b).unwrap()
+        );
+
+        // A bundle which depends on a file outside of it is not reproducible
+        std::fs::write(tmpdir.path().join("zome-4.wasm"), &[10, 11, 12]).unwrap();
+        let manifest_yaml = format!(
+            "{}\n  - name: zome4\n    path: ../zome-4.wasm\n",
+            manifest_yaml.trim_end()
+        );
+        std::fs::write(dir_a.join("dna.yaml"), manifest_yaml).unwrap();
+        matches::assert_matches!(
+            pack::<DnaManifest>(&dir_a, None, "test_dna".to_string(), true).await,
+            Err(HcBundleError::UnbundledResources(locations)) if locations.len() == 1
+        );
+        pack::<DnaManifest>(&dir_a, None, "test_dna".to_string(), false)
+            .await
+            .unwrap();
+    }
 }
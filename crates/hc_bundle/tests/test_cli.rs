@@ -1,6 +1,7 @@
 use assert_cmd::prelude::*;
 use holochain_types::{prelude::*, web_app::WebAppBundle};
 use holochain_util::ffs;
+use predicates::prelude::PredicateBooleanExt;
 use std::{
     path::{Path, PathBuf},
     process::Command,
@@ -51,3 +52,200 @@ async fn roundtrip() {
     let _original_dna1 = read_dna(&dna1_path).unwrap();
     let _original_dna2 = read_dna(&dna2_path).unwrap();
 }
+
+/// Copy the `my-app` fixture somewhere it can be packed and modified without
+/// touching the bundles written by other tests.
+fn copy_dir(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+    for entry in std::fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            std::fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+fn pack_app_copy(dir: &Path) {
+    copy_dir(Path::new("tests/fixtures/my-app"), dir);
+    for dna in ["dnas/dna1", "dnas/dna2"] {
+        let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+        cmd.arg("pack").arg(dir.join(dna)).assert().success();
+    }
+    let mut cmd = Command::cargo_bin("hc-app").unwrap();
+    cmd.arg("pack").arg(dir).assert().success();
+}
+
+#[tokio::test]
+async fn inspect_bundles() {
+    let tmp = tempfile::tempdir().unwrap();
+    let a = tmp.path().join("a");
+    let b = tmp.path().join("b");
+    pack_app_copy(&a);
+    let happ_yaml = ffs::sync::read_to_string(a.join("happ.yaml"))
+        .unwrap()
+        .replace("uid: 0123456", "uid: 6543210");
+    copy_dir(&a, &b);
+    std::fs::write(b.join("happ.yaml"), happ_yaml).unwrap();
+    let mut cmd = Command::cargo_bin("hc-app").unwrap();
+    cmd.arg("pack").arg(&b).assert().success();
+
+    let dna_path = a.join("dnas/dna1/a dna.dna");
+    let app_a = a.join("fixture-app.happ");
+    let app_b = b.join("fixture-app.happ");
+
+    let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+    cmd.arg("info")
+        .arg(&dna_path)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "dna_hash_without_uid_or_properties",
+        ))
+        .stdout(predicates::str::contains("name: zome1"))
+        // The fixture zomes are not real wasm.
+        .stdout(predicates::str::contains("entry_defs_error"));
+
+    let mut cmd = Command::cargo_bin("hc-app").unwrap();
+    cmd.arg("info")
+        .arg(&app_a)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("id: role-1"))
+        .stdout(predicates::str::contains("installed_dna_hash"));
+
+    let mut cmd = Command::cargo_bin("hc-app").unwrap();
+    cmd.arg("diff")
+        .arg(&app_a)
+        .arg(&app_a)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("same DNA hashes"));
+
+    let mut cmd = Command::cargo_bin("hc-app").unwrap();
+    cmd.arg("diff")
+        .arg(&app_a)
+        .arg(&app_b)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("role `role-1`: DNA hash"))
+        .stdout(predicates::str::contains("uid: ").and(predicates::str::contains("6543210")))
+        .stdout(predicates::str::contains("role-2").not());
+
+    let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+    cmd.arg("verify")
+        .arg(&dna_path)
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("the wasm does not compile"));
+}
+
+#[tokio::test]
+async fn reproducible_pack() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut bundles = Vec::new();
+    for name in ["first", "second"] {
+        let dir = tmp.path().join(name);
+        copy_dir(Path::new("tests/fixtures/my-app/dnas/dna1"), &dir);
+        let bundle_path = tmp.path().join(format!("{}.dna", name));
+        let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+        cmd.arg("pack")
+            .arg(&dir)
+            .arg("--reproducible")
+            .arg("-o")
+            .arg(&bundle_path)
+            .assert()
+            .success();
+        bundles.push(ffs::sync::read(&bundle_path).unwrap());
+    }
+    assert_eq!(bundles[0], bundles[1]);
+}
+
+/// Pack a DNA with a single zome called `zome` from the given wasm.
+fn pack_dna_with_zome(dir: &Path, wasm: &[u8]) -> PathBuf {
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(dir.join("zome.wasm"), wasm).unwrap();
+    std::fs::write(
+        dir.join("dna.yaml"),
+        r#"---
+manifest_version: "1"
+name: test dna
+uid: 00000000-0000-0000-0000-000000000000
+properties: ~
+origin_time: 2022-02-11T23:29:00.789576Z
+zomes:
+  - name: zome
+    bundled: ./zome.wasm
+"#,
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+    cmd.arg("pack").arg(dir).assert().success();
+    dir.join("test dna.dna")
+}
+
+#[tokio::test]
+async fn inspect_real_zome() {
+    use holochain_wasm_test_utils::TestWasm;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let wasm = DnaWasm::from(TestWasm::EntryDefs);
+    let dna_path = pack_dna_with_zome(&tmp.path().join("entry_defs"), &wasm.code);
+
+    let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+    cmd.arg("info")
+        .arg(&dna_path)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("App: post"))
+        .stdout(predicates::str::contains("App: comment"))
+        .stdout(predicates::str::contains("entry_defs_error").not());
+
+    let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+    cmd.arg("verify")
+        .arg(&dna_path)
+        .assert()
+        .success()
+        .stdout("zome: ok\n");
+
+    let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+    cmd.arg("verify")
+        .arg(&dna_path)
+        .args(&["-r", "validate"])
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("missing the `validate` callback"));
+}
+
+#[tokio::test]
+async fn verify_signatures() {
+    let tmp = tempfile::tempdir().unwrap();
+    let wasm = wasmer::wat2wasm(
+        br#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "__allocate") (param i32) (result i32) (i32.const 0))
+            (func (export "__deallocate") (param i32 i32))
+            (func (export "ok") (param i32 i32) (result i64) (i64.const 0))
+            (func (export "validate") (param i32) (result i32) (i32.const 0))
+            (func (export "bad_fn") (param i32 i32) (result i32) (i32.const 0)))
+        "#,
+    )
+    .unwrap();
+    let dna_path = pack_dna_with_zome(&tmp.path().join("bad"), &wasm);
+
+    let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+    cmd.arg("verify")
+        .arg(&dna_path)
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains(
+            "callback `validate` has the signature",
+        ))
+        .stdout(predicates::str::contains(
+            "function `bad_fn` has the signature",
+        ))
+        .stdout(predicates::str::contains("`ok`").not());
+}
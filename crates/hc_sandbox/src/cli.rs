@@ -80,6 +80,10 @@ pub enum HcSandboxSubcommand {
 
     /// Create a fresh sandbox with no apps installed.
     Create(Create),
+
+    /// Change the simulated network between running sandboxes
+    /// generated with `network simulated`.
+    NetSim(crate::netsim::NetSim),
 }
 
 /// Options for running a sandbox
@@ -165,17 +169,22 @@ impl HcSandbox {
                     "Creating {} conductor sandboxes with same settings",
                     num_sandboxes
                 );
+                let simulated = network.as_ref().map_or(false, |n| n.is_simulated());
                 for i in 0..num_sandboxes {
                     let path = crate::generate::generate(
                         network.clone().map(|n| n.into_inner().into()),
                         root.clone(),
                         directories.get(i).cloned(),
                     )?;
+                    if simulated {
+                        crate::netsim::simulate(path.clone())?;
+                    }
                     paths.push(path);
                 }
                 crate::save::save(std::env::current_dir()?, paths.clone())?;
                 msg!("Created {:?}", paths);
             }
+            HcSandboxSubcommand::NetSim(net_sim) => net_sim.run()?,
         }
 
         Ok(())
@@ -188,6 +197,14 @@ async fn run_n(
     app_ports: Vec<u16>,
     force_admin_ports: Vec<u16>,
 ) -> anyhow::Result<()> {
+    let simulated = crate::netsim::find_simulated(std::env::current_dir()?, &paths)?;
+    if !simulated.is_empty() {
+        msg!(
+            "Relaying {} sandboxes through the simulated network",
+            simulated.len()
+        );
+        crate::netsim::spawn_relay(std::env::current_dir()?, simulated).await?;
+    }
     let run_holochain = |holochain_path: PathBuf, path: PathBuf, ports, force_admin_port| async move {
         crate::run::run(&holochain_path, path, ports, force_admin_port).await?;
         Result::<_, anyhow::Error>::Ok(())
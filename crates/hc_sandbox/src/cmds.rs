@@ -46,6 +46,12 @@ impl NetworkCmd {
             NetworkCmd::Network(n) => n,
         }
     }
+
+    pub fn is_simulated(&self) -> bool {
+        match self {
+            NetworkCmd::Network(n) => matches!(n.transport, NetworkType::Simulated),
+        }
+    }
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -68,6 +74,14 @@ pub enum NetworkType {
     Quic(Quic),
     /// A transport that uses the MDNS protocol.
     Mdns,
+    /// A transport that uses the QUIC protocol and finds peers on the LAN
+    /// via MDNS and on the WAN via the bootstrap service at the same time.
+    Hybrid(Quic),
+    /// A QUIC transport on localhost that goes through a relay adding latency,
+    /// packet loss and partitions between sandboxes.
+    /// Peers are found via MDNS unless a bootstrap service is set.
+    /// Change the network while running with `hc sandbox net-sim`.
+    Simulated,
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -173,10 +187,14 @@ impl From<Network> for KitsuneP2pConfig {
             bootstrap,
         } = n;
         let mut kit = KitsuneP2pConfig::default();
-        kit.bootstrap_service = bootstrap;
+        kit.bootstrap_service = bootstrap.map(Into::into);
 
         match transport {
             NetworkType::Mem => (),
+            NetworkType::Hybrid(quic) => {
+                kit.network_type = holochain_p2p::kitsune_p2p::NetworkType::QuicHybrid;
+                kit.transport_pool = vec![quic_transport(quic)];
+            }
             NetworkType::Mdns => {
                 kit.network_type = holochain_p2p::kitsune_p2p::NetworkType::QuicMdns;
                 kit.transport_pool = vec![TransportConfig::Quic {
@@ -185,41 +203,47 @@ impl From<Network> for KitsuneP2pConfig {
                     override_port: None,
                 }];
             }
-            NetworkType::Quic(Quic {
-                bind_to,
-                override_host,
-                override_port,
-                proxy: None,
-            }) => {
+            NetworkType::Simulated => {
+                if kit.bootstrap_service.is_none() {
+                    kit.network_type = holochain_p2p::kitsune_p2p::NetworkType::QuicMdns;
+                }
+                // The ports are chosen for each sandbox by `netsim::simulate`.
                 kit.transport_pool = vec![TransportConfig::Quic {
-                    bind_to,
-                    override_host,
-                    override_port,
+                    bind_to: None,
+                    override_host: None,
+                    override_port: None,
                 }];
             }
-            NetworkType::Quic(Quic {
-                bind_to,
-                override_host,
-                override_port,
-                proxy: Some(proxy_url),
-            }) => {
-                let transport = TransportConfig::Quic {
-                    bind_to,
-                    override_host,
-                    override_port,
-                };
-                kit.transport_pool = vec![TransportConfig::Proxy {
-                    sub_transport: Box::new(transport),
-                    proxy_config: holochain_p2p::kitsune_p2p::ProxyConfig::RemoteProxyClient {
-                        proxy_url,
-                    },
-                }]
+            NetworkType::Quic(quic) => {
+                kit.transport_pool = vec![quic_transport(quic)];
             }
         }
         kit
     }
 }
 
+/// The quic transport, going through the proxy if one is set.
+fn quic_transport(quic: Quic) -> TransportConfig {
+    let Quic {
+        bind_to,
+        override_host,
+        override_port,
+        proxy,
+    } = quic;
+    let transport = TransportConfig::Quic {
+        bind_to,
+        override_host,
+        override_port,
+    };
+    match proxy {
+        None => transport,
+        Some(proxy_url) => TransportConfig::Proxy {
+            sub_transport: Box::new(transport),
+            proxy_config: holochain_p2p::kitsune_p2p::ProxyConfig::RemoteProxyClient { proxy_url },
+        },
+    }
+}
+
 impl Default for Create {
     fn default() -> Self {
         Self {
@@ -65,6 +65,19 @@
 //! ```shell
 //! hc sandbox r -n 5 ./elemental-chat.dna gen -a "my-app" network quic
 //! ```
+//! #### Simulated network
+//! The `simulated` network connects sandboxes on localhost through a relay
+//! that is started when they are run. It can add latency and packet loss
+//! and split the sandboxes into partitions while they are running,
+//! using the indices from `hc sandbox list`:
+//! ```shell
+//! hc sandbox gen -n 3 network simulated
+//! hc sandbox run --all
+//! # In another terminal
+//! hc sandbox net-sim link --latency-ms 200 --jitter-ms 50 --loss-percent 5
+//! hc sandbox net-sim partition left 0 1
+//! hc sandbox net-sim heal
+//! ```
 //! #### Call
 //! Allows calling the [`AdminRequest`] api.
 //! If the conductors are not already running they
@@ -137,6 +150,7 @@ pub mod cli;
 pub mod cmds;
 pub mod config;
 pub mod generate;
+pub mod netsim;
 pub mod run;
 pub mod sandbox;
 pub mod save;
@@ -0,0 +1,512 @@
+//! Simulate latency, packet loss and partitions between sandboxes.
+//!
+//! Sandboxes generated with the `simulated` network bind QUIC to a fixed
+//! local port but advertise a relay port to their peers.
+//! When they are run, a relay forwards UDP packets between the two,
+//! applying the [`NetSimConfig`] in the `.hc_netsim` file of the current
+//! directory. `hc sandbox net-sim` edits that file and the relay
+//! picks up the changes while the sandboxes are running.
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use holochain_p2p::kitsune_p2p::TransportConfig;
+use rand::Rng;
+use serde::Deserialize;
+use serde::Serialize;
+use structopt::StructOpt;
+use tokio::net::UdpSocket;
+use url2::url2;
+
+use crate::config::read_config;
+use crate::config::write_config;
+
+/// Name of the file in a sandbox directory that marks it
+/// as using the simulated network.
+pub const SIMULATED_NETWORK_FILE: &str = "simulated-network.yaml";
+
+/// Name of the file in the current directory holding the [`NetSimConfig`].
+pub const NET_SIM_FILE: &str = ".hc_netsim";
+
+/// How often the relay checks the [`NET_SIM_FILE`] for changes.
+const RELOAD_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Largest UDP payload the relay forwards.
+const MAX_DATAGRAM: usize = 65_535;
+
+/// The ports a sandbox on the simulated network uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulatedSandbox {
+    /// The port the conductor binds QUIC to.
+    pub bind_port: u16,
+    /// The port the conductor advertises, which the relay listens on.
+    pub relay_port: u16,
+}
+
+/// Conditions applied to the packets a sandbox sends.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkConfig {
+    /// Delay added to every packet.
+    pub latency_ms: u64,
+    /// Extra random delay of up to this much, which also reorders packets.
+    pub jitter_ms: u64,
+    /// Percentage of packets that are dropped.
+    pub loss_percent: f64,
+}
+
+/// The state of the simulated network.
+///
+/// Sandboxes are identified by their index in `hc sandbox list`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetSimConfig {
+    /// The link of any sandbox without its own.
+    pub default: LinkConfig,
+    /// Links of specific sandboxes.
+    pub sandboxes: BTreeMap<usize, LinkConfig>,
+    /// Named groups of sandboxes which can only reach each other.
+    /// Sandboxes not in any partition can only reach each other.
+    pub partitions: BTreeMap<String, BTreeSet<usize>>,
+}
+
+impl NetSimConfig {
+    /// The link packets sent by this sandbox go through.
+    pub fn link(&self, sandbox: Option<usize>) -> LinkConfig {
+        sandbox
+            .and_then(|i| self.sandboxes.get(&i))
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// The name of the partition this sandbox is in.
+    pub fn partition_of(&self, sandbox: Option<usize>) -> Option<&str> {
+        let sandbox = sandbox?;
+        self.partitions
+            .iter()
+            .find(|(_, members)| members.contains(&sandbox))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Can packets get from one sandbox to the other.
+    pub fn connected(&self, from: Option<usize>, to: Option<usize>) -> bool {
+        self.partition_of(from) == self.partition_of(to)
+    }
+
+    /// Move these sandboxes into the named partition.
+    pub fn partition(&mut self, name: String, sandboxes: impl IntoIterator<Item = usize>) {
+        let sandboxes: BTreeSet<_> = sandboxes.into_iter().collect();
+        for members in self.partitions.values_mut() {
+            members.retain(|i| !sandboxes.contains(i));
+        }
+        self.partitions.retain(|_, members| !members.is_empty());
+        self.partitions.entry(name).or_default().extend(sandboxes);
+    }
+
+    /// Remove the named partition or all partitions if no name is given.
+    pub fn heal(&mut self, name: Option<&str>) {
+        match name {
+            Some(name) => {
+                self.partitions.remove(name);
+            }
+            None => self.partitions.clear(),
+        }
+    }
+
+    /// Decide what happens to a packet from one sandbox to another.
+    /// Returns `None` if it is dropped or the delay before it is sent.
+    fn route(&self, from: Option<usize>, to: Option<usize>) -> Option<Duration> {
+        if !self.connected(from, to) {
+            return None;
+        }
+        let link = self.link(from);
+        let mut rng = rand::thread_rng();
+        if link.loss_percent > 0.0 && rng.gen::<f64>() * 100.0 < link.loss_percent {
+            return None;
+        }
+        let jitter = if link.jitter_ms > 0 {
+            rng.gen_range(0, link.jitter_ms + 1)
+        } else {
+            0
+        };
+        Some(Duration::from_millis(link.latency_ms + jitter))
+    }
+}
+
+/// Load the [`NetSimConfig`] from the `.hc_netsim` file in the `hc_dir` directory.
+/// A missing file is the default network with no latency, loss or partitions.
+pub fn load(mut hc_dir: PathBuf) -> anyhow::Result<NetSimConfig> {
+    hc_dir.push(NET_SIM_FILE);
+    match std::fs::read_to_string(hc_dir) {
+        Ok(yaml) => Ok(serde_yaml::from_str(&yaml)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(NetSimConfig::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Save the [`NetSimConfig`] to the `.hc_netsim` file in the `hc_dir` directory.
+pub fn save(mut hc_dir: PathBuf, config: &NetSimConfig) -> anyhow::Result<()> {
+    hc_dir.push(NET_SIM_FILE);
+    std::fs::write(hc_dir, serde_yaml::to_string(config)?)?;
+    Ok(())
+}
+
+/// Put a generated sandbox on the simulated network by binding QUIC
+/// to a free local port and advertising a free relay port instead.
+pub fn simulate(sandbox_path: PathBuf) -> anyhow::Result<SimulatedSandbox> {
+    let mut config = read_config(sandbox_path.clone())?
+        .ok_or_else(|| anyhow::anyhow!("Failed to find config to simulate network"))?;
+    let sandbox = SimulatedSandbox {
+        bind_port: free_udp_port()?,
+        relay_port: free_udp_port()?,
+    };
+    let network = config.network.get_or_insert_with(Default::default);
+    network.transport_pool = vec![TransportConfig::Quic {
+        bind_to: Some(url2!("kitsune-quic://127.0.0.1:{}", sandbox.bind_port)),
+        override_host: Some("127.0.0.1".to_string()),
+        override_port: Some(sandbox.relay_port),
+    }];
+    write_config(sandbox_path.clone(), &config);
+    std::fs::write(
+        sandbox_path.join(SIMULATED_NETWORK_FILE),
+        serde_yaml::to_string(&sandbox)?,
+    )?;
+    Ok(sandbox)
+}
+
+/// Read the simulated network ports of a sandbox,
+/// if it is on the simulated network.
+pub fn read_simulated(sandbox_path: &Path) -> anyhow::Result<Option<SimulatedSandbox>> {
+    match std::fs::read_to_string(sandbox_path.join(SIMULATED_NETWORK_FILE)) {
+        Ok(yaml) => Ok(Some(serde_yaml::from_str(&yaml)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The index of every sandbox on the simulated network in the `.hc` file
+/// of the `hc_dir` directory, by the port it binds QUIC to.
+fn simulated_indices(hc_dir: PathBuf) -> anyhow::Result<HashMap<u16, usize>> {
+    let mut indices = HashMap::new();
+    for (index, path) in crate::save::load(hc_dir)?.into_iter().enumerate() {
+        if let Some(sandbox) = read_simulated(&path)? {
+            indices.insert(sandbox.bind_port, index);
+        }
+    }
+    Ok(indices)
+}
+
+/// Find the sandboxes on the simulated network along with
+/// their index in the `.hc` file of the `hc_dir` directory.
+pub fn find_simulated(
+    hc_dir: PathBuf,
+    paths: &[PathBuf],
+) -> anyhow::Result<Vec<(Option<usize>, SimulatedSandbox)>> {
+    let indices = simulated_indices(hc_dir)?;
+    let mut simulated = Vec::new();
+    for path in paths {
+        if let Some(sandbox) = read_simulated(path)? {
+            let index = indices.get(&sandbox.bind_port).copied();
+            simulated.push((index, sandbox));
+        }
+    }
+    Ok(simulated)
+}
+
+/// Bind the relay ports of these sandboxes and forward packets between
+/// them and every other sandbox on the simulated network in the background,
+/// reloading the `.hc_netsim` file in the `hc_dir` directory when it changes.
+pub async fn spawn_relay(
+    hc_dir: PathBuf,
+    sandboxes: Vec<(Option<usize>, SimulatedSandbox)>,
+) -> anyhow::Result<()> {
+    let config = Arc::new(RwLock::new(load(hc_dir.clone())?));
+    // Packets also come from sandboxes relayed by other `hc sandbox run`
+    // commands, so every sandbox in the `.hc` file needs its index.
+    let own: HashMap<u16, usize> = sandboxes
+        .iter()
+        .filter_map(|(index, sandbox)| Some((sandbox.bind_port, (*index)?)))
+        .collect();
+    let indices = Arc::new(RwLock::new(relay_indices(hc_dir.clone(), &own)?));
+    for (index, sandbox) in sandboxes {
+        let listener =
+            UdpSocket::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, sandbox.relay_port))).await?;
+        let relay = Relay {
+            config: config.clone(),
+            indices: indices.clone(),
+            listener: Arc::new(listener),
+            target: SocketAddr::from((Ipv4Addr::LOCALHOST, sandbox.bind_port)),
+            target_index: index,
+        };
+        tokio::task::spawn(async move {
+            if let Err(e) = relay.run().await {
+                tracing::error!(relay_failed = ?e);
+            }
+        });
+    }
+    tokio::task::spawn(reload(hc_dir, config, indices, own));
+    Ok(())
+}
+
+/// The indices of the sandboxes in the `.hc` file along with those
+/// of the sandboxes a relay was spawned for.
+fn relay_indices(
+    hc_dir: PathBuf,
+    own: &HashMap<u16, usize>,
+) -> anyhow::Result<HashMap<u16, usize>> {
+    let mut indices = simulated_indices(hc_dir)?;
+    indices.extend(own);
+    Ok(indices)
+}
+
+/// Forwards the packets sent to one sandbox's relay port.
+struct Relay {
+    config: Arc<RwLock<NetSimConfig>>,
+    indices: Arc<RwLock<HashMap<u16, usize>>>,
+    listener: Arc<UdpSocket>,
+    target: SocketAddr,
+    target_index: Option<usize>,
+}
+
+impl Relay {
+    async fn run(self) -> anyhow::Result<()> {
+        // One socket per peer so replies from the target can be sent back
+        // to the right peer, like a NAT would.
+        let mut outbound: HashMap<SocketAddr, Arc<UdpSocket>> = HashMap::new();
+        let mut buf = vec![0; MAX_DATAGRAM];
+        loop {
+            let (len, peer) = self.listener.recv_from(&mut buf).await?;
+            let peer_index = match self.indices.read() {
+                Ok(indices) => indices.get(&peer.port()).copied(),
+                Err(_) => None,
+            };
+            let socket = match outbound.get(&peer) {
+                Some(socket) => socket.clone(),
+                None => {
+                    let socket = Arc::new(
+                        UdpSocket::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))).await?,
+                    );
+                    outbound.insert(peer, socket.clone());
+                    tokio::task::spawn(reply(
+                        self.config.clone(),
+                        socket.clone(),
+                        self.target,
+                        self.target_index,
+                        self.listener.clone(),
+                        peer,
+                        peer_index,
+                    ));
+                    socket
+                }
+            };
+            forward(
+                &self.config,
+                peer_index,
+                self.target_index,
+                buf[..len].to_vec(),
+                socket,
+                self.target,
+            )
+            .await;
+        }
+    }
+}
+
+/// Send the target's replies to a peer back through the relay port.
+async fn reply(
+    config: Arc<RwLock<NetSimConfig>>,
+    socket: Arc<UdpSocket>,
+    target: SocketAddr,
+    target_index: Option<usize>,
+    listener: Arc<UdpSocket>,
+    peer: SocketAddr,
+    peer_index: Option<usize>,
+) {
+    let mut buf = vec![0; MAX_DATAGRAM];
+    loop {
+        let (len, from) = match socket.recv_from(&mut buf).await {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::error!(relay_reply_failed = ?e);
+                return;
+            }
+        };
+        if from != target {
+            continue;
+        }
+        forward(
+            &config,
+            target_index,
+            peer_index,
+            buf[..len].to_vec(),
+            listener.clone(),
+            peer,
+        )
+        .await;
+    }
+}
+
+/// Send a packet unless it is dropped, after its delay.
+async fn forward(
+    config: &RwLock<NetSimConfig>,
+    from: Option<usize>,
+    to: Option<usize>,
+    data: Vec<u8>,
+    socket: Arc<UdpSocket>,
+    dest: SocketAddr,
+) {
+    let delay = match config.read() {
+        Ok(config) => config.route(from, to),
+        Err(_) => return,
+    };
+    match delay {
+        None => (),
+        Some(delay) if delay.is_zero() => {
+            socket.send_to(&data, dest).await.ok();
+        }
+        Some(delay) => {
+            tokio::task::spawn(async move {
+                tokio::time::sleep(delay).await;
+                socket.send_to(&data, dest).await.ok();
+            });
+        }
+    }
+}
+
+async fn reload(
+    hc_dir: PathBuf,
+    config: Arc<RwLock<NetSimConfig>>,
+    indices: Arc<RwLock<HashMap<u16, usize>>>,
+    own: HashMap<u16, usize>,
+) {
+    loop {
+        tokio::time::sleep(RELOAD_INTERVAL).await;
+        // Sandboxes may have been added since the relay started.
+        match relay_indices(hc_dir.clone(), &own) {
+            Ok(new_indices) => {
+                if let Ok(mut indices) = indices.write() {
+                    *indices = new_indices;
+                }
+            }
+            Err(e) => tracing::error!(failed_to_load_sandboxes = ?e),
+        }
+        match load(hc_dir.clone()) {
+            Ok(new_config) => {
+                if let Ok(mut config) = config.write() {
+                    if *config != new_config {
+                        *config = new_config;
+                        msg!("Simulated network updated");
+                    }
+                }
+            }
+            Err(e) => tracing::error!(failed_to_load_net_sim = ?e),
+        }
+    }
+}
+
+fn free_udp_port() -> anyhow::Result<u16> {
+    Ok(std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?
+        .local_addr()?
+        .port())
+}
+
+/// Change the simulated network between running sandboxes.
+///
+/// Sandboxes are chosen by their index in `hc sandbox list`.
+#[derive(Debug, StructOpt)]
+pub enum NetSim {
+    /// Print the current simulated network.
+    Show,
+    /// Set the latency, jitter and loss of the packets sandboxes send.
+    Link {
+        #[structopt(long)]
+        /// Delay added to every packet.
+        latency_ms: Option<u64>,
+        #[structopt(long)]
+        /// Extra random delay of up to this much.
+        jitter_ms: Option<u64>,
+        #[structopt(long)]
+        /// Percentage of packets that are dropped.
+        loss_percent: Option<f64>,
+        /// The sandboxes to change.
+        /// Changes the default for all sandboxes if none are given.
+        sandboxes: Vec<usize>,
+    },
+    /// Move sandboxes into a named partition.
+    /// Sandboxes can only reach other sandboxes in the same partition.
+    Partition {
+        /// Name of the partition.
+        name: String,
+        /// The sandboxes to move into it.
+        #[structopt(required = true)]
+        sandboxes: Vec<usize>,
+    },
+    /// Remove a partition, or all partitions if no name is given.
+    Heal {
+        /// Name of the partition.
+        name: Option<String>,
+    },
+    /// Remove all latency, loss and partitions.
+    Reset,
+}
+
+impl NetSim {
+    /// Run this command
+    pub fn run(self) -> anyhow::Result<()> {
+        let hc_dir = std::env::current_dir()?;
+        let mut config = load(hc_dir.clone())?;
+        match self {
+            NetSim::Show => {
+                msg!("Simulated network:\n{}", serde_yaml::to_string(&config)?);
+                return Ok(());
+            }
+            NetSim::Link {
+                latency_ms,
+                jitter_ms,
+                loss_percent,
+                sandboxes,
+            } => {
+                if let Some(loss_percent) = loss_percent {
+                    if !(0.0..=100.0).contains(&loss_percent) {
+                        anyhow::bail!("Loss must be between 0 and 100 percent");
+                    }
+                }
+                let set = |link: &mut LinkConfig| {
+                    if let Some(latency_ms) = latency_ms {
+                        link.latency_ms = latency_ms;
+                    }
+                    if let Some(jitter_ms) = jitter_ms {
+                        link.jitter_ms = jitter_ms;
+                    }
+                    if let Some(loss_percent) = loss_percent {
+                        link.loss_percent = loss_percent;
+                    }
+                };
+                if sandboxes.is_empty() {
+                    set(&mut config.default);
+                } else {
+                    for i in sandboxes {
+                        let default = config.default;
+                        set(config.sandboxes.entry(i).or_insert(default));
+                    }
+                }
+            }
+            NetSim::Partition { name, sandboxes } => config.partition(name, sandboxes),
+            NetSim::Heal { name } => config.heal(name.as_deref()),
+            NetSim::Reset => config = NetSimConfig::default(),
+        }
+        save(hc_dir, &config)?;
+        msg!(
+            "Simulated network set to:\n{}",
+            serde_yaml::to_string(&config)?
+        );
+        Ok(())
+    }
+}
@@ -20,7 +20,11 @@ pub async fn default_with_network(
     app_id: InstalledAppId,
 ) -> anyhow::Result<PathBuf> {
     let Create { network, root, .. } = create;
+    let simulated = network.as_ref().map_or(false, |n| n.is_simulated());
     let path = crate::generate::generate(network.map(|n| n.into_inner().into()), root, directory)?;
+    if simulated {
+        crate::netsim::simulate(path.clone())?;
+    }
     let conductor = run_async(holochain_path, path.clone(), None).await?;
     let mut cmd = CmdRunner::new(conductor.0).await;
     let install_bundle = InstallAppBundle {
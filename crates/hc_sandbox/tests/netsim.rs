@@ -0,0 +1,167 @@
+use std::net::SocketAddr;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+
+use holochain_cli_sandbox::config::CONDUCTOR_CONFIG;
+use holochain_cli_sandbox::netsim::*;
+use tokio::net::UdpSocket;
+
+/// Longer than the relay takes to notice a changed `.hc_netsim` file.
+const RELOAD_WAIT: Duration = Duration::from_millis(1500);
+
+async fn fake_sandbox() -> (UdpSocket, SimulatedSandbox) {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let sandbox = SimulatedSandbox {
+        bind_port: socket.local_addr().unwrap().port(),
+        relay_port: portpicker::pick_unused_port().unwrap(),
+    };
+    (socket, sandbox)
+}
+
+fn hc_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(nanoid::nanoid!());
+    std::fs::create_dir(&dir).unwrap();
+    dir
+}
+
+/// Add sandbox directories on the simulated network with these ports
+/// to the `.hc` file, in order.
+fn add_sandboxes(hc_dir: &Path, sandboxes: &[SimulatedSandbox]) -> Vec<PathBuf> {
+    let paths: Vec<_> = sandboxes
+        .iter()
+        .map(|sandbox| {
+            let path = hc_dir.join(nanoid::nanoid!());
+            std::fs::create_dir(&path).unwrap();
+            std::fs::write(path.join(CONDUCTOR_CONFIG), "").unwrap();
+            std::fs::write(
+                path.join(SIMULATED_NETWORK_FILE),
+                serde_yaml::to_string(sandbox).unwrap(),
+            )
+            .unwrap();
+            path
+        })
+        .collect();
+    holochain_cli_sandbox::save::save(hc_dir.to_path_buf(), paths.clone()).unwrap();
+    paths
+}
+
+/// Send from one fake sandbox to another through the relay and
+/// return what arrived, and where from, if anything did.
+async fn send(
+    from: &UdpSocket,
+    to: &UdpSocket,
+    to_sandbox: SimulatedSandbox,
+    data: &[u8],
+) -> Option<(Vec<u8>, SocketAddr)> {
+    from.send_to(data, ("127.0.0.1", to_sandbox.relay_port))
+        .await
+        .unwrap();
+    let mut buf = vec![0; 1024];
+    match tokio::time::timeout(Duration::from_millis(500), to.recv_from(&mut buf)).await {
+        Ok(r) => {
+            let (len, addr) = r.unwrap();
+            Some((buf[..len].to_vec(), addr))
+        }
+        Err(_) => None,
+    }
+}
+
+#[test]
+fn partitions_split_sandboxes() {
+    let mut config = NetSimConfig::default();
+    assert!(config.connected(Some(0), Some(1)));
+
+    config.partition("a".to_string(), vec![0, 1]);
+    assert!(config.connected(Some(0), Some(1)));
+    assert!(!config.connected(Some(0), Some(2)));
+    assert!(!config.connected(Some(1), None));
+
+    // Moving a sandbox takes it out of its old partition
+    config.partition("b".to_string(), vec![1, 2]);
+    assert!(!config.connected(Some(0), Some(1)));
+    assert!(config.connected(Some(1), Some(2)));
+
+    config.heal(Some("b"));
+    assert!(config.connected(Some(1), Some(2)));
+    assert!(!config.connected(Some(0), Some(1)));
+
+    config.heal(None);
+    assert!(config.connected(Some(0), Some(1)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn relay_applies_partitions_and_latency() {
+    let hc_dir = hc_dir();
+    let (alice, alice_sandbox) = fake_sandbox().await;
+    let (bob, bob_sandbox) = fake_sandbox().await;
+    spawn_relay(
+        hc_dir.clone(),
+        vec![(Some(0), alice_sandbox), (Some(1), bob_sandbox)],
+    )
+    .await
+    .unwrap();
+
+    // Packets get there and replies come back from the relay port
+    let (data, relay_addr) = send(&alice, &bob, bob_sandbox, b"ping").await.unwrap();
+    assert_eq!(b"ping".to_vec(), data);
+    bob.send_to(b"pong", relay_addr).await.unwrap();
+    let mut buf = vec![0; 1024];
+    let (len, addr) = tokio::time::timeout(Duration::from_millis(500), alice.recv_from(&mut buf))
+        .await
+        .unwrap()
+        .unwrap();
+    let data = buf[..len].to_vec();
+    assert_eq!(b"pong".to_vec(), data);
+    assert_eq!(bob_sandbox.relay_port, addr.port());
+
+    // Nothing crosses a partition
+    let mut config = NetSimConfig::default();
+    config.partition("alone".to_string(), vec![0]);
+    save(hc_dir.clone(), &config).unwrap();
+    tokio::time::sleep(RELOAD_WAIT).await;
+    assert!(send(&alice, &bob, bob_sandbox, b"ping").await.is_none());
+
+    // Healing the partition and adding latency delays packets
+    config.heal(None);
+    config.default.latency_ms = 200;
+    save(hc_dir.clone(), &config).unwrap();
+    tokio::time::sleep(RELOAD_WAIT).await;
+    let start = Instant::now();
+    assert!(send(&alice, &bob, bob_sandbox, b"ping").await.is_some());
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    std::fs::remove_dir_all(hc_dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn separately_spawned_relays_know_every_sandbox() {
+    let hc_dir = hc_dir();
+    let (alice, alice_sandbox) = fake_sandbox().await;
+    let (bob, bob_sandbox) = fake_sandbox().await;
+    let paths = add_sandboxes(&hc_dir, &[alice_sandbox, bob_sandbox]);
+
+    let mut config = NetSimConfig::default();
+    config.partition("alone".to_string(), vec![0]);
+    save(hc_dir.clone(), &config).unwrap();
+
+    // Each sandbox is run by its own command
+    for (index, path) in paths.into_iter().enumerate() {
+        let simulated = find_simulated(hc_dir.clone(), &[path]).unwrap();
+        assert_eq!(simulated[0].0, Some(index));
+        spawn_relay(hc_dir.clone(), simulated).await.unwrap();
+    }
+
+    // Each relay knows where the packets from the other sandbox come from
+    assert!(send(&alice, &bob, bob_sandbox, b"ping").await.is_none());
+    assert!(send(&bob, &alice, alice_sandbox, b"ping").await.is_none());
+
+    config.heal(None);
+    save(hc_dir.clone(), &config).unwrap();
+    tokio::time::sleep(RELOAD_WAIT).await;
+    assert!(send(&alice, &bob, bob_sandbox, b"ping").await.is_some());
+    assert!(send(&bob, &alice, alice_sandbox, b"ping").await.is_some());
+
+    std::fs::remove_dir_all(hc_dir).unwrap();
+}
@@ -53,3 +53,12 @@ pub mod path;
 ///
 /// The tests in the shard module include several examples of the DSL including multibyte characters.
 pub mod shard;
+
+/// Time bucketed [ `path::Path` ]s derived from a [ `Timestamp` ].
+///
+/// A [ `time::TimeBucket` ] picks how fine the buckets are, e.g. `Day` puts
+/// everything from one UTC day under `[ root "2022" "06" "08" ]`.
+///
+/// Every bucket between two timestamps can be listed so a time range can be
+/// read back without walking the whole tree.
+pub mod time;
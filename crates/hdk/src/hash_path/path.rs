@@ -284,6 +284,10 @@ impl Path {
     }
 
     /// Recursively touch this and every parent that doesn't exist yet.
+    /// A path that exists is left as it is, even if it is no longer linked
+    /// from its parent, so this costs no more than the `exists` check once a
+    /// path has been created. Use [ `Path::ensure_relinked` ] to link
+    /// a pruned path back into the tree.
     pub fn ensure(&self) -> ExternResult<()> {
         if !self.exists()? {
             create_entry(self.path_entry()?)?;
@@ -293,20 +297,44 @@ impl Path {
                     parent.path_entry_hash()?,
                     self.path_entry_hash()?,
                     HdkLinkType::Paths,
-                    LinkTag::new(match self.leaf() {
-                        None => <Vec<u8>>::with_capacity(0),
-                        Some(component) => UnsafeBytes::from(
-                            SerializedBytes::try_from(component)
-                                .map_err(|e| wasm_error!(e.into()))?,
-                        )
-                        .into(),
-                    }),
+                    path_link_tag(self)?,
                 )?;
             }
         }
         Ok(())
     }
 
+    /// Like [ `Path::ensure` ] but also links this path and every parent
+    /// back into the tree if they exist but are no longer linked from their
+    /// parent, e.g. after [ `Path::prune` ].
+    /// This looks up the links from every parent, so only use it for paths
+    /// that may have been pruned.
+    pub fn ensure_relinked(&self) -> ExternResult<()> {
+        if !self.exists()? {
+            create_entry(self.path_entry()?)?;
+        }
+        if let Some(parent) = self.parent() {
+            if self.links_from_parent(&parent)?.is_empty() {
+                parent.ensure_relinked()?;
+                create_link(
+                    parent.path_entry_hash()?,
+                    self.path_entry_hash()?,
+                    HdkLinkType::Paths,
+                    path_link_tag(self)?,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The links from `parent` to this path.
+    fn links_from_parent(&self, parent: &Path) -> ExternResult<Vec<Link>> {
+        let target = AnyLinkableHash::from(self.path_entry_hash()?);
+        let mut links = get_links(parent.path_entry_hash()?, Some(path_link_tag(self)?))?;
+        links.retain(|link| link.target == target);
+        Ok(links)
+    }
+
     /// The parent of the current path is simply the path truncated one level.
     pub fn parent(&self) -> Option<Path> {
         if self.as_ref().len() > 1 {
@@ -364,6 +392,76 @@ impl Path {
             .collect())
     }
 
+    /// List the paths directly below this one without touching anything.
+    /// Unlike `Path::children_paths` this skips links to anything other than
+    /// a child path, so it is safe to call on paths that other entries are
+    /// linked from.
+    fn existing_children_paths(&self) -> ExternResult<Vec<Self>> {
+        let mut links = get_links(self.path_entry_hash()?, None)?;
+        links.sort_unstable_by(|a, b| a.tag.cmp(&b.tag));
+        links.dedup_by(|a, b| a.tag.eq(&b.tag));
+        let mut children = Vec::with_capacity(links.len());
+        for link in links {
+            if link.tag.0.is_empty() {
+                continue;
+            }
+            let component: Component = SerializedBytes::from(UnsafeBytes::from(link.tag.0.clone()))
+                .try_into()
+                .map_err(|e: SerializedBytesError| wasm_error!(e.into()))?;
+            let mut child = self.clone();
+            child.append_component(component);
+            if AnyLinkableHash::from(child.path_entry_hash()?) == link.target {
+                children.push(child);
+            }
+        }
+        Ok(children)
+    }
+
+    /// Iterate depth first over every leaf below this path, i.e. every
+    /// descendant path that has no children of its own.
+    /// A path with no children is its own only leaf.
+    /// Nothing is touched so iterating does not write to the source chain.
+    pub fn leaves(&self) -> PathLeaves {
+        PathLeaves::new(self.clone())
+    }
+
+    /// Remove this path from the tree if nothing is linked from it, then do
+    /// the same for each ancestor that is left empty as a result.
+    /// Only the links this agent made are deleted, so a path that another
+    /// agent also linked stays in the tree and pruning stops there.
+    /// The path entries themselves remain on the DHT but are no longer
+    /// reachable by walking the tree down from the root until they are linked
+    /// again with [ `Path::ensure_relinked` ].
+    /// Returns the paths that were pruned, deepest first.
+    pub fn prune(&self) -> ExternResult<Vec<Self>> {
+        let agent = agent_info()?.agent_latest_pubkey;
+        let mut pruned = Vec::new();
+        let mut path = self.clone();
+        while let Some(parent) = path.parent() {
+            if !get_links(path.path_entry_hash()?, None)?.is_empty() {
+                break;
+            }
+            let mut linked_by_others = false;
+            for link in path.links_from_parent(&parent)? {
+                if *must_get_header(link.create_link_hash.clone())?
+                    .header()
+                    .author()
+                    == agent
+                {
+                    delete_link(link.create_link_hash)?;
+                } else {
+                    linked_by_others = true;
+                }
+            }
+            if linked_by_others {
+                break;
+            }
+            pruned.push(path);
+            path = parent;
+        }
+        Ok(pruned)
+    }
+
     pub fn children_details(&self) -> ExternResult<holochain_zome_types::link::LinkDetails> {
         Self::ensure(self)?;
         get_link_details(
@@ -385,6 +483,73 @@ impl Path {
     }
 }
 
+/// The tag on the link from a path's parent to the path itself.
+fn path_link_tag(path: &Path) -> ExternResult<LinkTag> {
+    Ok(LinkTag::new(match path.leaf() {
+        None => <Vec<u8>>::with_capacity(0),
+        Some(component) => UnsafeBytes::from(
+            SerializedBytes::try_from(component).map_err(|e| wasm_error!(e.into()))?,
+        )
+        .into(),
+    }))
+}
+
+/// A resumable depth first walk over the leaves below a [ `Path` ].
+///
+/// Leaves are returned in the order of their link tags, so zero padded or
+/// time bucketed components come back in order.
+/// The walk only holds the paths it has yet to visit so it can be serialized
+/// and handed back to a client, then picked up again in a later zome call
+/// with [ `PathLeaves::next_page` ].
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize, SerializedBytes)]
+pub struct PathLeaves {
+    stack: Vec<Path>,
+}
+
+impl PathLeaves {
+    /// Start a walk over the leaves below `root`.
+    pub fn new(root: Path) -> Self {
+        Self { stack: vec![root] }
+    }
+
+    /// True once every leaf has been returned.
+    pub fn is_done(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    /// The next `limit` leaves, or fewer if the walk finishes first.
+    pub fn next_page(&mut self, limit: usize) -> ExternResult<Vec<Path>> {
+        let mut page = Vec::with_capacity(limit);
+        while page.len() < limit {
+            match self.next() {
+                Some(leaf) => page.push(leaf?),
+                None => break,
+            }
+        }
+        Ok(page)
+    }
+}
+
+impl Iterator for PathLeaves {
+    type Item = ExternResult<Path>;
+
+    /// If listing the children of a path fails the path is kept so the walk
+    /// can be retried from the same place.
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(path) = self.stack.pop() {
+            match path.existing_children_paths() {
+                Ok(children) if children.is_empty() => return Some(Ok(path)),
+                Ok(children) => self.stack.extend(children.into_iter().rev()),
+                Err(e) => {
+                    self.stack.push(path);
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
 #[test]
 #[cfg(test)]
 fn hash_path_delimiter() {
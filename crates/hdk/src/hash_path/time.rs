@@ -0,0 +1,226 @@
+use crate::hash_path::path::Component;
+use crate::hash_path::path::Path;
+use crate::prelude::*;
+
+const MICROS_PER_MINUTE: i64 = 60_000_000;
+const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+const MICROS_PER_DAY: i64 = 24 * MICROS_PER_HOUR;
+
+/// How finely [ `Timestamp` ]s are bucketed into [ `Path` ]s.
+///
+/// Each bucket adds one component per unit of time, from the year down to the
+/// bucket size, e.g. a `Day` bucket is `[ "2022" "06" "08" ]`.
+/// Components are zero padded so sibling buckets sort chronologically.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+pub enum TimeBucket {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+}
+
+/// A UTC calendar date and time, to the minute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct CivilTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+}
+
+impl CivilTime {
+    fn from_timestamp(timestamp: Timestamp) -> Self {
+        let micros = timestamp.as_micros();
+        let (year, month, day) = civil_from_days(micros.div_euclid(MICROS_PER_DAY));
+        let micros_of_day = micros.rem_euclid(MICROS_PER_DAY);
+        Self {
+            year,
+            month,
+            day,
+            hour: (micros_of_day / MICROS_PER_HOUR) as u32,
+            minute: ((micros_of_day % MICROS_PER_HOUR) / MICROS_PER_MINUTE) as u32,
+        }
+    }
+
+    fn to_timestamp(self) -> Timestamp {
+        Timestamp::from_micros(
+            days_from_civil(self.year, self.month, self.day) * MICROS_PER_DAY
+                + self.hour as i64 * MICROS_PER_HOUR
+                + self.minute as i64 * MICROS_PER_MINUTE,
+        )
+    }
+}
+
+/// Days since 1970-01-01 to a proleptic gregorian (year, month, day).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = (if z >= 0 { z } else { z - 146_096 }) / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+/// A proleptic gregorian (year, month, day) to days since 1970-01-01.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let year_of_era = year - era * 400;
+    let month_from_march = if month > 2 { month - 3 } else { month + 9 } as i64;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+impl TimeBucket {
+    /// The components for the bucket that holds this timestamp.
+    pub fn components(&self, timestamp: Timestamp) -> Vec<Component> {
+        let time = CivilTime::from_timestamp(timestamp);
+        let mut components = vec![format!("{:04}", time.year)];
+        if *self >= TimeBucket::Month {
+            components.push(format!("{:02}", time.month));
+        }
+        if *self >= TimeBucket::Day {
+            components.push(format!("{:02}", time.day));
+        }
+        if *self >= TimeBucket::Hour {
+            components.push(format!("{:02}", time.hour));
+        }
+        if *self >= TimeBucket::Minute {
+            components.push(format!("{:02}", time.minute));
+        }
+        components.iter().map(Component::from).collect()
+    }
+
+    /// The path below `root` for the bucket that holds this timestamp.
+    pub fn path(&self, root: &Path, timestamp: Timestamp) -> Path {
+        let mut path = root.clone();
+        for component in self.components(timestamp) {
+            path.append_component(component);
+        }
+        path
+    }
+
+    /// The first timestamp in the bucket that holds this timestamp.
+    pub fn start_of(&self, timestamp: Timestamp) -> Timestamp {
+        let mut time = CivilTime::from_timestamp(timestamp);
+        if *self < TimeBucket::Minute {
+            time.minute = 0;
+        }
+        if *self < TimeBucket::Hour {
+            time.hour = 0;
+        }
+        if *self < TimeBucket::Day {
+            time.day = 1;
+        }
+        if *self < TimeBucket::Month {
+            time.month = 1;
+        }
+        time.to_timestamp()
+    }
+
+    /// The first timestamp in the bucket after the one that holds this timestamp.
+    pub fn next(&self, timestamp: Timestamp) -> Timestamp {
+        let start = self.start_of(timestamp);
+        match self {
+            TimeBucket::Minute => Timestamp::from_micros(start.as_micros() + MICROS_PER_MINUTE),
+            TimeBucket::Hour => Timestamp::from_micros(start.as_micros() + MICROS_PER_HOUR),
+            TimeBucket::Day => Timestamp::from_micros(start.as_micros() + MICROS_PER_DAY),
+            TimeBucket::Month => {
+                let mut time = CivilTime::from_timestamp(start);
+                if time.month == 12 {
+                    time.year += 1;
+                    time.month = 1;
+                } else {
+                    time.month += 1;
+                }
+                time.to_timestamp()
+            }
+            TimeBucket::Year => {
+                let mut time = CivilTime::from_timestamp(start);
+                time.year += 1;
+                time.to_timestamp()
+            }
+        }
+    }
+
+    /// The paths below `root` of every bucket from the one holding `start`
+    /// to the one holding `end`, inclusive, in chronological order.
+    pub fn paths_between(&self, root: &Path, start: Timestamp, end: Timestamp) -> Vec<Path> {
+        let mut paths = Vec::new();
+        let mut bucket = self.start_of(start);
+        while bucket <= end {
+            paths.push(self.path(root, bucket));
+            bucket = self.next(bucket);
+        }
+        paths
+    }
+}
+
+#[test]
+#[cfg(test)]
+fn hash_path_time_bucket() {
+    // 2022-02-28T23:59:30Z
+    let timestamp = Timestamp::from_micros(1_646_092_770_000_000);
+    let root = Path::from("events");
+
+    assert_eq!(
+        TimeBucket::Minute.path(&root, timestamp),
+        Path::from("events.2022.02.28.23.59"),
+    );
+    assert_eq!(
+        TimeBucket::Day.path(&root, timestamp),
+        Path::from("events.2022.02.28"),
+    );
+    assert_eq!(
+        TimeBucket::Year.path(&root, timestamp),
+        Path::from("events.2022")
+    );
+
+    assert_eq!(
+        TimeBucket::Day.next(timestamp),
+        // 2022-03-01T00:00:00Z
+        Timestamp::from_micros(1_646_092_800_000_000),
+    );
+    assert_eq!(
+        TimeBucket::Month.start_of(timestamp),
+        // 2022-02-01T00:00:00Z
+        Timestamp::from_micros(1_643_673_600_000_000),
+    );
+    // Before the unix epoch.
+    assert_eq!(
+        TimeBucket::Day.components(Timestamp::from_micros(-1)),
+        vec![
+            Component::from("1969"),
+            Component::from("12"),
+            Component::from("31")
+        ],
+    );
+
+    assert_eq!(
+        TimeBucket::Month.paths_between(
+            &root,
+            timestamp,
+            // 2022-04-01T00:00:00Z
+            Timestamp::from_micros(1_648_771_200_000_000)
+        ),
+        vec![
+            Path::from("events.2022.02"),
+            Path::from("events.2022.03"),
+            Path::from("events.2022.04"),
+        ],
+    );
+}
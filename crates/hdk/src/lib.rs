@@ -102,6 +102,10 @@
 //! Most externs are simply available to external processes and must be called explicitly e.g. via RPC over websockets.
 //! The external process only needs to ensure the input and output data is handled correctly as messagepack.
 //!
+//! Functions that only read data can be marked `#[hdk_extern(read_only)]`.
+//! The conductor runs read-only calls without committing to the source chain, so they never wait on other calls that write.
+//! Any attempt to write from a read-only function fails with an error.
+//!
 //! ## Internal callbacks
 //!
 //! Some externs function as callbacks the host will call at key points in Holochain internal system workflows.
@@ -6,7 +6,8 @@ use crate::prelude::*;
 /// same Cell but must be installed on the same conductor.
 ///
 /// ## Parameters
-/// - to_cell: The cell you want to call (If None will call the current cell).
+/// - to_cell: The cell you want to call. Use `CallTargetCell::Local` for the current cell
+///   or `CallTargetCell::OtherRole` for the cell with that role in the same app.
 /// - zome_name: The name of the zome you want to call.
 /// - fn_name: The name of the function in the zome you are calling.
 /// - cap_secret: The capability secret if required.
@@ -37,6 +37,8 @@ pub use crate::hash_path::anchor::list_anchor_type_addresses;
 pub use crate::hash_path::anchor::Anchor;
 pub use crate::hash_path::path::Path;
 pub use crate::hash_path::path::PathEntry;
+pub use crate::hash_path::path::PathLeaves;
+pub use crate::hash_path::time::TimeBucket;
 pub use crate::hdk::*;
 pub use crate::info::agent_info;
 pub use crate::info::call_info;
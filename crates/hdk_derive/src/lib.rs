@@ -206,17 +206,40 @@ pub fn hdk_extern(attrs: TokenStream, item: TokenStream) -> TokenStream {
 
     let internal_fn_ident = external_fn_ident.clone();
 
-    if attrs.to_string() == "infallible" {
-        (quote::quote! {
+    // e.g. `#[hdk_extern(infallible, read_only)]`
+    let attrs = attrs.to_string();
+    let attrs: Vec<&str> = attrs.split(',').map(str::trim).collect();
+
+    let map_extern = if attrs.contains(&"infallible") {
+        quote::quote! {
             map_extern_infallible!(#external_fn_ident, #internal_fn_ident, #input_type, #output_type);
-            #item_fn
-        })
-        .into()
+        }
     } else {
-        (quote::quote! {
+        quote::quote! {
             map_extern!(#external_fn_ident, #internal_fn_ident, #input_type, #output_type);
-            #item_fn
-        })
-        .into()
-    }
+        }
+    };
+
+    // The conductor finds read-only functions by this marker export when the
+    // DNA is installed and records them in the zome definition.
+    let read_only_marker = if attrs.contains(&"read_only") {
+        let marker_ident = quote::format_ident!(
+            "{}{}",
+            holochain_integrity_types::zome::READ_ONLY_FN_MARKER_PREFIX,
+            external_fn_ident
+        );
+        quote::quote! {
+            #[no_mangle]
+            pub extern "C" fn #marker_ident() {}
+        }
+    } else {
+        quote::quote! {}
+    };
+
+    (quote::quote! {
+        #map_extern
+        #read_only_marker
+        #item_fn
+    })
+    .into()
 }
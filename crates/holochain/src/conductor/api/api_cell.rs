@@ -169,6 +169,9 @@ pub trait CellConductorReadHandleT: Send + Sync {
 
     /// Get a zome from this cell's Dna
     fn get_zome(&self, dna_hash: &DnaHash, zome_name: &ZomeName) -> ConductorApiResult<Zome>;
+
+    /// Get the cell provisioned for a role in the app this cell belongs to
+    async fn find_cell_with_role(&self, role_id: &AppRoleId) -> ConductorApiResult<Option<CellId>>;
 }
 
 #[async_trait]
@@ -194,4 +197,11 @@ impl CellConductorReadHandleT for CellConductorApi {
     fn get_zome(&self, dna_hash: &DnaHash, zome_name: &ZomeName) -> ConductorApiResult<Zome> {
         CellConductorApiT::get_zome(self, dna_hash, zome_name)
     }
+
+    async fn find_cell_with_role(&self, role_id: &AppRoleId) -> ConductorApiResult<Option<CellId>> {
+        Ok(self
+            .conductor_handle
+            .find_cell_with_role_alongside_cell(&self.cell_id, role_id)
+            .await?)
+    }
 }
@@ -312,6 +312,17 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                 let dump = self.conductor_handle.dump_network_metrics(dna_hash).await?;
                 Ok(AdminResponse::NetworkMetricsDumped(dump))
             }
+            DumpFuelMetrics { dna_hash } => {
+                let dump =
+                    crate::core::ribosome::wasm_metering::dump_fuel_metrics(dna_hash.as_ref());
+                Ok(AdminResponse::FuelMetricsDumped(dump))
+            }
+            SetBandwidthLimits { scope, limits } => {
+                self.conductor_handle
+                    .set_bandwidth_limits(scope, limits)
+                    .await?;
+                Ok(AdminResponse::BandwidthLimitsSet)
+            }
             AddAgentInfo { agent_infos } => {
                 self.conductor_handle.add_agent_infos(agent_infos).await?;
                 Ok(AdminResponse::AgentInfoAdded)
@@ -5,6 +5,8 @@ use crate::conductor::api::error::SerializationError;
 use crate::conductor::interface::error::InterfaceError;
 use crate::conductor::interface::error::InterfaceResult;
 use crate::conductor::ConductorHandle;
+use crate::core::workflow::ZomeCallBatchResult;
+use crate::core::workflow::ZomeCallResult;
 
 use holochain_serialized_bytes::prelude::*;
 
@@ -79,25 +81,43 @@ impl AppInterfaceApi for RealAppInterfaceApi {
                     })
             }
             AppRequest::ZomeCall(call) => {
-                match self.conductor_handle.call_zome(*call.clone()).await? {
-                    Ok(ZomeCallResponse::Ok(output)) => Ok(AppResponse::ZomeCall(Box::new(output))),
-                    Ok(ZomeCallResponse::Unauthorized(_, _, _, _)) => Ok(AppResponse::Error(
-                        ExternalApiWireError::ZomeCallUnauthorized(format!(
-                            "No capabilities grant has been committed that allows the CapSecret {:?} to call the function {} in zome {}",
-                            call.cap_secret, call.fn_name, call.zome_name
-                        )),
-                    )),
-                    Ok(ZomeCallResponse::NetworkError(e)) => unreachable!(
-                        "Interface zome calls should never be routed to the network. This is a bug. Got {}",
-                        e
-                    ),
-                    Ok(ZomeCallResponse::CountersigningSession(e)) => Ok(AppResponse::Error(
-                        ExternalApiWireError::CountersigningSessionError(format!(
-                            "A countersigning session has failed to start on this zome call because: {}",
-                            e
-                        )),
+                let result = self.conductor_handle.call_zome(*call.clone()).await?;
+                match zome_call_output(&call, result) {
+                    Ok(output) => Ok(AppResponse::ZomeCall(Box::new(output))),
+                    Err(e) => Ok(AppResponse::Error(e)),
+                }
+            }
+            AppRequest::ZomeCallBatch(calls) => {
+                let ZomeCallBatchResult {
+                    results,
+                    commit_error,
+                } = self.conductor_handle.call_zome_batch(calls.clone()).await?;
+                let mut outputs = Vec::with_capacity(results.len());
+                // The batch stops at the first call that fails
+                for (index, (call, result)) in calls.iter().zip(results).enumerate() {
+                    match zome_call_output(call, result) {
+                        Ok(output) => outputs.push(output),
+                        Err(e) => {
+                            return Ok(AppResponse::Error(
+                                ExternalApiWireError::ZomeCallBatchFailed {
+                                    index,
+                                    outputs,
+                                    error: Box::new(e),
+                                },
+                            ))
+                        }
+                    }
+                }
+                match commit_error {
+                    // Every call succeeded but their writes weren't committed
+                    Some(e) => Ok(AppResponse::Error(
+                        ExternalApiWireError::ZomeCallBatchFailed {
+                            index: outputs.len(),
+                            outputs,
+                            error: Box::new(ExternalApiWireError::internal(e)),
+                        },
                     )),
-                    Err(e) => Ok(AppResponse::Error(e.into())),
+                    None => Ok(AppResponse::ZomeCallBatch(outputs)),
                 }
             }
             AppRequest::SignalSubscription(_) => Ok(AppResponse::Unimplemented(request)),
@@ -106,6 +126,34 @@ impl AppInterfaceApi for RealAppInterfaceApi {
     }
 }
 
+/// The output of a zome call made over the app interface, or the error to
+/// send back to the client.
+fn zome_call_output(
+    call: &ZomeCall,
+    result: ZomeCallResult,
+) -> Result<ExternIO, ExternalApiWireError> {
+    match result {
+        Ok(ZomeCallResponse::Ok(output)) => Ok(output),
+        Ok(ZomeCallResponse::Unauthorized(_, _, _, _)) => Err(
+            ExternalApiWireError::ZomeCallUnauthorized(format!(
+                "No capabilities grant has been committed that allows the CapSecret {:?} to call the function {} in zome {}",
+                call.cap_secret, call.fn_name, call.zome_name
+            )),
+        ),
+        Ok(ZomeCallResponse::NetworkError(e)) => unreachable!(
+            "Interface zome calls should never be routed to the network. This is a bug. Got {}",
+            e
+        ),
+        Ok(ZomeCallResponse::CountersigningSession(e)) => Err(
+            ExternalApiWireError::CountersigningSessionError(format!(
+                "A countersigning session has failed to start on this zome call because: {}",
+                e
+            )),
+        ),
+        Err(e) => Err(e.into()),
+    }
+}
+
 #[async_trait::async_trait]
 impl InterfaceApi for RealAppInterfaceApi {
     type ApiRequest = AppRequest;
@@ -31,6 +31,10 @@ pub enum ConductorApiError {
         call_cell_id: CellId,
     },
 
+    /// The calls in a zome call batch were to different cells.
+    #[error("All the calls in a zome call batch must be to the same cell, but calls were made to both {0:?} and {1:?}")]
+    ZomeCallBatchCellMismatch(CellId, CellId),
+
     /// Conductor threw an error during API call.
     #[error("Conductor returned an error while using a ConductorApi: {0:?}")]
     ConductorError(#[from] Box<ConductorError>),
@@ -19,14 +19,19 @@ use crate::core::queue_consumer::QueueTriggers;
 use crate::core::ribosome::guest_callback::init::InitResult;
 use crate::core::ribosome::real_ribosome::RealRibosome;
 use crate::core::ribosome::ZomeCallInvocation;
+use crate::core::workflow::call_zome_batch_workflow;
 use crate::core::workflow::call_zome_workflow;
+use crate::core::workflow::countersigning_workflow::countersigning_recovery;
 use crate::core::workflow::countersigning_workflow::countersigning_success;
+use crate::core::workflow::countersigning_workflow::incoming_countersigning;
 use crate::core::workflow::genesis_workflow::genesis_workflow;
 use crate::core::workflow::initialize_zomes_workflow;
+use crate::core::workflow::CallZomeBatchWorkflowArgs;
 use crate::core::workflow::CallZomeWorkflowArgs;
 use crate::core::workflow::GenesisWorkflowArgs;
 use crate::core::workflow::GenesisWorkspace;
 use crate::core::workflow::InitializeZomesWorkflowArgs;
+use crate::core::workflow::ZomeCallBatchResult;
 use crate::core::workflow::ZomeCallResult;
 use crate::{conductor::api::error::ConductorApiError, core::ribosome::RibosomeT};
 use error::CellError;
@@ -106,6 +111,7 @@ where
     holochain_p2p_cell: P2pCell,
     queue_triggers: QueueTriggers,
     init_mutex: tokio::sync::Mutex<()>,
+    countersigning_recovery: futures::future::AbortHandle,
 }
 
 impl Cell {
@@ -141,11 +147,39 @@ impl Cell {
                 holochain_p2p_cell.clone(),
                 &space,
                 conductor_handle.clone(),
-                managed_task_add_sender,
-                managed_task_stop_broadcaster,
+                managed_task_add_sender.clone(),
+                managed_task_stop_broadcaster.clone(),
             )
             .await;
 
+            // Resolve countersigning sessions that don't finish with the usual
+            // response. This is aborted when the cell is removed.
+            let (recovery_abort, abort_registration) = futures::future::AbortHandle::new_pair();
+            let handle = tokio::spawn({
+                let recovery = futures::future::Abortable::new(
+                    countersigning_recovery(
+                        space.clone(),
+                        holochain_p2p_cell.clone(),
+                        id.agent_pubkey().clone(),
+                        queue_triggers.clone(),
+                        conductor_api.signal_broadcaster().await,
+                    ),
+                    abort_registration,
+                );
+                let mut stop = managed_task_stop_broadcaster.subscribe();
+                async move {
+                    tokio::select! {
+                        _ = recovery => (),
+                        _ = stop.recv() => (),
+                    }
+                    Ok(())
+                }
+            });
+            managed_task_add_sender
+                .send(ManagedTaskAdd::ignore(handle, "countersigning_recovery"))
+                .await
+                .expect("Failed to manage countersigning recovery handle");
+
             Ok((
                 Self {
                     id,
@@ -155,6 +189,7 @@ impl Cell {
                     holochain_p2p_cell,
                     queue_triggers,
                     init_mutex: Default::default(),
+                    countersigning_recovery: recovery_abort,
                 },
                 initial_queue_triggers,
             ))
@@ -291,6 +326,7 @@ impl Cell {
                         payload,
                         provenance: self.id.agent_pubkey().clone(),
                         fn_name: scheduled_fn.fn_name().clone(),
+                        timeout_ms: None,
                     };
                     tasks.push(self.call_zome(invocation, None));
                 }
@@ -513,6 +549,17 @@ impl Cell {
                 .instrument(debug_span!("cell_handle_countersigning_response"))
                 .await;
             }
+            CountersigningEnzymePush { respond, op, .. } => {
+                async {
+                    let res = self
+                        .handle_countersigning_enzyme_push(op)
+                        .await
+                        .map_err(holochain_p2p::HolochainP2pError::other);
+                    respond.respond(Ok(async move { res }.boxed().into()));
+                }
+                .instrument(debug_span!("cell_handle_countersigning_enzyme_push"))
+                .await;
+            }
         }
         Ok(())
     }
@@ -535,6 +582,26 @@ impl Cell {
         .map_err(Box::new)?)
     }
 
+    #[instrument(skip(self, op))]
+    /// a signer is pushing their op to us because we are the
+    /// enzyme for their countersigning session
+    async fn handle_countersigning_enzyme_push(&self, op: DhtOp) -> CellResult<()> {
+        // Only gather sessions that name this cell as the enzyme.
+        // The op's signature and author are checked before it's stored.
+        if op.enzymatic_countersigning_enzyme() != Some(self.id.agent_pubkey()) {
+            return Ok(());
+        }
+        let hash = DhtOpHash::with_data_sync(&op);
+        Ok(incoming_countersigning(
+            vec![(hash, op)],
+            &self.space.countersigning_workspace,
+            &self.space.dht_db,
+            self.queue_triggers.countersigning.clone(),
+        )
+        .await
+        .map_err(Box::new)?)
+    }
+
     #[instrument(skip(self))]
     /// a remote node is attempting to retrieve a validation package
     #[tracing::instrument(skip(self), level = "trace")]
@@ -774,6 +841,7 @@ impl Cell {
             payload,
             provenance: from_agent,
             fn_name,
+            timeout_ms: None,
         };
         // double ? because
         // - ConductorApiResult
@@ -800,6 +868,12 @@ impl Cell {
 
         let keystore = self.conductor_api.keystore().clone();
 
+        let timeout = call.timeout_ms.map_or_else(
+            || self.conductor_handle.get_config().zome_call_timeout(),
+            std::time::Duration::from_millis,
+        );
+        let deadline = tokio::time::Instant::now() + timeout;
+
         let conductor_handle = self.conductor_handle.clone();
         let signal_tx = self.signal_broadcaster().await;
         let ribosome = self.get_ribosome().await?;
@@ -833,6 +907,7 @@ impl Cell {
             signal_tx,
             conductor_handle,
             is_root_zome_call,
+            deadline: Some(deadline),
         };
         Ok(call_zome_workflow(
             workspace_lock,
@@ -846,6 +921,58 @@ impl Cell {
         .map_err(Box::new)?)
     }
 
+    /// Make several zome calls on this cell in order and commit all of
+    /// their writes together, or none of them if any call fails.
+    #[instrument(skip(self, calls))]
+    pub async fn call_zome_batch(&self, calls: Vec<ZomeCall>) -> CellResult<ZomeCallBatchResult> {
+        self.check_or_run_zome_init().await?;
+
+        let keystore = self.conductor_api.keystore().clone();
+        let default_timeout = self.conductor_handle.get_config().zome_call_timeout();
+        let mut invocations = Vec::with_capacity(calls.len());
+        for call in calls {
+            let timeout = call
+                .timeout_ms
+                .map_or(default_timeout, std::time::Duration::from_millis);
+            let invocation =
+                ZomeCallInvocation::try_from_interface_call(self.conductor_api.clone(), call)
+                    .await?;
+            invocations.push((invocation, timeout));
+        }
+
+        let signal_tx = self.signal_broadcaster().await;
+        let ribosome = self.get_ribosome().await?;
+        let dna_def = ribosome.dna_def().as_content().clone();
+        let workspace = SourceChainWorkspace::new(
+            self.authored_db().clone(),
+            self.dht_db().clone(),
+            self.space.dht_query_cache.clone(),
+            self.cache().clone(),
+            keystore.clone(),
+            self.id.agent_pubkey().clone(),
+            Arc::new(dna_def),
+        )
+        .await?;
+
+        let args = CallZomeBatchWorkflowArgs {
+            cell_id: self.id.clone(),
+            ribosome,
+            invocations,
+            signal_tx,
+            conductor_handle: self.conductor_handle.clone(),
+        };
+        Ok(call_zome_batch_workflow(
+            workspace,
+            self.holochain_p2p_cell.clone(),
+            keystore,
+            args,
+            self.queue_triggers.publish_dht_ops.clone(),
+            self.queue_triggers.integrate_dht_ops.clone(),
+        )
+        .await
+        .map_err(Box::new)?)
+    }
+
     /// Check if each Zome's init callback has been run, and if not, run it.
     #[tracing::instrument(skip(self))]
     async fn check_or_run_zome_init(&self) -> CellResult<()> {
@@ -888,7 +1015,11 @@ impl Cell {
         trace!("running init");
 
         // Get the ribosome
-        let ribosome = RealRibosome::new(dna_file);
+        let limits = conductor_handle
+            .get_config()
+            .wasm_limits
+            .for_dna(id.dna_hash());
+        let ribosome = RealRibosome::new(dna_file).with_limits(limits);
 
         let signal_tx = self.signal_broadcaster().await;
 
@@ -924,6 +1055,7 @@ impl Cell {
     //        [ B-04176 ]
     pub async fn cleanup(&self) -> CellResult<()> {
         use holochain_p2p::HolochainP2pDnaT;
+        self.countersigning_recovery.abort();
         self.holochain_p2p_dna()
             .leave(self.id.agent_pubkey().clone())
             .await?;
@@ -934,7 +1066,12 @@ impl Cell {
     /// Instantiate a Ribosome for use by this Cell's workflows
     pub(crate) async fn get_ribosome(&self) -> CellResult<RealRibosome> {
         match self.conductor_api.get_dna(self.dna_hash()) {
-            Some(dna) => Ok(RealRibosome::new(dna)),
+            Some(dna) => Ok(RealRibosome::new(dna).with_limits(
+                self.conductor_handle
+                    .get_config()
+                    .wasm_limits
+                    .for_dna(self.dna_hash()),
+            )),
             None => Err(DnaError::DnaMissing(self.dna_hash().to_owned()).into()),
         }
     }
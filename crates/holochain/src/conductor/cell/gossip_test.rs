@@ -55,7 +55,8 @@ async fn signature_smoke_test() {
     network_config.transport_pool = vec![kitsune_p2p::TransportConfig::Mem {}];
     // Hit an actual bootstrap service so it can blow up and return an error if we get our end of
     // things totally wrong.
-    network_config.bootstrap_service = Some(url2::url2!("{}", kitsune_p2p::BOOTSTRAP_SERVICE_DEV));
+    network_config.bootstrap_service =
+        Some(url2::url2!("{}", kitsune_p2p::BOOTSTRAP_SERVICE_DEV).into());
     let zomes = vec![TestWasm::Anchor];
     let mut conductor_test =
         ConductorTestData::with_network_config(zomes.clone(), false, network_config.clone()).await;
@@ -1,3 +1,4 @@
+use crate::conductor::config::ConductorConfig;
 use crate::conductor::manager::spawn_task_manager;
 use crate::conductor::space::TestSpaces;
 use crate::core::ribosome::guest_callback::genesis_self_check::GenesisSelfCheckResult;
@@ -42,6 +43,9 @@ async fn test_cell_handle_publish() {
         .expect_get_queue_consumer_workflows()
         .return_const(spaces.queue_consumer_map.clone());
     mock_handle.expect_keystore().return_const(keystore.clone());
+    mock_handle
+        .expect_get_config()
+        .return_const(ConductorConfig::default());
 
     let mock_handle: crate::conductor::handle::ConductorHandle = Arc::new(mock_handle);
     let mut mock_ribosome = MockRibosomeT::new();
@@ -45,6 +45,7 @@ use crate::core::queue_consumer::QueueConsumerMap;
 use crate::core::ribosome::guest_callback::post_commit::PostCommitArgs;
 use crate::core::ribosome::guest_callback::post_commit::POST_COMMIT_CHANNEL_BOUND;
 use crate::core::ribosome::guest_callback::post_commit::POST_COMMIT_CONCURRENT_LIMIT;
+use crate::core::ribosome::wasm_metering;
 use crate::core::ribosome::RibosomeT;
 use crate::{
     conductor::api::error::ConductorApiResult, core::ribosome::real_ribosome::RealRibosome,
@@ -153,6 +154,10 @@ where
     /// Collection app interface data, keyed by id
     app_interfaces: RwShare<HashMap<AppInterfaceId, AppInterfaceRuntime>>,
 
+    /// The running apps, kept in step with the conductor state so that zome
+    /// calls can look up the app of a cell without reading the database.
+    running_apps: RwShare<HashMap<InstalledAppId, RunningApp>>,
+
     /// The channels and handles needed to interact with the task_manager task.
     /// If this is None, then the task manager has not yet been initialized.
     pub(super) task_manager: RwShare<Option<TaskManagerClient>>,
@@ -474,7 +479,10 @@ impl Conductor {
     pub(crate) fn get_ribosome(&self, dna_hash: &DnaHash) -> ConductorResult<RealRibosome> {
         self.dna_store
             .share_ref(|d| match d.get_dna_file(dna_hash) {
-                Some(dna) => Ok(RealRibosome::new(dna)),
+                Some(dna) => {
+                    Ok(RealRibosome::new(dna)
+                        .with_limits(self.config.wasm_limits.for_dna(dna_hash)))
+                }
                 None => Err(DnaError::DnaMissing(dna_hash.to_owned()).into()),
             })
     }
@@ -955,6 +963,112 @@ impl Conductor {
         Ok(zome_defs)
     }
 
+    /// Make sure the compiled modules for these dnas are in memory.
+    /// Modules are loaded from the wasm database if they were compiled
+    /// before, otherwise they are compiled and stored for the next startup.
+    /// Modules compiled by other versions of holochain are deleted.
+    pub(super) async fn load_compiled_wasm(&self, dnas: Vec<DnaFile>) -> ConductorResult<()> {
+        let db = self.spaces.wasm_db.clone();
+
+        // Every wasm that isn't in memory yet, with the memory limit
+        // of the dna that uses it.
+        let mut wanted = HashMap::new();
+        for dna in dnas {
+            let pages = self
+                .config
+                .wasm_limits
+                .for_dna(dna.dna_hash())
+                .max_memory_pages;
+            for (wasm_hash, wasm) in dna.code() {
+                if !wasm_metering::is_module_cached(
+                    wasm_metering::module_cache_key(wasm_hash),
+                    pages,
+                ) {
+                    wanted
+                        .entry((wasm_hash.clone(), pages))
+                        .or_insert_with(|| wasm.code());
+                }
+            }
+        }
+
+        let found = db
+            .async_reader({
+                let keys: Vec<_> = wanted.keys().cloned().collect();
+                move |txn| {
+                    let mut found = HashMap::new();
+                    for (wasm_hash, pages) in keys {
+                        let engine_key = wasm_metering::engine_key(pages);
+                        if let Some(compiled) =
+                            holochain_state::wasm::get_compiled(&txn, &wasm_hash, &engine_key)?
+                        {
+                            found.insert((wasm_hash, pages), compiled);
+                        }
+                    }
+                    StateQueryResult::Ok(found)
+                }
+            })
+            .await?;
+
+        let tasks = wanted.into_iter().map(|((wasm_hash, pages), code)| {
+            let serialized = found.get(&(wasm_hash.clone(), pages)).cloned();
+            tokio::task::spawn_blocking(move || {
+                let key = wasm_metering::module_cache_key(&wasm_hash);
+                if let Some(compiled) = serialized {
+                    // Safe because the blob was serialized by this version of
+                    // holochain for this engine, and deserializing checks it
+                    // against the hash stored with it.
+                    let module = unsafe {
+                        wasm_metering::deserialize_module(
+                            key,
+                            &compiled.blob,
+                            &compiled.blob_hash,
+                            pages,
+                        )
+                    };
+                    match module {
+                        Ok(_) => return None,
+                        Err(e) => tracing::warn!(%wasm_hash, ?e, "Recompiling wasm"),
+                    }
+                }
+                let compiled = wasm_metering::metered_module(key, &code, pages)
+                    .and_then(|module| wasm_metering::serialize_module(&module));
+                match compiled {
+                    Ok(bytes) => Some((wasm_hash, pages, bytes)),
+                    Err(e) => {
+                        tracing::warn!(%wasm_hash, ?e, "Failed to compile wasm");
+                        None
+                    }
+                }
+            })
+        });
+        let compiled = futures::future::try_join_all(tasks).await?;
+
+        db.async_commit(move |txn| {
+            let stale = holochain_state::wasm::delete_stale_compiled(
+                txn,
+                wasm_metering::HOLOCHAIN_VERSION,
+            )?;
+            if stale > 0 {
+                tracing::info!(
+                    "Deleted {} wasm modules compiled by other holochain versions",
+                    stale
+                );
+            }
+            for (wasm_hash, pages, bytes) in compiled.into_iter().flatten() {
+                holochain_state::wasm::put_compiled(
+                    txn,
+                    &wasm_hash,
+                    &wasm_metering::engine_key(pages),
+                    wasm_metering::HOLOCHAIN_VERSION,
+                    &bytes,
+                )?;
+            }
+            StateMutationResult::Ok(())
+        })
+        .await?;
+        Ok(())
+    }
+
     pub(super) fn list_cell_ids(&self, filter: Option<CellStatusFilter>) -> Vec<CellId> {
         self.cells.share_ref(|cells| {
             cells
@@ -1032,6 +1146,18 @@ impl Conductor {
             .collect())
     }
 
+    pub(super) fn find_cell_with_role_alongside_cell(
+        &self,
+        cell_id: &CellId,
+        role_id: &AppRoleId,
+    ) -> Option<CellId> {
+        self.running_apps.share_ref(|apps| {
+            apps.values()
+                .find(|app| app.all_cells().any(|i| i == cell_id))
+                .and_then(|app| app.role(role_id).ok()?.provisioned_cell().cloned())
+        })
+    }
+
     pub(super) fn print_setup(&self) {
         use std::fmt::Write;
         let mut out = String::new();
@@ -1264,6 +1390,7 @@ impl Conductor {
             config,
             shutting_down: Arc::new(AtomicBool::new(false)),
             app_interfaces: RwShare::new(HashMap::new()),
+            running_apps: RwShare::new(HashMap::new()),
             task_manager: RwShare::new(None),
             admin_websocket_ports: RwShare::new(Vec::new()),
             dna_store,
@@ -1331,7 +1458,8 @@ impl Conductor {
         O: Send + 'static,
     {
         self.check_running()?;
-        let output = self
+        let running_apps = self.running_apps.clone();
+        let (state, output) = self
             .spaces
             .conductor_db
             .async_commit(move |txn| {
@@ -1346,10 +1474,18 @@ impl Conductor {
                 };
                 let (new_state, output) = f(state)?;
                 mutations::insert_conductor_state(txn, (&new_state).try_into()?)?;
+                // Replaced while this is the only write to the state, so
+                // concurrent updates reach the cache in the order they commit.
+                running_apps.share_mut(|apps| {
+                    *apps = new_state
+                        .running_apps()
+                        .map(|(id, app)| (id.clone(), app))
+                        .collect();
+                });
                 Result::<_, ConductorError>::Ok((new_state, output))
             })
             .await?;
-        Ok(output)
+        Ok((state, output))
     }
 
     fn add_admin_port(&self, port: u16) {
@@ -5,6 +5,8 @@ use super::Conductor;
 use super::ConductorState;
 use super::*;
 use crate::conductor::api::error::ConductorApiError;
+use crate::conductor::api::AppInterfaceApi;
+use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::guest_callback::validate::ValidateResult;
 use crate::sweettest::*;
 use crate::test_utils::fake_valid_dna_file;
@@ -13,13 +15,16 @@ use crate::{
 };
 use ::fixt::prelude::*;
 use holochain_conductor_api::InstalledAppInfoStatus;
-use holochain_conductor_api::{AdminRequest, AdminResponse, AppRequest, AppResponse, ZomeCall};
+use holochain_conductor_api::{
+    AdminRequest, AdminResponse, AppRequest, AppResponse, ExternalApiWireError, ZomeCall,
+};
 use holochain_keystore::crude_mock_keystore::spawn_crude_mock_keystore;
 use holochain_keystore::crude_mock_keystore::spawn_real_or_mock_keystore;
 use holochain_state::prelude::{test_keystore, *};
 use holochain_types::test_utils::fake_cell_id;
 use holochain_wasm_test_utils::TestWasm;
 use holochain_websocket::WebsocketSender;
+use holochain_zome_types::inline_zome::BoxApi;
 use holochain_zome_types::op::Op;
 use kitsune_p2p_types::dependencies::lair_keystore_api_0_0::LairError;
 use maplit::hashset;
@@ -147,6 +152,83 @@ async fn can_add_clone_cell_to_app() {
     );
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_find_cell_with_role_alongside_clone_cell() {
+    let db_dir = test_db_dir();
+    let keystore = test_keystore();
+    let holochain_p2p = holochain_p2p::stub_network().await;
+
+    let agent = fixt!(AgentPubKey);
+    let dna_a = fake_valid_dna_file("a");
+    let dna_b = fake_valid_dna_file("b");
+    let cell_id_a = CellId::new(dna_a.dna_hash().to_owned(), agent.clone());
+    let cell_id_b = CellId::new(dna_b.dna_hash().to_owned(), agent.clone());
+
+    let dna_store = DnaStore::new();
+    let (post_commit_sender, _post_commit_receiver) =
+        tokio::sync::mpsc::channel(POST_COMMIT_CHANNEL_BOUND);
+    let spaces = Spaces::new(db_dir.path().to_path_buf().into(), Default::default()).unwrap();
+
+    let conductor = Conductor::new(
+        Default::default(),
+        dna_store,
+        keystore,
+        holochain_p2p,
+        spaces,
+        post_commit_sender,
+    )
+    .await
+    .unwrap();
+
+    let app = InstalledAppCommon::new(
+        "app",
+        agent,
+        vec![
+            (
+                "a".into(),
+                AppRoleAssignment::new(cell_id_a.clone(), true, 1),
+            ),
+            (
+                "b".into(),
+                AppRoleAssignment::new(cell_id_b.clone(), true, 0),
+            ),
+        ],
+    );
+    conductor.register_phenotype(dna_a);
+    conductor.register_phenotype(dna_b);
+    conductor
+        .update_state(move |mut state| {
+            state
+                .installed_apps_mut()
+                .insert(RunningApp::from(app.clone()).into());
+            Ok(state)
+        })
+        .await
+        .unwrap();
+
+    let clone_cell_id = conductor
+        .add_clone_cell_to_app("app".to_string(), "a".to_string(), ().into())
+        .await
+        .unwrap();
+
+    // - A clone cell finds the cells of the other roles in its app
+    assert_eq!(
+        conductor.find_cell_with_role_alongside_cell(&clone_cell_id, &"b".to_string()),
+        Some(cell_id_b.clone())
+    );
+    // - and the cell it was cloned from
+    assert_eq!(
+        conductor.find_cell_with_role_alongside_cell(&clone_cell_id, &"a".to_string()),
+        Some(cell_id_a)
+    );
+    // - A cell that isn't in a running app finds nothing
+    let other_cell_id = CellId::new(fixt!(DnaHash), fixt!(AgentPubKey));
+    assert_eq!(
+        conductor.find_cell_with_role_alongside_cell(&other_cell_id, &"b".to_string()),
+        None
+    );
+}
+
 /// App can't be installed if another app is already installed under the
 /// same InstalledAppId
 #[tokio::test(flavor = "multi_thread")]
@@ -416,6 +498,7 @@ async fn make_signing_call(client: &mut WebsocketSender, cell: &SweetCell) -> Ap
             payload: ExternIO::encode(()).unwrap(),
             cap_secret: None,
             provenance: cell.agent_pubkey().clone(),
+            timeout_ms: None,
         })))
         .await
         .unwrap()
@@ -666,6 +749,224 @@ async fn test_bad_entry_validation_after_genesis_returns_zome_call_error() {
     );
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zome_call_timeout_discards_writes() {
+    observability::test_run().ok();
+    let unit_entry_def = EntryDef::default_with_id("unit");
+    let zome = InlineZome::new_unique(vec![unit_entry_def.clone()])
+        .callback("slow_create", move |api, ()| {
+            let entry_def_id: EntryDefId = unit_entry_def.id.clone();
+            let entry = Entry::app(().try_into().unwrap()).unwrap();
+            api.create(CreateInput::new(
+                entry_def_id,
+                entry,
+                ChainTopOrdering::default(),
+            ))?;
+            std::thread::sleep(std::time::Duration::from_secs(2));
+            Ok(())
+        })
+        .callback("chain_len", |api, ()| {
+            Ok(api.query(ChainQueryFilter::new())?.len())
+        });
+
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let app = common_genesis_test_app(&mut conductor, zome).await.unwrap();
+    let (_, cell) = app.into_tuple();
+    let zome = cell.zome("custom");
+    let len_before: usize = conductor.call(&zome, "chain_len", ()).await;
+
+    let result = conductor
+        .handle()
+        .call_zome(ZomeCall {
+            cell_id: cell.cell_id().clone(),
+            zome_name: zome.name().clone(),
+            fn_name: "slow_create".into(),
+            payload: ExternIO::encode(()).unwrap(),
+            cap_secret: None,
+            provenance: cell.agent_pubkey().clone(),
+            timeout_ms: Some(100),
+        })
+        .await
+        .unwrap();
+
+    // - The call returns before the function finishes
+    assert_matches!(result, Err(RibosomeError::ZomeCallTimeout(..)));
+
+    // - Once the function does finish its entry is not committed
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    let len_after: usize = conductor.call(&zome, "chain_len", ()).await;
+    assert_eq!(len_before, len_after);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_nested_zome_call_timeout_discards_writes() {
+    observability::test_run().ok();
+    let unit_entry_def = EntryDef::default_with_id("unit");
+    let create_unit = move |api: &BoxApi| {
+        let entry_def_id: EntryDefId = unit_entry_def.id.clone();
+        let entry = Entry::app(().try_into().unwrap()).unwrap();
+        api.create(CreateInput::new(
+            entry_def_id,
+            entry,
+            ChainTopOrdering::default(),
+        ))
+    };
+    let create_then_call = create_unit.clone();
+    let zome = InlineZome::new_unique(vec![EntryDef::default_with_id("unit")])
+        .callback("slow_create", move |api, ()| {
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            // The call has timed out by now so this must be refused
+            create_unit(&api)?;
+            Ok(())
+        })
+        .callback("create_then_call", move |api, ()| {
+            create_then_call(&api)?;
+            api.call(vec![Call::new(
+                CallTarget::ConductorCell(CallTargetCell::Local),
+                "custom".into(),
+                "slow_create".into(),
+                None,
+                ExternIO::encode(()).unwrap(),
+            )])?;
+            Ok(())
+        })
+        .callback("chain_len", |api, ()| {
+            Ok(api.query(ChainQueryFilter::new())?.len())
+        });
+
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let app = common_genesis_test_app(&mut conductor, zome).await.unwrap();
+    let (_, cell) = app.into_tuple();
+    let zome = cell.zome("custom");
+    let len_before: usize = conductor.call(&zome, "chain_len", ()).await;
+
+    let result = conductor
+        .handle()
+        .call_zome(ZomeCall {
+            cell_id: cell.cell_id().clone(),
+            zome_name: zome.name().clone(),
+            fn_name: "create_then_call".into(),
+            payload: ExternIO::encode(()).unwrap(),
+            cap_secret: None,
+            provenance: cell.agent_pubkey().clone(),
+            timeout_ms: Some(100),
+        })
+        .await
+        .unwrap();
+
+    // - The outer call times out while the nested call is still running
+    assert_matches!(result, Err(RibosomeError::ZomeCallTimeout(..)));
+
+    // - Neither call's entry is committed, even once the nested function
+    //   wakes up and tries to write
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    let len_after: usize = conductor.call(&zome, "chain_len", ()).await;
+    assert_eq!(len_before, len_after);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zome_call_batch_is_all_or_nothing() {
+    observability::test_run().ok();
+    let unit_entry_def = EntryDef::default_with_id("unit");
+    let bad_entry_def = EntryDef::default_with_id("bad");
+    let zome = InlineZome::new_unique(vec![unit_entry_def.clone(), bad_entry_def.clone()])
+        .callback("validate", |_api, op: Op| match op {
+            Op::StoreEntry { header, .. }
+                if header.hashed.content.app_entry_type().map(|t| t.id())
+                    == Some(EntryDefIndex(1)) =>
+            {
+                Ok(ValidateResult::Invalid("bad entry".into()))
+            }
+            _ => Ok(ValidateResult::Valid),
+        })
+        .callback("create", move |api, ()| {
+            let entry_def_id: EntryDefId = unit_entry_def.id.clone();
+            let entry = Entry::app(().try_into().unwrap()).unwrap();
+            let hash = api.create(CreateInput::new(
+                entry_def_id,
+                entry,
+                ChainTopOrdering::default(),
+            ))?;
+            Ok(hash)
+        })
+        .callback("create_bad", move |api, ()| {
+            let entry_def_id: EntryDefId = bad_entry_def.id.clone();
+            let entry = Entry::app(().try_into().unwrap()).unwrap();
+            let hash = api.create(CreateInput::new(
+                entry_def_id,
+                entry,
+                ChainTopOrdering::default(),
+            ))?;
+            Ok(hash)
+        })
+        .callback("chain_len", |api, ()| {
+            Ok(api.query(ChainQueryFilter::new())?.len())
+        });
+
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let app = common_genesis_test_app(&mut conductor, zome).await.unwrap();
+    let (_, cell) = app.into_tuple();
+    let zome = cell.zome("custom");
+    let app_api = RealAppInterfaceApi::new(conductor.inner_handle());
+    let mk_call = |fn_name: &str| ZomeCall {
+        cell_id: cell.cell_id().clone(),
+        zome_name: zome.name().clone(),
+        fn_name: fn_name.into(),
+        payload: ExternIO::encode(()).unwrap(),
+        cap_secret: None,
+        provenance: cell.agent_pubkey().clone(),
+        timeout_ms: None,
+    };
+    let len_before: usize = conductor.call(&zome, "chain_len", ()).await;
+
+    // - A failing call stops the batch and nothing is committed
+    let response = app_api
+        .handle_app_request(AppRequest::ZomeCallBatch(vec![
+            mk_call("create"),
+            mk_call("create"),
+            mk_call("no_such_fn"),
+            mk_call("create"),
+        ]))
+        .await;
+    // - The outputs of the calls before the failed one are returned
+    assert_matches!(
+        response,
+        AppResponse::Error(ExternalApiWireError::ZomeCallBatchFailed { index: 2, ref outputs, .. })
+            if outputs.len() == 2
+    );
+    let len_after: usize = conductor.call(&zome, "chain_len", ()).await;
+    assert_eq!(len_before, len_after);
+
+    // - Invalid writes fail the batch after its last call and nothing is committed
+    let response = app_api
+        .handle_app_request(AppRequest::ZomeCallBatch(vec![
+            mk_call("create"),
+            mk_call("create_bad"),
+        ]))
+        .await;
+    assert_matches!(
+        response,
+        AppResponse::Error(ExternalApiWireError::ZomeCallBatchFailed { index: 2, ref outputs, .. })
+            if outputs.len() == 2
+    );
+    let len_after: usize = conductor.call(&zome, "chain_len", ()).await;
+    assert_eq!(len_before, len_after);
+
+    // - A successful batch commits every call and returns every output
+    let response = app_api
+        .handle_app_request(AppRequest::ZomeCallBatch(vec![
+            mk_call("create"),
+            mk_call("create"),
+        ]))
+        .await;
+    let outputs = unwrap_to::unwrap_to!(response => AppResponse::ZomeCallBatch).clone();
+    let hashes: Vec<HeaderHash> = outputs.iter().map(|o| o.decode().unwrap()).collect();
+    assert_eq!(hashes.len(), 2);
+    assert_ne!(hashes[0], hashes[1]);
+    let len_after: usize = conductor.call(&zome, "chain_len", ()).await;
+    assert_eq!(len_before + 2, len_after);
+}
+
 // NB: currently the pre-genesis and post-genesis handling of panics is the same.
 //   If we implement [ B-04188 ], then this test will be made more possible.
 //   Otherwise, we have to devise a way to discover whether a panic happened
@@ -980,3 +1281,107 @@ async fn test_init_concurrency() {
     assert_eq!(num_calls_clone.fetch_add(0, Ordering::SeqCst), 100);
     assert_eq!(num_inits_clone.fetch_add(0, Ordering::SeqCst), 1);
 }
+
+/// Compiled modules are loaded from the wasm database after a restart,
+/// and compiled again if they were stored for another engine.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_compiled_wasm_is_loaded_on_restart() {
+    use crate::conductor::config::WasmLimits;
+    use crate::core::ribosome::wasm_metering;
+    observability::test_run().ok();
+
+    let (dna, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Foo])
+        .await
+        .unwrap();
+    let (wasm_hash, wasm) = dna
+        .code()
+        .iter()
+        .map(|(hash, wasm)| (hash.clone(), wasm.code()))
+        .next()
+        .unwrap();
+    let key = wasm_metering::module_cache_key(&wasm_hash);
+    // Modules are cached in memory by wasm and memory limit for the whole
+    // process, so a limit no other test uses keeps them apart.
+    let pages = 12_345;
+    let engine_key = wasm_metering::engine_key(pages);
+
+    let mut config = standard_config();
+    config.wasm_limits.default = WasmLimits {
+        max_memory_pages: pages,
+        ..Default::default()
+    };
+    let mut conductor = SweetConductor::from_config(config).await;
+    conductor.setup_app("app", &[dna]).await.unwrap();
+    let wasm_db = conductor.get_spaces().wasm_db;
+
+    let get_compiled = || {
+        let wasm_hash = wasm_hash.clone();
+        let engine_key = engine_key.clone();
+        let wasm_db = wasm_db.clone();
+        async move {
+            wasm_db
+                .async_reader(move |txn| {
+                    holochain_state::wasm::get_compiled(&txn, &wasm_hash, &engine_key)
+                })
+                .await
+                .unwrap()
+        }
+    };
+    // Which module is in memory for the wasm.
+    let has_marker = || {
+        wasm_metering::is_module_cached(key, pages)
+            && wasm_metering::metered_module(key, &wasm, pages)
+                .unwrap()
+                .exports()
+                .any(|export| export.name() == "marker")
+    };
+
+    // The module was stored when the dna was installed.
+    assert!(get_compiled().await.is_some());
+
+    // Store a different module for the wasm, to tell
+    // a module loaded from the database from a compiled one.
+    let marker = wasmer::wat2wasm(br#"(module (func (export "marker")))"#).unwrap();
+    let marker = wasm_metering::metered_module([0xaa; 32], &marker, pages)
+        .and_then(|module| wasm_metering::serialize_module(&module))
+        .unwrap();
+    conductor.shutdown().await;
+    wasm_metering::forget_module(key, pages);
+    wasm_db
+        .async_commit({
+            let wasm_hash = wasm_hash.clone();
+            let engine_key = engine_key.clone();
+            let marker = marker.clone();
+            move |txn| {
+                holochain_state::wasm::put_compiled(
+                    txn,
+                    &wasm_hash,
+                    &engine_key,
+                    wasm_metering::HOLOCHAIN_VERSION,
+                    &marker,
+                )
+            }
+        })
+        .await
+        .unwrap();
+
+    conductor.startup().await;
+    assert!(has_marker());
+
+    // A module stored for another engine is not loaded.
+    conductor.shutdown().await;
+    wasm_metering::forget_module(key, pages);
+    wasm_db
+        .async_commit(|txn| {
+            txn.execute("UPDATE CompiledWasm SET engine_key = 'other-engine'", [])?;
+            StateMutationResult::Ok(())
+        })
+        .await
+        .unwrap();
+
+    conductor.startup().await;
+    assert!(wasm_metering::is_module_cached(key, pages));
+    assert!(!has_marker());
+    // The compiled module is stored for this engine again.
+    assert_ne!(get_compiled().await.unwrap().blob, marker);
+}
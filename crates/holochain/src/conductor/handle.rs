@@ -35,6 +35,7 @@
 //! types for testing. If we did not have a way of hiding this type genericity,
 //! code which interacted with the Conductor would also have to be highly generic.
 
+use super::api::error::ConductorApiError;
 use super::api::error::ConductorApiResult;
 use super::api::ZomeCall;
 use super::conductor::CellStatus;
@@ -61,6 +62,7 @@ use crate::core::queue_consumer::QueueConsumerMap;
 use crate::core::ribosome::guest_callback::post_commit::PostCommitArgs;
 use crate::core::ribosome::real_ribosome::RealRibosome;
 use crate::core::ribosome::RibosomeT;
+use crate::core::workflow::ZomeCallBatchResult;
 use crate::core::workflow::ZomeCallResult;
 use derive_more::From;
 use futures::future::FutureExt;
@@ -172,6 +174,13 @@ pub trait ConductorHandleT: Send + Sync {
         workspace_lock: SourceChainWorkspace,
     ) -> ConductorApiResult<ZomeCallResult>;
 
+    /// Invoke several zome functions on one Cell in order, committing all of
+    /// their writes together or none of them
+    async fn call_zome_batch(
+        &self,
+        calls: Vec<ZomeCall>,
+    ) -> ConductorApiResult<ZomeCallBatchResult>;
+
     /// Get a Websocket port which will
     fn get_arbitrary_admin_websocket_port(&self) -> Option<u16>;
 
@@ -298,6 +307,14 @@ pub trait ConductorHandleT: Send + Sync {
         dna_hash: &DnaHash,
     ) -> ConductorResult<HashSet<InstalledAppId>>;
 
+    /// Find the cell provisioned for a role in the running app which
+    /// contains the given cell, which may be a clone.
+    async fn find_cell_with_role_alongside_cell(
+        &self,
+        cell_id: &CellId,
+        role_id: &AppRoleId,
+    ) -> ConductorResult<Option<CellId>>;
+
     /// Dump the cells state
     async fn dump_cell_state(&self, cell_id: &CellId) -> ConductorApiResult<String>;
 
@@ -311,6 +328,13 @@ pub trait ConductorHandleT: Send + Sync {
     /// Dump the network metrics
     async fn dump_network_metrics(&self, dna_hash: Option<DnaHash>) -> ConductorApiResult<String>;
 
+    /// Change the limits of a network bandwidth budget
+    async fn set_bandwidth_limits(
+        &self,
+        scope: holochain_p2p::BandwidthLimitScope,
+        limits: kitsune_p2p::bandwidth::BandwidthLimits,
+    ) -> ConductorApiResult<()>;
+
     /// Access the broadcast Sender which will send a Signal across every
     /// attached app interface
     async fn signal_broadcaster(&self) -> SignalBroadcaster;
@@ -557,16 +581,27 @@ impl ConductorHandleT for ConductorHandleImpl {
 
     async fn register_dna(&self, dna: DnaFile) -> ConductorResult<()> {
         self.register_genotype(dna.clone()).await?;
-        self.conductor.register_phenotype(dna);
+        self.conductor.register_phenotype(dna.clone());
+        // The module cache is only an optimization
+        if let Err(e) = self.conductor.load_compiled_wasm(vec![dna]).await {
+            tracing::warn!(?e, "Failed to store compiled wasm");
+        }
         Ok(())
     }
 
     async fn load_dnas(&self) -> ConductorResult<()> {
         let (dnas, entry_defs) = self.conductor.load_wasms_into_dna_files().await?;
+        let dnas: Vec<_> = dnas.into_iter().collect();
+        let dna_files = dnas.iter().map(|(_, dna)| dna.clone()).collect();
         self.conductor.dna_store().share_mut(|ds| {
             ds.add_dnas(dnas);
             ds.add_entry_defs(entry_defs);
         });
+        // Compiling every zome can take minutes, so the modules compiled
+        // on previous runs are loaded before any cell starts
+        if let Err(e) = self.conductor.load_compiled_wasm(dna_files).await {
+            tracing::warn!(?e, "Failed to load compiled wasm");
+        }
         Ok(())
     }
 
@@ -705,6 +740,7 @@ impl ConductorHandleT for ConductorHandleImpl {
             }
             HolochainP2pEvent::CallRemote { .. }
             | CountersigningAuthorityResponse { .. }
+            | CountersigningEnzymePush { .. }
             | GetValidationPackage { .. }
             | Get { .. }
             | GetMeta { .. }
@@ -796,6 +832,29 @@ impl ConductorHandleT for ConductorHandleImpl {
         Ok(cell.call_zome(call, Some(workspace_lock)).await?)
     }
 
+    async fn call_zome_batch(
+        &self,
+        calls: Vec<ZomeCall>,
+    ) -> ConductorApiResult<ZomeCallBatchResult> {
+        let cell_id = match calls.first() {
+            Some(call) => call.cell_id.clone(),
+            None => {
+                return Ok(ZomeCallBatchResult {
+                    results: Vec::new(),
+                    commit_error: None,
+                })
+            }
+        };
+        if let Some(call) = calls.iter().find(|call| call.cell_id != cell_id) {
+            return Err(ConductorApiError::ZomeCallBatchCellMismatch(
+                cell_id,
+                call.cell_id.clone(),
+            ));
+        }
+        let cell = self.cell_by_id(&cell_id)?;
+        Ok(cell.call_zome_batch(calls).await?)
+    }
+
     fn take_shutdown_handle(&self) -> Option<TaskManagerRunHandle> {
         self.conductor.take_shutdown_handle()
     }
@@ -1104,6 +1163,16 @@ impl ConductorHandleT for ConductorHandleImpl {
             .await
     }
 
+    async fn find_cell_with_role_alongside_cell(
+        &self,
+        cell_id: &CellId,
+        role_id: &AppRoleId,
+    ) -> ConductorResult<Option<CellId>> {
+        Ok(self
+            .conductor
+            .find_cell_with_role_alongside_cell(cell_id, role_id))
+    }
+
     async fn dump_cell_state(&self, cell_id: &CellId) -> ConductorApiResult<String> {
         let cell = self.conductor.cell_by_id(cell_id)?;
         let authored_db = cell.authored_db();
@@ -1161,6 +1230,18 @@ impl ConductorHandleT for ConductorHandleImpl {
             .map_err(super::api::error::ConductorApiError::other)
     }
 
+    async fn set_bandwidth_limits(
+        &self,
+        scope: holochain_p2p::BandwidthLimitScope,
+        limits: kitsune_p2p::bandwidth::BandwidthLimits,
+    ) -> ConductorApiResult<()> {
+        use holochain_p2p::HolochainP2pSender;
+        self.holochain_p2p()
+            .set_bandwidth_limits(scope, limits)
+            .await
+            .map_err(super::api::error::ConductorApiError::other)
+    }
+
     async fn signal_broadcaster(&self) -> SignalBroadcaster {
         self.conductor.signal_broadcaster()
     }
@@ -1258,10 +1339,7 @@ impl ConductorHandleT for ConductorHandleImpl {
         // Validate the elements.
         if validate {
             // Create the ribosome.
-            let ribosome = self
-                .get_dna_file(cell_id.dna_hash())
-                .map(RealRibosome::new)
-                .ok_or_else(|| DnaError::DnaMissing(cell_id.dna_hash().to_owned()))?;
+            let ribosome = self.get_ribosome(cell_id.dna_hash())?;
 
             // Create a raw source chain to validate against because
             // genesis may not have been run yet.
@@ -392,9 +392,10 @@ impl Spaces {
         // send it to the countersigning workflow otherwise
         // send it to the incoming ops workflow.
         if countersigning_session {
-            let (workspace, trigger) = self.get_or_create_space_ref(dna_hash, |space| {
+            let (workspace, dht_db, trigger) = self.get_or_create_space_ref(dna_hash, |space| {
                 (
                     space.countersigning_workspace.clone(),
+                    space.dht_db.clone(),
                     self.queue_consumer_map
                         .countersigning_trigger(space.dna_hash.clone()),
                 )
@@ -404,7 +405,7 @@ impl Spaces {
                 // If the workflow has not been spawned yet we can't handle incoming messages.
                 None => return Ok(()),
             };
-            incoming_countersigning(ops, &workspace, trigger)?;
+            incoming_countersigning(ops, &workspace, &dht_db, trigger).await?;
         } else {
             let space = self.get_or_create_space(dna_hash)?;
             let trigger = match self
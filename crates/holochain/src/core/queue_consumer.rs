@@ -251,10 +251,17 @@ pub async fn spawn_queue_consumer_tasks(
         QueueTriggers {
             sys_validation: tx_sys.clone(),
             publish_dht_ops: tx_publish.clone(),
-            countersigning: tx_cs,
+            countersigning: tx_cs.clone(),
             integrate_dht_ops: tx_integration.clone(),
         },
-        InitialQueueTriggers::new(tx_sys, tx_publish, tx_app, tx_integration, tx_receipt),
+        InitialQueueTriggers::new(
+            tx_sys,
+            tx_publish,
+            tx_app,
+            tx_integration,
+            tx_receipt,
+            tx_cs,
+        ),
     )
 }
 
@@ -416,6 +423,7 @@ pub struct InitialQueueTriggers {
     app_validation: TriggerSender,
     integrate_dht_ops: TriggerSender,
     validation_receipt: TriggerSender,
+    countersigning: TriggerSender,
 }
 
 impl InitialQueueTriggers {
@@ -425,6 +433,7 @@ impl InitialQueueTriggers {
         app_validation: TriggerSender,
         integrate_dht_ops: TriggerSender,
         validation_receipt: TriggerSender,
+        countersigning: TriggerSender,
     ) -> Self {
         Self {
             sys_validation,
@@ -432,6 +441,7 @@ impl InitialQueueTriggers {
             app_validation,
             integrate_dht_ops,
             validation_receipt,
+            countersigning,
         }
     }
 
@@ -442,6 +452,8 @@ impl InitialQueueTriggers {
         self.integrate_dht_ops.trigger(&"init");
         self.publish_dht_ops.trigger(&"init");
         self.validation_receipt.trigger(&"init");
+        // Picks up any sessions that were being gathered before a restart.
+        self.countersigning.trigger(&"init");
     }
 }
 /// The means of nudging a queue consumer to tell it to look for more work
@@ -11,6 +11,7 @@ pub mod error;
 pub mod guest_callback;
 pub mod host_fn;
 pub mod real_ribosome;
+pub mod wasm_metering;
 
 use crate::conductor::api::CellConductorApi;
 use crate::conductor::api::CellConductorReadHandle;
@@ -27,7 +28,6 @@ use crate::core::ribosome::guest_callback::validate::ValidateResult;
 use crate::core::ribosome::guest_callback::validation_package::ValidationPackageInvocation;
 use crate::core::ribosome::guest_callback::validation_package::ValidationPackageResult;
 use crate::core::ribosome::guest_callback::CallIterator;
-use derive_more::Constructor;
 use error::RibosomeResult;
 use guest_callback::entry_defs::EntryDefsHostAccess;
 use guest_callback::init::InitHostAccess;
@@ -187,6 +187,24 @@ impl HostContext {
         }
     }
 
+    /// The instant at which the zome call is cancelled.
+    /// Only zome calls have a deadline.
+    pub fn deadline(&self) -> Option<tokio::time::Instant> {
+        match self {
+            Self::ZomeCall(ZomeCallHostAccess { deadline, .. }) => *deadline,
+            _ => None,
+        }
+    }
+
+    /// Is this a zome call that may not write,
+    /// because a read-only fn is in its call chain.
+    pub fn read_only(&self) -> bool {
+        match self {
+            Self::ZomeCall(ZomeCallHostAccess { read_only, .. }) => *read_only,
+            _ => false,
+        }
+    }
+
     /// Get the call zome handle, panics if none was provided
     pub fn call_zome_handle(&self) -> &CellConductorReadHandle {
         match self {
@@ -383,6 +401,7 @@ impl ZomeCallInvocation {
             cap_secret,
             payload,
             provenance,
+            ..
         } = call;
         let zome = conductor_api
             .get_zome(cell_id.dna_hash(), &zome_name)
@@ -415,17 +434,63 @@ impl From<ZomeCallInvocation> for ZomeCall {
             cap_secret,
             payload,
             provenance,
+            timeout_ms: None,
         }
     }
 }
 
-#[derive(Clone, Constructor)]
+#[derive(Clone)]
 pub struct ZomeCallHostAccess {
     pub workspace: HostFnWorkspace,
     pub keystore: MetaLairClient,
     pub network: HolochainP2pDna,
     pub signal_tx: SignalBroadcaster,
     pub call_zome_handle: CellConductorReadHandle,
+    /// When the zome call is cancelled, if it has a timeout.
+    pub deadline: Option<tokio::time::Instant>,
+    /// Stops the host fns of the zome call once it is cancelled.
+    pub cancel: host_fn::ZomeCallCancel,
+    /// The function was marked `#[hdk_extern(read_only)]` so it can't write.
+    pub read_only: bool,
+}
+
+impl ZomeCallHostAccess {
+    pub fn new(
+        workspace: HostFnWorkspace,
+        keystore: MetaLairClient,
+        network: HolochainP2pDna,
+        signal_tx: SignalBroadcaster,
+        call_zome_handle: CellConductorReadHandle,
+    ) -> Self {
+        Self {
+            workspace,
+            keystore,
+            network,
+            signal_tx,
+            call_zome_handle,
+            deadline: None,
+            cancel: Default::default(),
+            read_only: false,
+        }
+    }
+
+    /// Cancel the zome call's network requests once `deadline` passes.
+    pub fn with_deadline(mut self, deadline: Option<tokio::time::Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Stop the host fns of the zome call when `cancel` is cancelled.
+    pub fn with_cancel(mut self, cancel: host_fn::ZomeCallCancel) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Deny the host fns that write to the workspace.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
 }
 
 impl From<ZomeCallHostAccess> for HostContext {
@@ -435,8 +500,12 @@ impl From<ZomeCallHostAccess> for HostContext {
 }
 
 impl From<&ZomeCallHostAccess> for HostFnAccess {
-    fn from(_: &ZomeCallHostAccess) -> Self {
-        Self::all()
+    fn from(zome_call_host_access: &ZomeCallHostAccess) -> Self {
+        let mut access = Self::all();
+        if zome_call_host_access.read_only {
+            access.write_workspace = Permission::Deny;
+        }
+        access
     }
 }
 
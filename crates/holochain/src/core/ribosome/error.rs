@@ -95,6 +95,18 @@ pub enum RibosomeError {
     #[error(transparent)]
     SecurePrimitive(#[from] holochain_zome_types::SecurePrimitiveError),
 
+    /// A wasm call used up all the fuel it was given.
+    #[error("Zome {0} function {1} ran out of its {2} wasm fuel")]
+    WasmFuelExhausted(ZomeName, FunctionName, u64),
+
+    /// A zome call did not finish before its timeout and was cancelled.
+    #[error("Zome {0} function {1} timed out and was cancelled")]
+    ZomeCallTimeout(ZomeName, FunctionName),
+
+    /// A `call` targeted a role that isn't in the caller's app.
+    #[error("No cell with role {0} alongside cell {1}")]
+    CallTargetRoleNotFound(String, CellId),
+
     /// Zome function doesn't have permissions to call a Host function.
     #[error("Host function {2} cannot be called from zome function {1} in zome {0}")]
     HostFnPermissions(ZomeName, FunctionName, String),
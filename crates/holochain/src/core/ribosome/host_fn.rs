@@ -1,7 +1,12 @@
 use super::CallContext;
+use super::HostContext;
 use super::RibosomeT;
+use super::ZomeCallHostAccess;
 use holochain_types::prelude::*;
+use holochain_wasmer_host::prelude::*;
+use std::future::Future;
 use std::sync::Arc;
+use tokio::time::Instant;
 
 pub struct HostFnApi<Ribosome: RibosomeT> {
     ribosome: Arc<Ribosome>,
@@ -17,6 +22,76 @@ impl<Ribosome: RibosomeT> HostFnApi<Ribosome> {
     }
 }
 
+/// Block on the network requests of a host fn.
+/// If the zome call has a deadline the requests are cancelled when it
+/// passes and the host fn returns an error to the guest instead of waiting.
+pub(crate) fn block_on_network<T>(
+    deadline: Option<Instant>,
+    f: impl Future<Output = T>,
+) -> Result<T, RuntimeError> {
+    tokio_helper::block_forever_on(until_deadline(deadline, f))
+}
+
+/// Await a network request, giving up when the deadline passes.
+pub(crate) async fn until_deadline<T>(
+    deadline: Option<Instant>,
+    f: impl Future<Output = T>,
+) -> Result<T, RuntimeError> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, f)
+            .await
+            .map_err(|_| timed_out()),
+        None => Ok(f.await),
+    }
+}
+
+fn timed_out() -> RuntimeError {
+    wasm_error!(WasmErrorInner::Host("The zome call timed out".to_string())).into()
+}
+
+/// Stops a zome call that timed out from running host fns, so it can't
+/// write to its workspace once that was rolled back.
+///
+/// Host fns run while holding a read lock, cancelling takes the write
+/// lock so it waits for the host fns that are still running.
+#[derive(Clone, Default)]
+pub struct ZomeCallCancel(Arc<parking_lot::RwLock<bool>>);
+
+impl ZomeCallCancel {
+    /// Stop the zome call from running any more host fns,
+    /// once the ones running now have returned.
+    pub async fn cancel(&self) {
+        let cancelled = self.0.clone();
+        // Taking the lock blocks until the running host fns return.
+        let _ = tokio::task::spawn_blocking(move || *cancelled.write() = true).await;
+    }
+
+    fn run<O>(
+        &self,
+        deadline: Option<Instant>,
+        f: impl FnOnce() -> Result<O, RuntimeError>,
+    ) -> Result<O, RuntimeError> {
+        let cancelled = self.0.read();
+        if *cancelled || deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+            return Err(timed_out());
+        }
+        f()
+    }
+}
+
+/// Run a host fn, unless it's for a zome call that timed out.
+pub(crate) fn run_host_fn<O>(
+    call_context: &CallContext,
+    f: impl FnOnce() -> Result<O, RuntimeError>,
+) -> Result<O, RuntimeError> {
+    match &call_context.host_context {
+        HostContext::ZomeCall(ZomeCallHostAccess {
+            deadline, cancel, ..
+        }) => cancel.run(*deadline, f),
+        _ => f(),
+    }
+}
+
 macro_rules! host_fn_api_impls {
     ( $( fn $f:ident ( $input:ty ) -> $output:ty; )* ) => {
         $(
@@ -26,11 +101,11 @@ macro_rules! host_fn_api_impls {
         impl<Ribosome: RibosomeT> HostFnApiT for HostFnApi<Ribosome> {
             $(
                 fn $f(&self, input: $input) -> Result<$output, HostFnApiError> {
-                    $f::$f(
+                    run_host_fn(&self.call_context, || $f::$f(
                         self.ribosome.clone(),
                         self.call_context.clone(),
                         input.into()
-                    ).map_err(|e| HostFnApiError::RibosomeError(Box::new(e)))
+                    )).map_err(|e| HostFnApiError::RibosomeError(Box::new(e)))
                 }
             )*
         }
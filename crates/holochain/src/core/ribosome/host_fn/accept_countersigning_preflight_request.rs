@@ -112,10 +112,13 @@ pub fn accept_countersigning_preflight_request<'a>(
 pub mod wasm_test {
     use crate::conductor::api::error::ConductorApiError;
     use crate::conductor::api::ZomeCall;
+    use crate::conductor::handle::ConductorHandleT;
     use crate::conductor::CellError;
     use crate::core::ribosome::error::RibosomeError;
     use crate::core::ribosome::wasm_test::RibosomeTestFixture;
     use crate::core::workflow::error::WorkflowError;
+    use crate::sweettest::SweetConductor;
+    use crate::sweettest::SweetZome;
     use hdk::prelude::*;
     use holochain_state::source_chain::SourceChainError;
     use holochain_wasm_test_utils::TestWasm;
@@ -206,6 +209,7 @@ pub mod wasm_test {
                 cap_secret: None,
                 provenance: alice_pubkey.clone(),
                 payload: ExternIO::encode(()).unwrap(),
+                timeout_ms: None,
             })
             .await;
 
@@ -223,12 +227,186 @@ pub mod wasm_test {
                 provenance: alice_pubkey.clone(),
                 payload: ExternIO::encode(vec![alice_response.clone(), bob_response.clone()])
                     .unwrap(),
+                timeout_ms: None,
             })
             .await;
         assert!(matches!(countersign_fail_create_alice, Err(_)));
         let _: HeaderHash = conductor.call(&alice, "create_a_thing", ()).await;
     }
 
+    async fn create_a_thing(
+        conductor: &SweetConductor,
+        zome: &SweetZome,
+        provenance: &AgentPubKey,
+    ) -> Result<Result<ZomeCallResponse, RibosomeError>, ConductorApiError> {
+        conductor
+            .handle()
+            .call_zome(ZomeCall {
+                cell_id: zome.cell_id().clone(),
+                zome_name: zome.name().clone(),
+                fn_name: "create_a_thing".into(),
+                cap_secret: None,
+                provenance: provenance.clone(),
+                payload: ExternIO::encode(()).unwrap(),
+                timeout_ms: None,
+            })
+            .await
+    }
+
+    /// Keep trying to commit until the chain is unlocked.
+    async fn wait_for_unlock(
+        conductor: &SweetConductor,
+        zome: &SweetZome,
+        provenance: &AgentPubKey,
+    ) {
+        for _ in 0..200 {
+            match create_a_thing(conductor, zome, provenance).await {
+                Ok(Ok(ZomeCallResponse::Ok(_))) => return,
+                result => expect_chain_locked(result),
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        panic!("The chain was never unlocked");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[cfg(feature = "slow_tests")]
+    async fn enzymatic_session() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor,
+            alice,
+            alice_pubkey,
+            bob,
+            bob_pubkey,
+            ..
+        } = RibosomeTestFixture::new(TestWasm::CounterSigning).await;
+
+        // Alice is the enzyme for this session.
+        let preflight_request: PreflightRequest = conductor
+            .call(
+                &alice,
+                "generate_enzymatic_countersigning_preflight_request",
+                vec![
+                    (alice_pubkey.clone(), vec![Role(0)]),
+                    (bob_pubkey.clone(), vec![]),
+                ],
+            )
+            .await;
+        assert_eq!(preflight_request.enzyme(), Some(&alice_pubkey));
+
+        let mut responses = Vec::new();
+        for zome in [&alice, &bob] {
+            match conductor
+                .call(
+                    zome,
+                    "accept_countersigning_preflight_request",
+                    preflight_request.clone(),
+                )
+                .await
+            {
+                PreflightRequestAcceptance::Accepted(response) => responses.push(response),
+                _ => unreachable!(),
+            }
+        }
+
+        // Bob's header goes to alice, who can't complete the session
+        // without her own header so bob stays locked.
+        let _: HeaderHash = conductor
+            .call(&bob, "create_a_countersigned_thing", responses.clone())
+            .await;
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        expect_chain_locked(create_a_thing(&conductor, &bob, &bob_pubkey).await);
+
+        // Once the enzyme commits its own header the session is complete
+        // and both signers unlock.
+        let countersigned_header_hash_alice: HeaderHash = conductor
+            .call(&alice, "create_a_countersigned_thing", responses)
+            .await;
+        wait_for_unlock(&conductor, &alice, &alice_pubkey).await;
+        wait_for_unlock(&conductor, &bob, &bob_pubkey).await;
+
+        // The complete session was published so bob can get alice's element.
+        let _: SignedHeaderHashed = conductor
+            .call(&bob, "must_get_header", countersigned_header_hash_alice)
+            .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[cfg(feature = "slow_tests")]
+    async fn abandon_session() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor,
+            alice,
+            alice_pubkey,
+            bob,
+            bob_pubkey,
+            ..
+        } = RibosomeTestFixture::new(TestWasm::CounterSigning).await;
+
+        let preflight_request: PreflightRequest = conductor
+            .call(
+                &alice,
+                "generate_countersigning_preflight_request",
+                vec![
+                    (alice_pubkey.clone(), vec![Role(0)]),
+                    (bob_pubkey.clone(), vec![]),
+                ],
+            )
+            .await;
+        let mut responses = Vec::new();
+        for zome in [&alice, &bob] {
+            match conductor
+                .call(
+                    zome,
+                    "accept_countersigning_preflight_request",
+                    preflight_request.clone(),
+                )
+                .await
+            {
+                PreflightRequestAcceptance::Accepted(response) => responses.push(response),
+                _ => unreachable!(),
+            }
+        }
+
+        // Only alice commits so the session can never complete.
+        let countersigned_header_hash_alice: HeaderHash = conductor
+            .call(&alice, "create_a_countersigned_thing", responses)
+            .await;
+
+        // Alice stays locked until the session has ended and is abandoned.
+        expect_chain_locked(create_a_thing(&conductor, &alice, &alice_pubkey).await);
+        wait_for_unlock(&conductor, &alice, &alice_pubkey).await;
+
+        // The abandoned element is still on alice's chain but its ops are never published.
+        let authored_db = conductor
+            .handle()
+            .get_authored_db(alice.cell_id().dna_hash())
+            .unwrap();
+        let (headers, withheld): (i64, Vec<Option<i64>>) =
+            holochain_state::test_utils::fresh_reader_test(authored_db, |txn| {
+                let headers = txn
+                    .query_row(
+                        "SELECT COUNT(*) FROM Header WHERE hash = ?",
+                        [&countersigned_header_hash_alice],
+                        |row| row.get(0),
+                    )
+                    .unwrap();
+                let withheld = txn
+                    .prepare("SELECT withhold_publish FROM DhtOp WHERE header_hash = ?")
+                    .unwrap()
+                    .query_map([&countersigned_header_hash_alice], |row| row.get(0))
+                    .unwrap()
+                    .collect::<Result<_, _>>()
+                    .unwrap();
+                (headers, withheld)
+            });
+        assert_eq!(headers, 1);
+        assert!(!withheld.is_empty());
+        assert!(withheld.iter().all(|w| *w == Some(2)));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     #[cfg(feature = "slow_tests")]
     #[ignore = "flakey, line 422 gets 7 instead of 6"]
@@ -296,6 +474,7 @@ pub mod wasm_test {
                 cap_secret: None,
                 provenance: alice_pubkey.clone(),
                 payload: ExternIO::encode(&preflight_request_2).unwrap(),
+                timeout_ms: None,
             })
             .await;
         assert!(matches!(
@@ -328,6 +507,7 @@ pub mod wasm_test {
                 cap_secret: None,
                 provenance: alice_pubkey.clone(),
                 payload: ExternIO::encode(()).unwrap(),
+                timeout_ms: None,
             })
             .await;
         expect_chain_locked(thing_fail_create_alice);
@@ -341,6 +521,7 @@ pub mod wasm_test {
                 cap_secret: None,
                 provenance: bob_pubkey.clone(),
                 payload: ExternIO::encode(()).unwrap(),
+                timeout_ms: None,
             })
             .await;
         expect_chain_locked(thing_fail_create_bob);
@@ -363,6 +544,7 @@ pub mod wasm_test {
                 cap_secret: None,
                 provenance: alice_pubkey.clone(),
                 payload: ExternIO::encode(()).unwrap(),
+                timeout_ms: None,
             })
             .await;
         tokio::time::sleep(std::time::Duration::from_millis(500)).await;
@@ -393,6 +575,7 @@ pub mod wasm_test {
                 cap_secret: None,
                 provenance: bob_pubkey.clone(),
                 payload: ExternIO::encode(()).unwrap(),
+                timeout_ms: None,
             })
             .await;
         expect_chain_locked(thing_fail_create_bob);
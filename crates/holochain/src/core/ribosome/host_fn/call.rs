@@ -1,3 +1,4 @@
+use crate::core::ribosome::host_fn::until_deadline;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::HostFnAccess;
 use crate::core::ribosome::RibosomeError;
@@ -14,6 +15,8 @@ pub fn call(
     call_context: Arc<CallContext>,
     inputs: Vec<Call>,
 ) -> Result<Vec<ZomeCallResponse>, RuntimeError> {
+    // Only the remote calls are given up on at the deadline here, calls to
+    // cells on this conductor time out by themselves and roll back first.
     let results: Vec<Result<ZomeCallResponse, RuntimeError>> =
         tokio_helper::block_forever_on(async move {
             join_all(inputs.into_iter().map(|input| async {
@@ -28,7 +31,11 @@ pub fn call(
                     payload,
                 } = input;
 
-                match (&target, HostFnAccess::from(&call_context.host_context())) {
+                match (
+                    &target,
+                    HostFnAccess::from(&call_context.host_context()),
+                    call_context.host_context.read_only(),
+                ) {
                     (
                         CallTarget::ConductorCell(_),
                         HostFnAccess {
@@ -36,6 +43,17 @@ pub fn call(
                             agent_info: Permission::Allow,
                             ..
                         },
+                        _,
+                    )
+                    // A read-only fn can call fns on this conductor as they
+                    // share its workspace so they can't write either.
+                    | (
+                        CallTarget::ConductorCell(_),
+                        HostFnAccess {
+                            agent_info: Permission::Allow,
+                            ..
+                        },
+                        true,
                     )
                     | (
                         CallTarget::NetworkAgent(_),
@@ -44,6 +62,7 @@ pub fn call(
                             agent_info: Permission::Allow,
                             ..
                         },
+                        _,
                     ) => {
                         let provenance = call_context
                             .host_context
@@ -56,18 +75,18 @@ pub fn call(
 
                         let result: Result<ZomeCallResponse, RuntimeError> = match target {
                             CallTarget::NetworkAgent(target_agent) => {
-                                match call_context
-                                    .host_context()
-                                    .network()
-                                    .call_remote(
+                                match until_deadline(
+                                    call_context.host_context.deadline(),
+                                    call_context.host_context().network().call_remote(
                                         provenance,
                                         target_agent,
                                         zome_name,
                                         fn_name,
                                         cap_secret,
                                         payload,
-                                    )
-                                    .await
+                                    ),
+                                )
+                                .await?
                                 {
                                     Ok(serialized_bytes) => ZomeCallResponse::try_from(
                                         serialized_bytes,
@@ -77,14 +96,42 @@ pub fn call(
                                 }
                             }
                             CallTarget::ConductorCell(target_cell) => {
+                                let call_zome_handle =
+                                    call_context.host_context().call_zome_handle().clone();
                                 let cell_id = match target_cell {
                                     CallTargetCell::Other(cell_id) => cell_id,
-                                    CallTargetCell::Local => call_context
-                                        .host_context()
-                                        .call_zome_handle()
-                                        .cell_id()
-                                        .clone(),
+                                    CallTargetCell::OtherRole(role_id) => {
+                                        match call_zome_handle.find_cell_with_role(&role_id).await {
+                                            Ok(Some(cell_id)) => cell_id,
+                                            Ok(None) => {
+                                                return Err(wasm_error!(WasmErrorInner::Host(
+                                                    RibosomeError::CallTargetRoleNotFound(
+                                                        role_id,
+                                                        call_zome_handle.cell_id().clone(),
+                                                    )
+                                                    .to_string()
+                                                ))
+                                                .into())
+                                            }
+                                            Err(conductor_api_error) => {
+                                                return Err(wasm_error!(WasmErrorInner::Host(
+                                                    conductor_api_error.to_string()
+                                                ))
+                                                .into())
+                                            }
+                                        }
+                                    }
+                                    CallTargetCell::Local => call_zome_handle.cell_id().clone(),
                                 };
+                                // The called function gets whatever is left
+                                // of the caller's time.
+                                let timeout_ms =
+                                    call_context.host_context.deadline().map(|deadline| {
+                                        deadline
+                                            .saturating_duration_since(tokio::time::Instant::now())
+                                            .as_millis()
+                                            as u64
+                                    });
                                 let invocation = ZomeCall {
                                     cell_id,
                                     zome_name,
@@ -92,10 +139,9 @@ pub fn call(
                                     payload,
                                     cap_secret,
                                     provenance,
+                                    timeout_ms,
                                 };
-                                match call_context
-                                    .host_context()
-                                    .call_zome_handle()
+                                match call_zome_handle
                                     .call_zome(
                                         invocation,
                                         call_context
@@ -264,6 +310,58 @@ pub mod wasm_test {
         assert!(has_hash);
     }
 
+    /// test calling a cell in the same app by its role
+    #[tokio::test(flavor = "multi_thread")]
+    async fn role_call() {
+        observability::test_run().ok();
+
+        let (create_dna, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Create])
+            .await
+            .unwrap();
+        let (whoami_dna, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::WhoAmI])
+            .await
+            .unwrap();
+        let create_role_id = create_dna.dna_hash().to_string();
+
+        let mut conductor = SweetConductor::from_standard_config().await;
+        let app = conductor
+            .setup_app("app", &[create_dna, whoami_dna])
+            .await
+            .unwrap();
+        let (alice_create, alice_whoami) = app.into_tuple();
+
+        let header_hash: HeaderHash = conductor
+            .call(
+                &alice_whoami.zome(TestWasm::WhoAmI),
+                "call_create_entry_in_role",
+                create_role_id,
+            )
+            .await;
+
+        // The entry was written to alice's cell for that role
+        let has_hash: bool = fresh_reader_test(alice_create.authored_db().clone(), |txn| {
+            txn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM DhtOp WHERE header_hash = :hash)",
+                named_params! {
+                    ":hash": header_hash
+                },
+                |row| row.get(0),
+            )
+            .unwrap()
+        });
+        assert!(has_hash);
+
+        // A role that isn't in the app is an error
+        let result: Result<HeaderHash, _> = conductor
+            .call_fallible(
+                &alice_whoami.zome(TestWasm::WhoAmI),
+                "call_create_entry_in_role",
+                "no_such_role".to_string(),
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     /// we can call a fn on a remote
     async fn call_remote_test() {
@@ -97,7 +97,8 @@ pub fn create<'a>(
                             )
                             .await
                             .map_err(|source_chain_error| -> RuntimeError {
-                                wasm_error!(WasmErrorInner::Host(source_chain_error.to_string())).into()
+                                wasm_error!(WasmErrorInner::Host(source_chain_error.to_string()))
+                                    .into()
                             })
                     })
                 }
@@ -110,7 +111,8 @@ pub fn create<'a>(
                 "create".into(),
             )
             .to_string(),
-        )).into()),
+        ))
+        .into()),
     }
 }
 
@@ -235,6 +237,36 @@ pub mod wasm_test {
         assert_eq!(round_twice, vec![round.clone(), round],);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ribosome_read_only_create_entry_test() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor, alice, ..
+        } = RibosomeTestFixture::new(TestWasm::Create).await;
+
+        // a read only fn can't write to the source chain
+        let result: Result<HeaderHash, _> = conductor
+            .call_fallible(&alice, "read_only_create_entry", ())
+            .await;
+        assert!(result.is_err());
+
+        // nor can the fns it calls
+        let result: Result<ZomeCallResponse, _> = conductor
+            .call_fallible(&alice, "read_only_call_create_entry", ())
+            .await;
+        assert!(result.is_err());
+        let round: Option<Element> = conductor.call(&alice, "get_entry", ()).await;
+        assert!(round.is_none());
+
+        // but it can still signal
+        let _: () = conductor.call(&alice, "read_only_emit_signal", ()).await;
+
+        // and read
+        let _output: HeaderHash = conductor.call(&alice, "create_entry", ()).await;
+        let round: Option<Element> = conductor.call(&alice, "read_only_get_entry", ()).await;
+        assert!(round.is_some());
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     // TODO: rewrite with sweettest and check if still flaky.
     // maackle: this consistently passes for me with n = 37
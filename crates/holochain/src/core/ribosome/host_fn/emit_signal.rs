@@ -12,8 +12,9 @@ pub fn emit_signal(
     call_context: Arc<CallContext>,
     input: AppSignal,
 ) -> Result<(), RuntimeError> {
-    match HostFnAccess::from(&call_context.host_context()) {
-        HostFnAccess{ write_workspace: Permission::Allow, .. } => {
+    match (HostFnAccess::from(&call_context.host_context()), call_context.host_context.read_only()) {
+        // Read-only zome fns can't write but can still signal
+        (HostFnAccess{ write_workspace: Permission::Allow, .. }, _) | (_, true) => {
             let cell_id = CellId::new(
                 ribosome.dna_def().as_hash().clone(),
                 call_context.host_context.workspace().source_chain().as_ref().expect("Must have a source chain to emit signals").agent_pubkey().clone(),
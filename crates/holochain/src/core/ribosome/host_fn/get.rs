@@ -1,3 +1,4 @@
+use crate::core::ribosome::host_fn::block_on_network;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::HostFnAccess;
 use crate::core::ribosome::RibosomeError;
@@ -23,7 +24,7 @@ pub fn get<'a>(
             ..
         } => {
             let results: Vec<Result<Option<Element>, _>> =
-                tokio_helper::block_forever_on(async move {
+                block_on_network(call_context.host_context.deadline(), async move {
                     futures::stream::iter(inputs.into_iter().map(|input| async {
                         let GetInput {
                             any_dht_hash,
@@ -41,7 +42,7 @@ pub fn get<'a>(
                     .buffered(10)
                     .collect()
                     .await
-                });
+                })?;
             let results: Result<Vec<_>, RuntimeError> = results
                 .into_iter()
                 .map(|result| match result {
@@ -1,3 +1,4 @@
+use crate::core::ribosome::host_fn::block_on_network;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::RibosomeT;
 use holochain_cascade::Cascade;
@@ -36,8 +37,7 @@ pub fn get_agent_activity(
         // Get the network from the context
         let network = call_context.host_context.network().clone();
 
-        // timeouts must be handled by the network
-        tokio_helper::block_forever_on(async move {
+        block_on_network(call_context.host_context.deadline(), async move {
             let workspace = call_context.host_context.workspace();
             let mut cascade = Cascade::from_workspace_network(&workspace, network);
             let activity = cascade
@@ -46,7 +46,7 @@ pub fn get_agent_activity(
                 .map_err(|cascade_error| wasm_error!(WasmErrorInner::Host(cascade_error.to_string())))?;
 
                     Ok(activity.into())
-                })
+                })?
         },
         _ => Err(wasm_error!(WasmErrorInner::Host(RibosomeError::HostFnPermissions(
             call_context.zome.zome_name().clone(),
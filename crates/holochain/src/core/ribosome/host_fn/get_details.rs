@@ -1,3 +1,4 @@
+use crate::core::ribosome::host_fn::block_on_network;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::HostFnAccess;
 use crate::core::ribosome::RibosomeError;
@@ -20,7 +21,7 @@ pub fn get_details<'a>(
             ..
         } => {
             let results: Vec<Result<Option<Details>, _>> =
-                tokio_helper::block_forever_on(async move {
+                block_on_network(call_context.host_context.deadline(), async move {
                     join_all(inputs.into_iter().map(|input| async {
                         let GetInput {
                             any_dht_hash,
@@ -34,7 +35,7 @@ pub fn get_details<'a>(
                         .await
                     }))
                     .await
-                });
+                })?;
             let results: Result<Vec<_>, _> = results
                 .into_iter()
                 .map(|result| {
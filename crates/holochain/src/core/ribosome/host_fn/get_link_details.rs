@@ -1,3 +1,4 @@
+use crate::core::ribosome::host_fn::block_on_network;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::HostFnAccess;
 use crate::core::ribosome::RibosomeError;
@@ -20,29 +21,30 @@ pub fn get_link_details<'a>(
             read_workspace: Permission::Allow,
             ..
         } => {
-            let results: Vec<Result<Vec<_>, _>> = tokio_helper::block_forever_on(async move {
-                join_all(inputs.into_iter().map(|input| async {
-                    let GetLinksInput {
-                        base_address,
-                        tag_prefix,
-                    } = input;
-                    let zome_id = ribosome
-                        .zome_to_id(&call_context.zome)
-                        .expect("Failed to get ID for current zome.");
-                    let key = WireLinkKey {
-                        base: base_address,
-                        zome_id,
-                        tag: tag_prefix,
-                    };
-                    Cascade::from_workspace_network(
-                        &call_context.host_context.workspace(),
-                        call_context.host_context.network().to_owned(),
-                    )
-                    .get_link_details(key, GetLinksOptions::default())
+            let results: Vec<Result<Vec<_>, _>> =
+                block_on_network(call_context.host_context.deadline(), async move {
+                    join_all(inputs.into_iter().map(|input| async {
+                        let GetLinksInput {
+                            base_address,
+                            tag_prefix,
+                        } = input;
+                        let zome_id = ribosome
+                            .zome_to_id(&call_context.zome)
+                            .expect("Failed to get ID for current zome.");
+                        let key = WireLinkKey {
+                            base: base_address,
+                            zome_id,
+                            tag: tag_prefix,
+                        };
+                        Cascade::from_workspace_network(
+                            &call_context.host_context.workspace(),
+                            call_context.host_context.network().to_owned(),
+                        )
+                        .get_link_details(key, GetLinksOptions::default())
+                        .await
+                    }))
                     .await
-                }))
-                .await
-            });
+                })?;
             let results: Result<Vec<_>, RuntimeError> = results
                 .into_iter()
                 .map(|result| match result {
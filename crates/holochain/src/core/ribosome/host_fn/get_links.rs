@@ -1,3 +1,4 @@
+use crate::core::ribosome::host_fn::block_on_network;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::HostFnAccess;
 use crate::core::ribosome::RibosomeError;
@@ -23,33 +24,34 @@ pub fn get_links<'a>(
             read_workspace: Permission::Allow,
             ..
         } => {
-            let results: Vec<Result<Vec<Link>, _>> = tokio_helper::block_forever_on(async move {
-                futures::stream::iter(inputs.into_iter().map(|input| async {
-                    let GetLinksInput {
-                        base_address,
-                        tag_prefix,
-                    } = input;
-                    let zome_id = ribosome
-                        .zome_to_id(&call_context.zome)
-                        .expect("Failed to get ID for current zome.");
-                    let key = WireLinkKey {
-                        base: base_address,
-                        zome_id,
-                        tag: tag_prefix,
-                    };
-                    Cascade::from_workspace_network(
-                        &call_context.host_context.workspace(),
-                        call_context.host_context.network().to_owned(),
-                    )
-                    .dht_get_links(key, GetLinksOptions::default())
+            let results: Vec<Result<Vec<Link>, _>> =
+                block_on_network(call_context.host_context.deadline(), async move {
+                    futures::stream::iter(inputs.into_iter().map(|input| async {
+                        let GetLinksInput {
+                            base_address,
+                            tag_prefix,
+                        } = input;
+                        let zome_id = ribosome
+                            .zome_to_id(&call_context.zome)
+                            .expect("Failed to get ID for current zome.");
+                        let key = WireLinkKey {
+                            base: base_address,
+                            zome_id,
+                            tag: tag_prefix,
+                        };
+                        Cascade::from_workspace_network(
+                            &call_context.host_context.workspace(),
+                            call_context.host_context.network().to_owned(),
+                        )
+                        .dht_get_links(key, GetLinksOptions::default())
+                        .await
+                    }))
+                    // Limit concurrent calls to 10 as each call
+                    // can spawn multiple connections.
+                    .buffered(10)
+                    .collect()
                     .await
-                }))
-                // Limit concurrent calls to 10 as each call
-                // can spawn multiple connections.
-                .buffered(10)
-                .collect()
-                .await
-            });
+                })?;
             let results: Result<Vec<_>, RuntimeError> = results
                 .into_iter()
                 .map(|result| match result {
@@ -128,6 +130,73 @@ pub mod slow_tests {
         assert_eq!(links[1].target, foo_baz);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn hash_path_leaves() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor, alice, ..
+        } = RibosomeTestFixture::new(TestWasm::HashPath).await;
+
+        for path in ["a.b.d", "a.e", "a.b.c"] {
+            let _: () = conductor.call(&alice, "ensure", path.to_string()).await;
+        }
+
+        let leaves: Vec<Path> = conductor.call(&alice, "leaves", "a".to_string()).await;
+        assert_eq!(
+            leaves,
+            vec![Path::from("a.b.c"), Path::from("a.b.d"), Path::from("a.e")]
+        );
+
+        // A path without children is its own leaf.
+        let leaves: Vec<Path> = conductor.call(&alice, "leaves", "a.e".to_string()).await;
+        assert_eq!(leaves, vec![Path::from("a.e")]);
+
+        // Paging picks up where the last page stopped.
+        let (page, walk): (Vec<Path>, PathLeaves) = conductor
+            .call(&alice, "leaves_page", (PathLeaves::new(Path::from("a")), 2))
+            .await;
+        assert_eq!(page, vec![Path::from("a.b.c"), Path::from("a.b.d")]);
+        assert!(!walk.is_done());
+        let (page, walk): (Vec<Path>, PathLeaves) =
+            conductor.call(&alice, "leaves_page", (walk, 2)).await;
+        assert_eq!(page, vec![Path::from("a.e")]);
+        assert!(walk.is_done());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn hash_path_prune() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor, alice, ..
+        } = RibosomeTestFixture::new(TestWasm::HashPath).await;
+
+        for path in ["a.b.c", "a.d"] {
+            let _: () = conductor.call(&alice, "ensure", path.to_string()).await;
+        }
+
+        // `a` still has `a.d` below it so pruning stops there.
+        let pruned: Vec<Path> = conductor.call(&alice, "prune", "a.b.c".to_string()).await;
+        assert_eq!(pruned, vec![Path::from("a.b.c"), Path::from("a.b")]);
+        let leaves: Vec<Path> = conductor.call(&alice, "leaves", "a".to_string()).await;
+        assert_eq!(leaves, vec![Path::from("a.d")]);
+
+        // A path with children is not pruned.
+        let pruned: Vec<Path> = conductor.call(&alice, "prune", "a".to_string()).await;
+        assert!(pruned.is_empty());
+
+        // Ensuring a pruned path that exists doesn't look for its links.
+        let _: () = conductor.call(&alice, "ensure", "a.b.c".to_string()).await;
+        let leaves: Vec<Path> = conductor.call(&alice, "leaves", "a".to_string()).await;
+        assert_eq!(leaves, vec![Path::from("a.d")]);
+
+        // Relinking a pruned path links it back into the tree.
+        let _: () = conductor
+            .call(&alice, "ensure_relinked", "a.b.c".to_string())
+            .await;
+        let leaves: Vec<Path> = conductor.call(&alice, "leaves", "a".to_string()).await;
+        assert_eq!(leaves, vec![Path::from("a.b.c"), Path::from("a.d")]);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn hash_path_anchor_list_anchors() {
         observability::test_run().ok();
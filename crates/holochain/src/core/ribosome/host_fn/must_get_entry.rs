@@ -1,3 +1,4 @@
+use crate::core::ribosome::host_fn::block_on_network;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::HostContext;
 use crate::core::ribosome::RibosomeError;
@@ -20,8 +21,7 @@ pub fn must_get_entry<'a>(
             ..
         } => {
             let entry_hash = input.into_inner();
-            // timeouts must be handled by the network
-            tokio_helper::block_forever_on(async move {
+            block_on_network(call_context.host_context.deadline(), async move {
                 let workspace = call_context.host_context.workspace();
                 let mut cascade = match call_context.host_context {
                     HostContext::Validate(_) => Cascade::from_workspace(workspace.stores(), None),
@@ -89,7 +89,7 @@ pub fn must_get_entry<'a>(
                     },
                 };
                 result
-            })
+            })?
         }
         _ => Err(wasm_error!(WasmErrorInner::Host(
             RibosomeError::HostFnPermissions(
@@ -5,9 +5,11 @@ use super::guest_callback::post_commit::PostCommitHostAccess;
 use super::guest_callback::validate::ValidateHostAccess;
 use super::guest_callback::validation_package::ValidationPackageHostAccess;
 use super::host_fn::get_agent_activity::get_agent_activity;
+use super::host_fn::run_host_fn;
 use super::host_fn::HostFnApi;
 use super::HostContext;
 use super::ZomeCallHostAccess;
+use crate::conductor::config::WasmLimits;
 use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::error::RibosomeResult;
 use crate::core::ribosome::guest_callback::entry_defs::EntryDefsInvocation;
@@ -64,6 +66,7 @@ use crate::core::ribosome::host_fn::x_25519_x_salsa20_poly1305_encrypt::x_25519_
 use crate::core::ribosome::host_fn::x_salsa20_poly1305_decrypt::x_salsa20_poly1305_decrypt;
 use crate::core::ribosome::host_fn::x_salsa20_poly1305_encrypt::x_salsa20_poly1305_encrypt;
 use crate::core::ribosome::host_fn::zome_info::zome_info;
+use crate::core::ribosome::wasm_metering;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::Invocation;
 use crate::core::ribosome::RibosomeT;
@@ -86,6 +89,8 @@ pub struct RealRibosome {
     //      - is already in the wasm cache, and only include the DnaDef portion
     //      - here in the ribosome.
     pub dna_file: DnaFile,
+    /// The fuel and memory wasm calls for this dna may use.
+    pub limits: WasmLimits,
 }
 
 struct HostFnBuilder {
@@ -150,8 +155,11 @@ impl HostFnBuilder {
                             })
                             .clone()
                     };
+                    let call_context = Arc::clone(&context_arc);
                     let result = match db.consume_bytes_from_guest(guest_ptr, len) {
-                        Ok(input) => host_function(Arc::clone(&ribosome_arc), context_arc, input),
+                        Ok(input) => run_host_fn(&call_context, || {
+                            host_function(Arc::clone(&ribosome_arc), context_arc, input)
+                        }),
                         Err(runtime_error) => Result::<_, RuntimeError>::Err(runtime_error),
                     };
                     Ok(vec![Value::I64(i64::from_le_bytes(
@@ -214,9 +222,18 @@ fn context_key_from_key(key: &[u8; 32]) -> u64 {
 }
 
 impl RealRibosome {
-    /// Create a new instance
+    /// Create a new instance with the default wasm limits
     pub fn new(dna_file: DnaFile) -> Self {
-        Self { dna_file }
+        Self {
+            dna_file,
+            limits: WasmLimits::default(),
+        }
+    }
+
+    /// Use these limits for all wasm calls
+    pub fn with_limits(mut self, limits: WasmLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     pub fn dna_file(&self) -> &DnaFile {
@@ -224,9 +241,10 @@ impl RealRibosome {
     }
 
     pub fn module(&self, zome_name: &ZomeName) -> RibosomeResult<Arc<Module>> {
-        Ok(holochain_wasmer_host::module::MODULE_CACHE.write().get(
+        Ok(wasm_metering::metered_module(
             self.wasm_cache_key(zome_name)?,
             &*self.dna_file.get_wasm_for_zome(zome_name)?.code(),
+            self.limits.max_memory_pages,
         )?)
     }
 
@@ -234,15 +252,9 @@ impl RealRibosome {
         // TODO: make this actually the hash of the wasm once we can do that
         // watch out for cache misses in the tests that make things slooow if you change this!
         // format!("{}{}", &self.dna.dna_hash(), zome_name).into_bytes()
-        let mut key = [0; 32];
-        let bytes = self
-            .dna_file
-            .dna()
-            .get_wasm_zome(zome_name)?
-            .wasm_hash
-            .get_raw_32();
-        key.copy_from_slice(bytes);
-        Ok(key)
+        Ok(wasm_metering::module_cache_key(
+            &self.dna_file.dna().get_wasm_zome(zome_name)?.wasm_hash,
+        ))
     }
 
     pub fn cache_instance(
@@ -545,7 +557,9 @@ impl RibosomeT for RealRibosome {
                     // there is a callback to_call and it is implemented in the wasm
                     // it is important to fully instantiate this (e.g. don't try to use the module above)
                     // because it builds guards against memory leaks and handles imports correctly
+                    let fuel = wasm_metering::fuel_limit(&self.limits, &call_context.host_context);
                     let (instance, context_key) = self.instance(call_context)?;
+                    wasm_metering::set_fuel(&instance, fuel);
 
                     let result: Result<ExternIO, RuntimeError> = holochain_wasmer_host::guest::call(
                         instance.clone(),
@@ -556,6 +570,25 @@ impl RibosomeT for RealRibosome {
                         invocation.to_owned().host_input()?,
                     );
 
+                    let remaining = wasm_metering::remaining_fuel(&instance);
+                    wasm_metering::record_fuel(
+                        self.dna_file.dna_hash(),
+                        zome.zome_name(),
+                        to_call,
+                        fuel,
+                        remaining,
+                    );
+                    if remaining.is_none() {
+                        // The instance stopped at an arbitrary instruction
+                        // so it must not be reused.
+                        CONTEXT_MAP.lock().remove(&context_key);
+                        return Err(RibosomeError::WasmFuelExhausted(
+                            zome.zome_name().clone(),
+                            to_call.clone(),
+                            fuel,
+                        ));
+                    }
+
                     // Cache this instance.
                     self.cache_instance(context_key, instance, zome.zome_name())?;
 
@@ -726,6 +759,7 @@ pub mod wasm_test {
                 cap_secret: None,
                 provenance: alice_pubkey.clone(),
                 payload: ExternIO::encode(()).unwrap(),
+                timeout_ms: None,
             })
             .await
             .unwrap()
@@ -0,0 +1,303 @@
+//! Deterministic fuel metering and memory limits for zome wasm.
+//!
+//! Every wasm operator costs one unit of fuel, so a callback runs out of fuel
+//! on the same instruction on every node. Modules are compiled with the
+//! metering middleware and a memory limit baked in, the fuel for a call is set
+//! on the instance right before the call.
+//!
+//! Compiling is slow, so the conductor persists compiled modules keyed by
+//! their wasm hash and [`engine_key`] and loads them back on startup.
+
+use super::HostContext;
+use crate::conductor::config::WasmLimits;
+use holochain_types::dna::wasm_metering::metered_store;
+use holochain_types::dna::wasm_metering::METERING_VERSION;
+use holochain_types::prelude::*;
+use holochain_wasmer_host::prelude::*;
+use lru::LruCache;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+use wasmer::Target;
+
+/// The version of holochain that compiled modules are tied to.
+pub const HOLOCHAIN_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The most compiled modules kept in memory. The least recently used
+/// module is dropped first and compiled again when it is needed.
+pub const MAX_METERED_MODULES: usize = 128;
+
+/// Compiled metered modules by wasm cache key and memory limit.
+static METERED_MODULES: Lazy<Mutex<LruCache<([u8; 32], u32), Arc<Module>>>> =
+    Lazy::new(|| Mutex::new(LruCache::new(MAX_METERED_MODULES)));
+
+/// Fuel used per dna, zome and function since the conductor started.
+static FUEL_METRICS: Lazy<RwLock<HashMap<FuelMetricKey, FuelMetric>>> = Lazy::new(Default::default);
+
+type FuelMetricKey = (DnaHash, ZomeName, FunctionName);
+
+/// The fuel used by calls to one zome function.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct FuelMetric {
+    /// The number of calls.
+    pub calls: u64,
+    /// The fuel used by all calls.
+    pub fuel_used: u64,
+    /// The most fuel used by a single call.
+    pub max_fuel_used: u64,
+    /// The number of calls that ran out of fuel.
+    pub exhausted: u64,
+}
+
+/// A snapshot of the fuel used by every zome function called so far.
+pub fn fuel_metrics() -> Vec<(DnaHash, ZomeName, FunctionName, FuelMetric)> {
+    FUEL_METRICS
+        .read()
+        .iter()
+        .map(|((dna, zome, func), metric)| (dna.clone(), zome.clone(), func.clone(), *metric))
+        .collect()
+}
+
+/// The fuel metrics as a JSON blob, optionally only those of one dna.
+pub fn dump_fuel_metrics(dna_hash: Option<&DnaHash>) -> String {
+    let metrics: Vec<_> = fuel_metrics()
+        .into_iter()
+        .filter(|(dna, ..)| dna_hash.map_or(true, |dna_hash| dna == dna_hash))
+        .map(|(dna, zome, func, metric)| {
+            serde_json::json!({
+                "dna_hash": dna.to_string(),
+                "zome_name": zome.to_string(),
+                "fn_name": func.to_string(),
+                "metric": metric,
+            })
+        })
+        .collect();
+    serde_json::Value::from(metrics).to_string()
+}
+
+/// The fuel a call may burn in the given context.
+pub fn fuel_limit(limits: &WasmLimits, host_context: &HostContext) -> u64 {
+    match host_context {
+        HostContext::ZomeCall(_) => limits.zome_call_fuel,
+        HostContext::Validate(_) => limits.validate_fuel,
+        HostContext::Init(_) => limits.init_fuel,
+        HostContext::EntryDefs(_)
+        | HostContext::GenesisSelfCheck(_)
+        | HostContext::MigrateAgent(_)
+        | HostContext::PostCommit(_)
+        | HostContext::ValidationPackage(_) => limits.callback_fuel,
+    }
+}
+
+/// The key of a wasm in the module cache.
+pub fn module_cache_key(wasm_hash: &WasmHash) -> [u8; 32] {
+    let mut key = [0; 32];
+    key.copy_from_slice(wasm_hash.get_raw_32());
+    key
+}
+
+/// Identifies everything a compiled module depends on apart from its wasm.
+/// A module compiled under one engine key can't be loaded under another.
+pub fn engine_key(max_memory_pages: u32) -> String {
+    let target = Target::default();
+    format!(
+        "holochain-{}-wasmer-{}-metering-{}-cranelift-{}-{:?}-pages-{}",
+        HOLOCHAIN_VERSION,
+        wasmer::VERSION,
+        METERING_VERSION,
+        target.triple(),
+        target.cpu_features(),
+        max_memory_pages
+    )
+}
+
+/// Is the metered module for this wasm and memory limit in memory already?
+pub fn is_module_cached(key: [u8; 32], max_memory_pages: u32) -> bool {
+    METERED_MODULES.lock().contains(&(key, max_memory_pages))
+}
+
+/// Drop a module from memory, as if the conductor had just started.
+#[cfg(test)]
+pub fn forget_module(key: [u8; 32], max_memory_pages: u32) {
+    METERED_MODULES.lock().pop(&(key, max_memory_pages));
+}
+
+/// Serialize a compiled module so it can be persisted.
+pub fn serialize_module(module: &Module) -> Result<Vec<u8>, RuntimeError> {
+    module
+        .serialize()
+        .map_err(|e| -> RuntimeError { wasm_error!(WasmErrorInner::Compile(e.to_string())).into() })
+}
+
+/// Load a module serialized by [`serialize_module`] into the module cache.
+/// Fails without loading anything if the blake2b-256 hash of `bytes`
+/// isn't `checksum`.
+///
+/// # Safety
+/// The machine code in `bytes` is not validated, so it must have been
+/// serialized under the same [`engine_key`] and `checksum` must have been
+/// taken when it was serialized.
+pub unsafe fn deserialize_module(
+    key: [u8; 32],
+    bytes: &[u8],
+    checksum: &[u8],
+    max_memory_pages: u32,
+) -> Result<Arc<Module>, RuntimeError> {
+    if holo_hash::encode::blake2b_256(bytes) != checksum {
+        return Err(wasm_error!(WasmErrorInner::Compile(
+            "Compiled wasm does not match its checksum".to_string()
+        ))
+        .into());
+    }
+    let module = Arc::new(
+        Module::deserialize(&metered_store(max_memory_pages), bytes).map_err(
+            |e| -> RuntimeError { wasm_error!(WasmErrorInner::Compile(e.to_string())).into() },
+        )?,
+    );
+    METERED_MODULES
+        .lock()
+        .put((key, max_memory_pages), module.clone());
+    Ok(module)
+}
+
+/// Get the metered module for some wasm, compiling it on a cache miss.
+pub fn metered_module(
+    key: [u8; 32],
+    wasm: &[u8],
+    max_memory_pages: u32,
+) -> Result<Arc<Module>, RuntimeError> {
+    if let Some(module) = METERED_MODULES.lock().get(&(key, max_memory_pages)) {
+        return Ok(module.clone());
+    }
+    let module = Arc::new(
+        Module::from_binary(&metered_store(max_memory_pages), wasm).map_err(
+            |e| -> RuntimeError { wasm_error!(WasmErrorInner::Compile(e.to_string())).into() },
+        )?,
+    );
+    METERED_MODULES
+        .lock()
+        .put((key, max_memory_pages), module.clone());
+    Ok(module)
+}
+
+/// Give the instance `fuel` for its next call.
+pub fn set_fuel(instance: &Mutex<Instance>, fuel: u64) {
+    holochain_types::dna::wasm_metering::set_fuel(&instance.lock(), fuel);
+}
+
+/// The fuel the instance has left, `None` if it ran out.
+pub fn remaining_fuel(instance: &Mutex<Instance>) -> Option<u64> {
+    holochain_types::dna::wasm_metering::remaining_fuel(&instance.lock())
+}
+
+/// Record the fuel used by a call that was given `limit` fuel
+/// and has `remaining` left.
+pub fn record_fuel(
+    dna_hash: &DnaHash,
+    zome_name: &ZomeName,
+    fn_name: &FunctionName,
+    limit: u64,
+    remaining: Option<u64>,
+) {
+    let used = limit - remaining.unwrap_or(0);
+    tracing::debug!(
+        %dna_hash,
+        %zome_name,
+        %fn_name,
+        fuel_used = used,
+        fuel_limit = limit,
+        exhausted = remaining.is_none()
+    );
+    let mut metrics = FUEL_METRICS.write();
+    let metric = metrics
+        .entry((dna_hash.clone(), zome_name.clone(), fn_name.clone()))
+        .or_default();
+    metric.calls += 1;
+    metric.fuel_used = metric.fuel_used.saturating_add(used);
+    metric.max_fuel_used = metric.max_fuel_used.max(used);
+    if remaining.is_none() {
+        metric.exhausted += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOOP_WAT: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "spin") (param i32) (result i32)
+                (local i32)
+                (loop
+                    (local.set 1 (i32.add (local.get 1) (i32.const 1)))
+                    (br_if 0 (i32.lt_u (local.get 1) (local.get 0))))
+                (local.get 1)))
+    "#;
+
+    fn spin(instance: &Mutex<Instance>, n: i32) -> Result<Box<[Value]>, RuntimeError> {
+        instance
+            .lock()
+            .exports
+            .get_function("spin")
+            .unwrap()
+            .call(&[Value::I32(n)])
+    }
+
+    #[test]
+    fn fuel_is_deterministic_and_exhausts() {
+        let wasm = wasmer::wat2wasm(LOOP_WAT.as_bytes()).unwrap();
+        let module = metered_module([1; 32], &wasm, 16).unwrap();
+        let instance = Mutex::new(Instance::new(&module, &imports! {}).unwrap());
+
+        set_fuel(&instance, 1_000_000);
+        spin(&instance, 100).unwrap();
+        let used = 1_000_000 - remaining_fuel(&instance).unwrap();
+
+        // The same call always costs the same.
+        set_fuel(&instance, 1_000_000);
+        spin(&instance, 100).unwrap();
+        assert_eq!(Some(1_000_000 - used), remaining_fuel(&instance));
+
+        // A longer loop runs out.
+        set_fuel(&instance, used);
+        assert!(spin(&instance, 1000).is_err());
+        assert_eq!(None, remaining_fuel(&instance));
+    }
+
+    #[test]
+    fn serialized_module_is_still_metered() {
+        let wasm = wasmer::wat2wasm(LOOP_WAT.as_bytes()).unwrap();
+        let module = metered_module([4; 32], &wasm, 16).unwrap();
+        let bytes = serialize_module(&module).unwrap();
+        let checksum = holo_hash::encode::blake2b_256(&bytes);
+
+        // Tampered bytes are refused.
+        let mut tampered = bytes.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(unsafe { deserialize_module([5; 32], &tampered, &checksum, 16) }.is_err());
+        assert!(!is_module_cached([5; 32], 16));
+
+        let module = unsafe { deserialize_module([5; 32], &bytes, &checksum, 16) }.unwrap();
+        assert!(is_module_cached([5; 32], 16));
+        let instance = Mutex::new(Instance::new(&module, &imports! {}).unwrap());
+        set_fuel(&instance, 10);
+        assert!(spin(&instance, 1000).is_err());
+        assert_eq!(None, remaining_fuel(&instance));
+    }
+
+    #[test]
+    fn memory_is_capped() {
+        let wasm = wasmer::wat2wasm(LOOP_WAT.as_bytes()).unwrap();
+        let module = metered_module([2; 32], &wasm, 2).unwrap();
+        let instance = Instance::new(&module, &imports! {}).unwrap();
+        let memory = instance.exports.get_memory("memory").unwrap();
+        assert!(memory.grow(1).is_ok());
+        assert!(memory.grow(1).is_err());
+
+        // Needing more than the limit fails to instantiate.
+        let module = metered_module([3; 32], &wasm, 0).unwrap();
+        assert!(Instance::new(&module, &imports! {}).is_err());
+    }
+}
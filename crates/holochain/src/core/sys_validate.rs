@@ -54,24 +54,49 @@ pub async fn author_key_is_valid(_author: &AgentPubKey) -> SysValidationResult<(
 }
 
 /// Verify the countersigning session contains the specified header.
+/// The header must be the one its author commits at this entry's place in
+/// the session, following the author's header for the entry before it.
 pub fn check_countersigning_session_data_contains_header(
     entry_hash: EntryHash,
     session_data: &CounterSigningSessionData,
     header: NewEntryHeaderRef<'_>,
 ) -> SysValidationResult<()> {
-    let header_is_in_session = session_data
-        .build_header_set(entry_hash)
-        .map_err(SysValidationError::from)?
-        .iter()
-        .any(|session_header| match (&header, session_header) {
-            (NewEntryHeaderRef::Create(create), Header::Create(session_create)) => {
-                create == &session_create
-            }
-            (NewEntryHeaderRef::Update(update), Header::Update(session_update)) => {
-                update == &session_update
+    let as_header = Header::from(header.to_new_entry_header());
+    let index = session_data
+        .entry_index_for_header(&as_header)
+        .map_err(SysValidationError::from)?;
+    // The first entry follows the author's frozen chain top.
+    let prev_header = match index {
+        0 => Some(
+            session_data
+                .agent_state_for_agent(as_header.author())?
+                .chain_top()
+                .clone(),
+        ),
+        _ => as_header.prev_header().cloned(),
+    };
+    let header_is_in_session = match prev_header {
+        Some(prev_header) => {
+            let session_header = Header::from_countersigning_data_at(
+                index,
+                entry_hash,
+                prev_header,
+                session_data,
+                as_header.author().clone(),
+            )
+            .map_err(SysValidationError::from)?;
+            match (&header, &session_header) {
+                (NewEntryHeaderRef::Create(create), Header::Create(session_create)) => {
+                    create == &session_create
+                }
+                (NewEntryHeaderRef::Update(update), Header::Update(session_update)) => {
+                    update == &session_update
+                }
+                _ => false,
             }
-            _ => false,
-        });
+        }
+        None => false,
+    };
     if !header_is_in_session {
         Err(SysValidationError::ValidationOutcome(
             ValidationOutcome::HeaderNotInCounterSigningSession(
@@ -84,6 +109,28 @@ pub fn check_countersigning_session_data_contains_header(
     }
 }
 
+/// Verify the countersigned entry wraps the app entry that the session
+/// commits at the header's place in the session.
+pub fn check_countersigning_session_entry(
+    entry: &Entry,
+    session_data: &CounterSigningSessionData,
+    header: NewEntryHeaderRef<'_>,
+) -> SysValidationResult<()> {
+    let index = session_data
+        .entry_index_for_header(&Header::from(header.to_new_entry_header()))
+        .map_err(SysValidationError::from)?;
+    if session_entry_index(entry) == Some(index) {
+        Ok(())
+    } else {
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::HeaderNotInCounterSigningSession(
+                session_data.to_owned(),
+                header.to_new_entry_header(),
+            ),
+        ))
+    }
+}
+
 /// Verify that the signature on a preflight request is valid.
 pub async fn check_countersigning_preflight_response_signature(
     preflight_response: &PreflightResponse,
@@ -764,6 +811,7 @@ fn make_register_agent_activity(element: Element) -> Option<(DhtOpHash, DhtOp)>
 #[cfg(test)]
 pub mod test {
     use super::check_countersigning_preflight_response_signature;
+    use super::check_countersigning_session_data_contains_header;
     use crate::core::sys_validate::error::SysValidationError;
     use crate::core::ValidationOutcome;
     use arbitrary::Arbitrary;
@@ -772,6 +820,8 @@ pub mod test {
     use hdk::prelude::AgentPubKeyFixturator;
     use holochain_keystore::AgentPubKeyExt;
     use holochain_state::test_utils::test_keystore;
+    use holochain_types::countersigning::build_session_header_sets;
+    use holochain_types::prelude::*;
     use holochain_zome_types::countersigning::PreflightResponse;
     use matches::assert_matches;
 
@@ -808,4 +858,79 @@ pub mod test {
             (),
         );
     }
+
+    #[test]
+    pub fn test_check_countersigning_session_data_contains_header_multi_entry() {
+        let mut u = arbitrary::Unstructured::new(&[0; 1000]);
+        let mut session_data = CounterSigningSessionData::arbitrary(&mut u).unwrap();
+        let header_base =
+            HeaderBase::Create(CreateBase::new(EntryType::arbitrary(&mut u).unwrap()));
+        *session_data.preflight_request_mut().header_base_mut() = header_base.clone();
+        (*session_data
+            .preflight_request_mut()
+            .additional_entries_mut())
+        .push(PreflightEntry::new(
+            EntryHash::from_raw_36(vec![1; 36]),
+            header_base,
+        ));
+        let alice = fixt!(AgentPubKey, Predictable);
+        let bob = fixt!(AgentPubKey, Predictable, 1);
+        for (seq, agent) in [(3, alice), (7, bob)] {
+            let mut agent_state = CounterSigningAgentState::arbitrary(&mut u).unwrap();
+            *agent_state.header_seq_mut() = seq;
+            (*session_data.preflight_request_mut().signing_agents_mut()).push((agent, vec![]));
+            (*session_data.responses_mut())
+                .push((agent_state, Signature::arbitrary(&mut u).unwrap()));
+        }
+
+        let first = EntryHash::from_raw_36(vec![2; 36]);
+        let second = EntryHash::from_raw_36(vec![3; 36]);
+        let header_sets =
+            build_session_header_sets(&session_data, &[first.clone(), second.clone()]).unwrap();
+
+        // Every agent's header for every entry is in the session.
+        for (entry_hash, header_set) in [first.clone(), second.clone()].iter().zip(&header_sets) {
+            assert_eq!(header_set.len(), 2);
+            for header in header_set {
+                assert_eq!(
+                    check_countersigning_session_data_contains_header(
+                        entry_hash.clone(),
+                        &session_data,
+                        header.try_into().unwrap(),
+                    )
+                    .unwrap(),
+                    ()
+                );
+            }
+        }
+
+        // A header for the second entry isn't in the session as the first entry.
+        let header = &header_sets[1][1];
+        assert_matches!(
+            check_countersigning_session_data_contains_header(
+                first,
+                &session_data,
+                header.try_into().unwrap(),
+            ),
+            Err(SysValidationError::ValidationOutcome(
+                ValidationOutcome::HeaderNotInCounterSigningSession(_, _)
+            ))
+        );
+
+        // Nor is it with a different timestamp.
+        let mut header = header.clone();
+        if let Header::Create(create) = &mut header {
+            create.timestamp = Timestamp::from_micros(0);
+        }
+        assert_matches!(
+            check_countersigning_session_data_contains_header(
+                second,
+                &session_data,
+                (&header).try_into().unwrap(),
+            ),
+            Err(SysValidationError::ValidationOutcome(
+                ValidationOutcome::HeaderNotInCounterSigningSession(_, _)
+            ))
+        );
+    }
 }
@@ -8,6 +8,7 @@ use super::sys_validation_workflow::validation_query;
 use crate::conductor::ConductorHandle;
 use crate::core::queue_consumer::TriggerSender;
 use crate::core::queue_consumer::WorkComplete;
+use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::guest_callback::validate::ValidateHostAccess;
 use crate::core::ribosome::guest_callback::validate::ValidateInvocation;
 use crate::core::ribosome::guest_callback::validate::ValidateResult;
@@ -166,12 +167,13 @@ async fn app_validation_workflow_inner(
             "Committing {} ops",
             chunk.iter().map(|c| c.len()).sum::<usize>()
         );
-        let (t, a, r, activity) = workspace
+        let (t, a, r, ab, activity) = workspace
             .dht_db
             .async_commit(move |txn| {
                 let mut total = 0;
                 let mut awaiting = 0;
                 let mut rejected = 0;
+                let mut abandoned = 0;
                 let mut agent_activity = Vec::new();
                 for outcome in chunk.into_iter().flatten() {
                     let (op_hash, dependency, op_light, outcome, activity) = outcome;
@@ -181,13 +183,18 @@ async fn app_validation_workflow_inner(
                     // Collect all agent activity.
                     if let Some(activity) = activity {
                         // If the activity is accepted or rejected then it's ready to integrate.
-                        if matches!(&outcome, Outcome::Accepted | Outcome::Rejected(_)) {
+                        if matches!(
+                            &outcome,
+                            Outcome::Accepted | Outcome::Rejected(_) | Outcome::Abandoned(_)
+                        ) {
                             agent_activity.push(activity);
                         }
                     }
 
 
-                    if let Outcome::AwaitingDeps(_) | Outcome::Rejected(_) = &outcome {
+                    if let Outcome::AwaitingDeps(_) | Outcome::Rejected(_) | Outcome::Abandoned(_) =
+                        &outcome
+                    {
                         warn!(
                             msg = "DhtOp has failed app validation",
                             outcome = ?outcome,
@@ -216,9 +223,17 @@ async fn app_validation_workflow_inner(
                                 put_integration_limbo(txn, &op_hash, ValidationStatus::Rejected)?;
                             }
                         }
+                        Outcome::Abandoned(_) => {
+                            abandoned += 1;
+                            if let Dependency::Null = dependency {
+                                put_integrated(txn, &op_hash, ValidationStatus::Abandoned)?;
+                            } else {
+                                put_integration_limbo(txn, &op_hash, ValidationStatus::Abandoned)?;
+                            }
+                        }
                     }
                 }
-                WorkflowResult::Ok((total, awaiting, rejected, agent_activity))
+                WorkflowResult::Ok((total, awaiting, rejected, abandoned, agent_activity))
             })
             .await?;
         // Once the database transaction is committed, add agent activity to the cache
@@ -250,10 +265,11 @@ async fn app_validation_workflow_inner(
             );
         }
         tracing::debug!(
-            "{} committed, {} awaiting sys dep, {} rejected. {} committed this round",
+            "{} committed, {} awaiting sys dep, {} rejected, {} abandoned. {} committed this round",
             t,
             a,
             r,
+            ab,
             total
         );
     }
@@ -424,7 +440,11 @@ async fn validate_op_outer(
         .ok_or_else(|| AppValidationError::DnaMissing((*dna_hash).clone()))?;
 
     // Create the ribosome
-    let ribosome = RealRibosome::new(dna_file);
+    let limits = conductor_handle
+        .get_config()
+        .wasm_limits
+        .for_dna(dna_hash.as_ref());
+    let ribosome = RealRibosome::new(dna_file).with_limits(limits);
     validate_op(op, host_fn_workspace, network, &ribosome).await
 }
 
@@ -536,10 +556,16 @@ async fn run_validation_callback_inner<R>(
 where
     R: RibosomeT,
 {
-    let validate_result = ribosome.run_validate(
+    let validate_result = match ribosome.run_validate(
         ValidateHostAccess::new(workspace_read.clone(), network.clone()),
         invocation.clone(),
-    )?;
+    ) {
+        // Running out of fuel says nothing about the op being invalid.
+        Err(e @ RibosomeError::WasmFuelExhausted(..)) => {
+            return Ok(Outcome::Abandoned(e.to_string()))
+        }
+        r => r?,
+    };
     match validate_result {
         ValidateResult::Valid => Ok(Outcome::Accepted),
         ValidateResult::Invalid(reason) => Ok(Outcome::Rejected(reason)),
@@ -1,5 +1,7 @@
 use crate::conductor::ConductorHandle;
+use crate::core::ribosome::wasm_metering;
 use crate::core::ribosome::ZomeCallInvocation;
+use crate::sweettest::standard_config;
 use crate::sweettest::SweetConductorBatch;
 use crate::sweettest::SweetDnaFile;
 use crate::test_utils::host_fn_caller::*;
@@ -63,6 +65,67 @@ async fn app_validation_workflow_test() {
     .await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn validate_out_of_fuel_abandons_op() {
+    observability::test_run().ok();
+
+    let (dna_file, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Validate])
+        .await
+        .unwrap();
+
+    // Alice can't finish a single validate callback.
+    let mut starved_config = standard_config();
+    starved_config.wasm_limits.default.validate_fuel = 1;
+    let mut conductors =
+        SweetConductorBatch::from_configs(vec![starved_config, standard_config()]).await;
+    let apps = conductors
+        .setup_app(&"test_app", &[dna_file.clone()])
+        .await
+        .unwrap();
+    let ((alice,), (bob,)) = apps.into_tuples();
+
+    conductors.exchange_peer_info().await;
+
+    let invocation =
+        new_zome_call(bob.cell_id(), "always_validates", (), TestWasm::Validate).unwrap();
+    conductors[1].call_zome(invocation).await.unwrap().unwrap();
+
+    let alice_db = conductors[0]
+        .get_dht_db(alice.cell_id().dna_hash())
+        .unwrap();
+    let mut abandoned = 0;
+    for _ in 0..100 {
+        abandoned = fresh_reader_test(alice_db.clone(), |txn| {
+            txn.query_row(
+                "
+                SELECT count(hash) FROM DhtOp WHERE type = :store_entry
+                AND when_integrated IS NOT NULL AND validation_status = :abandoned
+                ",
+                named_params! {
+                    ":store_entry": DhtOpType::StoreEntry,
+                    ":abandoned": ValidationStatus::Abandoned,
+                },
+                |row| row.get::<_, usize>(0),
+            )
+            .unwrap()
+        });
+        if abandoned > 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    // The entry is neither valid nor rejected.
+    assert!(abandoned > 0);
+
+    let dump: serde_json::Value =
+        serde_json::from_str(&wasm_metering::dump_fuel_metrics(Some(dna_file.dna_hash()))).unwrap();
+    assert!(dump
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|m| m["fn_name"] == "validate" && m["metric"]["exhausted"].as_u64().unwrap() > 0));
+}
+
 const SELECT: &'static str = "SELECT count(hash) FROM DhtOp WHERE";
 
 // These are the expected invalid ops
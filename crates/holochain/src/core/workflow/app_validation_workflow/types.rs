@@ -17,6 +17,9 @@ pub enum Outcome {
     AwaitingDeps(Vec<AnyDhtHash>),
     /// Moves to integration with status rejected
     Rejected(String),
+    /// Moves to integration with status abandoned
+    /// because validation could not finish, e.g. it ran out of fuel
+    Abandoned(String),
 }
 
 impl Outcome {
@@ -8,8 +8,10 @@ use crate::conductor::api::CellConductorApiT;
 use crate::conductor::interface::SignalBroadcaster;
 use crate::conductor::ConductorHandle;
 use crate::core::queue_consumer::TriggerSender;
+use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::error::RibosomeResult;
 use crate::core::ribosome::guest_callback::post_commit::send_post_commit;
+use crate::core::ribosome::host_fn::ZomeCallCancel;
 use crate::core::ribosome::RibosomeT;
 use crate::core::ribosome::ZomeCallHostAccess;
 use crate::core::ribosome::ZomeCallInvocation;
@@ -37,6 +39,9 @@ pub struct CallZomeWorkflowArgs<RibosomeT> {
     pub conductor_handle: ConductorHandle,
     pub is_root_zome_call: bool,
     pub cell_id: CellId,
+    /// The call is cancelled and its changes are discarded if it hasn't
+    /// finished by this instant.
+    pub deadline: Option<tokio::time::Instant>,
 }
 
 #[instrument(skip(
@@ -58,56 +63,271 @@ pub async fn call_zome_workflow<Ribosome>(
 where
     Ribosome: RibosomeT + 'static,
 {
-    let should_write = args.is_root_zome_call;
+    // A read-only function can't write and neither can the functions it
+    // calls, as they share its workspace, so there is nothing to commit
+    let workspace = if args
+        .invocation
+        .zome
+        .zome_def()
+        .is_read_only(&args.invocation.fn_name)
+    {
+        workspace.into_read_only()
+    } else {
+        workspace
+    };
+    let should_write = args.is_root_zome_call && !workspace.is_read_only();
     let conductor_handle = args.conductor_handle.clone();
-    let result =
-        call_zome_workflow_inner(workspace.clone(), network.clone(), keystore.clone(), args)
-            .await?;
+    let deadline = args.deadline;
+    let zome_name = args.invocation.zome.zome_name().clone();
+    let fn_name = args.invocation.fn_name.clone();
+
+    // A nested call shares its caller's scratch, so keep a copy to put back
+    // if the call is cancelled. A root call's scratch is never flushed if
+    // it's cancelled so nothing reaches the source chain either way.
+    let scratch = workspace.source_chain().scratch();
+    let snapshot = scratch
+        .apply(|scratch| scratch.clone())
+        .map_err(SourceChainError::from)?;
+    let cancel = ZomeCallCancel::default();
+    let inner = call_zome_workflow_inner(
+        workspace.clone(),
+        network.clone(),
+        keystore.clone(),
+        args,
+        cancel.clone(),
+    );
+    let result = match deadline {
+        Some(deadline) => match tokio::time::timeout_at(deadline, inner).await {
+            Ok(result) => result?,
+            Err(_) => {
+                // The wasm may still be running on its blocking thread, so
+                // wait for its running host fns and stop it from running any
+                // more before rolling back what it wrote.
+                tracing::warn!(%zome_name, %fn_name, "Zome call timed out");
+                cancel.cancel().await;
+                scratch
+                    .apply(|scratch| *scratch = snapshot)
+                    .map_err(SourceChainError::from)?;
+                return Ok(Err(RibosomeError::ZomeCallTimeout(zome_name, fn_name)));
+            }
+        },
+        None => inner.await?,
+    };
 
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
 
     // commit the workspace
     if should_write {
-        let is_empty = workspace.source_chain().is_empty()?;
-        let countersigning_op = workspace.source_chain().countersigning_op()?;
-        let flushed_headers: Vec<(Option<Zome>, SignedHeaderHashed)> =
-            HostFnWorkspace::from(workspace.clone())
-                .flush(&network)
-                .await?;
-        if !is_empty {
-            match countersigning_op {
-                Some(op) => {
-                    if let Err(error_response) =
-                        super::countersigning_workflow::countersigning_publish(&network, op).await
-                    {
-                        return Ok(Ok(error_response));
-                    }
-                }
-                None => {
-                    trigger_publish_dht_ops.trigger(&"trigger_publish_dht_ops");
-                    trigger_integrate_dht_ops.trigger(&"trigger_integrate_dht_ops");
-                }
-            }
-        }
-
-        send_post_commit(
-            conductor_handle,
+        if let Some(error_response) = commit_workspace(
             workspace,
             network,
             keystore,
-            flushed_headers,
+            conductor_handle,
+            trigger_publish_dht_ops,
+            trigger_integrate_dht_ops,
         )
-        .await?;
+        .await?
+        {
+            return Ok(Ok(error_response));
+        }
     }
 
     Ok(result)
 }
 
+pub struct CallZomeBatchWorkflowArgs<RibosomeT> {
+    pub ribosome: RibosomeT,
+    /// The calls to make in order, each with how long it may run.
+    pub invocations: Vec<(ZomeCallInvocation, std::time::Duration)>,
+    pub signal_tx: SignalBroadcaster,
+    pub conductor_handle: ConductorHandle,
+    pub cell_id: CellId,
+}
+
+/// What happened to the calls of a batch.
+pub struct ZomeCallBatchResult {
+    /// The results of the calls that were made, in order.
+    pub results: Vec<ZomeCallResult>,
+    /// Why the writes couldn't be committed, if every call succeeded but
+    /// the writes were invalid or flushing them to the source chain failed.
+    pub commit_error: Option<WorkflowError>,
+}
+
+/// Make several zome calls that share one scratch space and commit it
+/// once at the end.
+///
+/// The calls stop at the first one that doesn't return
+/// [`ZomeCallResponse::Ok`]. The results of the calls that were made are
+/// returned, and the scratch space is only committed if every call
+/// succeeded and their writes are valid.
+#[instrument(skip(
+    workspace,
+    network,
+    keystore,
+    args,
+    trigger_publish_dht_ops,
+    trigger_integrate_dht_ops
+))]
+pub async fn call_zome_batch_workflow<Ribosome>(
+    workspace: SourceChainWorkspace,
+    network: HolochainP2pDna,
+    keystore: MetaLairClient,
+    args: CallZomeBatchWorkflowArgs<Ribosome>,
+    trigger_publish_dht_ops: TriggerSender,
+    trigger_integrate_dht_ops: TriggerSender,
+) -> WorkflowResult<ZomeCallBatchResult>
+where
+    Ribosome: RibosomeT + 'static,
+{
+    let CallZomeBatchWorkflowArgs {
+        mut ribosome,
+        invocations,
+        signal_tx,
+        conductor_handle,
+        cell_id,
+    } = args;
+    let num_calls = invocations.len();
+    let mut results = Vec::with_capacity(num_calls);
+
+    for (invocation, timeout) in invocations {
+        let zome_name = invocation.zome.zome_name().clone();
+        let fn_name = invocation.fn_name.clone();
+        let read_only = invocation.zome.zome_def().is_read_only(&fn_name);
+        let deadline = tokio::time::Instant::now() + timeout;
+        let cancel = ZomeCallCancel::default();
+        let call_zome_handle = CellConductorApi::new(conductor_handle.clone(), cell_id.clone())
+            .into_call_zome_handle();
+        let call_workspace = if read_only {
+            workspace.clone().into_read_only()
+        } else {
+            workspace.clone()
+        };
+        let host_access = ZomeCallHostAccess::new(
+            call_workspace.into(),
+            keystore.clone(),
+            network.clone(),
+            signal_tx.clone(),
+            call_zome_handle,
+        )
+        .with_deadline(Some(deadline))
+        .with_cancel(cancel.clone())
+        .with_read_only(read_only);
+        let call = call_zome_function_authorized(ribosome, host_access, invocation);
+        let result = match tokio::time::timeout_at(deadline, call).await {
+            Ok(r) => {
+                let (r, result) = r?;
+                ribosome = r;
+                result
+            }
+            Err(_) => {
+                // Nothing is committed so the cancelled call's writes are
+                // dropped with the rest of the batch.
+                tracing::warn!(%zome_name, %fn_name, "Zome call in batch timed out");
+                cancel.cancel().await;
+                results.push(Err(RibosomeError::ZomeCallTimeout(zome_name, fn_name)));
+                return Ok(ZomeCallBatchResult {
+                    results,
+                    commit_error: None,
+                });
+            }
+        };
+        let succeeded = matches!(result, Ok(ZomeCallResponse::Ok(_)));
+        results.push(result);
+        if !succeeded {
+            return Ok(ZomeCallBatchResult {
+                results,
+                commit_error: None,
+            });
+        }
+    }
+
+    if let Err(e) = validate_scratch(
+        workspace.clone(),
+        network.clone(),
+        conductor_handle.clone(),
+        ribosome,
+    )
+    .await
+    {
+        return Ok(ZomeCallBatchResult {
+            results,
+            commit_error: Some(e),
+        });
+    }
+
+    let commit_error = match commit_workspace(
+        workspace,
+        network,
+        keystore,
+        conductor_handle,
+        trigger_publish_dht_ops,
+        trigger_integrate_dht_ops,
+    )
+    .await
+    {
+        Ok(Some(error_response)) => {
+            if let Some(last) = results.last_mut() {
+                *last = Ok(error_response);
+            }
+            None
+        }
+        Ok(None) => None,
+        Err(e) => Some(e),
+    };
+    Ok(ZomeCallBatchResult {
+        results,
+        commit_error,
+    })
+}
+
+/// Flush the workspace of a finished zome call to the source chain and
+/// publish the new ops.
+///
+/// Returns the response to give the caller instead of the zome function's
+/// output if a countersigning session failed to start.
+async fn commit_workspace(
+    workspace: SourceChainWorkspace,
+    network: HolochainP2pDna,
+    keystore: MetaLairClient,
+    conductor_handle: ConductorHandle,
+    trigger_publish_dht_ops: TriggerSender,
+    trigger_integrate_dht_ops: TriggerSender,
+) -> WorkflowResult<Option<ZomeCallResponse>> {
+    let is_empty = workspace.source_chain().is_empty()?;
+    let countersigning_ops = workspace.source_chain().countersigning_ops()?;
+    let flushed_headers: Vec<(Option<Zome>, SignedHeaderHashed)> =
+        HostFnWorkspace::from(workspace.clone())
+            .flush(&network)
+            .await?;
+    if !is_empty {
+        if countersigning_ops.is_empty() {
+            trigger_publish_dht_ops.trigger(&"trigger_publish_dht_ops");
+            trigger_integrate_dht_ops.trigger(&"trigger_integrate_dht_ops");
+        } else if let Err(error_response) =
+            super::countersigning_workflow::countersigning_publish(&network, countersigning_ops)
+                .await
+        {
+            return Ok(Some(error_response));
+        }
+    }
+
+    send_post_commit(
+        conductor_handle,
+        workspace,
+        network,
+        keystore,
+        flushed_headers,
+    )
+    .await?;
+    Ok(None)
+}
+
 async fn call_zome_workflow_inner<Ribosome>(
     workspace: SourceChainWorkspace,
     network: HolochainP2pDna,
     keystore: MetaLairClient,
     args: CallZomeWorkflowArgs<Ribosome>,
+    cancel: ZomeCallCancel,
 ) -> WorkflowResult<ZomeCallResult>
 where
     Ribosome: RibosomeT + 'static,
@@ -118,8 +338,10 @@ where
         signal_tx,
         conductor_handle,
         cell_id,
+        deadline,
         ..
     } = args;
+    let read_only = workspace.is_read_only();
 
     let call_zome_handle =
         CellConductorApi::new(conductor_handle.clone(), cell_id).into_call_zome_handle();
@@ -131,11 +353,31 @@ where
         network.clone(),
         signal_tx,
         call_zome_handle,
-    );
+    )
+    .with_deadline(deadline)
+    .with_cancel(cancel)
+    .with_read_only(read_only);
     let (ribosome, result) =
         call_zome_function_authorized(ribosome, host_access, invocation).await?;
     tracing::trace!("After zome call");
 
+    if !read_only {
+        validate_scratch(workspace, network, conductor_handle, ribosome).await?;
+    }
+    Ok(result)
+}
+
+/// Validate everything in the scratch space before it is committed.
+/// An invalid countersigning session unlocks the chain again.
+async fn validate_scratch<Ribosome>(
+    workspace: SourceChainWorkspace,
+    network: HolochainP2pDna,
+    conductor_handle: ConductorHandle,
+    ribosome: Ribosome,
+) -> WorkflowResult<()>
+where
+    Ribosome: RibosomeT + 'static,
+{
     let validation_result =
         inline_validation(workspace.clone(), network, conductor_handle, ribosome).await;
     if matches!(
@@ -145,10 +387,9 @@ where
         ))
     ) {
         let scratch_elements = workspace.source_chain().scratch_elements()?;
-        if scratch_elements.len() == 1 {
-            let lock = holochain_state::source_chain::lock_for_entry(
-                scratch_elements[0].entry().as_option(),
-            )?;
+        // A countersigning session's elements all share the same lock.
+        if let Some(element) = scratch_elements.first() {
+            let lock = holochain_state::source_chain::lock_for_entry(element.entry().as_option())?;
             if !lock.is_empty()
                 && workspace
                     .source_chain()
@@ -162,8 +403,7 @@ where
             }
         }
     }
-    validation_result?;
-    Ok(result)
+    validation_result
 }
 
 /// First check if we are authorized to call
@@ -270,6 +510,9 @@ fn map_outcome(
         app_validation_workflow::Outcome::AwaitingDeps(hashes) => {
             return Err(SourceChainError::InvalidCommit(format!("{:?}", hashes)).into());
         }
+        app_validation_workflow::Outcome::Abandoned(reason) => {
+            return Err(SourceChainError::InvalidCommit(reason).into());
+        }
     }
     Ok(())
 }
@@ -1,19 +1,24 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use holo_hash::{AgentPubKey, DhtOpHash, HeaderHash};
-use holo_hash::{AnyDhtHash, EntryHash};
+use holo_hash::{AnyDhtHash, EntryHash, HasHash};
+use holochain_cascade::Cascade;
 use holochain_keystore::AgentPubKeyExt;
 use holochain_p2p::{HolochainP2pDna, HolochainP2pDnaT};
 use holochain_state::integrate::authored_ops_to_dht_db;
 use holochain_state::mutations;
 use holochain_state::prelude::{
-    current_countersigning_session, SourceChainResult, StateMutationResult, Store,
+    from_blob, unfinished_countersigning_session, SourceChainResult, StateMutationResult,
+    StateQueryResult, Store,
 };
+use holochain_types::countersigning::{build_session_header_sets, session_entry_hashes};
+use holochain_types::db::{DbKindDht, DbWrite};
 use holochain_types::dht_op::DhtOp;
 use holochain_types::signal::{Signal, SystemSignal};
 use holochain_zome_types::Timestamp;
-use holochain_zome_types::{Entry, SignedHeader, ZomeCallResponse};
+use holochain_zome_types::{Entry, GetOptions, SignedHeader, ZomeCallResponse};
 use kitsune_p2p_types::tx2::tx2_utils::Share;
 use rusqlite::{named_params, Transaction};
 
@@ -23,6 +28,10 @@ use crate::core::queue_consumer::{QueueTriggers, TriggerSender, WorkComplete};
 
 use super::{error::WorkflowResult, incoming_dht_ops_workflow::incoming_dht_ops_workflow};
 
+/// The longest the ops of a session are held while it is gathered,
+/// however far in the future the session claims to end.
+const MAX_SESSION_GATHER_TIME: Duration = Duration::from_secs(60 * 5);
+
 #[derive(Clone)]
 /// A cheaply clonable, thread safe and in-memory store for
 /// active countersigning sessions.
@@ -33,87 +42,211 @@ pub struct CountersigningWorkspace {
 #[derive(Default)]
 /// Pending countersigning sessions.
 pub struct CountersigningWorkspaceInner {
-    pending: HashMap<EntryHash, Session>,
+    /// Sessions by the chain lock their signers hold,
+    /// which is the same for every entry of a session.
+    pending: HashMap<Vec<u8>, Session>,
+    /// Whether the persisted sessions have been loaded.
+    restored: bool,
 }
 
 #[derive(Default)]
 struct Session {
-    /// Map of header hash for a each signers header to the
-    /// [`DhtOp`] and other required headers for this session to be
-    /// considered complete.
-    map: HashMap<HeaderHash, (DhtOpHash, DhtOp, Vec<HeaderHash>)>,
+    /// Map of header hash for each signers header of every entry
+    /// in the session to its [`DhtOp`].
+    map: HashMap<HeaderHash, (DhtOpHash, DhtOp)>,
     /// When this session expires.
     /// If this is none the session is empty.
     expires: Option<Timestamp>,
 }
 
 /// New incoming DhtOps for a countersigning session.
+/// The ops are persisted so a session that is still being
+/// gathered survives a restart of the conductor.
 // TODO: PERF: This takes a lock on the workspace which could
 // block other incoming DhtOps if there are many active sessions.
 // We could create an incoming buffer if this actually becomes an issue.
-pub(crate) fn incoming_countersigning(
+pub(crate) async fn incoming_countersigning(
     ops: Vec<(DhtOpHash, DhtOp)>,
     workspace: &CountersigningWorkspace,
+    dht_db: &DbWrite<DbKindDht>,
     trigger: TriggerSender,
 ) -> WorkflowResult<()> {
-    let mut should_trigger = false;
+    let mut sessions = Vec::new();
 
     // For each op check it's the right type and extract the
-    // entry hash, required headers and expires time.
+    // session and expires time.
     for (hash, op) in ops {
-        // Must be a store entry op.
-        if let DhtOp::StoreEntry(_, _, entry) = &op {
-            // Must have a counter sign entry type.
-            if let Entry::CounterSign(session_data, _) = entry.as_ref() {
-                let entry_hash = EntryHash::with_data_sync(&**entry);
-                // Get the required headers for this session.
-                let header_set = session_data.build_header_set(entry_hash)?;
-
-                // Get the expires time for this session.
-                let expires = *session_data.preflight_request().session_times().end();
-
-                // Get the entry hash from a header.
-                // If the headers have different entry hashes they will fail validation.
-                if let Some(entry_hash) = header_set.first().and_then(|h| h.entry_hash().cloned()) {
-                    // Hash the required headers.
-                    let required_headers: Vec<_> = header_set
-                        .into_iter()
-                        .map(|h| HeaderHash::with_data_sync(&h))
-                        .collect();
-
-                    // Check if already timed out.
-                    if holochain_zome_types::Timestamp::now() < expires {
-                        // Put this op in the pending map.
-                        workspace.put(entry_hash, hash, op, required_headers, expires);
-                        // We have new ops so we should trigger the workflow.
-                        should_trigger = true;
-                    }
+        if let Some((session, expires)) = session_requirements(&op)? {
+            // Check if already timed out.
+            if holochain_zome_types::Timestamp::now() < expires {
+                if is_signed_by_session_agent(&op).await {
+                    sessions.push((session, hash, op, expires));
+                } else {
+                    tracing::warn!(
+                        ?hash,
+                        "Dropping countersigning op that isn't signed by an agent of its session"
+                    );
                 }
             }
         }
     }
 
-    // Trigger the workflow if we have new ops.
-    if should_trigger {
-        trigger.trigger(&"incoming_countersigning");
+    if sessions.is_empty() {
+        return Ok(());
+    }
+
+    dht_db
+        .async_commit({
+            let ops: Vec<_> = sessions
+                .iter()
+                .map(|(_, hash, op, expires)| (hash.clone(), op.clone(), *expires))
+                .collect();
+            move |txn| {
+                for (hash, op, expires) in ops {
+                    mutations::insert_countersigning_op(txn, &hash, &op, &expires)?;
+                }
+                WorkflowResult::Ok(())
+            }
+        })
+        .await?;
+
+    for (session, hash, op, expires) in sessions {
+        // Put this op in the pending map.
+        workspace.put(session, hash, op, expires);
     }
+
+    // We have new ops so we should trigger the workflow.
+    trigger.trigger(&"incoming_countersigning");
     Ok(())
 }
 
+/// The session and expires time of the countersigning session
+/// this op belongs to, if any.
+/// A session is identified by the chain lock its signers hold.
+/// It expires when it ends or after [`MAX_SESSION_GATHER_TIME`],
+/// whichever is sooner.
+fn session_requirements(op: &DhtOp) -> WorkflowResult<Option<(Vec<u8>, Timestamp)>> {
+    // Must be a store entry op.
+    if let DhtOp::StoreEntry(_, _, entry) = op {
+        // Must have a counter sign entry type.
+        if let Entry::CounterSign(session_data, _) = entry.as_ref() {
+            let session = holochain_state::source_chain::lock_for_entry(Some(&**entry))?;
+            // Get the expires time for this session.
+            let end = *session_data.preflight_request().session_times().end();
+            let max = (Timestamp::now() + MAX_SESSION_GATHER_TIME)
+                .map_err(holochain_state::prelude::SourceChainError::from)?;
+            return Ok(Some((session, std::cmp::min(end, max))));
+        }
+    }
+    Ok(None)
+}
+
+/// Only the agents signing a session can add their own headers to it.
+async fn is_signed_by_session_agent(op: &DhtOp) -> bool {
+    let header = op.header();
+    let is_session_agent = match op.entry() {
+        Some(Entry::CounterSign(session_data, _)) => session_data
+            .preflight_request()
+            .signing_agents()
+            .iter()
+            .any(|(agent, _)| agent == header.author()),
+        _ => false,
+    };
+    is_session_agent
+        && header
+            .author()
+            .verify_signature(op.signature(), &header)
+            .await
+}
+
+/// Every signed header of the session if the ops cover every
+/// entry for every signer.
+fn complete_session_headers(session: &Session) -> Option<Vec<HeaderHash>> {
+    let session_data = session.map.values().find_map(|(_, op)| match op {
+        DhtOp::StoreEntry(_, _, entry) => match entry.as_ref() {
+            Entry::CounterSign(session_data, _) => Some(session_data),
+            _ => None,
+        },
+        _ => None,
+    })?;
+    let headers: Vec<_> = session.map.values().map(|(_, op)| op.header()).collect();
+    let entry_hashes = session_entry_hashes(session_data, &headers)?;
+    let required_headers: Vec<_> = build_session_header_sets(session_data, &entry_hashes)
+        .ok()?
+        .into_iter()
+        .flatten()
+        .map(|h| HeaderHash::with_data_sync(&h))
+        .collect();
+    if required_headers
+        .iter()
+        .all(|hash| session.map.contains_key(hash))
+    {
+        Some(required_headers)
+    } else {
+        None
+    }
+}
+
 /// Countersigning workflow that checks for complete sessions and
 /// pushes the complete ops to validation then messages the signers.
+/// This runs on entry authorities and on the enzyme of a session,
+/// which both gather sessions in the same way. The enzyme isn't
+/// necessarily an authority for the session so instead of validating
+/// the ops itself it publishes the complete session to the authorities.
 pub(crate) async fn countersigning_workflow(
     space: &Space,
     network: &(dyn HolochainP2pDnaT + Send + Sync),
     sys_validation_trigger: &TriggerSender,
 ) -> WorkflowResult<WorkComplete> {
+    // Pick up any sessions that were being gathered before a restart.
+    space
+        .countersigning_workspace
+        .restore(&space.dht_db)
+        .await?;
+
     // Get any complete sessions.
     let complete_sessions = space.countersigning_workspace.get_complete_sessions();
     let mut notify_agents = Vec::with_capacity(complete_sessions.len());
 
+    // Complete and expired sessions no longer need to be persisted.
+    let complete_op_hashes: Vec<_> = complete_sessions
+        .iter()
+        .flat_map(|(_, ops, _)| ops.iter().map(|(hash, _)| hash.clone()))
+        .collect();
+    space
+        .dht_db
+        .async_commit(move |txn| {
+            for hash in complete_op_hashes {
+                mutations::delete_countersigning_op(txn, &hash)?;
+            }
+            mutations::delete_expired_countersigning_ops(txn, &Timestamp::now())?;
+            WorkflowResult::Ok(())
+        })
+        .await?;
+
     // For each complete session send the ops to validation.
     for (agents, ops, headers) in complete_sessions {
-        incoming_dht_ops_workflow(space, sys_validation_trigger.clone(), ops, false).await?;
+        let has_enzyme = ops.first().map_or(false, |(_, op)| {
+            op.enzymatic_countersigning_enzyme().is_some()
+        });
+        if has_enzyme {
+            // Each entry of the session goes to its own authorities.
+            let mut by_basis: HashMap<AnyDhtHash, Vec<DhtOp>> = HashMap::new();
+            for (_, op) in ops {
+                by_basis.entry(op.dht_basis()).or_default().push(op);
+            }
+            for (basis, ops) in by_basis {
+                if let Err(e) = network.publish(false, false, basis, ops, None).await {
+                    // The signers publish their own ops once they are notified.
+                    tracing::info!(
+                        "Failed to publish complete countersigning session because of {:?}",
+                        e
+                    );
+                }
+            }
+        } else {
+            incoming_dht_ops_workflow(space, sys_validation_trigger.clone(), ops, false).await?;
+        }
         notify_agents.push((agents, headers));
     }
 
@@ -134,7 +267,34 @@ pub(crate) async fn countersigning_workflow(
 }
 
 /// An incoming countersigning session success.
+/// Responses that arrive after the session has ended are ignored and the
+/// session is instead resolved by [`countersigning_recovery`].
 pub(crate) async fn countersigning_success(
+    space: Space,
+    network: &HolochainP2pDna,
+    author: AgentPubKey,
+    signed_headers: Vec<SignedHeader>,
+    trigger: QueueTriggers,
+    signal: SignalBroadcaster,
+) -> WorkflowResult<()> {
+    let session = space
+        .authored_db
+        .async_reader({
+            let author = Arc::new(author.clone());
+            move |txn| unfinished_countersigning_session(&txn, author)
+        })
+        .await?;
+    match session {
+        Some((_, session_data))
+            if Timestamp::now() <= *session_data.preflight_request().session_times().end() => {}
+        _ => return Ok(()),
+    }
+    finish_countersigning_session(space, network, author, signed_headers, trigger, signal).await
+}
+
+/// Finish the session if the signed headers are the full set of
+/// headers for this cell's unfinished session.
+async fn finish_countersigning_session(
     space: Space,
     network: &HolochainP2pDna,
     author: AgentPubKey,
@@ -150,15 +310,15 @@ pub(crate) async fn countersigning_success(
         integrate_dht_ops: integration_trigger,
         ..
     } = trigger;
-    // Using iterators is fine in this function as there can only be a maximum of 8 headers.
-    let (this_cells_header_hash, entry_hash) = match signed_headers
+    // Using iterators is fine in this function as there can only be a maximum of
+    // 8 headers for each of the 8 entries in a session.
+    // The session is identified by this cell's header for the first entry.
+    let entry_hash = match signed_headers
         .iter()
-        .find(|h| *h.0.author() == author)
-        .and_then(|sh| {
-            sh.0.entry_hash()
-                .cloned()
-                .map(|eh| (HeaderHash::with_data_sync(&sh.0), eh))
-        }) {
+        .filter(|h| *h.0.author() == author)
+        .min_by_key(|h| h.0.header_seq())
+        .and_then(|sh| sh.0.entry_hash().cloned())
+    {
         Some(h) => h,
         None => return Ok(()),
     };
@@ -168,31 +328,33 @@ pub(crate) async fn countersigning_success(
     // unless there is an active session.
     let reader_closure = {
         let entry_hash = entry_hash.clone();
-        let this_cells_header_hash = this_cells_header_hash.clone();
         let author = author.clone();
         move |txn: Transaction| {
-            if holochain_state::chain_lock::is_chain_locked(&txn, &[], &author)? {
-                let transaction: holochain_state::prelude::Txn = (&txn).into();
-                if transaction.contains_entry(&entry_hash)? {
-                    // If this is a countersigning session we can grab all the ops
-                    // for this cells header so we can check if we need to self publish them.
-                    let r: Result<_, _> = txn
-                        .prepare(
-                            "SELECT basis_hash, hash FROM DhtOp WHERE header_hash = :header_hash",
-                        )?
-                        .query_map(
-                            named_params! {
-                                ":header_hash": this_cells_header_hash
-                            },
-                            |row| {
-                                let hash: DhtOpHash = row.get("hash")?;
-                                let basis: AnyDhtHash = row.get("basis_hash")?;
-                                Ok((hash, basis))
-                            },
-                        )?
-                        .collect();
-                    return Ok(r?);
-                }
+            let transaction: holochain_state::prelude::Txn = (&txn).into();
+            if transaction.contains_entry(&entry_hash)? {
+                // If this is a countersigning session we can grab all the ops
+                // for this cells session headers so we can check if we need to self publish them.
+                // Only the ops of an unresolved session are withheld.
+                let r: Result<_, _> = txn
+                    .prepare(
+                        "
+                        SELECT DhtOp.basis_hash, DhtOp.hash FROM DhtOp
+                        JOIN Header ON DhtOp.header_hash = Header.hash
+                        WHERE Header.author = :author AND DhtOp.withhold_publish = 1
+                        ",
+                    )?
+                    .query_map(
+                        named_params! {
+                            ":author": author
+                        },
+                        |row| {
+                            let hash: DhtOpHash = row.get("hash")?;
+                            let basis: AnyDhtHash = row.get("basis_hash")?;
+                            Ok((hash, basis))
+                        },
+                    )?
+                    .collect();
+                return Ok(r?);
             }
             StateMutationResult::Ok(Vec::with_capacity(0))
         }
@@ -217,16 +379,29 @@ pub(crate) async fn countersigning_success(
         .iter()
         .map(|SignedHeader(h, _)| HeaderHash::with_data_sync(h))
         .collect();
+    let session_headers: Vec<_> = signed_headers
+        .iter()
+        .map(|SignedHeader(h, _)| h.clone())
+        .collect();
 
     let result = authored_db
         .async_commit({
             let author = author.clone();
             let entry_hash = entry_hash.clone();
             move |txn| {
-            if let Some((cs_entry_hash, cs)) = current_countersigning_session(txn, Arc::new(author.clone()))? {
+            if let Some((cs_entry_hash, cs)) = unfinished_countersigning_session(txn, Arc::new(author.clone()))? {
                 // Check we have the right session.
                 if cs_entry_hash == entry_hash {
-                    let stored_headers = cs.build_header_set(entry_hash)?;
+                    // Every signer's header for every entry in the session.
+                    let stored_headers: Vec<_> = match session_entry_hashes(&cs, &session_headers) {
+                        Some(entry_hashes) if entry_hashes.first() == Some(&entry_hash) => {
+                            build_session_header_sets(&cs, &entry_hashes)?
+                                .into_iter()
+                                .flatten()
+                                .collect()
+                        }
+                        _ => return Ok(false),
+                    };
                     if stored_headers.len() == incoming_headers.len() {
                         // Check all stored header hashes match an incoming header hash.
                         if stored_headers.iter().all(|h| {
@@ -236,9 +411,9 @@ pub(crate) async fn countersigning_success(
                             // All checks have passed so unlock the chain.
                             mutations::unlock_chain(txn, &author)?;
                             // Update ops to publish.
-                            txn.execute("UPDATE DhtOp SET withhold_publish = NULL WHERE header_hash = :header_hash",
+                            txn.execute("UPDATE DhtOp SET withhold_publish = NULL WHERE withhold_publish = 1 AND header_hash IN (SELECT hash FROM Header WHERE author = :author)",
                             named_params! {
-                                ":header_hash": this_cells_header_hash,
+                                ":author": author,
                                 }
                             ).map_err(holochain_state::prelude::StateMutationError::from)?;
                             return Ok(true);
@@ -285,14 +460,222 @@ pub(crate) async fn countersigning_success(
     Ok(())
 }
 
+/// How often a signer asks the entry authorities about an unresolved session.
+#[cfg(not(test))]
+const RECOVERY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+#[cfg(test)]
+const RECOVERY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Resolve this cell's countersigning sessions that don't finish with the
+/// usual response, e.g. because the conductor restarted or the response was lost.
+/// The entry authorities are asked for every signer's header and if they
+/// have them all the session succeeds as if they had responded.
+/// If they still don't have them all a while after the session has ended
+/// the session is abandoned. Its elements stay on the source chain, their
+/// ops are never published and the chain is unlocked.
+/// This runs for as long as the cell does.
+pub(crate) async fn countersigning_recovery(
+    space: Space,
+    network: HolochainP2pDna,
+    author: AgentPubKey,
+    trigger: QueueTriggers,
+    signal: SignalBroadcaster,
+) {
+    loop {
+        if let Err(e) =
+            recover_countersigning_session(&space, &network, &author, &trigger, &signal).await
+        {
+            tracing::error!(
+                "Failed to recover countersigning session because of: {:?}",
+                e
+            );
+        }
+        tokio::time::sleep(RECOVERY_INTERVAL).await;
+    }
+}
+
+async fn recover_countersigning_session(
+    space: &Space,
+    network: &HolochainP2pDna,
+    author: &AgentPubKey,
+    trigger: &QueueTriggers,
+    signal: &SignalBroadcaster,
+) -> WorkflowResult<()> {
+    let session = space
+        .authored_db
+        .async_reader({
+            let author = Arc::new(author.clone());
+            move |txn| unfinished_countersigning_session(&txn, author)
+        })
+        .await?;
+    let (entry_hash, session_data) = match session {
+        Some(session) => session,
+        None => return Ok(()),
+    };
+    // This cell's session headers follow its frozen chain top in session order.
+    let session_start_seq = *session_data.agent_state_for_agent(author)?.header_seq();
+    let entries_len = session_data.preflight_request().entries_len();
+    let own_headers: Vec<SignedHeader> = space
+        .authored_db
+        .async_reader({
+            let author = author.clone();
+            move |txn| {
+                let r: Result<Vec<Vec<u8>>, _> = txn
+                    .prepare(
+                        "
+                        SELECT blob FROM Header
+                        WHERE author = :author AND seq > :start AND seq <= :end
+                        ORDER BY seq
+                        ",
+                    )?
+                    .query_map(
+                        named_params! {
+                            ":author": author,
+                            ":start": session_start_seq,
+                            ":end": session_start_seq + entries_len as u32,
+                        },
+                        |row| row.get("blob"),
+                    )?
+                    .collect();
+                r?.into_iter()
+                    .map(|blob| Ok(from_blob::<SignedHeader>(blob)?))
+                    .collect::<StateQueryResult<Vec<_>>>()
+            }
+        })
+        .await?;
+    let entry_hashes: Vec<EntryHash> = own_headers
+        .iter()
+        .filter_map(|SignedHeader(h, _)| h.entry_hash().cloned())
+        .collect();
+    if entry_hashes.len() != entries_len || entry_hashes.first() != Some(&entry_hash) {
+        return Ok(());
+    }
+    let required_headers: Vec<_> = build_session_header_sets(&session_data, &entry_hashes)?
+        .into_iter()
+        .flatten()
+        .map(|h| HeaderHash::with_data_sync(&h))
+        .collect();
+
+    // Only an answer given well after the session has ended confirms it was
+    // abandoned. Until then a signer may still publish their header and the
+    // authorities need time to validate it.
+    let asked_at = Timestamp::now();
+    let mut cascade = Cascade::empty()
+        .with_dht(space.dht_db.clone().into())
+        .with_network(network.clone(), space.cache_db.clone());
+    // This cell's own headers are withheld until the session
+    // is resolved so the authorities may not have them all.
+    let mut gathered: HashMap<HeaderHash, SignedHeader> = own_headers
+        .into_iter()
+        .map(|sh| (HeaderHash::with_data_sync(&sh.0), sh))
+        .collect();
+    for entry_hash in entry_hashes {
+        match cascade
+            .get_entry_details(entry_hash, GetOptions::latest())
+            .await
+        {
+            Ok(details) => gathered.extend(
+                details
+                    .map(|d| d.headers)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|shh| {
+                        let (header, signature) = shh.into_inner();
+                        let (header, hash) = header.into_inner();
+                        (hash, SignedHeader(header, signature))
+                    }),
+            ),
+            Err(e) => {
+                // We can't tell if the session completed so try again later.
+                tracing::info!(
+                    "Failed to get countersigning session from entry authorities because of {:?}",
+                    e
+                );
+                return Ok(());
+            }
+        }
+    }
+    let signed_headers: Vec<_> = required_headers
+        .iter()
+        .filter_map(|hash| gathered.remove(hash))
+        .collect();
+
+    if signed_headers.len() == required_headers.len() {
+        return finish_countersigning_session(
+            space.clone(),
+            network,
+            author.clone(),
+            signed_headers,
+            trigger.clone(),
+            signal.clone(),
+        )
+        .await;
+    }
+
+    let abandon_after = (*session_data.preflight_request().session_times().end()
+        + RECOVERY_INTERVAL * 3)
+        .map_err(holochain_state::prelude::SourceChainError::from)?;
+    if asked_at > abandon_after {
+        let abandoned = space
+            .authored_db
+            .async_commit({
+                let author = author.clone();
+                let entry_hash = entry_hash.clone();
+                move |txn| {
+                    // Check this is still the session we are abandoning.
+                    match unfinished_countersigning_session(txn, Arc::new(author.clone()))? {
+                        Some((cs_entry_hash, _)) if cs_entry_hash == entry_hash => (),
+                        _ => return SourceChainResult::Ok(false),
+                    }
+                    // The session's elements stay on the chain but
+                    // their ops are never published.
+                    mutations::abandon_countersigning_ops(txn, &author)?;
+                    mutations::unlock_chain(txn, &author)?;
+                    Ok(true)
+                }
+            })
+            .await?;
+        if abandoned {
+            // Signal to the UI.
+            signal
+                .clone()
+                .send(Signal::System(SystemSignal::AbandonedCountersigning(
+                    entry_hash,
+                )))?;
+        }
+    }
+    Ok(())
+}
+
 /// Publish to entry authorities so they can gather all the signed
 /// headers for this session and respond with a session complete.
+/// The ops for every entry of the session are sent to the authorities
+/// of the first entry, which gather the whole session together.
+/// If the session has an enzyme then the ops are sent directly to
+/// the enzyme instead, which gathers the signed headers itself.
+/// The enzyme is one of the signers and sends its own ops to itself,
+/// which the network delivers locally, so its headers are gathered too.
 pub async fn countersigning_publish(
     network: &HolochainP2pDna,
-    op: DhtOp,
+    ops: Vec<DhtOp>,
 ) -> Result<(), ZomeCallResponse> {
-    let basis = op.dht_basis();
-    let ops = vec![op];
+    let first = match ops.first() {
+        Some(first) => first,
+        None => return Ok(()),
+    };
+    if let Some(enzyme) = first.enzymatic_countersigning_enzyme().cloned() {
+        for op in ops {
+            if let Err(e) = network.countersigning_enzyme_push(enzyme.clone(), op).await {
+                tracing::error!(
+                    "Failed to push to the enzyme for countersigning session because of: {:?}",
+                    e
+                );
+                return Err(ZomeCallResponse::CountersigningSession(e.to_string()));
+            }
+        }
+        return Ok(());
+    }
+    let basis = first.dht_basis();
     if let Err(e) = network.publish(false, true, basis, ops, None).await {
         tracing::error!(
             "Failed to publish to entry authorities for countersigning session because of: {:?}",
@@ -316,25 +699,16 @@ impl CountersigningWorkspace {
     }
 
     /// Put a single signers store entry op in the workspace.
-    fn put(
-        &self,
-        entry_hash: EntryHash,
-        op_hash: DhtOpHash,
-        op: DhtOp,
-        required_headers: Vec<HeaderHash>,
-        expires: Timestamp,
-    ) {
+    fn put(&self, session: Vec<u8>, op_hash: DhtOpHash, op: DhtOp, expires: Timestamp) {
         // hash the header of this ops.
         let header_hash = HeaderHash::with_data_sync(&op.header());
         self.inner
             .share_mut(|i, _| {
-                // Get the session at this entry or create an empty one.
-                let session = i.pending.entry(entry_hash).or_default();
+                // Get the session or create an empty one.
+                let session = i.pending.entry(session).or_default();
 
                 // Insert the op into the session.
-                session
-                    .map
-                    .insert(header_hash, (op_hash, op, required_headers));
+                session.map.insert(header_hash, (op_hash, op));
 
                 // Set the expires time.
                 session.expires = Some(expires);
@@ -344,6 +718,53 @@ impl CountersigningWorkspace {
             .ok();
     }
 
+    /// Load the sessions that were persisted before the last restart.
+    /// This only reads the database the first time it's called.
+    async fn restore(&self, dht_db: &DbWrite<DbKindDht>) -> WorkflowResult<()> {
+        let restored = self
+            .inner
+            .share_ref(|i| Ok(i.restored))
+            // We don't close this share so we can ignore this error.
+            .unwrap_or(true);
+        if restored {
+            return Ok(());
+        }
+        let stored: Vec<(DhtOpHash, DhtOp)> = dht_db
+            .async_reader(|txn| {
+                let r: Result<Vec<_>, _> = txn
+                    .prepare(
+                        "SELECT op_hash, blob FROM CountersigningSession WHERE expires_at_timestamp >= :now",
+                    )?
+                    .query_map(
+                        named_params! {
+                            ":now": Timestamp::now(),
+                        },
+                        |row| {
+                            let hash: DhtOpHash = row.get("op_hash")?;
+                            let blob: Vec<u8> = row.get("blob")?;
+                            Ok((hash, blob))
+                        },
+                    )?
+                    .collect();
+                r?.into_iter()
+                    .map(|(hash, blob)| Ok((hash, from_blob::<DhtOp>(blob)?)))
+                    .collect::<StateQueryResult<Vec<_>>>()
+            })
+            .await?;
+        for (hash, op) in stored {
+            if let Some((session, expires)) = session_requirements(&op)? {
+                self.put(session, hash, op, expires);
+            }
+        }
+        self.inner
+            .share_mut(|i, _| {
+                i.restored = true;
+                Ok(())
+            })
+            .ok();
+        Ok(())
+    }
+
     fn get_complete_sessions(&self) -> Vec<(AgentsToNotify, Ops, SignedHeaders)> {
         let now = holochain_zome_types::Timestamp::now();
         self.inner
@@ -353,23 +774,12 @@ impl CountersigningWorkspace {
                     session.expires.as_ref().map(|e| now < *e).unwrap_or(false)
                 });
 
-                // Get all complete session's entry hashes.
+                // Get all complete sessions.
                 let complete: Vec<_> = i
                     .pending
                     .iter()
-                    .filter_map(|(entry_hash, session)| {
-                        // If all session required headers are contained in the map
-                        // then the session is complete.
-                        if session.map.values().all(|(_, _, required_hashes)| {
-                            required_hashes
-                                .iter()
-                                .all(|hash| session.map.contains_key(hash))
-                        }) {
-                            Some(entry_hash.clone())
-                        } else {
-                            None
-                        }
-                    })
+                    .filter(|(_, session)| complete_session_headers(session).is_some())
+                    .map(|(id, _)| id.clone())
                     .collect();
 
                 let mut ret = Vec::with_capacity(complete.len());
@@ -382,11 +792,13 @@ impl CountersigningWorkspace {
                         let map = session.map;
                         let r = map.into_iter().fold(
                             (Vec::new(), Vec::new(), Vec::new()),
-                            |(mut agents, mut ops, mut headers), (_, (op_hash, op, _))| {
+                            |(mut agents, mut ops, mut headers), (_, (op_hash, op))| {
                                 let header = op.header();
                                 let signature = op.signature().clone();
-                                // Agents to notify.
-                                agents.push(header.author().clone());
+                                // Agents to notify, once each.
+                                if !agents.contains(header.author()) {
+                                    agents.push(header.author().clone());
+                                }
                                 // Signed headers to notify them with.
                                 headers.push(SignedHeader(header, signature));
                                 // Ops to validate.
@@ -412,52 +824,80 @@ impl Default for CountersigningWorkspace {
 #[cfg(test)]
 mod tests {
     use arbitrary::Arbitrary;
+    use holochain_sqlite::db::{ReadManager, WriteManager};
+    use holochain_zome_types::prelude::*;
+    use holochain_zome_types::{fake_agent_pubkey_1, fake_agent_pubkey_2};
 
     use super::*;
 
     #[test]
-    /// Test that a session of 5 headers is complete when
-    /// the expiry time is in the future and all required headers
-    /// are present.
+    /// Test that a session of two agents committing two entries each
+    /// is complete when the expiry time is in the future and every
+    /// signer's header for every entry is present.
     fn gets_complete_sessions() {
-        let mut u = arbitrary::Unstructured::new(&holochain_zome_types::NOISE);
+        let mut u = arbitrary::Unstructured::new(&[0; 1000]);
         let workspace = CountersigningWorkspace::new();
 
-        // - Create the ops.
-        let data = |u: &mut arbitrary::Unstructured| {
-            let op_hash = DhtOpHash::arbitrary(u).unwrap();
-            let op = DhtOp::arbitrary(u).unwrap();
-            let header = op.header();
-            (op_hash, op, header)
-        };
-        let entry_hash = EntryHash::arbitrary(&mut u).unwrap();
-        let mut op_hashes = Vec::new();
-        let mut ops = Vec::new();
-        let mut required_headers = Vec::new();
-        for _ in 0..5 {
-            let (op_hash, op, header) = data(&mut u);
-            let header_hash = HeaderHash::with_data_sync(&header);
-            op_hashes.push(op_hash);
-            ops.push(op);
-            required_headers.push(header_hash);
+        // - Create a session with two entries for two agents.
+        let mut session_data = CounterSigningSessionData::arbitrary(&mut u).unwrap();
+        let header_base =
+            HeaderBase::Create(CreateBase::new(EntryType::arbitrary(&mut u).unwrap()));
+        *session_data.preflight_request_mut().header_base_mut() = header_base.clone();
+        (*session_data
+            .preflight_request_mut()
+            .additional_entries_mut())
+        .push(PreflightEntry::new(
+            EntryHash::from_raw_36(vec![1; 36]),
+            header_base,
+        ));
+        for i in 0..2 {
+            (*session_data.preflight_request_mut().signing_agents_mut())
+                .push((AgentPubKey::from_raw_36(vec![i; 36]), vec![]));
+            (*session_data.responses_mut()).push((
+                CounterSigningAgentState::arbitrary(&mut u).unwrap(),
+                Signature::arbitrary(&mut u).unwrap(),
+            ));
         }
+        let entries: Vec<_> = (0..2)
+            .map(|i| {
+                Entry::CounterSign(
+                    Box::new(session_data.clone()),
+                    AppEntryBytes(SerializedBytes::from(UnsafeBytes::from(vec![i]))),
+                )
+            })
+            .collect();
+        let entry_hashes: Vec<_> = entries.iter().map(EntryHash::with_data_sync).collect();
+        let header_sets = build_session_header_sets(&session_data, &entry_hashes).unwrap();
+        let ops: Vec<_> = header_sets
+            .into_iter()
+            .zip(entries)
+            .flat_map(|(header_set, entry)| {
+                header_set.into_iter().map(move |header| {
+                    DhtOp::StoreEntry(
+                        Signature([0; 64]),
+                        header.try_into().unwrap(),
+                        Box::new(entry.clone()),
+                    )
+                })
+            })
+            .collect();
+        assert_eq!(ops.len(), 4);
 
-        // - Put the ops in the workspace with expiry set to one hour from now.
-        for (op_h, op) in op_hashes.into_iter().zip(ops.into_iter()) {
+        // - Put the ops in the workspace, the session isn't
+        // complete until the last one is there.
+        for op in ops {
+            assert!(workspace.get_complete_sessions().is_empty());
+            let (session, _) = session_requirements(&op).unwrap().unwrap();
             let expires = (Timestamp::now() + std::time::Duration::from_secs(60 * 60)).unwrap();
-            workspace.put(
-                entry_hash.clone(),
-                op_h,
-                op,
-                required_headers.clone(),
-                expires,
-            );
+            workspace.put(session, DhtOpHash::arbitrary(&mut u).unwrap(), op, expires);
         }
 
         // - Get all complete sessions.
         let r = workspace.get_complete_sessions();
-        // - Expect we have one.
+        // - Expect we have one with both agents and all four headers.
         assert_eq!(r.len(), 1);
+        assert_eq!(r[0].0.len(), 2);
+        assert_eq!(r[0].2.len(), 4);
 
         workspace
             .inner
@@ -478,13 +918,10 @@ mod tests {
         // - Create an op for a session that has expired in the past.
         let op_hash = DhtOpHash::arbitrary(&mut u).unwrap();
         let op = DhtOp::arbitrary(&mut u).unwrap();
-        let header = op.header();
-        let entry_hash = EntryHash::arbitrary(&mut u).unwrap();
-        let header_hash = HeaderHash::with_data_sync(&header);
         let expires = (Timestamp::now() - std::time::Duration::from_secs(60 * 60)).unwrap();
 
         // - Add it to the workspace.
-        workspace.put(entry_hash, op_hash, op, vec![header_hash], expires);
+        workspace.put(vec![0; 32], op_hash, op, expires);
         let r = workspace.get_complete_sessions();
 
         // - Expect we have no complete sessions.
@@ -498,4 +935,164 @@ mod tests {
             })
             .unwrap();
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    /// Test that only ops signed by an agent of their session are gathered
+    /// and that a session is held no longer than the conductor allows.
+    async fn forged_ops_not_gathered() {
+        let mut u = arbitrary::Unstructured::new(&[0; 1000]);
+        let keystore = holochain_state::test_utils::test_keystore();
+        let test_db = holochain_state::test_utils::test_dht_db();
+        let dht_db = test_db.to_db();
+        let workspace = CountersigningWorkspace::new();
+        let (trigger, _rx) = TriggerSender::new();
+        let alice = fake_agent_pubkey_1();
+        let bob = fake_agent_pubkey_2();
+
+        // - Create a session between alice and another agent
+        // that claims to end a year from now.
+        let mut session_data = CounterSigningSessionData::arbitrary(&mut u).unwrap();
+        *session_data.preflight_request_mut().header_base_mut() =
+            HeaderBase::Create(CreateBase::new(EntryType::arbitrary(&mut u).unwrap()));
+        for agent in [alice.clone(), AgentPubKey::from_raw_36(vec![1; 36])] {
+            (*session_data.preflight_request_mut().signing_agents_mut()).push((agent, vec![]));
+            (*session_data.responses_mut()).push((
+                CounterSigningAgentState::arbitrary(&mut u).unwrap(),
+                Signature::arbitrary(&mut u).unwrap(),
+            ));
+        }
+        *session_data
+            .preflight_request_mut()
+            .session_times_mut()
+            .end_mut() = (Timestamp::now() + Duration::from_secs(60 * 60 * 24 * 365)).unwrap();
+        let entry = Entry::CounterSign(
+            Box::new(session_data.clone()),
+            AppEntryBytes(SerializedBytes::from(UnsafeBytes::from(vec![0]))),
+        );
+        let headers =
+            build_session_header_sets(&session_data, &[EntryHash::with_data_sync(&entry)])
+                .unwrap()
+                .remove(0);
+        let store_entry = |header: Header, signature| {
+            let op = DhtOp::StoreEntry(
+                signature,
+                header.try_into().unwrap(),
+                Box::new(entry.clone()),
+            );
+            (DhtOpHash::with_data_sync(&op), op)
+        };
+
+        // - Alice signs her own header.
+        let alice_header = headers
+            .iter()
+            .find(|h| *h.author() == alice)
+            .unwrap()
+            .clone();
+        let signature = alice.sign(&keystore, &alice_header).await.unwrap();
+        let (alice_hash, alice_op) = store_entry(alice_header.clone(), signature);
+
+        // - The other agent's header is pushed with a forged signature.
+        let other_header = headers
+            .iter()
+            .find(|h| *h.author() != alice)
+            .unwrap()
+            .clone();
+        let forged = store_entry(other_header, Signature([0; 64]));
+
+        // - Bob signs a header for a session he isn't part of.
+        let mut bob_header = alice_header;
+        if let Header::Create(create) = &mut bob_header {
+            create.author = bob.clone();
+        }
+        let signature = bob.sign(&keystore, &bob_header).await.unwrap();
+        let outsider = store_entry(bob_header, signature);
+
+        incoming_countersigning(
+            vec![(alice_hash.clone(), alice_op), forged, outsider],
+            &workspace,
+            &dht_db,
+            trigger,
+        )
+        .await
+        .unwrap();
+
+        // - Expect only alice's op to be gathered and persisted,
+        // expiring long before the session claims to end.
+        let max = (Timestamp::now() + MAX_SESSION_GATHER_TIME).unwrap();
+        workspace
+            .inner
+            .share_ref(|i| {
+                assert_eq!(i.pending.len(), 1);
+                let session = i.pending.values().next().unwrap();
+                assert_eq!(session.map.len(), 1);
+                assert_eq!(session.map.values().next().unwrap().0, alice_hash);
+                assert!(session.expires.unwrap() <= max);
+                Ok(())
+            })
+            .unwrap();
+        let persisted: Vec<DhtOpHash> = dht_db.conn().unwrap().with_reader_test(|txn| {
+            txn.prepare("SELECT op_hash FROM CountersigningSession")
+                .unwrap()
+                .query_map(rusqlite::NO_PARAMS, |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        });
+        assert_eq!(persisted, vec![alice_hash]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    /// Test that persisted session ops are removed once
+    /// their session expires or completes.
+    async fn persisted_sessions_removed() {
+        let mut u = arbitrary::Unstructured::new(&holochain_zome_types::NOISE);
+        let test_db = holochain_state::test_utils::test_dht_db();
+        let dht_db = test_db.to_db();
+
+        // - Persist an op for an expired session and one for an open session.
+        let expired_hash = DhtOpHash::arbitrary(&mut u).unwrap();
+        let open_hash = DhtOpHash::arbitrary(&mut u).unwrap();
+        let op = DhtOp::arbitrary(&mut u).unwrap();
+        let expired = (Timestamp::now() - std::time::Duration::from_secs(60 * 60)).unwrap();
+        let open = (Timestamp::now() + std::time::Duration::from_secs(60 * 60)).unwrap();
+        let count = |dht_db: &DbWrite<DbKindDht>| {
+            dht_db.conn().unwrap().with_reader_test(|txn| {
+                txn.query_row(
+                    "SELECT COUNT(*) FROM CountersigningSession",
+                    rusqlite::NO_PARAMS,
+                    |row| row.get::<_, u32>(0),
+                )
+                .unwrap()
+            })
+        };
+        dht_db
+            .conn()
+            .unwrap()
+            .with_commit_test(|txn| {
+                mutations::insert_countersigning_op(txn, &expired_hash, &op, &expired).unwrap();
+                mutations::insert_countersigning_op(txn, &open_hash, &op, &open).unwrap();
+            })
+            .unwrap();
+        assert_eq!(count(&dht_db), 2);
+
+        // - Expect the expired op to be removed.
+        dht_db
+            .conn()
+            .unwrap()
+            .with_commit_test(|txn| {
+                mutations::delete_expired_countersigning_ops(txn, &Timestamp::now()).unwrap();
+            })
+            .unwrap();
+        assert_eq!(count(&dht_db), 1);
+
+        // - Expect the open op to be removed once its session completes.
+        dht_db
+            .conn()
+            .unwrap()
+            .with_commit_test(|txn| {
+                mutations::delete_countersigning_op(txn, &open_hash).unwrap();
+            })
+            .unwrap();
+        assert_eq!(count(&dht_db), 0);
+    }
 }
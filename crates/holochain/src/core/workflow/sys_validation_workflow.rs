@@ -317,7 +317,15 @@ async fn validate_op_inner(
                 // Retrieve for all other headers on countersigned entry.
                 if let Entry::CounterSign(session_data, _) = &**entry {
                     let entry_hash = EntryHash::with_data_sync(&**entry);
-                    for header in session_data.build_header_set(entry_hash)? {
+                    for header in countersigning_session_header_set(
+                        header,
+                        entry_hash,
+                        session_data,
+                        workspace,
+                        network.clone(),
+                    )
+                    .await?
+                    {
                         let hh = HeaderHash::with_data_sync(&header);
                         if workspace
                             .full_cascade(network.clone())
@@ -349,7 +357,15 @@ async fn validate_op_inner(
             if let Entry::CounterSign(session_data, _) = &**entry {
                 let dependency_check = |_original_element: &Element| Ok(());
                 let entry_hash = EntryHash::with_data_sync(&**entry);
-                for header in session_data.build_header_set(entry_hash)? {
+                for header in countersigning_session_header_set(
+                    &Header::from(header.clone()),
+                    entry_hash,
+                    session_data,
+                    workspace,
+                    network.clone(),
+                )
+                .await?
+                {
                     check_and_hold_store_element(
                         &HeaderHash::with_data_sync(&header),
                         workspace,
@@ -476,13 +492,24 @@ async fn sys_validate_element_inner(
 
     async fn validate(
         header: &Header,
+        session_prev_header: Option<&Header>,
         maybe_entry: Option<&Entry>,
         workspace: &SysValidationWorkspace,
         network: HolochainP2pDna,
         conductor_handle: &dyn ConductorHandleT,
     ) -> SysValidationResult<()> {
         let incoming_dht_ops_sender = None;
-        store_element(header, workspace, network.clone()).await?;
+        match session_prev_header {
+            // Another signer's previous header in a countersigning session
+            // isn't published until the session completes so it's checked
+            // against the header built from the session instead.
+            Some(prev_header) => {
+                check_prev_header(header)?;
+                check_prev_timestamp(header, prev_header)?;
+                check_prev_seq(header, prev_header)?;
+            }
+            None => store_element(header, workspace, network.clone()).await?,
+        }
         if let Some((maybe_entry, EntryVisibility::Public)) =
             &maybe_entry.and_then(|e| header.entry_type().map(|et| (e, et.visibility())))
         {
@@ -520,9 +547,27 @@ async fn sys_validate_element_inner(
     match maybe_entry {
         Some(Entry::CounterSign(session, _)) => {
             let entry_hash = EntryHash::with_data_sync(maybe_entry.unwrap());
-            for header in session.build_header_set(entry_hash)? {
+            let mut header_sets = countersigning_session_header_sets(
+                header,
+                entry_hash,
+                session,
+                workspace,
+                network.clone(),
+            )
+            .await?;
+            let header_set = header_sets.pop().unwrap_or_default();
+            // The headers for the entry before this one in the session.
+            let prev_header_set = header_sets.pop();
+            for (agent_index, session_header) in header_set.iter().enumerate() {
+                let session_prev_header = match &prev_header_set {
+                    Some(prev_header_set) if session_header.author() != header.author() => {
+                        prev_header_set.get(agent_index)
+                    }
+                    _ => None,
+                };
                 validate(
-                    &header,
+                    session_header,
+                    session_prev_header,
                     maybe_entry,
                     workspace,
                     network.clone(),
@@ -532,7 +577,17 @@ async fn sys_validate_element_inner(
             }
             Ok(())
         }
-        _ => validate(header, maybe_entry, workspace, network, conductor_handle).await,
+        _ => {
+            validate(
+                header,
+                None,
+                maybe_entry,
+                workspace,
+                network,
+                conductor_handle,
+            )
+            .await
+        }
     }
 }
 
@@ -570,6 +625,59 @@ async fn register_agent_activity(
     Ok(())
 }
 
+/// Every agent's header for the same session entry as this header.
+async fn countersigning_session_header_set(
+    header: &Header,
+    entry_hash: EntryHash,
+    session_data: &CounterSigningSessionData,
+    workspace: &SysValidationWorkspace,
+    network: HolochainP2pDna,
+) -> SysValidationResult<Vec<Header>> {
+    Ok(
+        countersigning_session_header_sets(header, entry_hash, session_data, workspace, network)
+            .await?
+            .pop()
+            .unwrap_or_default(),
+    )
+}
+
+/// Every agent's headers for each session entry up to and including
+/// the entry of this header, in session order.
+/// The headers for later entries in a session depend on the entries before
+/// them, which are found by following this header back to the session start.
+async fn countersigning_session_header_sets(
+    header: &Header,
+    entry_hash: EntryHash,
+    session_data: &CounterSigningSessionData,
+    workspace: &SysValidationWorkspace,
+    network: HolochainP2pDna,
+) -> SysValidationResult<Vec<Vec<Header>>> {
+    let index = session_data.entry_index_for_header(header)?;
+    if index == 0 {
+        return Ok(vec![session_data.build_header_set(entry_hash)?]);
+    }
+    let mut entry_hashes = vec![entry_hash];
+    let mut cascade = workspace.full_cascade(network);
+    let mut prev_header = header.prev_header().cloned();
+    while entry_hashes.len() <= index {
+        let prev_header_hash = prev_header
+            .ok_or_else(|| ValidationOutcome::PrevHeaderError(PrevHeaderError::MissingPrev))?;
+        let prev = cascade
+            .retrieve_header(prev_header_hash.clone(), Default::default())
+            .await?
+            .ok_or_else(|| ValidationOutcome::DepMissingFromDht(prev_header_hash.into()))?;
+        let prev_entry_hash = prev
+            .header()
+            .entry_hash()
+            .cloned()
+            .ok_or_else(|| ValidationOutcome::NotNewEntry(prev.header().clone()))?;
+        entry_hashes.push(prev_entry_hash);
+        prev_header = prev.header().prev_header().cloned();
+    }
+    entry_hashes.reverse();
+    Ok(build_session_header_sets(session_data, &entry_hashes)?)
+}
+
 async fn store_element(
     header: &Header,
     workspace: &SysValidationWorkspace,
@@ -631,6 +739,7 @@ async fn store_entry(
     if let Entry::CounterSign(session_data, _) = entry {
         check_countersigning_session_data(EntryHash::with_data_sync(entry), session_data, header)
             .await?;
+        check_countersigning_session_entry(entry, session_data, header)?;
     }
     Ok(())
 }
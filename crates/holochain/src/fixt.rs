@@ -142,9 +142,7 @@ fixturator!(
         for (hash, _) in wasms {
             zomes.push((
                 zome_name_fixturator.next().unwrap(),
-                ZomeDef::Wasm(WasmZome {
-                    wasm_hash: hash.to_owned(),
-                }),
+                ZomeDef::Wasm(WasmZome::new(hash.to_owned())),
             ));
         }
         let mut dna_def = DnaDefFixturator::new(Unpredictable).next().unwrap();
@@ -163,9 +161,7 @@ fixturator!(
         for (hash, _) in wasms {
             zomes.push((
                 zome_name_fixturator.next().unwrap(),
-                ZomeDef::Wasm(WasmZome {
-                    wasm_hash: hash.to_owned(),
-                }),
+                ZomeDef::Wasm(WasmZome::new(hash.to_owned())),
             ));
         }
         let mut dna_def = DnaDefFixturator::new_indexed(Predictable, get_fixt_index!())
@@ -84,6 +84,7 @@ impl SweetConductorHandle {
             cap_secret,
             provenance: provenance.clone(),
             payload,
+            timeout_ms: None,
         };
         match self.handle().call_zome(call).await {
             Ok(Ok(response)) => Ok(unwrap_to!(response => ZomeCallResponse::Ok)
@@ -742,6 +742,7 @@ where
         fn_name: func.into(),
         payload: ExternIO::encode(payload)?,
         provenance: cell_id.agent_pubkey().clone(),
+        timeout_ms: None,
     })
 }
 
@@ -343,3 +343,176 @@ fn check_elements_for_private_entry(caller: AgentPubKey, elements: Vec<Element>)
         }
     }
 }
+
+#[cfg(feature = "test_utils")]
+#[tokio::test(flavor = "multi_thread")]
+async fn countersigning_session_recovers_after_restart() {
+    use holochain_wasm_test_utils::TestWasm;
+
+    let _g = observability::test_run().ok();
+    let mut conductors = SweetConductorBatch::from_standard_config(2).await;
+    let (dna_file, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::CounterSigning])
+        .await
+        .unwrap();
+    let apps = conductors.setup_app("app", &[dna_file]).await.unwrap();
+    conductors.exchange_peer_info().await;
+    let ((alice,), (bobbo,)) = apps.into_tuples();
+    let alice_zome = alice.zome(TestWasm::CounterSigning);
+    let bobbo_zome = bobbo.zome(TestWasm::CounterSigning);
+
+    let preflight_request: PreflightRequest = conductors[0]
+        .call(
+            &alice_zome,
+            "generate_countersigning_preflight_request",
+            vec![
+                (alice.agent_pubkey().clone(), vec![Role(0)]),
+                (bobbo.agent_pubkey().clone(), vec![]),
+            ],
+        )
+        .await;
+    let mut responses = Vec::new();
+    for (conductor, zome) in [(&conductors[0], &alice_zome), (&conductors[1], &bobbo_zome)] {
+        match conductor
+            .call(
+                zome,
+                "accept_countersigning_preflight_request",
+                preflight_request.clone(),
+            )
+            .await
+        {
+            PreflightRequestAcceptance::Accepted(response) => responses.push(response),
+            _ => unreachable!(),
+        }
+    }
+
+    // Alice commits then goes offline so she misses the session completing.
+    let countersigned_header_hash_alice: HeaderHash = conductors[0]
+        .call(
+            &alice_zome,
+            "create_a_countersigned_thing",
+            responses.clone(),
+        )
+        .await;
+    conductors[0].shutdown().await;
+    let _: HeaderHash = conductors[1]
+        .call(&bobbo_zome, "create_a_countersigned_thing", responses)
+        .await;
+    wait_for_unlock(&conductors[1], &bobbo_zome).await;
+
+    // After a restart alice finds the complete session at the entry authorities.
+    conductors[0].startup().await;
+    conductors.exchange_peer_info().await;
+    wait_for_unlock(&conductors[0], &alice_zome).await;
+
+    let withheld: Vec<Option<i64>> = fresh_reader_test(alice.authored_db().clone(), |txn| {
+        txn.prepare("SELECT withhold_publish FROM DhtOp WHERE header_hash = ?")
+            .unwrap()
+            .query_map([&countersigned_header_hash_alice], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    });
+    assert!(!withheld.is_empty());
+    assert!(withheld.iter().all(Option::is_none));
+}
+
+/// Keep trying to commit until the chain is unlocked.
+async fn wait_for_unlock(conductor: &SweetConductor, zome: &SweetZome) {
+    for _ in 0..600 {
+        let result: Result<HeaderHash, _> =
+            conductor.call_fallible(zome, "create_a_thing", ()).await;
+        if result.is_ok() {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    panic!("The chain was never unlocked");
+}
+
+#[cfg(feature = "test_utils")]
+#[tokio::test(flavor = "multi_thread")]
+async fn countersigning_session_with_several_entries() {
+    use holochain_wasm_test_utils::TestWasm;
+
+    let _g = observability::test_run().ok();
+    let mut conductors = SweetConductorBatch::from_standard_config(2).await;
+    let (dna_file, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::CounterSigning])
+        .await
+        .unwrap();
+    let apps = conductors.setup_app("app", &[dna_file]).await.unwrap();
+    conductors.exchange_peer_info().await;
+    let ((alice,), (bobbo,)) = apps.into_tuples();
+    let alice_zome = alice.zome(TestWasm::CounterSigning);
+    let bobbo_zome = bobbo.zome(TestWasm::CounterSigning);
+
+    let preflight_request: PreflightRequest = conductors[0]
+        .call(
+            &alice_zome,
+            "generate_multi_entry_countersigning_preflight_request",
+            vec![
+                (alice.agent_pubkey().clone(), vec![Role(0)]),
+                (bobbo.agent_pubkey().clone(), vec![]),
+            ],
+        )
+        .await;
+    let mut responses = Vec::new();
+    for (conductor, zome) in [(&conductors[0], &alice_zome), (&conductors[1], &bobbo_zome)] {
+        match conductor
+            .call(
+                zome,
+                "accept_countersigning_preflight_request",
+                preflight_request.clone(),
+            )
+            .await
+        {
+            PreflightRequestAcceptance::Accepted(response) => responses.push(response),
+            _ => unreachable!(),
+        }
+    }
+
+    // Each agent commits a thing and a note in the one session.
+    let alice_headers: Vec<HeaderHash> = conductors[0]
+        .call(
+            &alice_zome,
+            "create_a_countersigned_thing_and_note",
+            responses.clone(),
+        )
+        .await;
+    let bobbo_headers: Vec<HeaderHash> = conductors[1]
+        .call(
+            &bobbo_zome,
+            "create_a_countersigned_thing_and_note",
+            responses,
+        )
+        .await;
+    assert_eq!(alice_headers.len(), 2);
+    assert_eq!(bobbo_headers.len(), 2);
+    wait_for_unlock(&conductors[0], &alice_zome).await;
+    wait_for_unlock(&conductors[1], &bobbo_zome).await;
+
+    // Every entry of the session is published and valid.
+    for header_hash in alice_headers {
+        wait_for_valid_element(&conductors[1], &bobbo_zome, header_hash).await;
+    }
+    for header_hash in bobbo_headers {
+        wait_for_valid_element(&conductors[0], &alice_zome, header_hash).await;
+    }
+}
+
+/// Keep trying to get the element until it is found valid.
+async fn wait_for_valid_element(
+    conductor: &SweetConductor,
+    zome: &SweetZome,
+    header_hash: HeaderHash,
+) {
+    for _ in 0..600 {
+        let result: Result<Element, _> = conductor
+            .call_fallible(zome, "must_get_valid_element", header_hash.clone())
+            .await;
+        if result.is_ok() {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    panic!("The element was never found valid");
+}
@@ -107,6 +107,7 @@ async fn ser_regression_test() {
         fn_name: "create_channel".into(),
         payload: ExternIO::encode(channel).unwrap(),
         provenance: alice_agent_id.clone(),
+        timeout_ms: None,
     };
 
     let request = Box::new(invocation.clone());
@@ -136,6 +137,7 @@ async fn ser_regression_test() {
         fn_name: "create_message".into(),
         payload: ExternIO::encode(message).unwrap(),
         provenance: alice_agent_id.clone(),
+        timeout_ms: None,
     };
 
     let request = Box::new(invocation.clone());
@@ -314,6 +314,9 @@ async fn mock_network_sharded_gossip() {
                         holochain_p2p::WireMessage::CountersigningAuthorityResponse { .. } => {
                             debug!("countersigning_authority_response")
                         }
+                        holochain_p2p::WireMessage::CountersigningEnzymePush { .. } => {
+                            debug!("countersigning_enzyme_push")
+                        }
                     },
                     HolochainP2pMockMsg::CallResp(_) => debug!("CallResp"),
                     HolochainP2pMockMsg::PeerGet(_) => debug!("PeerGet"),
@@ -818,6 +821,9 @@ async fn mock_network_sharding() {
                         holochain_p2p::WireMessage::CountersigningAuthorityResponse { .. } => {
                             debug!("countersigning_authority_response")
                         }
+                        holochain_p2p::WireMessage::CountersigningEnzymePush { .. } => {
+                            debug!("countersigning_enzyme_push")
+                        }
                     },
                     HolochainP2pMockMsg::CallResp(_) => debug!("CallResp"),
                     HolochainP2pMockMsg::PeerGet(_) => eprintln!("PeerGet"),
@@ -1039,7 +1045,7 @@ async fn mock_network_sharding() {
     tuning.gossip_dynamic_arcs = true;
 
     let mut network = KitsuneP2pConfig::default();
-    network.bootstrap_service = Some(bootstrap);
+    network.bootstrap_service = Some(bootstrap.into());
     network.transport_pool = vec![TransportConfig::Mock {
         mock_network: mock_network.into(),
     }];
@@ -1119,9 +1125,15 @@ async fn run_bootstrap(
     peer_data: impl Iterator<Item = AgentInfoSigned>,
 ) -> (Url2, kitsune_p2p_bootstrap::BootstrapShutdown) {
     let mut url = url2::url2!("http://127.0.0.1:0");
-    let (driver, addr, shutdown) = kitsune_p2p_bootstrap::run(([127, 0, 0, 1], 0), vec![])
-        .await
-        .unwrap();
+    // The generated peer data isn't really signed.
+    let config = kitsune_p2p_bootstrap::BootstrapConfig {
+        verify_signatures: false,
+        ..Default::default()
+    };
+    let (driver, addr, shutdown) =
+        kitsune_p2p_bootstrap::run_with_config(([127, 0, 0, 1], 0), config)
+            .await
+            .unwrap();
     tokio::spawn(driver);
     let client = reqwest::Client::new();
     url.set_port(Some(addr.port())).unwrap();
@@ -204,6 +204,7 @@ async fn speed_test(n: Option<usize>) -> Arc<TempDir> {
             fn_name: func.into(),
             payload: ExternIO::encode(payload)?,
             provenance: cell_id.agent_pubkey().clone(),
+            timeout_ms: None,
         })
     }
 
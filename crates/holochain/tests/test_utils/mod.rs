@@ -278,6 +278,8 @@ pub fn create_config(port: u16, environment_path: PathBuf) -> ConductorConfig {
         dpki: None,
         keystore: KeystoreConfig::DangerTestKeystoreLegacyDeprecated,
         db_sync_strategy: DbSyncStrategy::default(),
+        wasm_limits: Default::default(),
+        zome_call_timeout_ms: None,
     }
 }
 
@@ -5,34 +5,49 @@ use holochain_p2p::actor::GetActivityOptions;
 
 pub(crate) fn merge_activities(
     agent: AgentPubKey,
+    query: &ChainQueryFilter,
     options: &GetActivityOptions,
     results: Vec<AgentActivityResponse<HeaderHash>>,
 ) -> CascadeResult<AgentActivityResponse<HeaderHash>> {
     if !options.include_rejected_activity && !options.include_valid_activity {
         return Ok(merge_status_only(agent, results));
     }
-    Ok(merge_hashes(agent, options, results))
+    Ok(merge_hashes(agent, query, options, results))
 }
 
+/// Each authority has already filtered its activity and returned the first
+/// `offset + limit` matches, so the first `offset + limit` of the merged
+/// hashes are all known and the page can be taken from them.
+/// The chain status comes from the authorities' view of the whole chain
+/// as well as any forks or rejections visible in the merged hashes.
 fn merge_hashes(
     agent: AgentPubKey,
+    query: &ChainQueryFilter,
     options: &GetActivityOptions,
     results: Vec<AgentActivityResponse<HeaderHash>>,
 ) -> AgentActivityResponse<HeaderHash> {
     let mut valid = HashSet::new();
     let mut rejected = HashSet::new();
     let mut merged_highest_observed = None;
+    let mut merged_status = None;
+    let mut agreement = Agreement::default();
     for result in results {
         let AgentActivityResponse {
             agent: the_agent,
             highest_observed,
             valid_activity,
             rejected_activity,
+            status,
             ..
         } = result;
         if the_agent != agent {
             continue;
         }
+        agreement.observe(&status, &highest_observed);
+        merged_status = Some(match merged_status.take() {
+            Some(last) => merge_status(last, status),
+            None => status,
+        });
 
         match (merged_highest_observed.take(), highest_observed) {
             (None, None) => {}
@@ -68,13 +83,17 @@ fn merge_hashes(
     }
 
     let (status, valid, rejected) = compute_chain_status(valid, rejected);
+    let status = match merged_status {
+        Some(merged_status) => merge_status(merged_status, status),
+        None => status,
+    };
     let valid_activity = if options.include_valid_activity {
-        ChainItems::Hashes(valid)
+        ChainItems::Hashes(order_and_page(query, valid))
     } else {
         ChainItems::NotRequested
     };
     let rejected_activity = if options.include_rejected_activity {
-        ChainItems::Hashes(rejected)
+        ChainItems::Hashes(order_and_page(query, rejected))
     } else {
         ChainItems::NotRequested
     };
@@ -84,6 +103,49 @@ fn merge_hashes(
         valid_activity,
         rejected_activity,
         highest_observed: merged_highest_observed,
+        authorities: agreement.into(),
+    }
+}
+
+/// Put ascending hashes into the query's order and apply its offset and limit.
+fn order_and_page(
+    query: &ChainQueryFilter,
+    mut hashes: Vec<(u32, HeaderHash)>,
+) -> Vec<(u32, HeaderHash)> {
+    if query.order_descending {
+        hashes.reverse();
+    }
+    query.paginate(hashes)
+}
+
+/// Tracks whether every authority reported the same chain.
+#[derive(Default)]
+struct Agreement {
+    first: Option<(ChainStatus, Option<HighestObserved>)>,
+    consulted: u32,
+    disagreed: bool,
+}
+
+impl Agreement {
+    fn observe(&mut self, status: &ChainStatus, highest_observed: &Option<HighestObserved>) {
+        self.consulted += 1;
+        match &self.first {
+            Some((first_status, first_highest_observed)) => {
+                if first_status != status || first_highest_observed != highest_observed {
+                    self.disagreed = true;
+                }
+            }
+            None => self.first = Some((status.clone(), highest_observed.clone())),
+        }
+    }
+}
+
+impl From<Agreement> for ActivityAuthorities {
+    fn from(agreement: Agreement) -> Self {
+        Self {
+            consulted: agreement.consulted,
+            agreed: !agreement.disagreed,
+        }
     }
 }
 
@@ -96,9 +158,10 @@ fn compute_chain_status(
 ) -> (ChainStatus, ValidHashes, RejectedHashes) {
     let mut valid: Vec<_> = valid.into_iter().collect();
     let mut rejected: Vec<_> = rejected.into_iter().collect();
-    // Sort ascending.
-    valid.sort_unstable_by(|a, b| a.0.cmp(&b.0));
-    rejected.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    // Sort ascending by sequence and then hash, the same order
+    // authorities page in.
+    valid.sort_unstable();
+    rejected.sort_unstable();
     let mut valid_out = Vec::with_capacity(valid.len());
     let mut status = None;
     for (seq, hash) in valid {
@@ -109,8 +172,9 @@ fn compute_chain_status(
             if let Some(fork) = fork {
                 status = Some(ChainStatus::Forked(ChainFork {
                     fork_seq: seq,
-                    first_header: hash.clone(),
-                    second_header: fork.1.clone(),
+                    first_header: fork.1.clone(),
+                    second_header: hash.clone(),
+                    branches: Vec::with_capacity(0),
                 }));
             }
         }
@@ -147,6 +211,7 @@ fn merge_status_only(
 ) -> AgentActivityResponse<HeaderHash> {
     let mut merged_status = None;
     let mut merged_highest_observed = None;
+    let mut agreement = Agreement::default();
     for result in results {
         let AgentActivityResponse {
             status,
@@ -157,6 +222,7 @@ fn merge_status_only(
         if the_agent != agent {
             continue;
         }
+        agreement.observe(&status, &highest_observed);
         match (merged_highest_observed.take(), highest_observed) {
             (None, None) => {}
             (Some(h), None) | (None, Some(h)) => {
@@ -167,66 +233,10 @@ fn merge_status_only(
                 merged_highest_observed = Some(c);
             }
         }
-        match merged_status.take() {
-            Some(last) => match (status, last) {
-                (ChainStatus::Empty, ChainStatus::Empty) => {
-                    merged_status = Some(ChainStatus::Empty);
-                }
-                (ChainStatus::Empty, ChainStatus::Valid(c))
-                | (ChainStatus::Valid(c), ChainStatus::Empty) => {
-                    merged_status = Some(ChainStatus::Valid(c));
-                }
-                (ChainStatus::Empty, ChainStatus::Forked(c))
-                | (ChainStatus::Forked(c), ChainStatus::Empty) => {
-                    merged_status = Some(ChainStatus::Forked(c));
-                }
-                (ChainStatus::Empty, ChainStatus::Invalid(c))
-                | (ChainStatus::Invalid(c), ChainStatus::Empty) => {
-                    merged_status = Some(ChainStatus::Invalid(c));
-                }
-                (ChainStatus::Valid(a), ChainStatus::Valid(b)) => {
-                    let c = if a.header_seq > b.header_seq { a } else { b };
-                    merged_status = Some(ChainStatus::Valid(c));
-                }
-                (ChainStatus::Valid(_), ChainStatus::Forked(c))
-                | (ChainStatus::Forked(c), ChainStatus::Valid(_)) => {
-                    // If the valid and forked chain heads are the same then they are in conflict here.
-                    // TODO: BACKLOG: When we handle conflicts this should count as a conflict.
-                    merged_status = Some(ChainStatus::Forked(c));
-                }
-                (ChainStatus::Invalid(c), ChainStatus::Valid(_))
-                | (ChainStatus::Valid(_), ChainStatus::Invalid(c)) => {
-                    // If the valid and invalid chain heads are the same then they are in conflict here.
-                    // TODO: BACKLOG: When we handle conflicts this should count as a conflict.
-                    merged_status = Some(ChainStatus::Invalid(c));
-                }
-                (ChainStatus::Forked(a), ChainStatus::Forked(b)) => {
-                    let c = if a.fork_seq < b.fork_seq { a } else { b };
-                    merged_status = Some(ChainStatus::Forked(c));
-                }
-                (ChainStatus::Invalid(a), ChainStatus::Invalid(b)) => {
-                    let c = if a.header_seq < b.header_seq { a } else { b };
-                    merged_status = Some(ChainStatus::Invalid(c));
-                }
-                (ChainStatus::Forked(a), ChainStatus::Invalid(b)) => {
-                    if a.fork_seq < b.header_seq {
-                        merged_status = Some(ChainStatus::Forked(a));
-                    } else {
-                        merged_status = Some(ChainStatus::Invalid(b));
-                    };
-                }
-                (ChainStatus::Invalid(a), ChainStatus::Forked(b)) => {
-                    if a.header_seq < b.fork_seq {
-                        merged_status = Some(ChainStatus::Invalid(a));
-                    } else {
-                        merged_status = Some(ChainStatus::Forked(b));
-                    };
-                }
-            },
-            None => {
-                merged_status = Some(status);
-            }
-        }
+        merged_status = Some(match merged_status.take() {
+            Some(last) => merge_status(last, status),
+            None => status,
+        });
     }
     AgentActivityResponse {
         status: merged_status.unwrap_or(ChainStatus::Empty),
@@ -234,5 +244,96 @@ fn merge_status_only(
         valid_activity: ChainItems::NotRequested,
         rejected_activity: ChainItems::NotRequested,
         highest_observed: merged_highest_observed,
+        authorities: agreement.into(),
+    }
+}
+
+/// Merge the chain status from two authorities, keeping the earliest
+/// problem with the chain or else the highest valid head.
+fn merge_status(last: ChainStatus, status: ChainStatus) -> ChainStatus {
+    match (status, last) {
+        (ChainStatus::Empty, ChainStatus::Empty) => ChainStatus::Empty,
+        (ChainStatus::Empty, ChainStatus::Valid(c))
+        | (ChainStatus::Valid(c), ChainStatus::Empty) => ChainStatus::Valid(c),
+        (ChainStatus::Empty, ChainStatus::Forked(c))
+        | (ChainStatus::Forked(c), ChainStatus::Empty) => ChainStatus::Forked(c),
+        (ChainStatus::Empty, ChainStatus::Invalid(c))
+        | (ChainStatus::Invalid(c), ChainStatus::Empty) => ChainStatus::Invalid(c),
+        (ChainStatus::Valid(a), ChainStatus::Valid(b)) => {
+            if a.header_seq == b.header_seq && a.hash != b.hash {
+                // Both heads were validated so the chain has forked
+                // and each authority has only seen one side.
+                ChainStatus::Forked(ChainFork {
+                    fork_seq: a.header_seq,
+                    first_header: a.hash,
+                    second_header: b.hash,
+                    branches: Vec::with_capacity(0),
+                })
+            } else if a.header_seq > b.header_seq {
+                ChainStatus::Valid(a)
+            } else {
+                ChainStatus::Valid(b)
+            }
+        }
+        (ChainStatus::Valid(_), ChainStatus::Forked(c))
+        | (ChainStatus::Forked(c), ChainStatus::Valid(_)) => {
+            // If the valid and forked chain heads are the same then they are in conflict here.
+            // TODO: BACKLOG: When we handle conflicts this should count as a conflict.
+            ChainStatus::Forked(c)
+        }
+        (ChainStatus::Invalid(c), ChainStatus::Valid(_))
+        | (ChainStatus::Valid(_), ChainStatus::Invalid(c)) => {
+            // If the valid and invalid chain heads are the same then they are in conflict here.
+            // TODO: BACKLOG: When we handle conflicts this should count as a conflict.
+            ChainStatus::Invalid(c)
+        }
+        (ChainStatus::Forked(a), ChainStatus::Forked(b)) => {
+            if a.fork_seq == b.fork_seq {
+                ChainStatus::Forked(merge_forks(a, b))
+            } else if a.fork_seq < b.fork_seq {
+                ChainStatus::Forked(a)
+            } else {
+                ChainStatus::Forked(b)
+            }
+        }
+        (ChainStatus::Invalid(a), ChainStatus::Invalid(b)) => {
+            let c = if a.header_seq < b.header_seq { a } else { b };
+            ChainStatus::Invalid(c)
+        }
+        (ChainStatus::Forked(a), ChainStatus::Invalid(b)) => {
+            if a.fork_seq < b.header_seq {
+                ChainStatus::Forked(a)
+            } else {
+                ChainStatus::Invalid(b)
+            }
+        }
+        (ChainStatus::Invalid(a), ChainStatus::Forked(b)) => {
+            if a.header_seq < b.fork_seq {
+                ChainStatus::Invalid(a)
+            } else {
+                ChainStatus::Forked(b)
+            }
+        }
+    }
+}
+
+/// Combine the branches two authorities see from the same fork point,
+/// keeping the furthest head each has seen for a branch.
+fn merge_forks(a: ChainFork, b: ChainFork) -> ChainFork {
+    let mut branches = a.branches;
+    for branch in b.branches {
+        match branches
+            .iter_mut()
+            .find(|known| known.header == branch.header)
+        {
+            Some(known) => {
+                if branch.head.header_seq > known.head.header_seq {
+                    *known = branch;
+                }
+            }
+            None => branches.push(branch),
+        }
     }
+    branches.sort_by_key(|branch| branch.timestamp);
+    ChainFork { branches, ..a }
 }
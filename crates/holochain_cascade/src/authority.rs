@@ -57,9 +57,7 @@ pub async fn handle_get_agent_activity(
     options: holochain_p2p::event::GetActivityOptions,
 ) -> CascadeResult<AgentActivityResponse<HeaderHash>> {
     let query = GetAgentActivityQuery::new(agent, query, options);
-    let results = env
-        .async_reader(move |txn| query.run(Txn::from(&txn)))
-        .await?;
+    let results = env.async_reader(move |txn| query.run(&txn)).await?;
     Ok(results)
 }
 
@@ -1,14 +1,25 @@
 use holo_hash::*;
 use holochain_p2p::event::GetActivityOptions;
 use holochain_sqlite::rusqlite::*;
-use holochain_state::{prelude::*, query::QueryData};
-use holochain_zome_types::Judged;
+use holochain_state::prelude::*;
+use holochain_state::query::chain_query::ChainQuery;
+use holochain_state::query::chain_query::ChainQuerySeqRange;
 use holochain_zome_types::*;
 use std::fmt::Debug;
-use std::sync::Arc;
 
 use crate::authority::*;
 
+/// The integrated register agent activity ops with a validation status,
+/// followed by the conditions on the header.
+const INTEGRATED_ACTIVITY: &str = "
+    FROM Header
+    JOIN DhtOp ON DhtOp.header_hash = Header.hash
+    WHERE DhtOp.type = :op_type
+    AND DhtOp.validation_status = :status
+    AND DhtOp.when_integrated IS NOT NULL
+    AND
+";
+
 #[derive(Debug, Clone)]
 pub struct GetAgentActivityQuery {
     agent: AgentPubKey,
@@ -24,201 +35,327 @@ impl GetAgentActivityQuery {
             options,
         }
     }
-}
-
-#[derive(Debug, Default)]
-pub struct State {
-    valid: Vec<HeaderHashed>,
-    rejected: Vec<HeaderHashed>,
-    pending: Vec<HeaderHashed>,
-    status: Option<ChainStatus>,
-}
-
-#[derive(Debug)]
-pub enum Item {
-    Integrated(HeaderHashed),
-    Pending(HeaderHashed),
-}
-
-impl Query for GetAgentActivityQuery {
-    type Item = Judged<Item>;
-    type State = State;
-    type Output = AgentActivityResponse<HeaderHash>;
-
-    fn query(&self) -> String {
-        "
-            SELECT Header.hash, DhtOp.validation_status, Header.blob AS header_blob,
-            DhtOp.when_integrated
-            FROM Header
-            JOIN DhtOp ON DhtOp.header_hash = Header.hash
-            WHERE Header.author = :author
-            AND DhtOp.type = :op_type
-            ORDER BY Header.seq ASC
-        "
-        .to_string()
-    }
 
-    fn params(&self) -> Vec<holochain_state::query::Params> {
-        (named_params! {
-            ":author": self.agent,
-            ":op_type": DhtOpType::RegisterAgentActivity,
-        })
-        .to_vec()
-    }
-
-    fn init_fold(&self) -> StateQueryResult<Self::State> {
-        Ok(Default::default())
-    }
-
-    fn as_filter(&self) -> Box<dyn Fn(&QueryData<Self>) -> bool> {
-        unimplemented!("This query should not be used with the scratch")
-    }
-
-    fn as_map(&self) -> Arc<dyn Fn(&Row) -> StateQueryResult<Self::Item>> {
-        Arc::new(move |row| {
-            let validation_status: Option<ValidationStatus> = row.get("validation_status")?;
-            let hash: HeaderHash = row.get("hash")?;
-            from_blob::<SignedHeader>(row.get("header_blob")?).and_then(|header| {
-                let integrated: Option<Timestamp> = row.get("when_integrated")?;
-                let header = HeaderHashed::with_pre_hashed(header.0, hash);
-                let item = if integrated.is_some() {
-                    Item::Integrated(header)
-                } else {
-                    Item::Pending(header)
-                };
-                Ok(Judged::raw(item, validation_status))
-            })
-        })
-    }
-
-    fn fold(&self, mut state: Self::State, item: Self::Item) -> StateQueryResult<Self::State> {
-        let status = item.validation_status();
-        match (status, item.data) {
-            (Some(ValidationStatus::Valid), Item::Integrated(header)) => {
-                let seq = header.header_seq();
-                if state.status.is_none() {
-                    let fork = state.valid.last().and_then(|v| {
-                        if seq == v.header_seq() {
-                            Some(v)
-                        } else {
-                            None
-                        }
-                    });
-                    if let Some(fork) = fork {
-                        state.status = Some(ChainStatus::Forked(ChainFork {
-                            fork_seq: seq,
-                            first_header: header.as_hash().clone(),
-                            second_header: fork.as_hash().clone(),
-                        }));
-                    }
-                }
-
-                state.valid.push(header);
-            }
-            (Some(ValidationStatus::Rejected), Item::Integrated(header)) => {
-                if state.status.is_none() {
-                    state.status = Some(ChainStatus::Invalid(ChainHead {
-                        header_seq: header.header_seq(),
-                        hash: header.as_hash().clone(),
-                    }));
-                }
-                state.rejected.push(header);
-            }
-            (_, Item::Pending(data)) => state.pending.push(data),
-            _ => (),
-        }
-        Ok(state)
-    }
-
-    fn render<S>(&self, state: Self::State, _stores: S) -> StateQueryResult<Self::Output>
-    where
-        S: Store,
-    {
-        let highest_observed = compute_highest_observed(&state);
-        let status = compute_chain_status(&state);
-
-        let valid = state.valid;
-        let rejected = state.rejected;
+    /// The activity this authority holds that matches the filter
+    /// and the status of the whole chain.
+    ///
+    /// Only the first `offset + limit` matching headers in the filter's
+    /// order are returned. The offset is not skipped here because other
+    /// authorities may hold headers this one hasn't seen, so the cascade
+    /// skips it once the pages from every authority are merged.
+    pub fn run(&self, txn: &Transaction) -> StateQueryResult<AgentActivityResponse<HeaderHash>> {
         let valid_activity = if self.options.include_valid_activity {
-            let valid = self
-                .filter
-                .filter_headers(valid)
-                .into_iter()
-                .map(|h| (h.header_seq(), h.into_hash()))
-                .collect();
-            ChainItems::Hashes(valid)
+            ChainItems::Hashes(self.page(txn, ValidationStatus::Valid)?)
         } else {
             ChainItems::NotRequested
         };
         let rejected_activity = if self.options.include_rejected_activity {
-            let rejected = self
-                .filter
-                .filter_headers(rejected)
-                .into_iter()
-                .map(|h| (h.header_seq(), h.into_hash()))
-                .collect();
-            ChainItems::Hashes(rejected)
+            ChainItems::Hashes(self.page(txn, ValidationStatus::Rejected)?)
         } else {
             ChainItems::NotRequested
         };
-
         Ok(AgentActivityResponse {
             agent: self.agent.clone(),
             valid_activity,
             rejected_activity,
-            status,
-            highest_observed,
+            status: self.chain_status(txn)?,
+            highest_observed: self.highest_observed(txn)?,
+            authorities: ActivityAuthorities::single(),
         })
     }
-}
-
-fn compute_chain_status(state: &State) -> ChainStatus {
-    state.status.clone().unwrap_or_else(|| {
-        if state.valid.is_empty() && state.rejected.is_empty() {
-            ChainStatus::Empty
-        } else {
-            let last = state.valid.last().expect("Safe due to is_empty check");
-            ChainStatus::Valid(ChainHead {
-                header_seq: last.header_seq(),
-                hash: last.as_hash().clone(),
-            })
-        }
-    })
-}
 
-fn compute_highest_observed(state: &State) -> Option<HighestObserved> {
-    let mut highest_observed = None;
-    let mut hashes = Vec::new();
-    let mut check_highest = |seq: u32, hash: &HeaderHash| {
-        if highest_observed.is_none() {
-            highest_observed = Some(seq);
-            hashes.push(hash.clone());
-        } else {
-            let last = highest_observed
-                .as_mut()
-                .expect("Safe due to none check above");
-            match seq.cmp(last) {
-                std::cmp::Ordering::Less => {}
-                std::cmp::Ordering::Equal => hashes.push(hash.clone()),
-                std::cmp::Ordering::Greater => {
-                    hashes.clear();
-                    hashes.push(hash.clone());
-                    *last = seq;
+    /// The matching activity with this validation status,
+    /// ordered by sequence and then hash.
+    fn page(
+        &self,
+        txn: &Transaction,
+        status: ValidationStatus,
+    ) -> StateQueryResult<Vec<(u32, HeaderHash)>> {
+        let seq_range = match ChainQuery::resolve_seq_range(&self.filter.sequence_range, |hash| {
+            ChainQuery::header_seq(txn, &self.agent, hash)
+        })? {
+            ChainQuerySeqRange::Unbounded => None,
+            ChainQuerySeqRange::Bounded(start, end) => Some((start, end)),
+            ChainQuerySeqRange::Empty => return Ok(Vec::new()),
+        };
+        let max = self
+            .filter
+            .limit
+            .map(|limit| limit.saturating_add(self.filter.offset));
+        match &self.filter.sequence_range {
+            ChainQueryFilterRange::HeaderHashRange(_, _)
+            | ChainQueryFilterRange::HeaderHashTerminated(_, _) => {
+                // A hash bounded range follows the previous header from the
+                // end of the range to pick a side of any fork so every header
+                // between the bounds is read before the filter is applied.
+                let unfiltered = ChainQueryFilter::new();
+                let chain_query = ChainQuery::new(&self.agent, &unfiltered, seq_range);
+                let params = with_activity_params(chain_query.params(), status);
+                let params: Vec<_> = params
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.as_ref() as &dyn ToSql))
+                    .collect();
+                let headers = headers(
+                    txn,
+                    &format!(
+                        "SELECT Header.hash, Header.blob AS header_blob {} {}",
+                        INTEGRATED_ACTIVITY,
+                        chain_query.conditions()
+                    ),
+                    &params[..],
+                )?;
+                let mut hashes: Vec<_> = self
+                    .filter
+                    .filter_headers(headers)
+                    .into_iter()
+                    .map(|header| (header.header_seq(), header.into_hash()))
+                    .collect();
+                hashes.sort_unstable();
+                if self.filter.order_descending {
+                    hashes.reverse();
                 }
+                if let Some(max) = max {
+                    hashes.truncate(max as usize);
+                }
+                Ok(hashes)
+            }
+            ChainQueryFilterRange::Unbounded | ChainQueryFilterRange::HeaderSeqRange(_, _) => {
+                let chain_query = ChainQuery::new(&self.agent, &self.filter, seq_range);
+                let order = if self.filter.order_descending {
+                    "DESC"
+                } else {
+                    "ASC"
+                };
+                // A negative limit means no limit in sqlite.
+                let mut params = with_activity_params(chain_query.params(), status);
+                params.push((
+                    ":limit".to_string(),
+                    Box::new(max.map(i64::from).unwrap_or(-1)),
+                ));
+                let params: Vec<_> = params
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.as_ref() as &dyn ToSql))
+                    .collect();
+                let mut stmt = txn.prepare(&format!(
+                    "
+                    SELECT Header.seq, Header.hash {} {}
+                    ORDER BY Header.seq {order}, Header.hash {order}
+                    LIMIT :limit
+                    ",
+                    INTEGRATED_ACTIVITY,
+                    chain_query.conditions(),
+                    order = order,
+                ))?;
+                let hashes = stmt
+                    .query_and_then(&params[..], |row| {
+                        StateQueryResult::Ok((row.get(0)?, row.get(1)?))
+                    })?
+                    .collect::<StateQueryResult<Vec<_>>>();
+                hashes
             }
         }
-    };
-    if let Some(valid) = state.valid.last() {
-        check_highest(valid.header_seq(), valid.as_hash());
     }
-    if let Some(rejected) = state.rejected.last() {
-        check_highest(rejected.header_seq(), rejected.as_hash());
+
+    /// The earliest fork or rejected header on the chain,
+    /// otherwise the highest valid header.
+    /// A rejected header wins over a fork at the same sequence.
+    fn chain_status(&self, txn: &Transaction) -> StateQueryResult<ChainStatus> {
+        let fork_seq: Option<u32> = txn
+            .query_row(
+                &format!(
+                    "
+                    SELECT Header.seq {} Header.author = :author
+                    GROUP BY Header.seq
+                    HAVING COUNT(DISTINCT Header.hash) > 1
+                    ORDER BY Header.seq ASC
+                    LIMIT 1
+                    ",
+                    INTEGRATED_ACTIVITY
+                ),
+                named_params! {
+                    ":author": self.agent,
+                    ":op_type": DhtOpType::RegisterAgentActivity,
+                    ":status": ValidationStatus::Valid,
+                },
+                |row| row.get(0),
+            )
+            .optional()?;
+        let invalid = self.first_or_last(txn, ValidationStatus::Rejected, "ASC")?;
+        Ok(match (fork_seq, invalid) {
+            (Some(fork_seq), Some(invalid)) if invalid.header_seq <= fork_seq => {
+                ChainStatus::Invalid(invalid)
+            }
+            (Some(fork_seq), _) => ChainStatus::Forked(self.fork(txn, fork_seq)?),
+            (None, Some(invalid)) => ChainStatus::Invalid(invalid),
+            (None, None) => match self.first_or_last(txn, ValidationStatus::Valid, "DESC")? {
+                Some(head) => ChainStatus::Valid(head),
+                None => ChainStatus::Empty,
+            },
+        })
+    }
+
+    /// The lowest (`ASC`) or highest (`DESC`) header with this status.
+    fn first_or_last(
+        &self,
+        txn: &Transaction,
+        status: ValidationStatus,
+        order: &str,
+    ) -> StateQueryResult<Option<ChainHead>> {
+        Ok(txn
+            .query_row(
+                &format!(
+                    "
+                    SELECT Header.seq, Header.hash {} Header.author = :author
+                    ORDER BY Header.seq {order}, Header.hash {order}
+                    LIMIT 1
+                    ",
+                    INTEGRATED_ACTIVITY,
+                    order = order,
+                ),
+                named_params! {
+                    ":author": self.agent,
+                    ":op_type": DhtOpType::RegisterAgentActivity,
+                    ":status": status,
+                },
+                |row| {
+                    Ok(ChainHead {
+                        header_seq: row.get(0)?,
+                        hash: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// The fork at this sequence and how far each branch goes.
+    /// Only the valid headers from the fork onwards are read.
+    fn fork(&self, txn: &Transaction, fork_seq: u32) -> StateQueryResult<ChainFork> {
+        let valid = headers(
+            txn,
+            &format!(
+                "
+                SELECT Header.hash, Header.blob AS header_blob {}
+                Header.author = :author AND Header.seq >= :fork_seq
+                ORDER BY Header.seq ASC, Header.hash ASC
+                ",
+                INTEGRATED_ACTIVITY
+            ),
+            named_params! {
+                ":author": self.agent,
+                ":op_type": DhtOpType::RegisterAgentActivity,
+                ":status": ValidationStatus::Valid,
+                ":fork_seq": fork_seq,
+            },
+        )?;
+        let mut at_fork = valid.iter().filter(|h| h.header_seq() == fork_seq);
+        let first_header = at_fork.next().map(|h| h.as_hash().clone());
+        let second_header = at_fork.next().map(|h| h.as_hash().clone());
+        match (first_header, second_header) {
+            (Some(first_header), Some(second_header)) => Ok(ChainFork {
+                fork_seq,
+                first_header,
+                second_header,
+                branches: compute_fork_branches(fork_seq, &valid),
+            }),
+            // The fork was found in the same transaction.
+            _ => unreachable!("A fork has at least two headers"),
+        }
     }
-    if let Some(pending) = state.pending.last() {
-        check_highest(pending.header_seq(), pending.as_hash());
+
+    /// Every header at the highest sequence this authority has seen,
+    /// whether it is still being validated or was found valid or rejected.
+    fn highest_observed(&self, txn: &Transaction) -> StateQueryResult<Option<HighestObserved>> {
+        let mut stmt = txn.prepare(
+            "
+            SELECT Header.seq, Header.hash FROM Header
+            JOIN DhtOp ON DhtOp.header_hash = Header.hash
+            WHERE Header.author = :author
+            AND DhtOp.type = :op_type
+            AND (DhtOp.when_integrated IS NULL OR DhtOp.validation_status IN (:valid, :rejected))
+            AND Header.seq = (
+                SELECT MAX(Header.seq) FROM Header
+                JOIN DhtOp ON DhtOp.header_hash = Header.hash
+                WHERE Header.author = :author
+                AND DhtOp.type = :op_type
+                AND (DhtOp.when_integrated IS NULL OR DhtOp.validation_status IN (:valid, :rejected))
+            )
+            ORDER BY Header.hash ASC
+            ",
+        )?;
+        let highest = stmt
+            .query_and_then(
+                named_params! {
+                    ":author": self.agent,
+                    ":op_type": DhtOpType::RegisterAgentActivity,
+                    ":valid": ValidationStatus::Valid,
+                    ":rejected": ValidationStatus::Rejected,
+                },
+                |row| StateQueryResult::Ok((row.get::<_, u32>(0)?, row.get::<_, HeaderHash>(1)?)),
+            )?
+            .collect::<StateQueryResult<Vec<_>>>()?;
+        Ok(highest.first().map(|(header_seq, _)| HighestObserved {
+            header_seq: *header_seq,
+            hash: highest.iter().map(|(_, hash)| hash.clone()).collect(),
+        }))
     }
-    highest_observed.map(|header_seq| HighestObserved {
-        header_seq,
-        hash: hashes,
-    })
+}
+
+/// Add the op type and validation status to a [`ChainQuery`]'s params
+/// for [`INTEGRATED_ACTIVITY`].
+fn with_activity_params<'a>(
+    mut params: Vec<(String, Box<dyn ToSql + 'a>)>,
+    status: ValidationStatus,
+) -> Vec<(String, Box<dyn ToSql + 'a>)> {
+    params.push((
+        ":op_type".to_string(),
+        Box::new(DhtOpType::RegisterAgentActivity),
+    ));
+    params.push((":status".to_string(), Box::new(status)));
+    params
+}
+
+/// Read the headers selected by `sql` as `hash` and `header_blob`.
+fn headers(
+    txn: &Transaction,
+    sql: &str,
+    params: &[(&str, &dyn ToSql)],
+) -> StateQueryResult<Vec<HeaderHashed>> {
+    let mut stmt = txn.prepare(sql)?;
+    let headers = stmt
+        .query_and_then(params, |row| {
+            let hash: HeaderHash = row.get("hash")?;
+            let SignedHeader(header, _) = from_blob::<SignedHeader>(row.get("header_blob")?)?;
+            StateQueryResult::Ok(HeaderHashed::with_pre_hashed(header, hash))
+        })?
+        .collect::<StateQueryResult<Vec<_>>>();
+    headers
+}
+
+/// Follow each header at the fork sequence to the last valid header
+/// that builds on it.
+/// Valid headers are in sequence order so each branch can be followed
+/// forward in a single pass.
+fn compute_fork_branches(fork_seq: u32, valid: &[HeaderHashed]) -> Vec<ForkBranch> {
+    let mut branches: Vec<_> = valid
+        .iter()
+        .filter(|start| start.header_seq() == fork_seq)
+        .map(|start| {
+            let mut head = start;
+            for header in valid.iter().filter(|h| h.header_seq() > fork_seq) {
+                if header.prev_header() == Some(head.as_hash()) {
+                    head = header;
+                }
+            }
+            ForkBranch {
+                header: start.as_hash().clone(),
+                timestamp: start.timestamp(),
+                head: ChainHead {
+                    header_seq: head.header_seq(),
+                    hash: head.as_hash().clone(),
+                },
+            }
+        })
+        .collect();
+    branches.sort_by_key(|branch| branch.timestamp);
+    branches
 }
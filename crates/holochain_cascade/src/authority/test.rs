@@ -224,6 +224,7 @@ async fn get_agent_activity() {
         rejected_activity: ChainItems::NotRequested,
         status: ChainStatus::Valid(td.chain_head.clone()),
         highest_observed: Some(td.highest_observed.clone()),
+        authorities: ActivityAuthorities::single(),
     };
     assert_eq!(result, expected);
 
@@ -234,6 +235,7 @@ async fn get_agent_activity() {
 
     let filter = td
         .query_filter
+        .clone()
         .sequence_range(ChainQueryFilterRange::HeaderSeqRange(0, 19));
     let result = handle_get_agent_activity(
         db.to_db().into(),
@@ -245,4 +247,25 @@ async fn get_agent_activity() {
     .unwrap();
 
     assert_eq!(result, expected);
+
+    // Pages are taken from the chain head down but the status
+    // still covers the whole chain.
+    // The offset is left to the cascade so it can be applied after
+    // merging the pages from every authority.
+    expected.valid_activity = match td.valid_hashes.clone() {
+        ChainItems::Hashes(v) => ChainItems::Hashes(v.into_iter().rev().take(5).collect()),
+        _ => unreachable!(),
+    };
+
+    let filter = td.query_filter.clone().descending().offset(2).limit(3);
+    let result = handle_get_agent_activity(
+        db.to_db().into(),
+        td.agent.clone(),
+        filter,
+        (&options).into(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result, expected);
 }
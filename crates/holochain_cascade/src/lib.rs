@@ -750,8 +750,13 @@ where
     /// - include_full_headers will fetch the valid headers in parallel (requires include_valid_activity)
     /// Query:
     /// - include_entries will also fetch the entries in parallel (requires include_full_headers)
-    /// - sequence_range will get all the activity in the exclusive range
-    /// - header_type and entry_type will filter the activity (requires include_full_headers)
+    /// - sequence_range will get all the activity in the inclusive range
+    /// - header_type and entry_type will filter the activity
+    /// - order_descending, offset and limit will page through the activity.
+    ///   Each authority sends at most `offset + limit` hashes so for deep pages
+    ///   narrow the sequence_range rather than growing the offset.
+    /// The chain status always covers the whole chain and the response
+    /// reports how many authorities were consulted and whether they agreed.
     pub async fn get_agent_activity(
         &mut self,
         agent: AgentPubKey,
@@ -766,21 +771,24 @@ where
                 .fetch_agent_activity(agent.clone(), query.clone(), options.clone())
                 .await?;
             let merged_response: AgentActivityResponse<HeaderHash> =
-                agent_activity::merge_activities(agent.clone(), &options, results)?;
+                agent_activity::merge_activities(agent.clone(), &query, &options, results)?;
             merged_response
         } else {
             match self.dht.clone() {
                 Some(vault) => {
-                    authority::handle_get_agent_activity(
+                    let result = authority::handle_get_agent_activity(
                         vault,
                         agent.clone(),
                         query.clone(),
                         (&options).into(),
                     )
-                    .await?
+                    .await?;
+                    // The authority leaves the offset to the merge.
+                    agent_activity::merge_activities(agent.clone(), &query, &options, vec![result])?
                 }
                 None => agent_activity::merge_activities(
                     agent.clone(),
+                    &query,
                     &options,
                     Vec::with_capacity(0),
                 )?,
@@ -809,6 +817,7 @@ where
             rejected_activity,
             status,
             highest_observed,
+            authorities,
         } = merged_response;
         let valid_activity = match valid_activity {
             ChainItems::Hashes(hashes) => {
@@ -824,7 +833,12 @@ where
                     .collect();
                 match maybe_chain {
                     Some(mut chain) => {
-                        chain.sort_unstable_by_key(|el| el.header().header_seq());
+                        chain.sort_unstable_by_key(|el| {
+                            (el.header().header_seq(), el.header_address().clone())
+                        });
+                        if query.order_descending {
+                            chain.reverse();
+                        }
                         ChainItems::Full(chain)
                     }
                     None => ChainItems::Full(Vec::with_capacity(0)),
@@ -847,7 +861,12 @@ where
                     .collect();
                 match maybe_chain {
                     Some(mut chain) => {
-                        chain.sort_unstable_by_key(|el| el.header().header_seq());
+                        chain.sort_unstable_by_key(|el| {
+                            (el.header().header_seq(), el.header_address().clone())
+                        });
+                        if query.order_descending {
+                            chain.reverse();
+                        }
                         ChainItems::Full(chain)
                     }
                     None => ChainItems::Full(Vec::with_capacity(0)),
@@ -863,6 +882,7 @@ where
             rejected_activity,
             status,
             highest_observed,
+            authorities,
         };
         Ok(r)
     }
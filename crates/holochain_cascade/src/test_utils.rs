@@ -218,6 +218,14 @@ impl HolochainP2pDnaT for PassThroughNetwork {
         todo!()
     }
 
+    async fn countersigning_enzyme_push(
+        &self,
+        _enzyme: AgentPubKey,
+        _op: holochain_types::dht_op::DhtOp,
+    ) -> actor::HolochainP2pResult<()> {
+        todo!()
+    }
+
     async fn new_integrated_data(&self) -> actor::HolochainP2pResult<()> {
         todo!()
     }
@@ -396,6 +404,14 @@ impl HolochainP2pDnaT for MockNetwork {
         todo!()
     }
 
+    async fn countersigning_enzyme_push(
+        &self,
+        _enzyme: AgentPubKey,
+        _op: holochain_types::dht_op::DhtOp,
+    ) -> actor::HolochainP2pResult<()> {
+        todo!()
+    }
+
     async fn new_integrated_data(&self) -> actor::HolochainP2pResult<()> {
         todo!()
     }
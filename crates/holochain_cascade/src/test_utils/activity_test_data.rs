@@ -139,4 +139,29 @@ impl ActivityTestData {
             valid_elements: ChainItems::Full(valid_elements),
         }
     }
+
+    /// A branch of `len` valid headers that forks the chain at `fork_seq`,
+    /// as activity ops and the headers they register.
+    pub fn fork(&self, fork_seq: u32, len: usize) -> (Vec<DhtOpHashed>, Vec<HeaderHashed>) {
+        let mut prev_hash = match &self.valid_hashes {
+            ChainItems::Hashes(hashes) => hashes[fork_seq as usize - 1].1.clone(),
+            _ => unreachable!(),
+        };
+        CreateFixturator::new(Unpredictable)
+            .take(len)
+            .enumerate()
+            .map(|(i, mut create)| {
+                create.author = self.agent.clone();
+                create.header_seq = fork_seq + i as u32;
+                create.prev_header = prev_hash.clone();
+                let header = HeaderHashed::from_content_sync(Header::Create(create));
+                prev_hash = header.as_hash().clone();
+                let op = DhtOpHashed::from_content_sync(DhtOp::RegisterAgentActivity(
+                    fixt!(Signature),
+                    header.as_content().clone(),
+                ));
+                (op, header)
+            })
+            .unzip()
+    }
 }
@@ -4,7 +4,11 @@ use holochain_cascade::Cascade;
 use holochain_state::prelude::test_cache_db;
 use holochain_state::prelude::test_dht_db;
 use holochain_types::activity::*;
+use holochain_zome_types::ActivityAuthorities;
+use holochain_zome_types::ChainFork;
+use holochain_zome_types::ChainHead;
 use holochain_zome_types::ChainStatus;
+use holochain_zome_types::ForkBranch;
 use pretty_assertions::assert_eq;
 
 #[tokio::test(flavor = "multi_thread")]
@@ -52,6 +56,180 @@ async fn get_activity() {
         rejected_activity: ChainItems::NotRequested,
         status: ChainStatus::Valid(td.chain_head.clone()),
         highest_observed: Some(td.highest_observed.clone()),
+        authorities: ActivityAuthorities::single(),
     };
     assert_eq!(r, expected);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_activity_pages_across_authorities() {
+    observability::test_run().ok();
+
+    // Environments
+    let cache = test_cache_db();
+    let even = test_dht_db();
+    let odd = test_dht_db();
+
+    // Data
+    let td = ActivityTestData::valid_chain_scenario();
+    let valid_hashes = match td.valid_hashes.clone() {
+        ChainItems::Hashes(hashes) => hashes,
+        _ => unreachable!(),
+    };
+
+    // Neither authority holds the whole chain.
+    for (seq, hash_op) in td.hash_ops.iter().cloned().enumerate() {
+        if seq % 2 == 0 {
+            fill_db(&even.to_db(), hash_op);
+        } else {
+            fill_db(&odd.to_db(), hash_op);
+        }
+    }
+
+    let options = holochain_p2p::actor::GetActivityOptions {
+        include_valid_activity: true,
+        include_rejected_activity: false,
+        include_full_headers: false,
+        ..Default::default()
+    };
+
+    // Network
+    let network = PassThroughNetwork::authority_for_nothing(vec![
+        even.to_db().clone().into(),
+        odd.to_db().clone().into(),
+    ]);
+
+    // Cascade
+    let mut cascade = Cascade::empty().with_network(network, cache.to_db());
+
+    let r = cascade
+        .get_agent_activity(td.agent.clone(), td.query_filter.clone(), options.clone())
+        .await
+        .unwrap();
+
+    // The authorities see different chain heads.
+    assert_eq!(r.status, ChainStatus::Valid(td.chain_head.clone()));
+    assert_eq!(r.highest_observed, Some(td.highest_observed.clone()));
+    assert_eq!(
+        r.authorities,
+        ActivityAuthorities {
+            consulted: 2,
+            agreed: false,
+        }
+    );
+
+    // Paging through the merged activity neither skips nor repeats headers.
+    for descending in [false, true] {
+        let mut paged = Vec::new();
+        for offset in (0..valid_hashes.len() as u32 + 7).step_by(7) {
+            let mut filter = td.query_filter.clone().offset(offset).limit(7);
+            if descending {
+                filter = filter.descending();
+            }
+            let r = cascade
+                .get_agent_activity(td.agent.clone(), filter, options.clone())
+                .await
+                .unwrap();
+            match r.valid_activity {
+                ChainItems::Hashes(hashes) => paged.extend(hashes),
+                _ => unreachable!(),
+            }
+        }
+        let mut expected = valid_hashes.clone();
+        if descending {
+            expected.reverse();
+        }
+        assert_eq!(paged, expected);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_activity_forked_chain() {
+    observability::test_run().ok();
+
+    // Environments
+    let cache = test_cache_db();
+    let forked = test_dht_db();
+    let linear = test_dht_db();
+
+    // Data
+    let td = ActivityTestData::valid_chain_scenario();
+    let valid_hashes = match td.valid_hashes.clone() {
+        ChainItems::Hashes(hashes) => hashes,
+        _ => unreachable!(),
+    };
+    let valid_elements = match td.valid_elements.clone() {
+        ChainItems::Full(elements) => elements,
+        _ => unreachable!(),
+    };
+    let (fork_ops, fork) = td.fork(10, 5);
+
+    // Only one authority has seen the fork.
+    for hash_op in td.hash_ops.iter().cloned() {
+        fill_db(&forked.to_db(), hash_op.clone());
+        fill_db(&linear.to_db(), hash_op);
+    }
+    for hash_op in fork_ops {
+        fill_db(&forked.to_db(), hash_op);
+    }
+
+    let options = holochain_p2p::actor::GetActivityOptions {
+        include_valid_activity: true,
+        include_rejected_activity: false,
+        include_full_headers: false,
+        ..Default::default()
+    };
+
+    // Network
+    let network = PassThroughNetwork::authority_for_nothing(vec![
+        forked.to_db().clone().into(),
+        linear.to_db().clone().into(),
+    ]);
+
+    // Cascade
+    let mut cascade = Cascade::empty().with_network(network, cache.to_db());
+
+    let r = cascade
+        .get_agent_activity(td.agent.clone(), td.query_filter.clone(), options)
+        .await
+        .unwrap();
+
+    let mut fork_headers = vec![valid_hashes[10].1.clone(), fork[0].as_hash().clone()];
+    fork_headers.sort_unstable();
+    let mut branches = vec![
+        ForkBranch {
+            header: valid_hashes[10].1.clone(),
+            timestamp: valid_elements[10].header().timestamp(),
+            head: td.chain_head.clone(),
+        },
+        ForkBranch {
+            header: fork[0].as_hash().clone(),
+            timestamp: fork[0].timestamp(),
+            head: ChainHead {
+                header_seq: 14,
+                hash: fork[4].as_hash().clone(),
+            },
+        },
+    ];
+    branches.sort_by_key(|branch| branch.timestamp);
+    let expected_status = ChainStatus::Forked(ChainFork {
+        fork_seq: 10,
+        first_header: fork_headers[0].clone(),
+        second_header: fork_headers[1].clone(),
+        branches,
+    });
+    assert_eq!(r.status, expected_status);
+    assert_eq!(
+        r.authorities,
+        ActivityAuthorities {
+            consulted: 2,
+            agreed: false,
+        }
+    );
+
+    // Both sides of the fork are valid activity.
+    let mut expected = valid_hashes;
+    expected.extend(fork.iter().map(|h| (h.header_seq(), h.as_hash().clone())));
+    expected.sort_unstable();
+    assert_eq!(r.valid_activity, ChainItems::Hashes(expected));
+}
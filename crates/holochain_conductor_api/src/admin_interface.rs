@@ -254,6 +254,32 @@ pub enum AdminRequest {
         dna_hash: Option<DnaHash>,
     },
 
+    /// Dump the fuel used by the wasm calls of every zome function
+    /// since the conductor started.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::FuelMetricsDumped`]
+    DumpFuelMetrics {
+        /// If set, limits the metrics dumped to a single DNA.
+        dna_hash: Option<DnaHash>,
+    },
+
+    /// Change the limits of one of the network's bandwidth budgets.
+    ///
+    /// The new limits apply straight away and last until the conductor restarts.
+    /// A limit of zero removes that limit.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::BandwidthLimitsSet`]
+    SetBandwidthLimits {
+        /// The bandwidth budget to change.
+        scope: holochain_p2p::BandwidthLimitScope,
+        /// The new limits of the budget.
+        limits: kitsune_p2p::bandwidth::BandwidthLimits,
+    },
+
     /// Add a list of agents to this conductor's peer store.
     ///
     /// This is a way of shortcutting peer discovery and is useful for testing.
@@ -474,6 +500,15 @@ pub enum AdminResponse {
     /// The string is a JSON blob of the metrics results.
     NetworkMetricsDumped(String),
 
+    /// The successful result of a call to [`AdminRequest::DumpFuelMetrics`].
+    ///
+    /// The string is a JSON blob with the calls, fuel used and calls that
+    /// ran out of fuel per DNA, zome and function.
+    FuelMetricsDumped(String),
+
+    /// The successful response to an [`AdminRequest::SetBandwidthLimits`].
+    BandwidthLimitsSet,
+
     /// The successful response to an [`AdminRequest::AddAgentInfo`].
     ///
     /// This means the agent info was successfully added to the peer store.
@@ -511,6 +546,18 @@ pub enum ExternalApiWireError {
     ZomeCallUnauthorized(String),
     /// A countersigning session has failed.
     CountersigningSessionError(String),
+    /// A call in a zome call batch failed, so nothing in the batch was committed.
+    ZomeCallBatchFailed {
+        /// The position of the failed call in the batch. It is the number of
+        /// calls in the batch if every call succeeded but their writes were
+        /// invalid or couldn't be committed.
+        index: usize,
+        /// The outputs of the calls before the failed one, in order. Their
+        /// writes were not committed either.
+        outputs: Vec<ExternIO>,
+        /// Why the call failed.
+        error: Box<ExternalApiWireError>,
+    },
 }
 
 impl ExternalApiWireError {
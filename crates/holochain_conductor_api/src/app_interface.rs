@@ -39,6 +39,19 @@ pub enum AppRequest {
     #[deprecated = "use ZomeCall"]
     ZomeCallInvocation(Box<ZomeCall>),
 
+    /// Call several zome functions on the same cell, in order.
+    ///
+    /// The calls share one scratch space, so each call sees the writes of
+    /// the calls before it. The writes are committed to the source chain
+    /// together once every call has succeeded. If any call fails, the
+    /// batch stops and nothing is committed.
+    ///
+    /// # Returns
+    ///
+    /// [`AppResponse::ZomeCallBatch`], or [`AppResponse::Error`] with
+    /// [`ExternalApiWireError::ZomeCallBatchFailed`] if a call failed.
+    ZomeCallBatch(Vec<ZomeCall>),
+
     /// Is currently unimplemented and will return
     /// an [`AppResponse::Unimplemented`].
     SignalSubscription(SignalSubscription),
@@ -72,6 +85,11 @@ pub enum AppResponse {
 
     #[deprecated = "use ZomeCall"]
     ZomeCallInvocation(Box<ExternIO>),
+
+    /// The successful response to an [`AppRequest::ZomeCallBatch`].
+    ///
+    /// Contains the output of each call, in the order of the calls.
+    ZomeCallBatch(Vec<ExternIO>),
 }
 
 /// The data provided over an app interface in order to make a zome call
@@ -97,6 +115,12 @@ pub struct ZomeCall {
     /// is making this zome call over this interface. Until we do, the caller simply
     /// provides this data and Holochain trusts them.
     pub provenance: AgentPubKey,
+    /// How long the call may run, in milliseconds, before it is cancelled
+    /// and its changes are discarded.
+    ///
+    /// If `None`, the conductor's `zome_call_timeout_ms` applies.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 
 #[allow(missing_docs)]
@@ -12,6 +12,7 @@ mod dpki_config;
 mod error;
 mod keystore_config;
 pub mod paths;
+mod wasm_limits_config;
 //mod logger_config;
 //mod signal_config;
 pub use paths::DatabaseRootPath;
@@ -21,9 +22,15 @@ pub use dpki_config::DpkiConfig;
 //pub use logger_config::LoggerConfig;
 pub use error::*;
 pub use keystore_config::KeystoreConfig;
+pub use wasm_limits_config::*;
 //pub use signal_config::SignalConfig;
 use std::path::Path;
 
+/// How long a zome call may run, in milliseconds, if neither the call
+/// nor the conductor config sets a timeout.
+/// This matches the default request timeout of the app websocket.
+pub const DEFAULT_ZOME_CALL_TIMEOUT_MS: u64 = 30_000;
+
 // TODO change types from "stringly typed" to Url2
 /// All the config information for the conductor
 #[derive(Clone, Deserialize, Serialize, Default, Debug, PartialEq)]
@@ -56,6 +63,15 @@ pub struct ConductorConfig {
     ///
     /// [sqlite documentation]: https://www.sqlite.org/pragma.html#pragma_synchronous
     pub db_sync_strategy: DbSyncStrategy,
+
+    /// Limits on the fuel and memory of wasm calls, for all DNAs or
+    /// for specific ones.
+    #[serde(default)]
+    pub wasm_limits: WasmLimitsConfig,
+
+    /// How long a zome call may run, in milliseconds, when the call doesn't
+    /// set its own timeout. Defaults to [`DEFAULT_ZOME_CALL_TIMEOUT_MS`].
+    pub zome_call_timeout_ms: Option<u64>,
     //
     //
     // Which signals to emit
@@ -72,6 +88,14 @@ where
 }
 
 impl ConductorConfig {
+    /// The timeout for a zome call that doesn't set its own.
+    pub fn zome_call_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(
+            self.zome_call_timeout_ms
+                .unwrap_or(DEFAULT_ZOME_CALL_TIMEOUT_MS),
+        )
+    }
+
     /// Create a conductor config from a YAML file path.
     pub fn load_yaml(path: &Path) -> ConductorConfigResult<ConductorConfig> {
         let config_yaml = std::fs::read_to_string(path).map_err(|err| match err {
@@ -127,6 +151,8 @@ pub mod tests {
                 keystore: KeystoreConfig::DangerTestKeystoreLegacyDeprecated,
                 admin_interfaces: None,
                 db_sync_strategy: DbSyncStrategy::default(),
+                wasm_limits: WasmLimitsConfig::default(),
+                zome_call_timeout_ms: None,
             }
         );
     }
@@ -177,11 +203,18 @@ pub mod tests {
       network_type: quic_bootstrap
 
     db_sync_strategy: Fast
+
+    wasm_limits:
+      default:
+        validate_fuel: 42
+
+    zome_call_timeout_ms: 42
     "#;
         let result: ConductorConfigResult<ConductorConfig> = config_from_yaml(yaml);
         use holochain_p2p::kitsune_p2p::*;
         let mut network_config = KitsuneP2pConfig::default();
-        network_config.bootstrap_service = Some(url2::url2!("https://bootstrap-staging.holo.host"));
+        network_config.bootstrap_service =
+            Some(url2::url2!("https://bootstrap-staging.holo.host").into());
         network_config.transport_pool.push(TransportConfig::Proxy {
             sub_transport: Box::new(TransportConfig::Quic {
                 bind_to: Some(url2::url2!("kitsune-quic://0.0.0.0:0")),
@@ -221,6 +254,14 @@ pub mod tests {
                 }]),
                 network: Some(network_config),
                 db_sync_strategy: DbSyncStrategy::Fast,
+                wasm_limits: WasmLimitsConfig {
+                    default: WasmLimits {
+                        validate_fuel: 42,
+                        ..Default::default()
+                    },
+                    dnas: Default::default(),
+                },
+                zome_call_timeout_ms: Some(42),
             }
         );
     }
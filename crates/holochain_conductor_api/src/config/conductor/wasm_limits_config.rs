@@ -0,0 +1,60 @@
+use holo_hash::DnaHash;
+use holo_hash::DnaHashB64;
+pub use holochain_types::dna::wasm_metering::DEFAULT_WASM_FUEL;
+pub use holochain_types::dna::wasm_metering::DEFAULT_WASM_MEMORY_PAGES;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Limits on the resources a single call into a zome's wasm may use.
+///
+/// Fuel is metered deterministically, so every node runs out of fuel on the
+/// same instruction when running the same callback with the same input.
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct WasmLimits {
+    /// Fuel for a zome function called through the app interface or
+    /// by another zome.
+    pub zome_call_fuel: u64,
+    /// Fuel for a `validate` callback.
+    pub validate_fuel: u64,
+    /// Fuel for an `init` callback.
+    pub init_fuel: u64,
+    /// Fuel for every other callback, e.g. `entry_defs` or `post_commit`.
+    pub callback_fuel: u64,
+    /// The number of 64KiB pages of linear memory an instance may grow to.
+    pub max_memory_pages: u32,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            zome_call_fuel: DEFAULT_WASM_FUEL,
+            validate_fuel: DEFAULT_WASM_FUEL,
+            init_fuel: DEFAULT_WASM_FUEL,
+            callback_fuel: DEFAULT_WASM_FUEL,
+            max_memory_pages: DEFAULT_WASM_MEMORY_PAGES,
+        }
+    }
+}
+
+/// Wasm resource limits for all DNAs, with optional overrides per DNA.
+#[derive(Clone, Deserialize, Serialize, Default, Debug, PartialEq)]
+pub struct WasmLimitsConfig {
+    /// The limits for any DNA without an override.
+    #[serde(default)]
+    pub default: WasmLimits,
+    /// Overrides for specific DNAs.
+    #[serde(default)]
+    pub dnas: HashMap<DnaHashB64, WasmLimits>,
+}
+
+impl WasmLimitsConfig {
+    /// The limits that apply to a DNA.
+    pub fn for_dna(&self, dna_hash: &DnaHash) -> WasmLimits {
+        self.dnas
+            .get(&dna_hash.clone().into())
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
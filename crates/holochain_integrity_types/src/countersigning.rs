@@ -22,6 +22,8 @@ pub const SESSION_TIME_FUTURE_MAX: Duration =
 pub const MIN_COUNTERSIGNING_AGENTS: usize = 2;
 /// 8 seems like a reasonable limit of agents to countersign.
 pub const MAX_COUNTERSIGNING_AGENTS: usize = 8;
+/// 8 seems like a reasonable limit of entries for each agent to commit in one session.
+pub const MAX_COUNTERSIGNING_ENTRIES: usize = 8;
 
 pub use error::CounterSigningError;
 mod error;
@@ -102,6 +104,36 @@ impl Role {
 /// Alias for a list of agents and their roles.
 pub type CounterSigningAgents = Vec<(AgentPubKey, Vec<Role>)>;
 
+/// An entry that every agent commits in a session after the first app entry.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct PreflightEntry {
+    /// The hash of the app entry, as if it were not countersigned.
+    app_entry_hash: EntryHash,
+    /// The header information for this entry that is shared by all agents.
+    header_base: HeaderBase,
+}
+
+impl PreflightEntry {
+    /// Constructor.
+    pub fn new(app_entry_hash: EntryHash, header_base: HeaderBase) -> Self {
+        Self {
+            app_entry_hash,
+            header_base,
+        }
+    }
+
+    /// App entry hash accessor.
+    pub fn app_entry_hash(&self) -> &EntryHash {
+        &self.app_entry_hash
+    }
+
+    /// Header base accessor.
+    pub fn header_base(&self) -> &HeaderBase {
+        &self.header_base
+    }
+}
+
 /// The same PreflightRequest is sent to every agent.
 /// Each agent signs this data as part of their PreflightResponse.
 /// Every preflight must be identical and signed by every agent for a session to be valid.
@@ -113,8 +145,9 @@ pub struct PreflightRequest {
     app_entry_hash: EntryHash,
     /// The agents that are participating in this countersignature session.
     signing_agents: CounterSigningAgents,
-    /// The agent that must receive and include all other headers in their own header.
-    /// @todo implement enzymes
+    /// The agent that receives every signed header in the session directly
+    /// and sends the complete set back to all the signing agents.
+    /// If this is none then the entry authorities gather the signed headers.
     enzyme_index: Option<u8>,
     /// The session times.
     /// Session headers must all have the same timestamp, which is the session offset.
@@ -124,6 +157,11 @@ pub struct PreflightRequest {
     header_base: HeaderBase,
     /// The preflight bytes for session.
     preflight_bytes: PreflightBytes,
+    /// Further entries every agent commits in this session, in order,
+    /// after the app entry above.
+    /// All the entries are committed atomically or not at all.
+    #[serde(default)]
+    additional_entries: Vec<PreflightEntry>,
 }
 
 impl PreflightRequest {
@@ -143,18 +181,50 @@ impl PreflightRequest {
             session_times,
             header_base,
             preflight_bytes,
+            additional_entries: Vec::new(),
         };
         preflight_request.check_integrity()?;
         Ok(preflight_request)
     }
+
+    /// Fallibly add entries that every agent commits after the app entry.
+    pub fn try_with_additional_entries(
+        mut self,
+        additional_entries: Vec<PreflightEntry>,
+    ) -> Result<Self, CounterSigningError> {
+        self.additional_entries = additional_entries;
+        self.check_integrity()?;
+        Ok(self)
+    }
+
     /// Combined integrity checks.
     pub fn check_integrity(&self) -> Result<(), CounterSigningError> {
         self.check_enzyme_index()?;
         self.session_times().check_integrity()?;
         self.check_agents()?;
+        self.check_entries()?;
         Ok(())
     }
 
+    /// Verify the number of entries is within the limit and there are no
+    /// duplicate app entries, so each entry has one place in the session.
+    pub fn check_entries(&self) -> Result<(), CounterSigningError> {
+        if self.entries_len() > MAX_COUNTERSIGNING_ENTRIES {
+            return Err(CounterSigningError::EntriesLength(self.entries_len()));
+        }
+        let app_entry_hashes: Vec<EntryHash> = (0..self.entries_len())
+            .filter_map(|index| self.entry_at(index))
+            .map(|(app_entry_hash, _)| app_entry_hash.clone())
+            .collect();
+        if std::collections::HashSet::<EntryHash>::from_iter(app_entry_hashes.clone()).len()
+            == self.entries_len()
+        {
+            Ok(())
+        } else {
+            Err(CounterSigningError::EntriesDupes(app_entry_hashes))
+        }
+    }
+
     /// Verify there are no duplicate agents to sign.
     pub fn check_agents_dupes(&self) -> Result<(), CounterSigningError> {
         let v: Vec<AgentPubKey> = self
@@ -224,6 +294,13 @@ impl PreflightRequest {
         &self.enzyme_index
     }
 
+    /// The enzyme agent for this session, if there is one.
+    pub fn enzyme(&self) -> Option<&AgentPubKey> {
+        self.enzyme_index
+            .and_then(|index| self.signing_agents.get(index as usize))
+            .map(|(agent, _roles)| agent)
+    }
+
     /// Mutable enzyme index accessor for testing.
     #[cfg(feature = "test_utils")]
     pub fn enzyme_index_mut(&mut self) -> &mut Option<u8> {
@@ -262,6 +339,34 @@ impl PreflightRequest {
     pub fn preflight_bytes_mut(&mut self) -> &mut PreflightBytes {
         &mut self.preflight_bytes
     }
+
+    /// Additional entries accessor.
+    pub fn additional_entries(&self) -> &Vec<PreflightEntry> {
+        &self.additional_entries
+    }
+
+    /// Mutable additional entries accessor for testing.
+    #[cfg(feature = "test_utils")]
+    pub fn additional_entries_mut(&mut self) -> &mut Vec<PreflightEntry> {
+        &mut self.additional_entries
+    }
+
+    /// The number of entries each agent commits in this session.
+    pub fn entries_len(&self) -> usize {
+        1 + self.additional_entries.len()
+    }
+
+    /// The app entry hash and header base of the entry at this
+    /// position in the session.
+    pub fn entry_at(&self, index: usize) -> Option<(&EntryHash, &HeaderBase)> {
+        match index {
+            0 => Some((&self.app_entry_hash, &self.header_base)),
+            _ => self
+                .additional_entries
+                .get(index - 1)
+                .map(|entry| (&entry.app_entry_hash, &entry.header_base)),
+        }
+    }
 }
 
 /// Every agent must send back a preflight response.
@@ -455,26 +560,50 @@ pub struct UpdateBase {
 
 impl Header {
     /// Construct a Header from the HeaderBase and associated session data.
+    /// This is the header for the first entry in the session.
     pub fn from_countersigning_data(
         entry_hash: EntryHash,
         session_data: &CounterSigningSessionData,
         author: AgentPubKey,
+    ) -> Result<Self, CounterSigningError> {
+        let chain_top = session_data
+            .agent_state_for_agent(&author)?
+            .chain_top
+            .clone();
+        Self::from_countersigning_data_at(0, entry_hash, chain_top, session_data, author)
+    }
+
+    /// Construct the Header for the entry at this position in the session.
+    /// The previous header is the author's chain top for the first entry
+    /// and the author's header for the entry before it otherwise.
+    pub fn from_countersigning_data_at(
+        index: usize,
+        entry_hash: EntryHash,
+        prev_header: HeaderHash,
+        session_data: &CounterSigningSessionData,
+        author: AgentPubKey,
     ) -> Result<Self, CounterSigningError> {
         let agent_state = session_data.agent_state_for_agent(&author)?;
-        Ok(match session_data.preflight_request().header_base() {
+        let (_, header_base) = session_data
+            .preflight_request()
+            .entry_at(index)
+            .ok_or(CounterSigningError::EntryIndexOutOfBounds(index))?;
+        let timestamp = session_data.to_timestamp_at(index);
+        let header_seq = agent_state.header_seq + 1 + index as u32;
+        Ok(match header_base {
             HeaderBase::Create(create_base) => Header::Create(Create {
                 author,
-                timestamp: session_data.to_timestamp(),
-                header_seq: agent_state.header_seq + 1,
-                prev_header: agent_state.chain_top.clone(),
+                timestamp,
+                header_seq,
+                prev_header,
                 entry_type: create_base.entry_type.clone(),
                 entry_hash,
             }),
             HeaderBase::Update(update_base) => Header::Update(Update {
                 author,
-                timestamp: session_data.to_timestamp(),
-                header_seq: agent_state.header_seq + 1,
-                prev_header: agent_state.chain_top.clone(),
+                timestamp,
+                header_seq,
+                prev_header,
                 original_header_address: update_base.original_header_address.clone(),
                 original_entry_address: update_base.original_entry_address.clone(),
                 entry_type: update_base.entry_type.clone(),
@@ -548,6 +677,53 @@ impl CounterSigningSessionData {
         Ok(headers)
     }
 
+    /// Map the entry at this position in the session to its set of headers,
+    /// one for each agent in the same order as the signing agents.
+    /// Each agent's previous header must be given as it depends on the
+    /// hashes of the entries committed before this one in the session.
+    pub fn build_header_set_at(
+        &self,
+        index: usize,
+        entry_hash: EntryHash,
+        prev_headers: &[HeaderHash],
+    ) -> Result<Vec<Header>, CounterSigningError> {
+        let mut headers = vec![];
+        for (agent_index, (agent, _role)) in
+            self.preflight_request.signing_agents().iter().enumerate()
+        {
+            let prev_header = prev_headers
+                .get(agent_index)
+                .ok_or(CounterSigningError::AgentIndexOutOfBounds)?;
+            headers.push(Header::from_countersigning_data_at(
+                index,
+                entry_hash.clone(),
+                prev_header.clone(),
+                self,
+                agent.clone(),
+            )?);
+        }
+        Ok(headers)
+    }
+
+    /// The position in the session of the entry this header commits.
+    /// Session entries are committed one after the other so this is how
+    /// far past the author's frozen chain top the header is.
+    pub fn entry_index_for_header(&self, header: &Header) -> Result<usize, CounterSigningError> {
+        let agent_state = self.agent_state_for_agent(header.author())?;
+        let index = header
+            .header_seq()
+            .checked_sub(agent_state.header_seq + 1)
+            .ok_or_else(|| CounterSigningError::HeaderSeqNotInSession(header.header_seq()))?
+            as usize;
+        if index < self.preflight_request.entries_len() {
+            Ok(index)
+        } else {
+            Err(CounterSigningError::HeaderSeqNotInSession(
+                header.header_seq(),
+            ))
+        }
+    }
+
     /// Fallible constructor.
     pub fn try_new(
         preflight_request: PreflightRequest,
@@ -594,6 +770,13 @@ impl CounterSigningSessionData {
             .unwrap_or(Timestamp::MAX)
     }
 
+    /// Timestamp for the header of the entry at this position in the session.
+    /// Each entry is one microsecond after the one before so the
+    /// headers have strictly increasing timestamps on each chain.
+    pub fn to_timestamp_at(&self, index: usize) -> Timestamp {
+        (self.to_timestamp() + Duration::from_micros(index as u64)).unwrap_or(Timestamp::MAX)
+    }
+
     /// Accessor to the preflight request.
     pub fn preflight_request(&self) -> &PreflightRequest {
         &self.preflight_request
@@ -631,10 +814,16 @@ pub mod test {
 
     use super::CounterSigningError;
     use super::CounterSigningSessionTimes;
+    use super::PreflightEntry;
     use super::PreflightRequest;
+    use super::MAX_COUNTERSIGNING_ENTRIES;
     use super::SESSION_HEADER_TIME_OFFSET;
+    use crate::Header;
+    use crate::HeaderBase;
     use crate::Role;
     use arbitrary::Arbitrary;
+    use holo_hash::EntryHash;
+    use holo_hash::HeaderHash;
 
     #[test]
     pub fn test_check_countersigning_session_times() {
@@ -683,6 +872,7 @@ pub mod test {
 
         // None is always a pass.
         assert_eq!(preflight_request.check_enzyme_index().unwrap(), ());
+        assert_eq!(preflight_request.enzyme(), None);
 
         let alice = AgentPubKey::arbitrary(&mut u).unwrap();
         (*preflight_request.signing_agents_mut()).push((alice.clone(), vec![]));
@@ -691,6 +881,7 @@ pub mod test {
         *preflight_request.enzyme_index_mut() = Some(0);
 
         assert_eq!(preflight_request.check_enzyme_index().unwrap(), (),);
+        assert_eq!(preflight_request.enzyme(), Some(&alice));
 
         // 1 is out of bounds for zero signing agents.
         *preflight_request.enzyme_index_mut() = Some(1);
@@ -798,4 +989,98 @@ pub mod test {
         (*session_data.responses_mut()).push((bob_state, bob_signature));
         assert_eq!(session_data.check_responses_indexes().unwrap(), (),);
     }
+
+    #[test]
+    pub fn test_check_countersigning_preflight_request_entries() {
+        let mut u = arbitrary::Unstructured::new(&[0; 1000]);
+        let mut preflight_request = PreflightRequest::arbitrary(&mut u).unwrap();
+        let header_base = HeaderBase::arbitrary(&mut u).unwrap();
+
+        // A single entry is always a pass.
+        assert_eq!(preflight_request.check_entries().unwrap(), ());
+        assert_eq!(preflight_request.entries_len(), 1);
+
+        // The same app entry twice is a dupe.
+        let app_entry_hash = preflight_request.entry_at(0).unwrap().0.clone();
+        (*preflight_request.additional_entries_mut())
+            .push(PreflightEntry::new(app_entry_hash, header_base.clone()));
+        assert!(matches!(
+            preflight_request.check_entries(),
+            Err(CounterSigningError::EntriesDupes(_))
+        ));
+
+        // Different app entries are fine.
+        let other = EntryHash::from_raw_36(vec![1; 36]);
+        (*preflight_request.additional_entries_mut()).pop();
+        (*preflight_request.additional_entries_mut())
+            .push(PreflightEntry::new(other.clone(), header_base.clone()));
+        assert_eq!(preflight_request.check_entries().unwrap(), ());
+        assert_eq!(preflight_request.entry_at(1).unwrap().0, &other);
+        assert_eq!(preflight_request.entry_at(2), None);
+
+        // Too many entries.
+        for i in 0..MAX_COUNTERSIGNING_ENTRIES {
+            (*preflight_request.additional_entries_mut()).push(PreflightEntry::new(
+                EntryHash::from_raw_36(vec![i as u8 + 2; 36]),
+                header_base.clone(),
+            ));
+        }
+        assert!(matches!(
+            preflight_request.check_entries(),
+            Err(CounterSigningError::EntriesLength(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_countersigning_session_entry_index_for_header() {
+        let mut u = arbitrary::Unstructured::new(&[0; 1000]);
+        let mut session_data = CounterSigningSessionData::arbitrary(&mut u).unwrap();
+        let header_base = HeaderBase::arbitrary(&mut u).unwrap();
+        let alice = AgentPubKey::arbitrary(&mut u).unwrap();
+        let mut alice_state = CounterSigningAgentState::arbitrary(&mut u).unwrap();
+        let alice_signature = Signature::arbitrary(&mut u).unwrap();
+        *alice_state.header_seq_mut() = 3;
+
+        (*session_data.preflight_request_mut().signing_agents_mut()).push((alice.clone(), vec![]));
+        (*session_data.responses_mut()).push((alice_state, alice_signature));
+        (*session_data
+            .preflight_request_mut()
+            .additional_entries_mut())
+        .push(PreflightEntry::new(
+            EntryHash::from_raw_36(vec![1; 36]),
+            header_base,
+        ));
+
+        let entry_hash = EntryHash::from_raw_36(vec![2; 36]);
+        let first =
+            Header::from_countersigning_data(entry_hash.clone(), &session_data, alice.clone())
+                .unwrap();
+        let second = Header::from_countersigning_data_at(
+            1,
+            entry_hash.clone(),
+            HeaderHash::from_raw_36(vec![3; 36]),
+            &session_data,
+            alice.clone(),
+        )
+        .unwrap();
+
+        // Each entry follows the one before on the author's chain.
+        assert_eq!(first.header_seq(), 4);
+        assert_eq!(second.header_seq(), 5);
+        assert!(second.timestamp() > first.timestamp());
+        assert_eq!(session_data.entry_index_for_header(&first).unwrap(), 0);
+        assert_eq!(session_data.entry_index_for_header(&second).unwrap(), 1);
+
+        // There is no third entry.
+        assert!(matches!(
+            Header::from_countersigning_data_at(
+                2,
+                entry_hash,
+                HeaderHash::from_raw_36(vec![3; 36]),
+                &session_data,
+                alice,
+            ),
+            Err(CounterSigningError::EntryIndexOutOfBounds(2))
+        ));
+    }
 }
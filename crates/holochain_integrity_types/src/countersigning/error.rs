@@ -17,6 +17,14 @@ pub enum CounterSigningError {
     AgentsDupes(Vec<holo_hash::AgentPubKey>),
     /// The session times must validate.
     CounterSigningSessionTimes(crate::CounterSigningSessionTimes),
+    /// Entries length cannot be longer than max.
+    EntriesLength(usize),
+    /// There cannot be duplicates in the session entries.
+    EntriesDupes(Vec<holo_hash::EntryHash>),
+    /// Entry index is out of bounds for the session entries.
+    EntryIndexOutOfBounds(usize),
+    /// The header seq is not one of the author's session headers.
+    HeaderSeqNotInSession(u32),
 }
 
 impl std::error::Error for CounterSigningError {}
@@ -60,6 +68,24 @@ impl core::fmt::Display for CounterSigningError {
                 "The countersigning session times were not valid {:?}",
                 times
             ),
+            CounterSigningError::EntriesLength(len) => {
+                write!(f, "The session entries list is too long {}", len)
+            }
+            CounterSigningError::EntriesDupes(entries) => write!(
+                f,
+                "The session entries list contains duplicates {:?}",
+                entries
+            ),
+            CounterSigningError::EntryIndexOutOfBounds(index) => write!(
+                f,
+                "Entry index {} is out of bounds for the session entries.",
+                index
+            ),
+            CounterSigningError::HeaderSeqNotInSession(seq) => write!(
+                f,
+                "The header seq {} is not one of the author's countersigning session headers.",
+                seq
+            ),
         }
     }
 }
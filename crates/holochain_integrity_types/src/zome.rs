@@ -8,6 +8,10 @@
 
 use holochain_serialized_bytes::prelude::*;
 
+/// `#[hdk_extern(read_only)]` marks a function by exporting an empty function
+/// named with this prefix followed by the function's name.
+pub const READ_ONLY_FN_MARKER_PREFIX: &str = "__hc_read_only__";
+
 /// ZomeName as a String.
 #[derive(Clone, Debug, Serialize, Hash, Deserialize, Ord, Eq, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
@@ -132,6 +132,14 @@ pub trait HolochainP2pDnaT {
         response: Vec<SignedHeader>,
     ) -> actor::HolochainP2pResult<()>;
 
+    /// Send a signer's store entry op directly to
+    /// the enzyme agent of a countersigning session.
+    async fn countersigning_enzyme_push(
+        &self,
+        enzyme: AgentPubKey,
+        op: holochain_types::dht_op::DhtOp,
+    ) -> actor::HolochainP2pResult<()>;
+
     /// New data has been integrated and is ready for gossiping.
     async fn new_integrated_data(&self) -> actor::HolochainP2pResult<()>;
 }
@@ -329,6 +337,16 @@ impl HolochainP2pDnaT for HolochainP2pDna {
             .await
     }
 
+    async fn countersigning_enzyme_push(
+        &self,
+        enzyme: AgentPubKey,
+        op: holochain_types::dht_op::DhtOp,
+    ) -> actor::HolochainP2pResult<()> {
+        self.sender
+            .countersigning_enzyme_push((*self.dna_hash).clone(), enzyme, op)
+            .await
+    }
+
     async fn new_integrated_data(&self) -> actor::HolochainP2pResult<()> {
         self.sender
             .new_integrated_data((*self.dna_hash).clone())
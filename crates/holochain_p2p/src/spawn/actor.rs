@@ -307,6 +307,18 @@ impl WrapEvtSender {
             "(hp2p:handle) signed_header"
         )
     }
+
+    fn countersigning_enzyme_push(
+        &self,
+        dna_hash: DnaHash,
+        to_agent: AgentPubKey,
+        op: holochain_types::dht_op::DhtOp,
+    ) -> impl Future<Output = HolochainP2pResult<()>> + 'static + Send {
+        timing_trace!(
+            { self.0.countersigning_enzyme_push(dna_hash, to_agent, op) },
+            "(hp2p:handle) countersigning_enzyme_push"
+        )
+    }
 }
 
 pub(crate) struct HolochainP2pActor {
@@ -539,6 +551,23 @@ impl HolochainP2pActor {
         .boxed()
         .into())
     }
+
+    fn handle_incoming_countersigning_enzyme_push(
+        &mut self,
+        dna_hash: DnaHash,
+        to_agent: AgentPubKey,
+        op: holochain_types::dht_op::DhtOp,
+    ) -> kitsune_p2p::actor::KitsuneP2pHandlerResult<()> {
+        let evt_sender = self.evt_sender.clone();
+        Ok(async move {
+            evt_sender
+                .countersigning_enzyme_push(dna_hash, to_agent, op)
+                .await?;
+            Ok(())
+        }
+        .boxed()
+        .into())
+    }
 }
 
 impl ghost_actor::GhostHandler<kitsune_p2p::event::KitsuneP2pEvent> for HolochainP2pActor {}
@@ -700,6 +729,14 @@ impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
                 )
                 .into())
             }
+            // holochain_p2p only broadcasts this message.
+            crate::wire::WireMessage::CountersigningEnzymePush { .. } => {
+                Err(HolochainP2pError::invalid_p2p_message(
+                    "invalid: countersigning enzyme push is a broadcast type, not a request"
+                        .to_string(),
+                )
+                .into())
+            }
         }
     }
 
@@ -759,6 +796,9 @@ impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
             ),
             crate::wire::WireMessage::CountersigningAuthorityResponse { signed_headers } => self
                 .handle_incoming_countersigning_authority_response(space, to_agent, signed_headers),
+            crate::wire::WireMessage::CountersigningEnzymePush { op } => {
+                self.handle_incoming_countersigning_enzyme_push(space, to_agent, *op)
+            }
         }
     }
 
@@ -1238,6 +1278,31 @@ impl HolochainP2pHandler for HolochainP2pActor {
         .into())
     }
 
+    #[tracing::instrument(skip(self), level = "trace")]
+    fn handle_countersigning_enzyme_push(
+        &mut self,
+        dna_hash: DnaHash,
+        enzyme: AgentPubKey,
+        op: holochain_types::dht_op::DhtOp,
+    ) -> HolochainP2pHandlerResult<()> {
+        let space = dna_hash.into_kitsune();
+        let agents = vec![enzyme.into_kitsune()];
+
+        let timeout = self.tuning_params.implicit_timeout();
+
+        let payload = crate::wire::WireMessage::countersigning_enzyme_push(op).encode()?;
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            kitsune_p2p
+                .targeted_broadcast(space, agents, timeout, payload, false)
+                .await?;
+            Ok(())
+        }
+        .boxed()
+        .into())
+    }
+
     fn handle_dump_network_metrics(
         &mut self,
         dna_hash: Option<DnaHash>,
@@ -1251,4 +1316,20 @@ impl HolochainP2pHandler for HolochainP2pActor {
         .boxed()
         .into())
     }
+
+    fn handle_set_bandwidth_limits(
+        &mut self,
+        scope: BandwidthLimitScope,
+        limits: kitsune_p2p::bandwidth::BandwidthLimits,
+    ) -> HolochainP2pHandlerResult<()> {
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            kitsune_p2p
+                .set_bandwidth_limits(scope.into(), limits)
+                .await?;
+            Ok(())
+        }
+        .boxed()
+        .into())
+    }
 }
@@ -128,12 +128,27 @@ impl HolochainP2pHandler for StubNetwork {
     ) -> HolochainP2pHandlerResult<()> {
         Err("stub".into())
     }
+    fn handle_countersigning_enzyme_push(
+        &mut self,
+        dna_hash: DnaHash,
+        enzyme: AgentPubKey,
+        op: holochain_types::dht_op::DhtOp,
+    ) -> HolochainP2pHandlerResult<()> {
+        Err("stub".into())
+    }
     fn handle_dump_network_metrics(
         &mut self,
         dna_hash: Option<DnaHash>,
     ) -> HolochainP2pHandlerResult<String> {
         Err("stub".into())
     }
+    fn handle_set_bandwidth_limits(
+        &mut self,
+        scope: BandwidthLimitScope,
+        limits: kitsune_p2p::bandwidth::BandwidthLimits,
+    ) -> HolochainP2pHandlerResult<()> {
+        Err("stub".into())
+    }
 }
 
 /// Spawn a stub network that doesn't respond to any messages.
@@ -5,6 +5,36 @@ use crate::event::GetRequest;
 use crate::*;
 use holochain_types::activity::AgentActivityResponse;
 
+/// The bandwidth budget changed by
+/// [`set_bandwidth_limits`](HolochainP2pSender::set_bandwidth_limits).
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BandwidthLimitScope {
+    /// All network traffic of the conductor.
+    Total,
+    /// All network traffic of a single dna.
+    Dna(DnaHash),
+    /// The traffic exchanged with each peer.
+    Peers,
+    /// Recent gossip.
+    RecentGossip,
+    /// Historical gossip.
+    HistoricalGossip,
+}
+
+impl From<BandwidthLimitScope> for kitsune_p2p::bandwidth::BandwidthScope {
+    fn from(scope: BandwidthLimitScope) -> Self {
+        use kitsune_p2p::gossip::sharded_gossip::GossipType;
+        match scope {
+            BandwidthLimitScope::Total => Self::Total,
+            BandwidthLimitScope::Dna(dna_hash) => Self::Space(dna_hash.into_kitsune()),
+            BandwidthLimitScope::Peers => Self::Peers,
+            BandwidthLimitScope::RecentGossip => Self::Gossip(GossipType::Recent),
+            BandwidthLimitScope::HistoricalGossip => Self::Gossip(GossipType::Historical),
+        }
+    }
+}
+
 /// Request a validation package.
 #[derive(Clone, Debug)]
 pub struct GetValidationPackage {
@@ -297,10 +327,24 @@ ghost_actor::ghost_chan! {
             signed_headers: Vec<SignedHeader>,
         ) -> ();
 
+        /// Send a signer's store entry op for a countersigning
+        /// session directly to the session's enzyme agent.
+        fn countersigning_enzyme_push(
+            dna_hash: DnaHash,
+            enzyme: AgentPubKey,
+            op: holochain_types::dht_op::DhtOp,
+        ) -> ();
+
         /// Dump network metrics.
         fn dump_network_metrics(
             dna_hash: Option<DnaHash>,
         ) -> String;
+
+        /// Change the limits of a bandwidth budget at runtime.
+        fn set_bandwidth_limits(
+            scope: BandwidthLimitScope,
+            limits: kitsune_p2p::bandwidth::BandwidthLimits,
+        ) -> ();
     }
 }
 
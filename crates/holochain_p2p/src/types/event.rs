@@ -230,6 +230,14 @@ ghost_actor::ghost_chan! {
             to_agent: AgentPubKey,
             signed_headers: Vec<SignedHeader>,
         ) -> ();
+
+        /// A signer's store entry op pushed to this agent
+        /// because it is the enzyme for the session.
+        fn countersigning_enzyme_push(
+            dna_hash: DnaHash,
+            to_agent: AgentPubKey,
+            op: holochain_types::dht_op::DhtOp,
+        ) -> ();
     }
 }
 
@@ -246,6 +254,7 @@ macro_rules! match_p2p_evt {
             HolochainP2pEvent::ValidationReceiptReceived { $i, .. } => { $($t)* }
             HolochainP2pEvent::SignNetworkData { $i, .. } => { $($t)* }
             HolochainP2pEvent::CountersigningAuthorityResponse { $i, .. } => { $($t)* }
+            HolochainP2pEvent::CountersigningEnzymePush { $i, .. } => { $($t)* }
             $($t2)*
         }
     };
@@ -134,9 +134,9 @@ pub enum HolochainP2pMockMsg {
 #[derive(Debug)]
 /// The type of protocol for the gossip wire message.
 pub enum GossipProtocol {
-    /// Simple bloom gossip wire protocol.
-    // TODO: Implement this.
-    Simple,
+    /// The wire protocol of a gossip module the mock
+    /// network doesn't decode, passed through untouched.
+    Opaque(kitsune_p2p::wire::WireData),
     /// Sharded gossip wire protocol.
     Sharded(kitsune_p2p::gossip::sharded_gossip::ShardedGossipWire),
 }
@@ -349,9 +349,8 @@ impl HolochainP2pMockMsg {
                 | crate::wire::WireMessage::GetAgentActivity { .. }
                 | crate::wire::WireMessage::GetValidationPackage { .. } => next_msg_id().as_req(),
                 crate::wire::WireMessage::Publish { .. }
-                | crate::wire::WireMessage::CountersigningAuthorityResponse { .. } => {
-                    MsgId::new_notify()
-                }
+                | crate::wire::WireMessage::CountersigningAuthorityResponse { .. }
+                | crate::wire::WireMessage::CountersigningEnzymePush { .. } => MsgId::new_notify(),
             },
             HolochainP2pMockMsg::PeerGet(_) | HolochainP2pMockMsg::PeerQuery(_) => {
                 next_msg_id().as_req()
@@ -376,7 +375,8 @@ impl HolochainP2pMockMsg {
                     | crate::wire::WireMessage::GetAgentActivity { .. }
                     | crate::wire::WireMessage::GetValidationPackage { .. } => true,
                     crate::wire::WireMessage::Publish { .. }
-                    | crate::wire::WireMessage::CountersigningAuthorityResponse { .. } => false,
+                    | crate::wire::WireMessage::CountersigningAuthorityResponse { .. }
+                    | crate::wire::WireMessage::CountersigningEnzymePush { .. } => false,
                 };
                 let to_agent = to_agent.to_kitsune();
                 let space = dna.to_kitsune();
@@ -409,7 +409,7 @@ impl HolochainP2pMockMsg {
                 use kitsune_p2p_types::codec::Codec;
                 let space = dna.to_kitsune();
                 let data = match gossip {
-                    GossipProtocol::Simple => todo!(),
+                    GossipProtocol::Opaque(data) => data,
                     GossipProtocol::Sharded(gossip) => gossip.encode_vec().unwrap().into(),
                 };
                 kwire::Wire::Gossip(kwire::Gossip {
@@ -489,12 +489,14 @@ impl HolochainP2pMockMsg {
                 use kitsune_p2p::gossip::sharded_gossip::*;
                 use kitsune_p2p_types::codec::Codec;
                 let gossip = match module {
-                    GossipModuleType::Simple => todo!(),
                     GossipModuleType::ShardedRecent | GossipModuleType::ShardedHistorical => {
                         GossipProtocol::Sharded(
                             ShardedGossipWire::decode_ref(data.as_ref()).unwrap().1,
                         )
                     }
+                    GossipModuleType::Simple | GossipModuleType::Custom(_) => {
+                        GossipProtocol::Opaque(data)
+                    }
                 };
                 let dna = holo_hash::DnaHash::from_kitsune(&space);
                 HolochainP2pMockMsg::Gossip {
@@ -66,6 +66,9 @@ pub enum WireMessage {
     CountersigningAuthorityResponse {
         signed_headers: Vec<SignedHeader>,
     },
+    CountersigningEnzymePush {
+        op: Box<holochain_types::dht_op::DhtOp>,
+    },
 }
 
 #[allow(missing_docs)]
@@ -147,4 +150,8 @@ impl WireMessage {
     pub fn countersigning_authority_response(signed_headers: Vec<SignedHeader>) -> WireMessage {
         Self::CountersigningAuthorityResponse { signed_headers }
     }
+
+    pub fn countersigning_enzyme_push(op: holochain_types::dht_op::DhtOp) -> WireMessage {
+        Self::CountersigningEnzymePush { op: Box::new(op) }
+    }
 }
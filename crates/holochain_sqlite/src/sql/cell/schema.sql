@@ -32,6 +32,7 @@ CREATE TABLE IF NOT EXISTS Header (
     type             TEXT           NOT NULL,
     seq              INTEGER        NOT NULL,
     author           BLOB           NOT NULL,
+    timestamp        INTEGER        NOT NULL,
 
     blob             BLOB           NOT NULL,
     prev_hash        BLOB           NULL,
@@ -105,7 +106,9 @@ CREATE TABLE IF NOT EXISTS DhtOp (
 
     -- Used to withhold ops from publishing for things
     -- like countersigning.
-    withhold_publish    INTEGER     NULL, -- BOOLEAN
+    -- 1 while the countersigning session is pending and
+    -- 2 once the session has been abandoned.
+    withhold_publish    INTEGER     NULL,
     -- The op has received enough validation receipts.
     -- This is required as a field because different ops have different EntryTypes,
     -- which have different numbers of required validation receipts.
@@ -161,6 +164,15 @@ CREATE TABLE IF NOT EXISTS ChainLock (
     expires_at_timestamp INTEGER NOT NULL
 );
 
+-- Countersigning ops gathered by an authority or enzyme
+-- until their session completes or expires.
+CREATE TABLE IF NOT EXISTS CountersigningSession (
+    op_hash BLOB PRIMARY KEY ON CONFLICT IGNORE,
+    blob BLOB NOT NULL,
+    -- The end of the session as a Timestamp (microseconds)
+    expires_at_timestamp INTEGER NOT NULL
+);
+
 CREATE TABLE IF NOT EXISTS ScheduledFunctions (
     author BLOB NOT NULL,
     zome_name TEXT NOT NULL,
@@ -16,3 +16,17 @@ CREATE TABLE IF NOT EXISTS EntryDef (
     key             BLOB           PRIMARY KEY ON CONFLICT IGNORE,
     blob            BLOB           NOT NULL
 );
+
+-- Compiled wasm modules, so a restart doesn't have to recompile every zome.
+CREATE TABLE IF NOT EXISTS CompiledWasm (
+    wasm_hash           BLOB           NOT NULL,
+    -- The wasmer and metering versions, compiler, target, cpu features
+    -- and memory limit the module was compiled for.
+    engine_key          TEXT           NOT NULL,
+    -- Modules compiled by another version of holochain are deleted on startup.
+    holochain_version   TEXT           NOT NULL,
+    -- blake2b-256 of the blob, checked before the module is loaded.
+    blob_hash           BLOB           NOT NULL,
+    blob                BLOB           NOT NULL,
+    PRIMARY KEY (wasm_hash, engine_key) ON CONFLICT REPLACE
+);
@@ -7,6 +7,9 @@ use holochain_zome_types::Timestamp;
 /// True if the chain is currently locked for the given lock id.
 /// The chain is never locked for the id that created it.
 /// The chain is always locked for all other ids until the lock end time is in the past.
+/// If a countersigning session was committed under the lock the chain stays
+/// locked past the end time until the session is resolved, because until then
+/// the session's ops are withheld from publishing.
 pub fn is_chain_locked(
     txn: &Transaction,
     lock: &[u8],
@@ -19,9 +22,17 @@ pub fn is_chain_locked(
             "
             SELECT 1
             FROM ChainLock
-            WHERE expires_at_timestamp >= :now
-            AND lock != :lock
+            WHERE lock != :lock
             AND author = :author
+            AND (
+                expires_at_timestamp >= :now
+                OR EXISTS (
+                    SELECT 1 FROM DhtOp
+                    JOIN Header ON DhtOp.header_hash = Header.hash
+                    WHERE Header.author = :author
+                    AND DhtOp.withhold_publish = 1
+                )
+            )
             LIMIT 1
             ",
             named_params! {
@@ -66,3 +77,18 @@ pub fn is_lock_expired(
     // If there's no lock then it's expired.
     Ok(r.unwrap_or(true))
 }
+
+/// True if the author's chain has a lock, whether or not it has expired.
+/// A lock is only removed once its countersigning session is resolved.
+pub fn has_chain_lock(txn: &Transaction, author: &AgentPubKey) -> StateMutationResult<bool> {
+    Ok(txn
+        .query_row(
+            "SELECT 1 FROM ChainLock WHERE author = :author LIMIT 1",
+            named_params! {
+                ":author": author,
+            },
+            |row| row.get::<_, u32>(0),
+        )
+        .optional()?
+        .is_some())
+}
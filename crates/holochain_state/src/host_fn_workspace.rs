@@ -34,6 +34,9 @@ pub struct HostFnWorkspace<
     /// This is needed so that we don't run init recursively inside
     /// init calls.
     init_is_root: bool,
+    /// Is a read-only zome function in this call chain.
+    /// Every call it makes shares its workspace so none of them can write.
+    read_only: bool,
 }
 
 #[derive(Clone, shrinkwraprs::Shrinkwrap)]
@@ -151,6 +154,7 @@ impl SourceChainWorkspace {
                 dna_def,
                 cache,
                 init_is_root,
+                read_only: false,
             },
             source_chain,
         })
@@ -161,6 +165,18 @@ impl SourceChainWorkspace {
     pub fn called_from_init(&self) -> bool {
         self.inner.init_is_root
     }
+
+    /// Deny writes to this workspace in this zome call
+    /// and in the zome calls it makes.
+    pub fn into_read_only(mut self) -> Self {
+        self.inner.read_only = true;
+        self
+    }
+
+    /// Is this workspace used by a read-only zome call chain.
+    pub fn is_read_only(&self) -> bool {
+        self.inner.read_only
+    }
 }
 
 impl<SourceChainDb, SourceChainDht> HostFnWorkspace<SourceChainDb, SourceChainDht>
@@ -197,6 +213,7 @@ where
             cache,
             dna_def,
             init_is_root: false,
+            read_only: false,
         })
     }
     pub fn source_chain(&self) -> &Option<SourceChain<SourceChainDb, SourceChainDht>> {
@@ -242,6 +259,7 @@ impl From<HostFnWorkspace> for HostFnWorkspaceRead {
             cache: workspace.cache,
             dna_def: workspace.dna_def,
             init_is_root: workspace.init_is_root,
+            read_only: workspace.read_only,
         }
     }
 }
@@ -261,6 +279,7 @@ impl From<SourceChainWorkspace> for HostFnWorkspaceRead {
             cache: workspace.inner.cache,
             dna_def: workspace.inner.dna_def,
             init_is_root: workspace.inner.init_is_root,
+            read_only: workspace.inner.read_only,
         }
     }
 }
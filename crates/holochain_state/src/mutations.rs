@@ -10,6 +10,7 @@ use holo_hash::*;
 use holochain_sqlite::rusqlite::named_params;
 use holochain_sqlite::rusqlite::types::Null;
 use holochain_sqlite::rusqlite::Transaction;
+use holochain_types::dht_op::DhtOp;
 use holochain_types::dht_op::DhtOpLight;
 use holochain_types::dht_op::OpOrder;
 use holochain_types::dht_op::{DhtOpHashed, DhtOpType};
@@ -221,6 +222,25 @@ pub fn insert_wasm(txn: &mut Transaction, wasm: DnaWasmHashed) -> StateMutationR
     Ok(())
 }
 
+/// Insert a compiled wasm module into the database,
+/// replacing any module for the same wasm and engine.
+pub fn insert_compiled_wasm(
+    txn: &mut Transaction,
+    wasm_hash: &WasmHash,
+    engine_key: &str,
+    holochain_version: &str,
+    blob: &[u8],
+) -> StateMutationResult<()> {
+    sql_insert!(txn, CompiledWasm, {
+        "wasm_hash": wasm_hash,
+        "engine_key": engine_key,
+        "holochain_version": holochain_version,
+        "blob_hash": blake2b_256(blob),
+        "blob": blob,
+    })?;
+    Ok(())
+}
+
 /// Insert a [`DnaDef`] into the database.
 pub fn insert_dna_def(txn: &mut Transaction, dna_def: &DnaDefHashed) -> StateMutationResult<()> {
     let hash = dna_def.as_hash();
@@ -410,6 +430,7 @@ pub fn insert_header(
     let header_type = header_type.as_sql();
     let header_seq = header.header_seq();
     let author = header.author().clone();
+    let timestamp = header.timestamp();
     let prev_hash = header.prev_header().cloned();
     let private = match header.entry_type().map(|et| et.visibility()) {
         Some(EntryVisibility::Private) => true,
@@ -423,6 +444,7 @@ pub fn insert_header(
                 "type": header_type,
                 "seq": header_seq,
                 "author": author,
+                "timestamp": timestamp,
                 "prev_hash": prev_hash,
                 "base_hash": create_link.base_address,
                 "zome_id": create_link.zome_id.index() as u32,
@@ -436,6 +458,7 @@ pub fn insert_header(
                 "type": header_type,
                 "seq": header_seq,
                 "author": author,
+                "timestamp": timestamp,
                 "prev_hash": prev_hash,
                 "create_link_hash": delete_link.link_add_address,
                 "blob": to_blob(&signed_header)?,
@@ -447,6 +470,7 @@ pub fn insert_header(
                 "type": header_type,
                 "seq": header_seq,
                 "author": author,
+                "timestamp": timestamp,
                 "prev_hash": prev_hash,
                 "entry_hash": create.entry_hash,
                 "entry_type": create.entry_type.as_sql(),
@@ -460,6 +484,7 @@ pub fn insert_header(
                 "type": header_type,
                 "seq": header_seq,
                 "author": author,
+                "timestamp": timestamp,
                 "prev_hash": prev_hash,
                 "deletes_entry_hash": delete.deletes_entry_address,
                 "deletes_header_hash": delete.deletes_address,
@@ -472,6 +497,7 @@ pub fn insert_header(
                 "type": header_type,
                 "seq": header_seq,
                 "author": author,
+                "timestamp": timestamp,
                 "prev_hash": prev_hash,
                 "entry_hash": update.entry_hash,
                 "entry_type": update.entry_type.as_sql(),
@@ -491,6 +517,7 @@ pub fn insert_header(
                 "type": header_type,
                 "seq": header_seq,
                 "author": author,
+                "timestamp": timestamp,
                 "prev_hash": prev_hash,
                 "blob": to_blob(&signed_header)?,
             })?;
@@ -587,6 +614,67 @@ pub fn unlock_chain(txn: &mut Transaction, author: &AgentPubKey) -> StateMutatio
     Ok(())
 }
 
+/// Store an op for a countersigning session that is being gathered.
+pub fn insert_countersigning_op(
+    txn: &mut Transaction,
+    op_hash: &DhtOpHash,
+    op: &DhtOp,
+    expires_at: &Timestamp,
+) -> StateMutationResult<()> {
+    sql_insert!(txn, CountersigningSession, {
+        "op_hash": op_hash,
+        "blob": to_blob(op)?,
+        "expires_at_timestamp": expires_at,
+    })?;
+    Ok(())
+}
+
+/// Remove an op once its countersigning session is complete.
+pub fn delete_countersigning_op(
+    txn: &mut Transaction,
+    op_hash: &DhtOpHash,
+) -> StateMutationResult<()> {
+    txn.execute(
+        "DELETE FROM CountersigningSession WHERE op_hash = ?",
+        [op_hash],
+    )?;
+    Ok(())
+}
+
+/// Record that the author's pending countersigning session was abandoned.
+/// The session's elements stay on the source chain but their ops are
+/// marked so they are never published and no longer hold the chain lock.
+pub fn abandon_countersigning_ops(
+    txn: &mut Transaction,
+    author: &AgentPubKey,
+) -> StateMutationResult<()> {
+    txn.execute(
+        "
+        UPDATE DhtOp SET withhold_publish = 2
+        WHERE withhold_publish = 1
+        AND header_hash IN (SELECT hash FROM Header WHERE author = :author)
+        ",
+        named_params! {
+            ":author": author,
+        },
+    )?;
+    Ok(())
+}
+
+/// Remove the ops of any countersigning sessions that ended before `now`.
+pub fn delete_expired_countersigning_ops(
+    txn: &mut Transaction,
+    now: &Timestamp,
+) -> StateMutationResult<()> {
+    txn.execute(
+        "DELETE FROM CountersigningSession WHERE expires_at_timestamp < :now",
+        named_params! {
+            ":now": now,
+        },
+    )?;
+    Ok(())
+}
+
 pub fn delete_all_ephemeral_scheduled_fns(
     txn: &mut Transaction,
     author: &AgentPubKey,
@@ -37,6 +37,7 @@ mod test_data;
 mod tests;
 
 pub mod chain_head;
+pub mod chain_query;
 pub mod element_details;
 pub mod entry_details;
 pub mod error;
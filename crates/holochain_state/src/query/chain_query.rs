@@ -0,0 +1,322 @@
+//! Source chain queries that apply a [`ChainQueryFilter`] in sql.
+
+use holo_hash::*;
+use holochain_sqlite::rusqlite::types::Value;
+use holochain_sqlite::rusqlite::*;
+use holochain_types::sql::AsSql;
+use holochain_zome_types::*;
+
+use super::*;
+
+/// A [`ChainQueryFilter`] over a single author's chain in the authored database.
+///
+/// The filter's sequence range must be resolved to sequence numbers with
+/// [`ChainQuery::resolve_seq_range`] first. This is possible because an
+/// author's own chain in the authored database never forks, so a hash bounded
+/// range is equivalent to the sequence range between those hashes.
+#[derive(Debug, Clone)]
+pub struct ChainQuery<'a> {
+    author: &'a AgentPubKey,
+    filter: &'a ChainQueryFilter,
+    seq_range: Option<(u32, u32)>,
+}
+
+/// The sequence numbers a [`ChainQueryFilterRange`] covers on a linear chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainQuerySeqRange {
+    /// Every sequence number.
+    Unbounded,
+    /// Inclusive start and end sequence numbers.
+    Bounded(u32, u32),
+    /// The range ends at a header that is not on this chain
+    /// so nothing can match.
+    Empty,
+}
+
+impl ChainQuerySeqRange {
+    /// The equivalent range for filtering headers in memory.
+    pub fn as_filter_range(&self) -> Option<ChainQueryFilterRange> {
+        match self {
+            ChainQuerySeqRange::Unbounded => Some(ChainQueryFilterRange::Unbounded),
+            ChainQuerySeqRange::Bounded(start, end) => {
+                Some(ChainQueryFilterRange::HeaderSeqRange(*start, *end))
+            }
+            ChainQuerySeqRange::Empty => None,
+        }
+    }
+}
+
+/// The window of matching elements to return from the database.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChainQueryWindow {
+    /// Matching elements to skip.
+    pub offset: u32,
+    /// Maximum elements to return.
+    pub limit: Option<u32>,
+}
+
+impl<'a> ChainQuery<'a> {
+    pub fn new(
+        author: &'a AgentPubKey,
+        filter: &'a ChainQueryFilter,
+        seq_range: Option<(u32, u32)>,
+    ) -> Self {
+        Self {
+            author,
+            filter,
+            seq_range,
+        }
+    }
+
+    /// Resolve the filter's range to inclusive sequence bounds.
+    /// `lookup` finds the sequence number of a header on this chain.
+    pub fn resolve_seq_range<F>(
+        range: &ChainQueryFilterRange,
+        mut lookup: F,
+    ) -> StateQueryResult<ChainQuerySeqRange>
+    where
+        F: FnMut(&HeaderHash) -> StateQueryResult<Option<u32>>,
+    {
+        Ok(match range {
+            ChainQueryFilterRange::Unbounded => ChainQuerySeqRange::Unbounded,
+            ChainQueryFilterRange::HeaderSeqRange(start, end) => {
+                ChainQuerySeqRange::Bounded(*start, *end)
+            }
+            ChainQueryFilterRange::HeaderHashRange(start, end) => match lookup(end)? {
+                Some(end_seq) => {
+                    // If the start is not an ancestor of the end then the
+                    // range walks all the way back to genesis.
+                    let start_seq = lookup(start)?
+                        .filter(|start_seq| *start_seq <= end_seq)
+                        .unwrap_or(0);
+                    ChainQuerySeqRange::Bounded(start_seq, end_seq)
+                }
+                None => ChainQuerySeqRange::Empty,
+            },
+            ChainQueryFilterRange::HeaderHashTerminated(end, prior_count) => match lookup(end)? {
+                Some(end_seq) => {
+                    ChainQuerySeqRange::Bounded(end_seq.saturating_sub(*prior_count), end_seq)
+                }
+                None => ChainQuerySeqRange::Empty,
+            },
+        })
+    }
+
+    /// Find the sequence number of a header by this author in the database.
+    pub fn header_seq(
+        txn: &Transaction,
+        author: &AgentPubKey,
+        hash: &HeaderHash,
+    ) -> StateQueryResult<Option<u32>> {
+        Ok(txn
+            .query_row(
+                "SELECT seq FROM Header WHERE hash = :hash AND author = :author",
+                named_params! {
+                    ":hash": hash,
+                    ":author": author,
+                },
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    fn sql(&self) -> String {
+        let mut sql = "
+            SELECT DISTINCT
+            Header.hash AS header_hash, Header.blob AS header_blob, Header.seq AS header_seq
+        "
+        .to_string();
+        if self.filter.include_entries {
+            sql.push_str(", Entry.blob AS entry_blob");
+        }
+        sql.push_str(" FROM Header ");
+        if self.filter.include_entries {
+            sql.push_str(" LEFT JOIN Entry ON Header.entry_hash = Entry.hash ");
+        }
+        sql.push_str(" JOIN DhtOp ON DhtOp.header_hash = Header.hash WHERE ");
+        sql.push_str(&self.conditions());
+        sql
+    }
+
+    /// The sql conditions on the `Header` table for this filter, e.g. to
+    /// use after `WHERE` in another query of the same author's headers.
+    /// The values are bound by [`ChainQuery::params`].
+    pub fn conditions(&self) -> String {
+        let mut sql = " Header.author = :author ".to_string();
+        if self.seq_range.is_some() {
+            sql.push_str(" AND Header.seq BETWEEN :range_start AND :range_end ");
+        }
+        if let Some(entry_types) = &self.filter.entry_type {
+            sql.push_str(&in_list(
+                "Header.entry_type",
+                "entry_type",
+                entry_types.len(),
+            ));
+        }
+        if let Some(header_types) = &self.filter.header_type {
+            sql.push_str(&in_list("Header.type", "header_type", header_types.len()));
+        }
+        if let Some(entry_hashes) = &self.filter.entry_hashes {
+            sql.push_str(&in_list(
+                "Header.entry_hash",
+                "entry_hash",
+                entry_hashes.len(),
+            ));
+        }
+        if !self.filter.exclude_entry_type.is_empty() {
+            // Headers without an entry are never excluded.
+            sql.push_str(&format!(
+                " AND (Header.entry_type IS NULL OR Header.entry_type NOT IN ({})) ",
+                placeholders("exclude_entry_type", self.filter.exclude_entry_type.len())
+            ));
+        }
+        if !self.filter.exclude_header_type.is_empty() {
+            sql.push_str(&format!(
+                " AND Header.type NOT IN ({}) ",
+                placeholders("exclude_header_type", self.filter.exclude_header_type.len())
+            ));
+        }
+        if self.filter.timestamp_range.is_some() {
+            sql.push_str(" AND Header.timestamp BETWEEN :timestamp_start AND :timestamp_end ");
+        }
+        sql
+    }
+
+    /// The named values for [`ChainQuery::conditions`].
+    pub fn params(&self) -> Vec<(String, Box<dyn ToSql + 'a>)> {
+        let mut params: Vec<(String, Box<dyn ToSql + 'a>)> = vec![(
+            ":author".to_string(),
+            Box::new(Value::Blob(self.author.get_raw_39().to_vec())),
+        )];
+        if let Some((start, end)) = self.seq_range {
+            params.push((":range_start".to_string(), Box::new(start)));
+            params.push((":range_end".to_string(), Box::new(end)));
+        }
+        let filter: &'a ChainQueryFilter = self.filter;
+        if let Some(entry_types) = &filter.entry_type {
+            params.extend(
+                entry_types
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| sql_param(format!(":entry_type_{}", i), t.as_sql())),
+            );
+        }
+        if let Some(header_types) = &filter.header_type {
+            params.extend(
+                header_types
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| sql_param(format!(":header_type_{}", i), t.as_sql())),
+            );
+        }
+        if let Some(entry_hashes) = &filter.entry_hashes {
+            params.extend(entry_hashes.iter().enumerate().map(|(i, h)| {
+                sql_param(
+                    format!(":entry_hash_{}", i),
+                    Value::Blob(h.get_raw_39().to_vec()),
+                )
+            }));
+        }
+        params.extend(
+            filter
+                .exclude_entry_type
+                .iter()
+                .enumerate()
+                .map(|(i, t)| sql_param(format!(":exclude_entry_type_{}", i), t.as_sql())),
+        );
+        params.extend(
+            filter
+                .exclude_header_type
+                .iter()
+                .enumerate()
+                .map(|(i, t)| sql_param(format!(":exclude_header_type_{}", i), t.as_sql())),
+        );
+        if let Some((start, end)) = filter.timestamp_range {
+            params.push((":timestamp_start".to_string(), Box::new(start)));
+            params.push((":timestamp_end".to_string(), Box::new(end)));
+        }
+        params
+    }
+
+    /// Count every element on the chain that matches the filter.
+    pub fn count(&self, txn: &Transaction) -> StateQueryResult<u32> {
+        let sql = format!("SELECT COUNT(*) FROM ({})", self.sql());
+        let params = self.params();
+        let params: Vec<_> = params
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_ref() as &dyn ToSql))
+            .collect();
+        Ok(txn.query_row(&sql, &params[..], |row| row.get(0))?)
+    }
+
+    /// Fetch the matching elements within this window, in the filter's order.
+    /// Private entries are only included if `public_only` is false.
+    pub fn elements(
+        &self,
+        txn: &Transaction,
+        window: ChainQueryWindow,
+        public_only: bool,
+    ) -> StateQueryResult<Vec<Element>> {
+        let mut sql = self.sql();
+        sql.push_str(if self.filter.order_descending {
+            " ORDER BY header_seq DESC "
+        } else {
+            " ORDER BY header_seq ASC "
+        });
+        // A negative limit means no limit in sqlite.
+        sql.push_str(" LIMIT :limit OFFSET :offset ");
+        let mut params = self.params();
+        params.push((
+            ":limit".to_string(),
+            Box::new(window.limit.map(i64::from).unwrap_or(-1)),
+        ));
+        params.push((":offset".to_string(), Box::new(window.offset)));
+        let params: Vec<_> = params
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_ref() as &dyn ToSql))
+            .collect();
+        let include_entries = self.filter.include_entries;
+        let mut stmt = txn.prepare(&sql)?;
+        let elements = stmt
+            .query_and_then(&params[..], |row| {
+                let header = from_blob::<SignedHeader>(row.get("header_blob")?)?;
+                let SignedHeader(header, signature) = header;
+                let private_entry = header
+                    .entry_type()
+                    .map_or(false, |e| *e.visibility() == EntryVisibility::Private);
+                let hash: HeaderHash = row.get("header_hash")?;
+                let header = HeaderHashed::with_pre_hashed(header, hash);
+                let shh = SignedHeaderHashed::with_presigned(header, signature);
+                let entry = if include_entries && (!private_entry || !public_only) {
+                    let entry: Option<Vec<u8>> = row.get("entry_blob")?;
+                    match entry {
+                        Some(entry) => Some(from_blob::<Entry>(entry)?),
+                        None => None,
+                    }
+                } else {
+                    None
+                };
+                StateQueryResult::Ok(Element::new(shh, entry))
+            })?
+            .collect::<StateQueryResult<Vec<_>>>();
+        elements
+    }
+}
+
+fn sql_param<'a>(name: String, value: impl ToSql + 'a) -> (String, Box<dyn ToSql + 'a>) {
+    (name, Box::new(value))
+}
+
+/// Sql for `AND column IN (:name_0, :name_1, ..)`.
+/// An empty list matches nothing.
+fn in_list(column: &str, name: &str, len: usize) -> String {
+    format!(" AND {} IN ({}) ", column, placeholders(name, len))
+}
+
+/// Named placeholders `:name_0, :name_1, ..` for a list of values.
+fn placeholders(name: &str, len: usize) -> String {
+    (0..len)
+        .map(|i| format!(":{}_{}", name, i))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -13,6 +13,7 @@ use holo_hash::HeaderHash;
 use holochain_keystore::MetaLairClient;
 use holochain_p2p::HolochainP2pDnaT;
 use holochain_sqlite::rusqlite::Transaction;
+use holochain_types::countersigning::session_entry_index;
 use holochain_types::db::DbRead;
 use holochain_types::db::DbWrite;
 use holochain_types::db_cache::DhtDbQueryCache;
@@ -23,7 +24,6 @@ use holochain_types::dht_op::DhtOpLight;
 use holochain_types::dht_op::OpOrder;
 use holochain_types::dht_op::UniqueForm;
 use holochain_types::element::SignedHeaderHashedExt;
-use holochain_types::sql::AsSql;
 use holochain_zome_types::header;
 use holochain_zome_types::query::ChainQueryFilterRange;
 use holochain_zome_types::CapAccess;
@@ -35,7 +35,6 @@ use holochain_zome_types::CounterSigningAgentState;
 use holochain_zome_types::CounterSigningSessionData;
 use holochain_zome_types::Element;
 use holochain_zome_types::Entry;
-use holochain_zome_types::EntryVisibility;
 use holochain_zome_types::GrantedFunction;
 use holochain_zome_types::Header;
 use holochain_zome_types::HeaderBuilder;
@@ -52,10 +51,14 @@ use holochain_zome_types::SignedHeaderHashed;
 use holochain_zome_types::Timestamp;
 use holochain_zome_types::Zome;
 
+use crate::chain_lock::has_chain_lock;
 use crate::chain_lock::is_chain_locked;
 use crate::chain_lock::is_lock_expired;
 use crate::prelude::*;
 use crate::query::chain_head::ChainHeadQuery;
+use crate::query::chain_query::ChainQuery;
+use crate::query::chain_query::ChainQuerySeqRange;
+use crate::query::chain_query::ChainQueryWindow;
 use crate::scratch::Scratch;
 use crate::scratch::SyncScratch;
 use holo_hash::EntryHash;
@@ -171,13 +174,28 @@ impl SourceChain {
     ) -> SourceChainResult<HeaderHash> {
         let entry_hash = EntryHash::with_data_sync(&entry);
         if let Entry::CounterSign(ref session_data, _) = entry {
-            self.put_with_header(
-                zome,
-                Header::from_countersigning_data(entry_hash, session_data, (*self.author).clone())?,
-                Some(entry),
-                chain_top_ordering,
-            )
-            .await
+            let author = (*self.author).clone();
+            let header = match session_entry_index(&entry) {
+                Some(0) => Header::from_countersigning_data(entry_hash, session_data, author)?,
+                // Later session entries follow the one before them on this chain.
+                Some(index) => {
+                    let (prev_header, _, _) = self.chain_head()?;
+                    Header::from_countersigning_data_at(
+                        index,
+                        entry_hash,
+                        prev_header,
+                        session_data,
+                        author,
+                    )?
+                }
+                None => {
+                    return Err(SourceChainError::CounterSigningEntryNotInSession(
+                        entry_hash,
+                    ))
+                }
+            };
+            self.put_with_header(zome, header, Some(entry), chain_top_ordering)
+                .await
         } else {
             // The caller MUST guard against this case.
             unreachable!("Put countersigned called with the wrong entry type");
@@ -245,10 +263,17 @@ impl SourceChain {
             .map(|entry| entry.as_content())
             .find(|entry| matches!(entry, Entry::CounterSign(_, _)));
 
-        if matches!(maybe_countersigned_entry, Some(Entry::CounterSign(_, _)))
-            && zomed_headers.len() != 1
-        {
-            return Err(SourceChainError::DirtyCounterSigningWrite);
+        if let Some(Entry::CounterSign(session_data, _)) = maybe_countersigned_entry {
+            // Every entry of the session and nothing else must be written together.
+            let countersigned_entries = entries
+                .iter()
+                .filter(|entry| matches!(entry.as_content(), Entry::CounterSign(_, _)))
+                .count();
+            if zomed_headers.len() != session_data.preflight_request().entries_len()
+                || countersigned_entries != zomed_headers.len()
+            {
+                return Err(SourceChainError::DirtyCounterSigningWrite);
+            }
         }
         let lock = lock_for_entry(maybe_countersigned_entry)?;
 
@@ -659,134 +684,15 @@ where
 
     /// Query Headers in the source chain.
     /// This returns a Vec rather than an iterator because it is intended to be
-    /// used by the `query` host function, which crosses the wasm boundary
-    // FIXME: This query needs to be tested.
+    /// used by the `query` host function, which crosses the wasm boundary.
+    /// Committed elements are filtered, ordered and paginated in the database.
+    /// Elements still in the scratch are always newer than the database so
+    /// they are filtered in memory and placed before or after the database
+    /// results depending on the order.
     pub async fn query(&self, query: QueryFilter) -> SourceChainResult<Vec<Element>> {
         let author = self.author.clone();
         let public_only = self.public_only;
-        let mut elements = self
-            .vault
-            .async_reader({
-                let query = query.clone();
-                move |txn| {
-                    let mut sql = "
-                SELECT DISTINCT
-                Header.hash AS header_hash, Header.blob AS header_blob
-            "
-                    .to_string();
-                    if query.include_entries {
-                        sql.push_str(
-                            "
-                    , Entry.blob AS entry_blob
-                    ",
-                        );
-                    }
-                    sql.push_str(
-                        "
-                FROM Header
-                ",
-                    );
-                    if query.include_entries {
-                        sql.push_str(
-                            "
-                    LEFT JOIN Entry On Header.entry_hash = Entry.hash
-                    ",
-                        );
-                    }
-                    sql.push_str(
-                        "
-                JOIN DhtOp On DhtOp.header_hash = Header.hash
-                WHERE
-                Header.author = :author
-                AND
-                (:range_start IS NULL AND :range_end IS NULL AND :range_start_hash IS NULL AND :range_end_hash IS NULL AND :range_prior_count IS NULL)
-                ",
-                    );
-                    sql.push_str(match query.sequence_range {
-                        ChainQueryFilterRange::Unbounded => "",
-                        ChainQueryFilterRange::HeaderSeqRange(_, _) => "
-                        OR (Header.seq BETWEEN :range_start AND :range_end)",
-                        ChainQueryFilterRange::HeaderHashRange(_, _) => "
-                        OR (
-                            Header.seq BETWEEN
-                            (SELECT Header.seq WHERE Header.hash = :range_start_hash)
-                            AND
-                            (SELECT Header.seq WHERE Header.hash = :range_end_hash)
-                        )",
-                        ChainQueryFilterRange::HeaderHashTerminated(_, _) => "
-                        OR (
-                            Header.seq BETWEEN
-                            (SELECT Header.seq WHERE Header.hash = :range_end_hash) - :range_prior_count
-                            AND
-                            (SELECT Header.seq WHERE Header.hash = :range_end_hash)
-                        )",
-                    });
-                    sql.push_str(
-                        "
-                AND
-                (:entry_type IS NULL OR Header.entry_type = :entry_type)
-                AND
-                (:header_type IS NULL OR Header.type = :header_type)
-                ORDER BY Header.seq ASC
-                ",
-                    );
-                    let mut stmt = txn.prepare(&sql)?;
-                    let elements = stmt
-                        .query_and_then(
-                        named_params! {
-                                ":author": author.as_ref(),
-                                ":entry_type": query.entry_type.as_sql(),
-                                ":header_type": query.header_type.as_sql(),
-                                ":range_start": match query.sequence_range {
-                                    ChainQueryFilterRange::HeaderSeqRange(start, _) => Some(start),
-                                    _ => None,
-                                },
-                                ":range_end": match query.sequence_range {
-                                    ChainQueryFilterRange::HeaderSeqRange(_, end) => Some(end),
-                                    _ => None,
-                                },
-                                ":range_start_hash": match &query.sequence_range {
-                                    ChainQueryFilterRange::HeaderHashRange(start_hash, _) => Some(start_hash.clone()),
-                                    _ => None,
-                                },
-                                ":range_end_hash": match &query.sequence_range {
-                                    ChainQueryFilterRange::HeaderHashRange(_, end_hash)
-                                    | ChainQueryFilterRange::HeaderHashTerminated(end_hash, _) => Some(end_hash.clone()),
-                                    _ => None,
-                                },
-                                ":range_prior_count": match query.sequence_range {
-                                    ChainQueryFilterRange::HeaderHashTerminated(_, prior_count) => Some(prior_count),
-                                    _ => None,
-                                },
-                            },
-                            |row| {
-                                let header = from_blob::<SignedHeader>(row.get("header_blob")?)?;
-                                let SignedHeader(header, signature) = header;
-                                let private_entry = header
-                                    .entry_type()
-                                    .map_or(false, |e| *e.visibility() == EntryVisibility::Private);
-                                let hash: HeaderHash = row.get("header_hash")?;
-                                let header = HeaderHashed::with_pre_hashed(header, hash);
-                                let shh = SignedHeaderHashed::with_presigned(header, signature);
-                                let entry =
-                                    if query.include_entries && (!private_entry || !public_only) {
-                                        let entry: Option<Vec<u8>> = row.get("entry_blob")?;
-                                        match entry {
-                                            Some(entry) => Some(from_blob::<Entry>(entry)?),
-                                            None => None,
-                                        }
-                                    } else {
-                                        None
-                                    };
-                                StateQueryResult::Ok(Element::new(shh, entry))
-                            },
-                        )?
-                        .collect::<StateQueryResult<Vec<_>>>();
-                    elements
-                }
-            })
-            .await?;
-        self.scratch.apply(|scratch| {
+        let scratch_elements = self.scratch.apply(|scratch| {
             let mut scratch_elements: Vec<_> = scratch
                 .headers()
                 .filter_map(|shh| {
@@ -798,10 +704,83 @@ where
                 })
                 .collect();
             scratch_elements.sort_unstable_by_key(|e| e.header().header_seq());
-
-            elements.extend(scratch_elements);
+            scratch_elements
         })?;
-        Ok(query.filter_elements(elements))
+        let elements = self
+            .vault
+            .async_reader(move |txn| {
+                let seq_range = ChainQuery::resolve_seq_range(&query.sequence_range, |hash| {
+                    match scratch_elements
+                        .iter()
+                        .find(|element| element.header_address() == hash)
+                    {
+                        Some(element) => Ok(Some(element.header().header_seq())),
+                        None => ChainQuery::header_seq(&txn, &author, hash),
+                    }
+                })?;
+                let mut scratch_filter = query.clone();
+                scratch_filter.offset = 0;
+                scratch_filter.limit = None;
+                let seq_range = match seq_range {
+                    ChainQuerySeqRange::Unbounded => {
+                        scratch_filter.sequence_range = ChainQueryFilterRange::Unbounded;
+                        None
+                    }
+                    ChainQuerySeqRange::Bounded(start, end) => {
+                        scratch_filter.sequence_range =
+                            ChainQueryFilterRange::HeaderSeqRange(start, end);
+                        Some((start, end))
+                    }
+                    ChainQuerySeqRange::Empty => return StateQueryResult::Ok(Vec::new()),
+                };
+                let scratch_matches = scratch_filter.filter_elements(scratch_elements);
+                let chain_query = ChainQuery::new(&author, &query, seq_range);
+                let offset = query.offset;
+
+                if query.order_descending {
+                    // The scratch is the head of the chain so it comes first.
+                    let scratch_len = scratch_matches.len() as u32;
+                    let mut elements = query.paginate(scratch_matches);
+                    let window = ChainQueryWindow {
+                        offset: offset.saturating_sub(scratch_len),
+                        limit: query.limit.map(|limit| limit - elements.len() as u32),
+                    };
+                    if window.limit != Some(0) {
+                        elements.extend(chain_query.elements(&txn, window, public_only)?);
+                    }
+                    Ok(elements)
+                } else {
+                    let window = ChainQueryWindow {
+                        offset,
+                        limit: query.limit,
+                    };
+                    let mut elements = chain_query.elements(&txn, window, public_only)?;
+                    let remaining = query.limit.map(|limit| limit - elements.len() as u32);
+                    if remaining != Some(0) {
+                        // The database ran out of matches within this page so
+                        // work out how far the offset reaches into the scratch.
+                        let db_total = if !elements.is_empty() {
+                            offset + elements.len() as u32
+                        } else if offset > 0 {
+                            chain_query.count(&txn)?
+                        } else {
+                            0
+                        };
+                        let scratch_page = scratch_matches
+                            .into_iter()
+                            .skip(offset.saturating_sub(db_total) as usize);
+                        match remaining {
+                            Some(remaining) => {
+                                elements.extend(scratch_page.take(remaining as usize))
+                            }
+                            None => elements.extend(scratch_page),
+                        }
+                    }
+                    Ok(elements)
+                }
+            })
+            .await?;
+        Ok(elements)
     }
 
     pub async fn is_chain_locked(&self, lock: Vec<u8>) -> SourceChainResult<bool> {
@@ -813,29 +792,32 @@ where
     }
 
     /// If there is a countersigning session get the
-    /// StoreEntry op to send to the entry authorities.
-    pub fn countersigning_op(&self) -> SourceChainResult<Option<DhtOp>> {
+    /// StoreEntry ops for every entry of the session, in session
+    /// order, to send to the entry authorities.
+    pub fn countersigning_ops(&self) -> SourceChainResult<Vec<DhtOp>> {
         let r = self.scratch.apply(|scratch| {
-            scratch
+            let mut ops: Vec<_> = scratch
                 .entries()
-                .find(|e| matches!(**e.1, Entry::CounterSign(_, _)))
-                .and_then(|(entry_hash, entry)| {
-                    scratch
-                        .headers()
-                        .find(|shh| {
-                            shh.header()
-                                .entry_hash()
-                                .map(|eh| eh == entry_hash)
-                                .unwrap_or(false)
-                        })
-                        .and_then(|shh| {
-                            Some(DhtOp::StoreEntry(
-                                shh.signature().clone(),
-                                shh.header().clone().try_into().ok()?,
-                                Box::new((**entry).clone()),
-                            ))
-                        })
+                .filter_map(|(entry_hash, entry)| {
+                    let index = session_entry_index(&**entry)?;
+                    let shh = scratch.headers().find(|shh| {
+                        shh.header()
+                            .entry_hash()
+                            .map(|eh| eh == entry_hash)
+                            .unwrap_or(false)
+                    })?;
+                    Some((
+                        index,
+                        DhtOp::StoreEntry(
+                            shh.signature().clone(),
+                            shh.header().clone().try_into().ok()?,
+                            Box::new((**entry).clone()),
+                        ),
+                    ))
                 })
+                .collect();
+            ops.sort_by_key(|(index, _)| *index);
+            ops.into_iter().map(|(_, op)| op).collect()
         })?;
         Ok(r)
     }
@@ -1053,28 +1035,65 @@ pub fn current_countersigning_session(
 ) -> SourceChainResult<Option<(EntryHash, CounterSigningSessionData)>> {
     // The chain must be locked for a session to be active.
     if is_chain_locked(txn, &[], author.as_ref())? {
-        match chain_head_db(txn, author) {
-            // We haven't done genesis so no session can be active.
-            Err(SourceChainError::ChainEmpty) => Ok(None),
-            Err(e) => Err(e),
-            Ok((hash, _, _)) => {
-                let txn: Txn = txn.into();
-                // Get the session data from the database.
-                let element = match txn.get_element(&hash.into())? {
-                    Some(element) => element,
+        countersigning_session_at_head(txn, author)
+    } else {
+        Ok(None)
+    }
+}
+
+/// The countersigning session that locked the chain and has not yet been
+/// resolved, even if the lock has since expired.
+/// This is how an interrupted session is found again after a restart.
+pub fn unfinished_countersigning_session(
+    txn: &Transaction<'_>,
+    author: Arc<AgentPubKey>,
+) -> SourceChainResult<Option<(EntryHash, CounterSigningSessionData)>> {
+    if has_chain_lock(txn, author.as_ref())? {
+        countersigning_session_at_head(txn, author)
+    } else {
+        Ok(None)
+    }
+}
+
+fn countersigning_session_at_head(
+    txn: &Transaction<'_>,
+    author: Arc<AgentPubKey>,
+) -> SourceChainResult<Option<(EntryHash, CounterSigningSessionData)>> {
+    match chain_head_db(txn, author) {
+        // We haven't done genesis so no session can be active.
+        Err(SourceChainError::ChainEmpty) => Ok(None),
+        Err(e) => Err(e),
+        Ok((hash, _, _)) => {
+            let txn: Txn = txn.into();
+            // Get the session data from the database.
+            let element = match txn.get_element(&hash.into())? {
+                Some(element) => element,
+                None => return Ok(None),
+            };
+            let (shh, ee) = element.into_inner();
+            let (mut entry_hash, cs) = match (shh.header().entry_hash(), ee.into_option()) {
+                (Some(entry_hash), Some(Entry::CounterSign(cs, _))) => (entry_hash.to_owned(), *cs),
+                _ => return Ok(None),
+            };
+            // The session is identified by its first entry, which is behind
+            // the head when the session commits more than one entry.
+            let mut header = shh.header().clone();
+            for _ in 0..cs.entry_index_for_header(&header)? {
+                let prev_header = match header.prev_header() {
+                    Some(prev_header) => prev_header.clone(),
+                    None => return Ok(None),
+                };
+                header = match txn.get_element(&prev_header.into())? {
+                    Some(element) => element.header().clone(),
+                    None => return Ok(None),
+                };
+                entry_hash = match header.entry_hash() {
+                    Some(entry_hash) => entry_hash.clone(),
                     None => return Ok(None),
                 };
-                let (shh, ee) = element.into_inner();
-                Ok(match (shh.header().entry_hash(), ee.into_option()) {
-                    (Some(entry_hash), Some(Entry::CounterSign(cs, _))) => {
-                        Some((entry_hash.to_owned(), *cs))
-                    }
-                    _ => None,
-                })
             }
+            Ok(Some((entry_hash, cs)))
         }
-    } else {
-        Ok(None)
     }
 }
 
@@ -1875,6 +1894,185 @@ pub mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn query_pages_across_database_and_scratch() -> SourceChainResult<()> {
+        let test_db = test_authored_db();
+        let dht_db = test_dht_db();
+        let dht_db_cache = DhtDbQueryCache::new(dht_db.to_db().into());
+        let keystore = test_keystore();
+        let vault = test_db.to_db();
+        let mut mock = MockHolochainP2pDnaT::new();
+        mock.expect_authority_for_hash().returning(|_| Ok(false));
+        let author = keystore.new_sign_keypair_random().await.unwrap();
+        let zome = fixt!(Zome);
+
+        genesis(
+            vault.clone().into(),
+            dht_db.to_db(),
+            &dht_db_cache,
+            keystore.clone(),
+            fixt!(DnaHash),
+            author.clone(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let source_chain = SourceChain::new(
+            vault.clone(),
+            dht_db.to_db(),
+            dht_db_cache.clone(),
+            keystore.clone(),
+            author.clone(),
+        )
+        .await
+        .unwrap();
+        let entry_type = EntryType::App(fixt!(AppEntryType));
+        // Seq 3 and 4 are flushed to the database.
+        for _ in 0..2 {
+            let entry = Entry::App(fixt!(AppEntryBytes));
+            let create = builder::Create {
+                entry_type: entry_type.clone(),
+                entry_hash: EntryHash::with_data_sync(&entry),
+            };
+            source_chain
+                .put(
+                    Some(zome.clone()),
+                    create,
+                    Some(entry),
+                    ChainTopOrdering::default(),
+                )
+                .await
+                .unwrap();
+        }
+        source_chain.flush(&mock).await.unwrap();
+        let source_chain = SourceChain::new(
+            vault.clone(),
+            dht_db.to_db(),
+            dht_db_cache.clone(),
+            keystore.clone(),
+            author.clone(),
+        )
+        .await
+        .unwrap();
+        // Seq 5 and 6 stay in the scratch.
+        for _ in 0..2 {
+            let entry = Entry::App(fixt!(AppEntryBytes));
+            let create = builder::Create {
+                entry_type: entry_type.clone(),
+                entry_hash: EntryHash::with_data_sync(&entry),
+            };
+            source_chain
+                .put(
+                    Some(zome.clone()),
+                    create,
+                    Some(entry),
+                    ChainTopOrdering::default(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let seqs = |elements: Vec<Element>| {
+            elements
+                .iter()
+                .map(|e| e.header().header_seq())
+                .collect::<Vec<_>>()
+        };
+        let query = |filter: QueryFilter| source_chain.query(filter);
+
+        assert_eq!(
+            seqs(query(QueryFilter::new()).await?),
+            vec![0, 1, 2, 3, 4, 5, 6]
+        );
+        assert_eq!(
+            seqs(query(QueryFilter::new().entry_type(entry_type.clone())).await?),
+            vec![3, 4, 5, 6]
+        );
+        assert_eq!(
+            seqs(
+                query(
+                    QueryFilter::new()
+                        .entry_type(entry_type.clone())
+                        .entry_type(EntryType::AgentPubKey)
+                )
+                .await?
+            ),
+            vec![2, 3, 4, 5, 6]
+        );
+        assert_eq!(
+            seqs(query(QueryFilter::new().offset(3).limit(3)).await?),
+            vec![3, 4, 5]
+        );
+        assert_eq!(
+            seqs(query(QueryFilter::new().offset(6).limit(3)).await?),
+            vec![6]
+        );
+        assert_eq!(
+            seqs(query(QueryFilter::new().descending().limit(3)).await?),
+            vec![6, 5, 4]
+        );
+        assert_eq!(
+            seqs(query(QueryFilter::new().descending().offset(3).limit(2)).await?),
+            vec![3, 2]
+        );
+        assert_eq!(
+            seqs(
+                query(
+                    QueryFilter::new()
+                        .header_type(HeaderType::Create)
+                        .sequence_range(ChainQueryFilterRange::HeaderSeqRange(4, 5))
+                )
+                .await?
+            ),
+            vec![4, 5]
+        );
+        assert_eq!(
+            seqs(
+                query(
+                    QueryFilter::new()
+                        .exclude_entry_type(entry_type.clone())
+                        .exclude_header_type(HeaderType::Dna)
+                )
+                .await?
+            ),
+            vec![1, 2]
+        );
+
+        // - Timestamp ranges page in the database and the scratch alike.
+        let all = query(QueryFilter::new()).await?;
+        let (start, end) = (all[1].header().timestamp(), all[5].header().timestamp());
+        let in_range = |offset: usize, limit: usize| {
+            let expected: Vec<_> = all
+                .iter()
+                .filter(|e| start <= e.header().timestamp() && e.header().timestamp() <= end)
+                .map(|e| e.header().header_seq())
+                .skip(offset)
+                .take(limit)
+                .collect();
+            (QueryFilter::new().timestamp_range(start, end), expected)
+        };
+        for (offset, limit) in [(0, 7), (1, 2), (3, 3)] {
+            let (filter, expected) = in_range(offset, limit);
+            let filter = filter.offset(offset as u32).limit(limit as u32);
+            assert_eq!(seqs(query(filter).await?), expected);
+        }
+        let (filter, expected) = in_range(0, 7);
+        let vault_count = vault
+            .async_reader({
+                let author = author.clone();
+                move |txn| ChainQuery::new(&author, &filter, None).count(&txn)
+            })
+            .await?;
+        // Seq 5 and 6 are only in the scratch.
+        assert_eq!(
+            vault_count as usize,
+            expected.iter().filter(|seq| **seq < 5).count()
+        );
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn source_chain_buffer_dump_entries_json() -> SourceChainResult<()> {
         let test_db = test_authored_db();
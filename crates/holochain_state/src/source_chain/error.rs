@@ -39,9 +39,14 @@ pub enum SourceChainError {
     #[error("Attempted to write a countersigning session that has already expired")]
     LockExpired,
 
-    #[error("Attempted to write anything other than the countersigning session entry at the same time as the session entry.")]
+    #[error("Attempted to write anything other than the countersigning session entries at the same time as the session entries.")]
     DirtyCounterSigningWrite,
 
+    #[error(
+        "Attempted to write countersigned entry {0} that is not one of its session's entries."
+    )]
+    CounterSigningEntryNotInSession(EntryHash),
+
     #[error(
         "The source chain's structure is invalid. This error is not recoverable. Detail:\n{0}"
     )]
@@ -49,6 +49,69 @@ pub fn put(txn: &mut Transaction, wasm: DnaWasmHashed) -> StateMutationResult<()
     mutations::insert_wasm(txn, wasm)
 }
 
+/// A compiled module as stored, with the blake2b-256 hash of its blob.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledWasm {
+    pub blob: Vec<u8>,
+    pub blob_hash: Vec<u8>,
+}
+
+/// Get a compiled module for a wasm and engine.
+/// Returns `None` if there is none or if its blob does not match its hash.
+pub fn get_compiled(
+    txn: &Transaction<'_>,
+    wasm_hash: &WasmHash,
+    engine_key: &str,
+) -> StateQueryResult<Option<CompiledWasm>> {
+    let item = txn
+        .query_row(
+            "SELECT blob_hash, blob FROM CompiledWasm
+            WHERE wasm_hash = :wasm_hash AND engine_key = :engine_key",
+            named_params! {
+                ":wasm_hash": wasm_hash,
+                ":engine_key": engine_key,
+            },
+            |row| {
+                let blob_hash: Vec<u8> = row.get("blob_hash")?;
+                let blob: Vec<u8> = row.get("blob")?;
+                Ok((blob_hash, blob))
+            },
+        )
+        .optional()?;
+    Ok(item.and_then(|(blob_hash, blob)| {
+        if holo_hash::encode::blake2b_256(&blob) == blob_hash {
+            Some(CompiledWasm { blob, blob_hash })
+        } else {
+            tracing::warn!(%wasm_hash, engine_key, "Ignoring corrupt compiled wasm");
+            None
+        }
+    }))
+}
+
+/// Store a compiled module for a wasm and engine.
+pub fn put_compiled(
+    txn: &mut Transaction,
+    wasm_hash: &WasmHash,
+    engine_key: &str,
+    holochain_version: &str,
+    blob: &[u8],
+) -> StateMutationResult<()> {
+    mutations::insert_compiled_wasm(txn, wasm_hash, engine_key, holochain_version, blob)
+}
+
+/// Delete the modules compiled by any other version of holochain.
+pub fn delete_stale_compiled(
+    txn: &mut Transaction,
+    holochain_version: &str,
+) -> StateMutationResult<usize> {
+    Ok(txn.execute(
+        "DELETE FROM CompiledWasm WHERE holochain_version != :holochain_version",
+        named_params! {
+            ":holochain_version": holochain_version,
+        },
+    )?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -84,4 +147,49 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn compiled_wasm_round_trip() -> DatabaseResult<()> {
+        use holochain_sqlite::prelude::*;
+        observability::test_run().ok();
+
+        let db = crate::test_utils::test_wasm_db();
+        let wasm_hash = WasmHash::from_raw_32(vec![1; 32]);
+
+        db.conn()?
+            .with_commit_sync(|txn| {
+                put_compiled(txn, &wasm_hash, "old-engine", "0.0.1", &[1, 2, 3])?;
+                put_compiled(txn, &wasm_hash, "engine", "0.0.2", &[4, 5, 6])
+            })
+            .unwrap();
+        fresh_reader_test!(db, |txn| {
+            assert_eq!(
+                get_compiled(&txn, &wasm_hash, "engine")
+                    .unwrap()
+                    .map(|compiled| compiled.blob),
+                Some(vec![4, 5, 6])
+            );
+            assert_eq!(get_compiled(&txn, &wasm_hash, "other").unwrap(), None);
+        });
+
+        // Corrupt blobs are not returned.
+        db.conn()?
+            .with_commit_sync(|txn| {
+                txn.execute("UPDATE CompiledWasm SET blob = X'07'", [])?;
+                StateMutationResult::Ok(())
+            })
+            .unwrap();
+        fresh_reader_test!(db, |txn| {
+            assert_eq!(get_compiled(&txn, &wasm_hash, "engine").unwrap(), None);
+        });
+
+        // Only the current version survives a cleanup.
+        let deleted = db
+            .conn()?
+            .with_commit_sync(|txn| delete_stale_compiled(txn, "0.0.2"))
+            .unwrap();
+        assert_eq!(deleted, 1);
+
+        Ok(())
+    }
 }
@@ -20,6 +20,9 @@ pub struct AgentActivityResponse<T = SignedHeaderHashed> {
     /// The highest chain header that has
     /// been observed by this authority.
     pub highest_observed: Option<HighestObserved>,
+    /// The authorities this response was merged from.
+    #[serde(default)]
+    pub authorities: ActivityAuthorities,
 }
 
 holochain_serial!(AgentActivityResponse<HeaderHash>);
@@ -38,6 +41,7 @@ impl<A> AgentActivityResponse<A> {
             rejected_activity: convert_activity(&other.rejected_activity),
             status: ChainStatus::Empty,
             highest_observed: other.highest_observed,
+            authorities: other.authorities,
         }
     }
 
@@ -49,6 +53,7 @@ impl<A> AgentActivityResponse<A> {
             rejected_activity: ChainItems::NotRequested,
             status: ChainStatus::Empty,
             highest_observed: other.highest_observed,
+            authorities: other.authorities,
         }
     }
 
@@ -65,6 +70,7 @@ impl<A> AgentActivityResponse<A> {
             rejected_activity: convert_activity(other.rejected_activity),
             status: other.status,
             highest_observed: other.highest_observed,
+            authorities: other.authorities,
         }
     }
 }
@@ -104,6 +110,7 @@ impl From<AgentActivityResponse<Element>> for holochain_zome_types::query::Agent
             status: a.status,
             highest_observed: a.highest_observed,
             warrants: Vec::with_capacity(0),
+            authorities: a.authorities,
         }
     }
 }
@@ -0,0 +1,67 @@
+//! Countersigning session helpers that need to hash headers and entries.
+
+use holochain_zome_types::prelude::*;
+
+/// Build every agent's headers for each entry of a countersigning session,
+/// in the order the entries are committed.
+/// The entry hashes are the final countersigned entry hashes,
+/// which are the same for every agent.
+pub fn build_session_header_sets(
+    session_data: &CounterSigningSessionData,
+    entry_hashes: &[EntryHash],
+) -> Result<Vec<Vec<Header>>, CounterSigningError> {
+    // The first entry follows each agent's frozen chain top.
+    let mut prev_headers: Vec<HeaderHash> = session_data
+        .responses()
+        .iter()
+        .map(|(agent_state, _)| agent_state.chain_top().clone())
+        .collect();
+    let mut header_sets = Vec::with_capacity(entry_hashes.len());
+    for (index, entry_hash) in entry_hashes.iter().enumerate() {
+        let header_set =
+            session_data.build_header_set_at(index, entry_hash.clone(), &prev_headers)?;
+        prev_headers = header_set
+            .iter()
+            .map(|header| HeaderHash::with_data_sync(header))
+            .collect();
+        header_sets.push(header_set);
+    }
+    Ok(header_sets)
+}
+
+/// The position in its session of a countersigned entry,
+/// found from the hash of the app entry it wraps.
+pub fn session_entry_index(entry: &Entry) -> Option<usize> {
+    match entry {
+        Entry::CounterSign(session_data, app_entry_bytes) => {
+            let app_entry_hash = EntryHash::with_data_sync(&Entry::App(app_entry_bytes.clone()));
+            let preflight_request = session_data.preflight_request();
+            (0..preflight_request.entries_len()).find(|index| {
+                preflight_request
+                    .entry_at(*index)
+                    .map(|(hash, _)| *hash == app_entry_hash)
+                    .unwrap_or(false)
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The hash of each entry of a countersigning session, in the order the
+/// entries are committed, found from any of the session's headers.
+/// None if the headers don't cover every entry of the session.
+pub fn session_entry_hashes<'a>(
+    session_data: &CounterSigningSessionData,
+    headers: impl IntoIterator<Item = &'a Header>,
+) -> Option<Vec<EntryHash>> {
+    let mut entry_hashes = vec![None; session_data.preflight_request().entries_len()];
+    for header in headers {
+        if let (Ok(index), Some(entry_hash)) = (
+            session_data.entry_index_for_header(header),
+            header.entry_hash(),
+        ) {
+            entry_hashes[index] = Some(entry_hash.clone());
+        }
+    }
+    entry_hashes.into_iter().collect()
+}
@@ -337,6 +337,15 @@ impl DhtOp {
         }
     }
 
+    /// The enzyme agent that gathers the signed headers for this op's
+    /// countersigning session, if the session has one.
+    pub fn enzymatic_countersigning_enzyme(&self) -> Option<&AgentPubKey> {
+        match self.entry() {
+            Some(Entry::CounterSign(session_data, _)) => session_data.preflight_request().enzyme(),
+            _ => None,
+        }
+    }
+
     /// Get the entry from this op, if one exists
     pub fn entry(&self) -> Option<&Entry> {
         match self {
@@ -10,6 +10,7 @@ mod dna_store;
 
 pub mod error;
 pub mod wasm;
+pub mod wasm_metering;
 pub use dna_bundle::*;
 pub use dna_file::*;
 pub use dna_manifest::*;
@@ -94,10 +94,13 @@ impl DnaBundle {
 
         let zomes: Zomes = data
             .iter()
-            .map(|(zome_name, hash, _)| {
+            .map(|(zome_name, hash, wasm)| {
                 (
                     zome_name.clone(),
-                    ZomeDef::Wasm(WasmZome::new(hash.clone())),
+                    ZomeDef::Wasm(WasmZome {
+                        wasm_hash: hash.clone(),
+                        read_only_fns: wasm.read_only_fns(),
+                    }),
                 )
             })
             .collect();
@@ -4,8 +4,11 @@
 use backtrace::Backtrace;
 use holo_hash::*;
 use holochain_serialized_bytes::prelude::*;
+use holochain_zome_types::zome::FunctionName;
+use holochain_zome_types::zome::READ_ONLY_FN_MARKER_PREFIX;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::BTreeSet;
 use std::fmt;
 use std::hash::Hash;
 use std::hash::Hasher;
@@ -79,6 +82,30 @@ impl DnaWasm {
     pub fn code(&self) -> Arc<Box<[u8]>> {
         Arc::clone(&self.code)
     }
+
+    /// The functions marked with `#[hdk_extern(read_only)]`, found by their
+    /// marker exports.
+    ///
+    /// Wasm that can't be parsed has no read-only functions. It will fail
+    /// to compile when the zome is called anyway.
+    pub fn read_only_fns(&self) -> BTreeSet<FunctionName> {
+        let mut read_only_fns = BTreeSet::new();
+        for payload in wasmparser::Parser::new(0).parse_all(&self.code) {
+            match payload {
+                Ok(wasmparser::Payload::ExportSection(exports)) => {
+                    for export in exports.into_iter().flatten() {
+                        if let Some(fn_name) = export.field.strip_prefix(READ_ONLY_FN_MARKER_PREFIX)
+                        {
+                            read_only_fns.insert(FunctionName::new(fn_name));
+                        }
+                    }
+                }
+                Ok(_) => (),
+                Err(_) => break,
+            }
+        }
+        read_only_fns
+    }
 }
 
 impl fmt::Debug for DnaWasm {
@@ -106,3 +133,32 @@ impl From<Vec<u8>> for DnaWasm {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A wasm module that exports one empty function with this name.
+    fn wasm_exporting(name: &str) -> DnaWasm {
+        let mut code = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+        // The type `() -> ()` and one function of that type
+        code.extend_from_slice(&[0x01, 0x04, 0x01, 0x60, 0x00, 0x00]);
+        code.extend_from_slice(&[0x03, 0x02, 0x01, 0x00]);
+        code.extend_from_slice(&[0x07, name.len() as u8 + 4, 0x01, name.len() as u8]);
+        code.extend_from_slice(name.as_bytes());
+        code.extend_from_slice(&[0x00, 0x00]);
+        code.extend_from_slice(&[0x0a, 0x04, 0x01, 0x02, 0x00, 0x0b]);
+        DnaWasm::from(code)
+    }
+
+    #[test]
+    fn read_only_fns_are_found_by_marker() {
+        let wasm = wasm_exporting(&format!("{}get_thing", READ_ONLY_FN_MARKER_PREFIX));
+        assert_eq!(
+            wasm.read_only_fns(),
+            [FunctionName::new("get_thing")].into_iter().collect()
+        );
+        assert!(wasm_exporting("get_thing").read_only_fns().is_empty());
+        assert!(DnaWasm::new_invalid().read_only_fns().is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+//! Deterministic fuel metering and memory limits for zome wasm, shared by
+//! the conductor and the tools that run zome callbacks outside of it.
+//!
+//! Every wasm operator costs one unit of fuel, so a callback runs out of fuel
+//! on the same instruction on every node.
+
+use std::ptr::NonNull;
+use std::sync::Arc;
+use wasmer::vm;
+use wasmer::vm::MemoryError;
+use wasmer::vm::MemoryStyle;
+use wasmer::vm::TableStyle;
+use wasmer::vm::VMMemoryDefinition;
+use wasmer::vm::VMTableDefinition;
+use wasmer::wasmparser::Operator;
+use wasmer::BaseTunables;
+use wasmer::CompilerConfig;
+use wasmer::Cranelift;
+use wasmer::Instance;
+use wasmer::MemoryType;
+use wasmer::Pages;
+use wasmer::Store;
+use wasmer::TableType;
+use wasmer::Target;
+use wasmer::Tunables;
+use wasmer::Universal;
+use wasmer_middlewares::metering::get_remaining_points;
+use wasmer_middlewares::metering::set_remaining_points;
+use wasmer_middlewares::metering::MeteringPoints;
+use wasmer_middlewares::Metering;
+
+/// The fuel a wasm call may burn if not configured otherwise.
+/// Every wasm instruction costs one unit of fuel.
+pub const DEFAULT_WASM_FUEL: u64 = 10_000_000_000;
+
+/// The linear memory a wasm instance may grow to if not configured
+/// otherwise, in 64KiB wasm pages (1GiB).
+pub const DEFAULT_WASM_MEMORY_PAGES: u32 = 16_384;
+
+/// The version of how [`metered_store`] compiles modules. Bump it whenever
+/// the middleware, its cost function or the compiler settings change, so
+/// modules compiled before are not loaded.
+pub const METERING_VERSION: u32 = 1;
+
+/// A store that compiles modules with metering and instantiates them with
+/// at most `max_memory_pages` of memory.
+/// The metering middleware can only be used for a single module so every
+/// module needs its own store.
+pub fn metered_store(max_memory_pages: u32) -> Store {
+    // The limit is overwritten before every call.
+    let metering = Arc::new(Metering::new(0, |_: &Operator| -> u64 { 1 }));
+    let mut compiler = Cranelift::default();
+    compiler.canonicalize_nans(true);
+    compiler.push_middleware(metering);
+    let engine = Universal::new(compiler).engine();
+    let tunables = LimitingTunables {
+        base: BaseTunables::for_target(&Target::default()),
+        limit: Pages(max_memory_pages),
+    };
+    Store::new_with_tunables(&engine, tunables)
+}
+
+/// Give an instance of a module compiled by a [`metered_store`]
+/// `fuel` for its next call.
+pub fn set_fuel(instance: &Instance, fuel: u64) {
+    set_remaining_points(instance, fuel);
+}
+
+/// The fuel an instance of a module compiled by a [`metered_store`] has
+/// left, `None` if it ran out.
+pub fn remaining_fuel(instance: &Instance) -> Option<u64> {
+    match get_remaining_points(instance) {
+        MeteringPoints::Remaining(fuel) => Some(fuel),
+        MeteringPoints::Exhausted => None,
+    }
+}
+
+/// Caps the linear memory of every instance.
+struct LimitingTunables {
+    base: BaseTunables,
+    limit: Pages,
+}
+
+impl LimitingTunables {
+    /// Clamp the maximum memory the module asks for to the limit.
+    fn adjust_memory(&self, requested: &MemoryType) -> MemoryType {
+        let mut adjusted = *requested;
+        adjusted.maximum = Some(requested.maximum.map_or(self.limit, |m| m.min(self.limit)));
+        adjusted
+    }
+
+    fn validate_memory(&self, ty: &MemoryType) -> Result<(), MemoryError> {
+        if ty.minimum > self.limit {
+            return Err(MemoryError::Generic(format!(
+                "wasm needs {} pages of memory but is limited to {}",
+                ty.minimum.0, self.limit.0
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Tunables for LimitingTunables {
+    fn memory_style(&self, memory: &MemoryType) -> MemoryStyle {
+        self.base.memory_style(&self.adjust_memory(memory))
+    }
+
+    fn table_style(&self, table: &TableType) -> TableStyle {
+        self.base.table_style(table)
+    }
+
+    fn create_host_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+    ) -> Result<Arc<dyn vm::Memory>, MemoryError> {
+        let adjusted = self.adjust_memory(ty);
+        self.validate_memory(&adjusted)?;
+        self.base.create_host_memory(&adjusted, style)
+    }
+
+    unsafe fn create_vm_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+        vm_definition_location: NonNull<VMMemoryDefinition>,
+    ) -> Result<Arc<dyn vm::Memory>, MemoryError> {
+        let adjusted = self.adjust_memory(ty);
+        self.validate_memory(&adjusted)?;
+        self.base
+            .create_vm_memory(&adjusted, style, vm_definition_location)
+    }
+
+    fn create_host_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+    ) -> Result<Arc<dyn vm::Table>, String> {
+        self.base.create_host_table(ty, style)
+    }
+
+    unsafe fn create_vm_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+        vm_definition_location: NonNull<VMTableDefinition>,
+    ) -> Result<Arc<dyn vm::Table>, String> {
+        self.base.create_vm_table(ty, style, vm_definition_location)
+    }
+}
@@ -18,6 +18,7 @@ pub mod app;
 pub mod autonomic;
 pub mod chain;
 pub mod combinators;
+pub mod countersigning;
 pub mod db;
 pub mod db_cache;
 pub mod dht_op;
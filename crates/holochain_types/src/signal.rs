@@ -29,6 +29,9 @@ pub enum SystemSignal {
     Test(String),
     /// A countersigning session has successfully completed.
     SuccessfulCountersigning(holo_hash::EntryHash),
+    /// A countersigning session ended without completing.
+    /// Its entry stays on the source chain but is never published.
+    AbandonedCountersigning(holo_hash::EntryHash),
 }
 
 /// Create a test signal
@@ -8,6 +8,8 @@ use holo_hash::AgentPubKey;
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum CallTargetCell {
     Other(CellId),
+    /// The cell provisioned for this role in the caller's app.
+    OtherRole(String),
     Local,
 }
 
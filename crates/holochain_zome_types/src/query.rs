@@ -8,6 +8,7 @@ use crate::header::HeaderType;
 use crate::warrant::Warrant;
 use crate::Element;
 use crate::HeaderHashed;
+use crate::Timestamp;
 use holo_hash::EntryHash;
 use holo_hash::HasHash;
 use holo_hash::HeaderHash;
@@ -58,6 +59,11 @@ impl Default for ChainQueryFilterRange {
 }
 
 /// Query arguments
+///
+/// Only a hash bounded sequence range gives results that can be verified
+/// against the chain, so only those are safe to use in validation.
+/// Every other filter drops elements from the chain, and the remaining
+/// elements can't be checked for gaps.
 #[derive(
     serde::Serialize, serde::Deserialize, SerializedBytes, Default, PartialEq, Clone, Debug,
 )]
@@ -65,18 +71,51 @@ impl Default for ChainQueryFilterRange {
 pub struct ChainQueryFilter {
     /// Limit the results to a range of elements according to their headers.
     pub sequence_range: ChainQueryFilterRange,
-    /// Filter by EntryType
+    /// Filter by EntryType.
+    /// An element matches if its entry type is any of these.
     // NB: if this filter is set, you can't verify the results, so don't
     //     use this in validation
-    pub entry_type: Option<EntryType>,
+    #[serde(default, deserialize_with = "one_or_many::deserialize")]
+    pub entry_type: Option<Vec<EntryType>>,
     /// Filter by a list of `EntryHash`.
     pub entry_hashes: Option<HashSet<EntryHash>>,
-    /// Filter by HeaderType
+    /// Filter by HeaderType.
+    /// An element matches if its header type is any of these.
+    // NB: if this filter is set, you can't verify the results, so don't
+    //     use this in validation
+    #[serde(default, deserialize_with = "one_or_many::deserialize")]
+    pub header_type: Option<Vec<HeaderType>>,
+    /// Exclude elements with any of these entry types.
+    /// Headers without an entry are never excluded by this.
+    // NB: if this filter is set, you can't verify the results, so don't
+    //     use this in validation
+    #[serde(default)]
+    pub exclude_entry_type: Vec<EntryType>,
+    /// Exclude elements with any of these header types.
     // NB: if this filter is set, you can't verify the results, so don't
     //     use this in validation
-    pub header_type: Option<HeaderType>,
+    #[serde(default)]
+    pub exclude_header_type: Vec<HeaderType>,
+    /// Only include elements whose header timestamp is within this range.
+    /// Inclusive start, inclusive end.
+    // NB: if this filter is set, you can't verify the results, so don't
+    //     use this in validation
+    #[serde(default)]
+    pub timestamp_range: Option<(Timestamp, Timestamp)>,
     /// Include the entries in the elements
     pub include_entries: bool,
+    /// Return the elements from the chain head towards genesis
+    /// instead of from genesis towards the chain head.
+    #[serde(default)]
+    pub order_descending: bool,
+    /// Skip this many matching elements before returning any.
+    /// The offset is applied after ordering.
+    #[serde(default)]
+    pub offset: u32,
+    /// Return at most this many matching elements.
+    /// `None` returns every element after the offset.
+    #[serde(default)]
+    pub limit: Option<u32>,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, SerializedBytes)]
@@ -94,6 +133,38 @@ pub struct AgentActivity {
     /// Warrants about this AgentActivity.
     /// Placeholder for future.
     pub warrants: Vec<Warrant>,
+    /// The authorities that were asked for this activity.
+    pub authorities: ActivityAuthorities,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// How many agent activity authorities were consulted
+/// and whether they all reported the same chain.
+pub struct ActivityAuthorities {
+    /// The number of authorities that responded.
+    pub consulted: u32,
+    /// True if every authority reported the same
+    /// status and highest observed header.
+    pub agreed: bool,
+}
+
+impl ActivityAuthorities {
+    /// A response from a single authority, which always agrees with itself.
+    pub fn single() -> Self {
+        Self {
+            consulted: 1,
+            agreed: true,
+        }
+    }
+}
+
+impl Default for ActivityAuthorities {
+    fn default() -> Self {
+        Self {
+            consulted: 0,
+            agreed: true,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize, SerializedBytes)]
@@ -165,6 +236,22 @@ pub struct ChainFork {
     pub first_header: HeaderHash,
     /// The second header at this sequence position.
     pub second_header: HeaderHash,
+    /// Every branch from the fork point that is known to
+    /// the authorities, ordered by the time they were authored.
+    /// Empty if the fork was only seen as a pair of hashes.
+    #[serde(default)]
+    pub branches: Vec<ForkBranch>,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+/// One side of a forked chain.
+pub struct ForkBranch {
+    /// The header at the fork sequence that starts this branch.
+    pub header: HeaderHash,
+    /// When the header that starts this branch was authored.
+    pub timestamp: Timestamp,
+    /// The last header that builds on this branch.
+    pub head: ChainHead,
 }
 
 impl ChainQueryFilter {
@@ -183,8 +270,11 @@ impl ChainQueryFilter {
     }
 
     /// Filter on entry type.
+    /// Calling this more than once matches any of the given entry types.
     pub fn entry_type(mut self, entry_type: EntryType) -> Self {
-        self.entry_type = Some(entry_type);
+        self.entry_type
+            .get_or_insert_with(Vec::new)
+            .push(entry_type);
         self
     }
 
@@ -195,8 +285,31 @@ impl ChainQueryFilter {
     }
 
     /// Filter on header type.
+    /// Calling this more than once matches any of the given header types.
     pub fn header_type(mut self, header_type: HeaderType) -> Self {
-        self.header_type = Some(header_type);
+        self.header_type
+            .get_or_insert_with(Vec::new)
+            .push(header_type);
+        self
+    }
+
+    /// Exclude an entry type.
+    /// Calling this more than once excludes all of the given entry types.
+    pub fn exclude_entry_type(mut self, entry_type: EntryType) -> Self {
+        self.exclude_entry_type.push(entry_type);
+        self
+    }
+
+    /// Exclude a header type.
+    /// Calling this more than once excludes all of the given header types.
+    pub fn exclude_header_type(mut self, header_type: HeaderType) -> Self {
+        self.exclude_header_type.push(header_type);
+        self
+    }
+
+    /// Filter on header timestamps, inclusive of both `start` and `end`.
+    pub fn timestamp_range(mut self, start: Timestamp, end: Timestamp) -> Self {
+        self.timestamp_range = Some((start, end));
         self
     }
 
@@ -206,6 +319,34 @@ impl ChainQueryFilter {
         self
     }
 
+    /// Return the elements in descending chain order.
+    pub fn descending(mut self) -> Self {
+        self.order_descending = true;
+        self
+    }
+
+    /// Skip this many matching elements.
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Return at most this many matching elements.
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Apply the offset and limit of this query to an already
+    /// filtered and ordered list of items.
+    pub fn paginate<T>(&self, items: Vec<T>) -> Vec<T> {
+        let items = items.into_iter().skip(self.offset as usize);
+        match self.limit {
+            Some(limit) => items.take(limit as usize).collect(),
+            None => items.collect(),
+        }
+    }
+
     /// If the sequence range supports fork disambiguation, apply it to remove
     /// headers that are not in the correct branch.
     /// Numerical range bounds do NOT support fork disambiguation, and neither
@@ -263,19 +404,33 @@ impl ChainQueryFilter {
         }
     }
 
+    /// Whether this query orders or pages its results.
+    /// Ordered queries sort their results by header sequence, other queries
+    /// keep the order of the headers they are given.
+    pub fn is_ordered(&self) -> bool {
+        self.order_descending || self.offset > 0 || self.limit.is_some()
+    }
+
     /// Filter a vector of hashed headers according to the query.
+    /// If the query [is ordered](Self::is_ordered) the headers are returned
+    /// in that order, otherwise they keep the order they were given in.
+    /// The offset and limit are NOT applied.
     pub fn filter_headers(&self, headers: Vec<HeaderHashed>) -> Vec<HeaderHashed> {
-        self.disambiguate_forks(headers)
+        let mut headers: Vec<_> = self
+            .disambiguate_forks(headers)
             .into_iter()
             .filter(|header| {
                 self.header_type
                     .as_ref()
-                    .map(|header_type| header.header_type() == *header_type)
+                    .map(|header_types| header_types.contains(&header.header_type()))
                     .unwrap_or(true)
                     && self
                         .entry_type
                         .as_ref()
-                        .map(|entry_type| header.entry_type() == Some(entry_type))
+                        .map(|entry_types| match header.entry_type() {
+                            Some(entry_type) => entry_types.contains(entry_type),
+                            None => false,
+                        })
                         .unwrap_or(true)
                     && self
                         .entry_hashes
@@ -285,11 +440,30 @@ impl ChainQueryFilter {
                             None => false,
                         })
                         .unwrap_or(true)
+                    && !self.exclude_header_type.contains(&header.header_type())
+                    && !header
+                        .entry_type()
+                        .map(|entry_type| self.exclude_entry_type.contains(entry_type))
+                        .unwrap_or(false)
+                    && self
+                        .timestamp_range
+                        .map(|(start, end)| {
+                            start <= header.timestamp() && header.timestamp() <= end
+                        })
+                        .unwrap_or(true)
             })
-            .collect()
+            .collect();
+        if self.is_ordered() {
+            headers.sort_by_key(|header| header.header_seq());
+            if self.order_descending {
+                headers.reverse();
+            }
+        }
+        headers
     }
 
     /// Filter a vector of elements according to the query.
+    /// This applies the ordering, offset and limit of the query.
     pub fn filter_elements(&self, elements: Vec<Element>) -> Vec<Element> {
         let headers = self.filter_headers(
             elements
@@ -297,14 +471,39 @@ impl ChainQueryFilter {
                 .map(|element| element.header_hashed().clone())
                 .collect(),
         );
-        let header_hashset = headers
-            .iter()
-            .map(|header| header.as_hash().clone())
-            .collect::<HashSet<HeaderHash>>();
-        elements
+        let mut element_hashmap = elements
             .into_iter()
-            .filter(|element| header_hashset.contains(element.header_address()))
-            .collect()
+            .map(|element| (element.header_address().clone(), element))
+            .collect::<HashMap<HeaderHash, Element>>();
+        self.paginate(
+            headers
+                .iter()
+                .filter_map(|header| element_hashmap.remove(header.as_hash()))
+                .collect(),
+        )
+    }
+}
+
+/// Filters serialized before several entry and header types could be given
+/// hold a single type, read those as a list of one.
+mod one_or_many {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        Many(Vec<T>),
+        One(T),
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        T: serde::Deserialize<'de>,
+    {
+        let value: Option<OneOrMany<T>> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(value.map(|value| match value {
+            OneOrMany::Many(many) => many,
+            OneOrMany::One(one) => vec![one],
+        }))
     }
 }
 
@@ -317,6 +516,7 @@ mod tests {
     use crate::header::EntryType;
     use crate::ChainQueryFilterRange;
     use crate::HeaderHashed;
+    use crate::Timestamp;
     use ::fixt::prelude::*;
     use holo_hash::HasHash;
 
@@ -331,23 +531,27 @@ mod tests {
         let mut h0 = fixt!(Create);
         h0.entry_type = entry_type_1.clone();
         h0.header_seq = 0;
+        h0.timestamp = Timestamp::from_micros(0);
         h0.entry_hash = entry_hash_0.clone();
         let hh0 = HeaderHashed::from_content_sync(h0.into());
 
         let mut h1 = fixt!(Update);
         h1.entry_type = entry_type_2.clone();
         h1.header_seq = 1;
+        h1.timestamp = Timestamp::from_micros(1);
         h1.prev_header = hh0.as_hash().clone();
         let hh1 = HeaderHashed::from_content_sync(h1.into());
 
         let mut h2 = fixt!(CreateLink);
         h2.header_seq = 2;
+        h2.timestamp = Timestamp::from_micros(2);
         h2.prev_header = hh1.as_hash().clone();
         let hh2 = HeaderHashed::from_content_sync(h2.into());
 
         let mut h3 = fixt!(Create);
         h3.entry_type = entry_type_2.clone();
         h3.header_seq = 3;
+        h3.timestamp = Timestamp::from_micros(3);
         h3.prev_header = hh2.as_hash().clone();
         let hh3 = HeaderHashed::from_content_sync(h3.into());
 
@@ -355,6 +559,7 @@ mod tests {
         let mut h3a = fixt!(Create);
         h3a.entry_type = entry_type_1.clone();
         h3a.header_seq = 3;
+        h3a.timestamp = Timestamp::from_micros(3);
         h3a.prev_header = hh2.as_hash().clone();
         let hh3a = HeaderHashed::from_content_sync(h3a.into());
 
@@ -363,11 +568,13 @@ mod tests {
         // same entry content as h0
         h4.entry_hash = entry_hash_0;
         h4.header_seq = 4;
+        h4.timestamp = Timestamp::from_micros(4);
         h4.prev_header = hh3.as_hash().clone();
         let hh4 = HeaderHashed::from_content_sync(h4.into());
 
         let mut h5 = fixt!(CreateLink);
         h5.header_seq = 5;
+        h5.timestamp = Timestamp::from_micros(5);
         h5.prev_header = hh4.as_hash().clone();
         let hh5 = HeaderHashed::from_content_sync(h5.into());
 
@@ -560,4 +767,196 @@ mod tests {
             [true, false, false, false, true, true, false].to_vec()
         );
     }
+
+    #[test]
+    fn filter_by_many_types() {
+        let headers = fixtures();
+
+        assert_eq!(
+            map_query(
+                &ChainQueryFilter::new()
+                    .entry_type(headers[0].entry_type().unwrap().clone())
+                    .entry_type(headers[1].entry_type().unwrap().clone()),
+                &headers
+            ),
+            [true, true, false, true, true, true, false].to_vec()
+        );
+
+        assert_eq!(
+            map_query(
+                &ChainQueryFilter::new()
+                    .header_type(headers[1].header_type())
+                    .header_type(headers[2].header_type()),
+                &headers
+            ),
+            [false, true, true, false, false, true, true].to_vec()
+        );
+    }
+
+    #[test]
+    fn order_and_paginate() {
+        let headers = fixtures();
+        // Drop the fork so the chain is linear.
+        let chain: Vec<_> = headers
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != 4)
+            .map(|(_, h)| h.clone())
+            .collect();
+        let seqs = |query: &ChainQueryFilter| {
+            query
+                .paginate(query.filter_headers(chain.clone()))
+                .iter()
+                .map(|h| h.header_seq())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(seqs(&ChainQueryFilter::new()), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(
+            seqs(&ChainQueryFilter::new().descending()),
+            vec![5, 4, 3, 2, 1, 0]
+        );
+        assert_eq!(
+            seqs(&ChainQueryFilter::new().offset(1).limit(2)),
+            vec![1, 2]
+        );
+        assert_eq!(
+            seqs(&ChainQueryFilter::new().descending().offset(1).limit(2)),
+            vec![4, 3]
+        );
+        assert_eq!(seqs(&ChainQueryFilter::new().offset(5).limit(2)), vec![5]);
+        assert_eq!(seqs(&ChainQueryFilter::new().offset(9)), Vec::<u32>::new());
+
+        // Without ordering or paging the headers keep their order.
+        let reversed: Vec<_> = chain.iter().rev().cloned().collect();
+        assert_eq!(
+            ChainQueryFilter::new().filter_headers(reversed.clone()),
+            reversed
+        );
+    }
+
+    #[test]
+    fn missing_paging_fields_deserialize_to_defaults() {
+        /// The filter as it was before paging and several types were added.
+        #[derive(Debug, serde::Serialize)]
+        struct OldChainQueryFilter {
+            sequence_range: ChainQueryFilterRange,
+            entry_type: Option<EntryType>,
+            entry_hashes: Option<std::collections::HashSet<holo_hash::EntryHash>>,
+            header_type: Option<crate::header::HeaderType>,
+            include_entries: bool,
+        }
+        let old = OldChainQueryFilter {
+            sequence_range: ChainQueryFilterRange::Unbounded,
+            entry_type: None,
+            entry_hashes: None,
+            header_type: None,
+            include_entries: true,
+        };
+        let bytes = holochain_serialized_bytes::encode(&old).unwrap();
+        let filter: ChainQueryFilter = holochain_serialized_bytes::decode(&bytes).unwrap();
+        assert_eq!(filter, ChainQueryFilter::new().include_entries(true));
+
+        // - A single entry or header type is read as a list of one.
+        for entry_type in [EntryType::App(fixt!(AppEntryType)), EntryType::AgentPubKey] {
+            let old = OldChainQueryFilter {
+                sequence_range: ChainQueryFilterRange::HeaderSeqRange(1, 3),
+                entry_type: Some(entry_type.clone()),
+                entry_hashes: None,
+                header_type: Some(crate::header::HeaderType::Create),
+                include_entries: false,
+            };
+            let bytes = holochain_serialized_bytes::encode(&old).unwrap();
+            let filter: ChainQueryFilter = holochain_serialized_bytes::decode(&bytes).unwrap();
+            assert_eq!(
+                filter,
+                ChainQueryFilter::new()
+                    .sequence_range(ChainQueryFilterRange::HeaderSeqRange(1, 3))
+                    .entry_type(entry_type)
+                    .header_type(crate::header::HeaderType::Create)
+            );
+        }
+
+        // - Anything else fails to decode instead of being read as no filter.
+        #[derive(Debug, serde::Serialize)]
+        struct BadChainQueryFilter {
+            sequence_range: ChainQueryFilterRange,
+            entry_type: Option<u32>,
+            entry_hashes: Option<std::collections::HashSet<holo_hash::EntryHash>>,
+            header_type: Option<crate::header::HeaderType>,
+            include_entries: bool,
+        }
+        let bad = BadChainQueryFilter {
+            sequence_range: ChainQueryFilterRange::Unbounded,
+            entry_type: Some(7),
+            entry_hashes: None,
+            header_type: None,
+            include_entries: true,
+        };
+        let bytes = holochain_serialized_bytes::encode(&bad).unwrap();
+        let decoded: Result<ChainQueryFilter, _> = holochain_serialized_bytes::decode(&bytes);
+        assert!(decoded.is_err());
+    }
+
+    #[test]
+    fn filter_by_exclusions() {
+        let headers = fixtures();
+
+        assert_eq!(
+            map_query(
+                &ChainQueryFilter::new()
+                    .exclude_entry_type(headers[0].entry_type().unwrap().clone()),
+                &headers
+            ),
+            [false, true, true, true, false, false, true].to_vec()
+        );
+
+        assert_eq!(
+            map_query(
+                &ChainQueryFilter::new()
+                    .exclude_header_type(headers[0].header_type())
+                    .exclude_header_type(headers[2].header_type()),
+                &headers
+            ),
+            [false, true, false, false, false, true, false].to_vec()
+        );
+
+        assert_eq!(
+            map_query(
+                &ChainQueryFilter::new()
+                    .entry_type(headers[0].entry_type().unwrap().clone())
+                    .exclude_header_type(headers[1].header_type()),
+                &headers
+            ),
+            [true, false, false, false, true, false, false].to_vec()
+        );
+    }
+
+    #[test]
+    fn filter_by_timestamp() {
+        let headers = fixtures();
+
+        assert_eq!(
+            map_query(
+                &ChainQueryFilter::new()
+                    .timestamp_range(Timestamp::from_micros(1), Timestamp::from_micros(3)),
+                &headers
+            ),
+            [false, true, true, true, true, false, false].to_vec()
+        );
+
+        // Timestamps are applied after fork disambiguation.
+        assert_eq!(
+            map_query(
+                &ChainQueryFilter::new()
+                    .timestamp_range(Timestamp::from_micros(1), Timestamp::from_micros(3))
+                    .sequence_range(ChainQueryFilterRange::HeaderHashRange(
+                        headers[0].as_hash().clone(),
+                        headers[6].as_hash().clone(),
+                    )),
+                &headers
+            ),
+            [false, true, true, true, false, false, false].to_vec()
+        );
+    }
 }
@@ -15,6 +15,7 @@ pub mod error;
 pub mod inline_zome;
 
 use error::ZomeResult;
+use std::collections::BTreeSet;
 
 #[cfg(feature = "full-dna-def")]
 use self::inline_zome::InlineZome;
@@ -140,11 +141,21 @@ impl ZomeDef {
     //     the arg is unused.
     pub fn wasm_hash(&self, _zome_name: &ZomeName) -> ZomeResult<holo_hash::WasmHash> {
         match self {
-            ZomeDef::Wasm(WasmZome { wasm_hash }) => Ok(wasm_hash.clone()),
+            ZomeDef::Wasm(WasmZome { wasm_hash, .. }) => Ok(wasm_hash.clone()),
             #[cfg(feature = "full-dna-def")]
             ZomeDef::Inline(_) => Err(ZomeError::NonWasmZome(_zome_name.clone())),
         }
     }
+
+    /// Whether a function was marked with `#[hdk_extern(read_only)]`, so it
+    /// may not write to the source chain.
+    pub fn is_read_only(&self, fn_name: &FunctionName) -> bool {
+        match self {
+            ZomeDef::Wasm(WasmZome { read_only_fns, .. }) => read_only_fns.contains(fn_name),
+            #[cfg(feature = "full-dna-def")]
+            ZomeDef::Inline(_) => false,
+        }
+    }
 }
 
 #[cfg(feature = "test_utils")]
@@ -162,18 +173,25 @@ impl<'a> arbitrary::Arbitrary<'a> for ZomeDef {
 pub struct WasmZome {
     /// The WasmHash representing the WASM byte code for this zome.
     pub wasm_hash: holo_hash::WasmHash,
+    /// The functions marked with `#[hdk_extern(read_only)]`.
+    /// Skipped when empty so the hashes of existing DNAs don't change.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub read_only_fns: BTreeSet<FunctionName>,
 }
 
 impl WasmZome {
     /// Constructor
     pub fn new(wasm_hash: holo_hash::WasmHash) -> Self {
-        Self { wasm_hash }
+        Self {
+            wasm_hash,
+            read_only_fns: BTreeSet::new(),
+        }
     }
 }
 
 impl ZomeDef {
     /// create a Zome from a holo_hash WasmHash instead of a holo_hash one
     pub fn from_hash(wasm_hash: holo_hash::WasmHash) -> Self {
-        Self::Wasm(WasmZome { wasm_hash })
+        Self::Wasm(WasmZome::new(wasm_hash))
     }
 }
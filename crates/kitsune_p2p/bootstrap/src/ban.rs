@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use crate::store::Store;
+
+use super::*;
+use kitsune_p2p_types::bin_types::KitsuneSpace;
+use warp::http::StatusCode;
+use warp::Filter;
+
+/// Ban the space in the body, dropping all its agent infos and
+/// refusing new ones. Requires the `X-Admin-Token` header.
+pub(crate) fn ban(
+    store: Store,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::header::exact("X-Op", "ban"))
+        .and(warp::header::optional::<String>("X-Admin-Token"))
+        .and(warp::body::content_length_limit(SIZE_LIMIT))
+        .and(warp::body::bytes())
+        .and(with_store(store))
+        .and_then(ban_space)
+}
+
+/// Lift a ban put in place by [`ban`].
+pub(crate) fn unban(
+    store: Store,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::header::exact("X-Op", "unban"))
+        .and(warp::header::optional::<String>("X-Admin-Token"))
+        .and(warp::body::content_length_limit(SIZE_LIMIT))
+        .and(warp::body::bytes())
+        .and(with_store(store))
+        .and_then(unban_space)
+}
+
+async fn ban_space(
+    token: Option<String>,
+    space: Bytes,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if !store.is_admin(token.as_deref()) {
+        return Ok(StatusCode::UNAUTHORIZED);
+    }
+    let space: Arc<KitsuneSpace> =
+        rmp_decode(&mut AsRef::<[u8]>::as_ref(&space)).map_err(|_| warp::reject())?;
+    match store.ban(space) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(_) => Ok(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn unban_space(
+    token: Option<String>,
+    space: Bytes,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if !store.is_admin(token.as_deref()) {
+        return Ok(StatusCode::UNAUTHORIZED);
+    }
+    let space: Arc<KitsuneSpace> =
+        rmp_decode(&mut AsRef::<[u8]>::as_ref(&space)).map_err(|_| warp::reject())?;
+    match store.unban(&space) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(_) => Ok(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixt::prelude::*;
+    use kitsune_p2p::fixt::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ban() {
+        let store = Store::with_config(&BootstrapConfig {
+            admin_token: Some("secret".into()),
+            ..Default::default()
+        })
+        .unwrap();
+        let filter = super::ban(store.clone());
+        let space: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace));
+        let mut buf = Vec::new();
+        rmp_encode(&mut buf, space.clone()).unwrap();
+
+        let res = warp::test::request()
+            .method("POST")
+            .header("Content-type", "application/octet")
+            .header("X-Op", "ban")
+            .header("X-Admin-Token", "wrong")
+            .body(buf.clone())
+            .reply(&filter)
+            .await;
+        assert_eq!(res.status(), 401);
+        assert!(!store.is_banned(&space));
+
+        let res = warp::test::request()
+            .method("POST")
+            .header("Content-type", "application/octet")
+            .header("X-Op", "ban")
+            .header("X-Admin-Token", "secret")
+            .body(buf.clone())
+            .reply(&filter)
+            .await;
+        assert_eq!(res.status(), 200);
+        assert!(store.is_banned(&space));
+
+        let res = warp::test::request()
+            .method("POST")
+            .header("Content-type", "application/octet")
+            .header("X-Op", "unban")
+            .header("X-Admin-Token", "secret")
+            .body(buf)
+            .reply(&super::unban(store.clone()))
+            .await;
+        assert_eq!(res.status(), 200);
+        assert!(!store.is_banned(&space));
+    }
+}
@@ -1,20 +1,31 @@
 use crate::store::Store;
 
 use super::*;
+use warp::http::StatusCode;
 use warp::Filter;
 
+/// Drop every agent info. Requires the `X-Admin-Token` header.
 pub(crate) fn clear(
     store: Store,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::header::exact("X-Op", "clear"))
+        .and(warp::header::optional::<String>("X-Admin-Token"))
         .and(with_store(store))
         .and_then(clear_info)
 }
 
-async fn clear_info(store: Store) -> Result<impl warp::Reply, warp::Rejection> {
-    store.clear();
-    Ok(warp::reply())
+async fn clear_info(
+    token: Option<String>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if !store.is_admin(token.as_deref()) {
+        return Ok(StatusCode::UNAUTHORIZED);
+    }
+    match store.clear() {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(_) => Ok(StatusCode::INTERNAL_SERVER_ERROR),
+    }
 }
 
 #[cfg(test)]
@@ -27,7 +38,11 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_clear() {
-        let store = Store::new(vec![]);
+        let store = Store::with_config(&BootstrapConfig {
+            admin_token: Some("secret".into()),
+            ..Default::default()
+        })
+        .unwrap();
 
         let filter = super::clear(store.clone());
         let space: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace));
@@ -44,13 +59,28 @@ mod tests {
             )
             .await
             .unwrap();
-            store.put(info);
+            store.put(info).await.unwrap();
+        }
+
+        // Clearing needs the admin token.
+        for token in [None, Some("wrong")] {
+            let mut req = warp::test::request()
+                .method("POST")
+                .header("Content-type", "application/octet")
+                .header("X-Op", "clear");
+            if let Some(token) = token {
+                req = req.header("X-Admin-Token", token);
+            }
+            let res = req.reply(&filter).await;
+            assert_eq!(res.status(), 401);
+            assert_eq!(store.all().get(&space).unwrap().len(), 20);
         }
 
         let res = warp::test::request()
             .method("POST")
             .header("Content-type", "application/octet")
             .header("X-Op", "clear")
+            .header("X-Admin-Token", "secret")
             .reply(&filter)
             .await;
         assert_eq!(res.status(), 200);
@@ -0,0 +1,269 @@
+use std::sync::atomic::Ordering;
+
+use crate::store::Store;
+
+use super::*;
+use kitsune_p2p_types::agent_info::AgentInfoSigned;
+use warp::http::StatusCode;
+use warp::Filter;
+
+/// How often new agent infos are pushed to the federation peers by default.
+pub const FEDERATION_FREQ: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Agent infos are passed on between federated servers at most this many
+/// times, so a chain of servers still gets every agent info but a
+/// misconfigured federation can't amplify traffic without bound.
+pub const MAX_FEDERATION_HOPS: u8 = 4;
+
+/// A push to a federation peer that takes longer than this is given up on.
+const FEDERATION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Agent infos are at most [`SIZE_LIMIT`] each so this
+/// keeps a batch under [`FEDERATION_SIZE_LIMIT`].
+const FEDERATION_BATCH_LEN: usize = 512;
+
+const FEDERATION_SIZE_LIMIT: u64 = SIZE_LIMIT * FEDERATION_BATCH_LEN as u64 + 1024;
+
+/// Agent infos pushed from one federated server to another.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct FederationBatch {
+    /// How many servers the agent infos already passed through.
+    hops: u8,
+    infos: Vec<AgentInfoSigned>,
+}
+
+/// Accept agent infos pushed by another federated server.
+/// Requires the `X-Federation-Secret` header.
+pub(crate) fn federate(
+    store: Store,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::header::exact("X-Op", "federate"))
+        .and(warp::header::optional::<String>("X-Federation-Secret"))
+        .and(warp::body::content_length_limit(FEDERATION_SIZE_LIMIT))
+        .and(warp::body::bytes())
+        .and(with_store(store))
+        .and_then(federate_infos)
+}
+
+async fn federate_infos(
+    secret: Option<String>,
+    batch: Bytes,
+    store: Store,
+) -> Result<warp::reply::WithStatus<Vec<u8>>, warp::Rejection> {
+    if !store.is_federation_peer(secret.as_deref()) {
+        return Ok(warp::reply::with_status(
+            Vec::new(),
+            StatusCode::UNAUTHORIZED,
+        ));
+    }
+    let FederationBatch { hops, infos } =
+        rmp_decode(&mut AsRef::<[u8]>::as_ref(&batch)).map_err(|_| warp::reject())?;
+    let hops = hops.saturating_add(1);
+    for info in infos {
+        // Federation peers pass on the puts of every agent they serve so
+        // they are exempt from the ip quota, but their infos must be valid
+        // and fit the space quota like any other put. Invalid infos are
+        // dropped without failing the batch.
+        if store.is_banned(&info.space) {
+            continue;
+        }
+        if !put::valid(&info, store.verify_signatures()).await {
+            PUT_INVALID.fetch_add(1, Ordering::Relaxed);
+            continue;
+        }
+        if !store.quota().check_space(&info.space) {
+            PUT_RATE_LIMITED.fetch_add(1, Ordering::Relaxed);
+            continue;
+        }
+        store
+            .put_federated(info, hops)
+            .await
+            .map_err(|_| warp::reject())?;
+        FEDERATED.fetch_add(1, Ordering::Relaxed);
+    }
+    let mut buf = Vec::with_capacity(1);
+    rmp_encode(&mut buf, ()).map_err(|_| warp::reject())?;
+    Ok(warp::reply::with_status(buf, StatusCode::OK))
+}
+
+/// Periodically push the agent infos this server learned about since the
+/// last push to all the federation peers.
+pub(crate) fn spawn_federation(
+    store: Store,
+    peers: Vec<String>,
+    secret: Option<String>,
+    frequency: std::time::Duration,
+) {
+    tokio::task::spawn(async move {
+        let client = match reqwest::Client::builder()
+            .timeout(FEDERATION_TIMEOUT)
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("Failed to start federation: {:?}", e);
+                return;
+            }
+        };
+        loop {
+            tokio::time::sleep(frequency).await;
+            let mut by_hops: Vec<Vec<AgentInfoSigned>> = Vec::new();
+            for (info, hops) in store.take_outbox() {
+                let hops = hops as usize;
+                if by_hops.len() <= hops {
+                    by_hops.resize_with(hops + 1, Vec::new);
+                }
+                by_hops[hops].push(info);
+            }
+            for (hops, infos) in by_hops.into_iter().enumerate() {
+                for infos in infos.chunks(FEDERATION_BATCH_LEN) {
+                    let batch = FederationBatch {
+                        hops: hops as u8,
+                        infos: infos.to_vec(),
+                    };
+                    let mut body = Vec::new();
+                    if rmp_encode(&mut body, &batch).is_err() {
+                        continue;
+                    }
+                    // A peer that is down misses these agent infos, agents
+                    // put fresh ones long before the old ones expire.
+                    futures::future::join_all(peers.iter().map(|peer| {
+                        let mut req = client
+                            .post(peer.as_str())
+                            .body(body.clone())
+                            .header("X-Op", "federate")
+                            .header(reqwest::header::CONTENT_TYPE, "application/octet");
+                        if let Some(secret) = &secret {
+                            req = req.header("X-Federation-Secret", secret.as_str());
+                        }
+                        async move {
+                            let sent = req.send().await;
+                            if !matches!(sent, Ok(res) if res.status().is_success()) {
+                                FEDERATION_PUSH_FAILED.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                    }))
+                    .await;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::test_util::signed_info;
+    use fixt::prelude::*;
+    use kitsune_p2p::{fixt::*, KitsuneSpace};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_federate() {
+        let store = Store::with_config(&BootstrapConfig {
+            federation_peers: vec!["http://127.0.0.1:1".into()],
+            federation_secret: Some("secret".into()),
+            ..Default::default()
+        })
+        .unwrap();
+        let filter = super::federate(store.clone());
+        let space: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace));
+        let expires_at_ms =
+            std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 + 60_000_000;
+        let info = signed_info(space.clone(), fixt!(UrlList, Empty), expires_at_ms).await;
+        let last_hop = signed_info(space.clone(), fixt!(UrlList, Empty), expires_at_ms).await;
+
+        // Pushes without the shared secret are refused.
+        for secret in [None, Some("wrong")] {
+            let mut buf = Vec::new();
+            rmp_encode(
+                &mut buf,
+                FederationBatch {
+                    hops: 0,
+                    infos: vec![info.clone()],
+                },
+            )
+            .unwrap();
+            let mut req = warp::test::request()
+                .method("POST")
+                .header("Content-type", "application/octet")
+                .header("X-Op", "federate");
+            if let Some(secret) = secret {
+                req = req.header("X-Federation-Secret", secret);
+            }
+            let res = req.body(buf).reply(&filter).await;
+            assert_eq!(res.status(), 401);
+            assert!(store.all().is_empty());
+        }
+
+        for batch in [
+            FederationBatch {
+                hops: 0,
+                infos: vec![info.clone()],
+            },
+            // A duplicate isn't passed on again.
+            FederationBatch {
+                hops: 0,
+                infos: vec![info.clone()],
+            },
+            FederationBatch {
+                hops: MAX_FEDERATION_HOPS - 1,
+                infos: vec![last_hop.clone()],
+            },
+        ] {
+            let mut buf = Vec::new();
+            rmp_encode(&mut buf, batch).unwrap();
+            let res = warp::test::request()
+                .method("POST")
+                .header("Content-type", "application/octet")
+                .header("X-Op", "federate")
+                .header("X-Federation-Secret", "secret")
+                .body(buf)
+                .reply(&filter)
+                .await;
+            assert_eq!(res.status(), 200);
+        }
+
+        assert_eq!(store.all().get(&space).unwrap().len(), 2);
+        // Only the info that still has hops left goes out again.
+        let outbox = store.take_outbox();
+        assert_eq!(outbox, vec![(info, 1)]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_federate_quota() {
+        let store = Store::with_config(&BootstrapConfig {
+            put_quota_per_ip: Some(1),
+            put_quota_per_space: Some(2),
+            federation_secret: Some("secret".into()),
+            ..Default::default()
+        })
+        .unwrap();
+        let filter = super::federate(store.clone());
+        let space: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace));
+        let expires_at_ms =
+            std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 + 60_000_000;
+        let mut infos = Vec::new();
+        for _ in 0..3 {
+            infos.push(signed_info(space.clone(), fixt!(UrlList, Empty), expires_at_ms).await);
+        }
+
+        let mut buf = Vec::new();
+        rmp_encode(&mut buf, FederationBatch { hops: 0, infos }).unwrap();
+        let res = warp::test::request()
+            .method("POST")
+            .header("Content-type", "application/octet")
+            .header("X-Op", "federate")
+            .header("X-Federation-Secret", "secret")
+            .remote_addr(([10, 0, 0, 1], 5000).into())
+            .body(buf)
+            .reply(&filter)
+            .await;
+        assert_eq!(res.status(), 200);
+        // The peer isn't limited by the ip quota but only
+        // the first two infos fit in the space quota.
+        assert_eq!(store.all().get(&space).unwrap().len(), 2);
+    }
+}
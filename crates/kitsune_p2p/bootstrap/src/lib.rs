@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::atomic::AtomicUsize;
 
 use kitsune_p2p_types::codec::rmp_decode;
@@ -9,14 +10,26 @@ use warp::{hyper::body::Bytes, Filter};
 static NOW: AtomicUsize = AtomicUsize::new(0);
 static RANDOM: AtomicUsize = AtomicUsize::new(0);
 static PUT: AtomicUsize = AtomicUsize::new(0);
+static PUT_INVALID: AtomicUsize = AtomicUsize::new(0);
+static PUT_RATE_LIMITED: AtomicUsize = AtomicUsize::new(0);
+static FEDERATED: AtomicUsize = AtomicUsize::new(0);
+static FEDERATION_PUSH_FAILED: AtomicUsize = AtomicUsize::new(0);
 
+mod ban;
 mod clear;
+mod federate;
+mod metrics;
 mod now;
+mod persist;
 mod proxy_list;
 mod put;
+mod quota;
 mod random;
 mod store;
 
+pub use federate::{FEDERATION_FREQ, MAX_FEDERATION_HOPS};
+pub use quota::QUOTA_WINDOW;
+
 /// No reason to accept a peer data bigger then 1KB.
 // TODO: Maybe even that's too high?
 const SIZE_LIMIT: u64 = 1024;
@@ -28,6 +41,56 @@ pub type BootstrapDriver = futures::future::BoxFuture<'static, ()>;
 
 pub type BootstrapShutdown = Box<dyn FnOnce() + 'static + Send>;
 
+/// Configuration for a bootstrap server.
+#[derive(Clone, Debug)]
+pub struct BootstrapConfig {
+    /// Proxy server addresses returned from the `proxy_list` call.
+    pub proxy_list: Vec<String>,
+    /// How often expired agent infos are pruned.
+    pub prune_frequency: std::time::Duration,
+    /// Sqlite database file to persist agent infos and banned spaces in.
+    /// If `None` everything is kept in memory and lost on restart.
+    pub database: Option<PathBuf>,
+    /// Maximum puts accepted for a single space per [`QUOTA_WINDOW`].
+    pub put_quota_per_space: Option<u32>,
+    /// Maximum puts accepted from a single remote ip per [`QUOTA_WINDOW`].
+    pub put_quota_per_ip: Option<u32>,
+    /// Token that must be sent in the `X-Admin-Token` header to
+    /// ban or unban spaces. The admin ops are disabled if `None`.
+    pub admin_token: Option<String>,
+    /// Check agent info signatures on `put`. Only turn this off
+    /// for test setups loaded with fixture data.
+    pub verify_signatures: bool,
+    /// Urls of other bootstrap servers to federate with. New agent infos
+    /// are pushed to all of them so agents registered on any of the
+    /// servers can discover each other.
+    pub federation_peers: Vec<String>,
+    /// Secret shared by all the federated servers, sent in the
+    /// `X-Federation-Secret` header. Agent infos pushed by other
+    /// servers are only accepted if it matches, so the `federate`
+    /// op is disabled if `None`.
+    pub federation_secret: Option<String>,
+    /// How often new agent infos are pushed to the federation peers.
+    pub federation_frequency: std::time::Duration,
+}
+
+impl Default for BootstrapConfig {
+    fn default() -> Self {
+        Self {
+            proxy_list: Vec::new(),
+            prune_frequency: PRUNE_EXPIRED_FREQ,
+            database: None,
+            put_quota_per_space: None,
+            put_quota_per_ip: None,
+            admin_token: None,
+            verify_signatures: true,
+            federation_peers: Vec::new(),
+            federation_secret: None,
+            federation_frequency: FEDERATION_FREQ,
+        }
+    }
+}
+
 /// Run a bootstrap with the default prune frequency [`PRUNE_EXPIRED_FREQ`].
 pub async fn run(
     addr: impl Into<SocketAddr> + 'static,
@@ -42,7 +105,24 @@ pub async fn run_with_prune_freq(
     proxy_list: Vec<String>,
     prune_frequency: std::time::Duration,
 ) -> Result<(BootstrapDriver, SocketAddr, BootstrapShutdown), String> {
-    let store = Store::new(proxy_list);
+    run_with_config(
+        addr,
+        BootstrapConfig {
+            proxy_list,
+            prune_frequency,
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+/// Run a bootstrap server with the given config.
+pub async fn run_with_config(
+    addr: impl Into<SocketAddr> + 'static,
+    config: BootstrapConfig,
+) -> Result<(BootstrapDriver, SocketAddr, BootstrapShutdown), String> {
+    let prune_frequency = config.prune_frequency;
+    let store = Store::with_config(&config)?;
 
     {
         let store = store.clone();
@@ -54,11 +134,24 @@ pub async fn run_with_prune_freq(
         });
     }
 
+    if !config.federation_peers.is_empty() {
+        federate::spawn_federation(
+            store.clone(),
+            config.federation_peers.clone(),
+            config.federation_secret.clone(),
+            config.federation_frequency,
+        );
+    }
+
     let boot = now::now()
         .or(put::put(store.clone()))
         .or(random::random(store.clone()))
         .or(proxy_list::proxy_list(store.clone()))
-        .or(clear::clear(store));
+        .or(clear::clear(store.clone()))
+        .or(ban::ban(store.clone()))
+        .or(ban::unban(store.clone()))
+        .or(federate::federate(store.clone()))
+        .or(metrics::metrics(store));
 
     let (s, r) = tokio::sync::oneshot::channel();
     let shutdown = Box::new(move || {
@@ -81,3 +174,6 @@ fn with_store(
 ) -> impl Filter<Extract = (Store,), Error = std::convert::Infallible> + Clone {
     warp::any().map(move || store.clone())
 }
+
+#[cfg(test)]
+pub(crate) mod test_util;
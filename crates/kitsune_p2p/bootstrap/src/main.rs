@@ -12,6 +12,43 @@ struct Args {
     /// multiple times
     #[clap(short, long, verbatim_doc_comment)]
     proxy: Vec<String>,
+
+    /// persist agent infos and banned spaces
+    /// to this sqlite database so they survive
+    /// a restart, kept in memory if not set
+    #[clap(short, long, verbatim_doc_comment)]
+    database: Option<std::path::PathBuf>,
+
+    /// maximum puts accepted per space per minute
+    #[clap(long)]
+    put_quota_per_space: Option<u32>,
+
+    /// maximum puts accepted per remote ip per minute
+    #[clap(long)]
+    put_quota_per_ip: Option<u32>,
+
+    /// token required in the `X-Admin-Token` header
+    /// to ban and unban spaces, admin ops are
+    /// disabled if not set
+    #[clap(long, verbatim_doc_comment)]
+    admin_token: Option<String>,
+
+    /// url of another bootstrap server to exchange
+    /// agent infos with, can be specified
+    /// multiple times
+    #[clap(long, verbatim_doc_comment)]
+    federation_peer: Vec<String>,
+
+    /// secret shared by all the federated servers,
+    /// agent infos pushed by other servers are
+    /// refused if not set
+    #[clap(long, verbatim_doc_comment)]
+    federation_secret: Option<String>,
+
+    /// how often to push new agent infos
+    /// to the federation peers
+    #[clap(long, default_value = "5000", verbatim_doc_comment)]
+    federation_interval_ms: u64,
 }
 
 #[tokio::main(flavor = "multi_thread")]
@@ -27,7 +64,19 @@ async fn main() {
         .next()
         .unwrap();
 
-    match kitsune_p2p_bootstrap::run(addr, args.proxy).await {
+    let config = kitsune_p2p_bootstrap::BootstrapConfig {
+        proxy_list: args.proxy,
+        database: args.database,
+        put_quota_per_space: args.put_quota_per_space,
+        put_quota_per_ip: args.put_quota_per_ip,
+        admin_token: args.admin_token,
+        federation_peers: args.federation_peer,
+        federation_secret: args.federation_secret,
+        federation_frequency: std::time::Duration::from_millis(args.federation_interval_ms),
+        ..Default::default()
+    };
+
+    match kitsune_p2p_bootstrap::run_with_config(addr, config).await {
         Ok((driver, addr, _shutdown)) => {
             println!("http://{}", addr);
             driver.await;
@@ -0,0 +1,89 @@
+use std::fmt::Write;
+use std::sync::atomic::Ordering;
+
+use crate::store::Store;
+
+use super::*;
+use warp::Filter;
+
+/// Plain text counters in the prometheus exposition format on `GET /metrics`.
+pub(crate) fn metrics(
+    store: Store,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path("metrics"))
+        .and(warp::path::end())
+        .and(with_store(store))
+        .and_then(get_metrics)
+}
+
+async fn get_metrics(store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    let (spaces, agents) = store.counts();
+    let counters = [
+        ("now", NOW.load(Ordering::Relaxed)),
+        ("random", RANDOM.load(Ordering::Relaxed)),
+        ("put", PUT.load(Ordering::Relaxed)),
+        ("put_invalid", PUT_INVALID.load(Ordering::Relaxed)),
+        ("put_rate_limited", PUT_RATE_LIMITED.load(Ordering::Relaxed)),
+        ("federated", FEDERATED.load(Ordering::Relaxed)),
+        (
+            "federation_push_failed",
+            FEDERATION_PUSH_FAILED.load(Ordering::Relaxed),
+        ),
+    ];
+    let gauges = [
+        ("spaces", spaces),
+        ("agents", agents),
+        ("banned_spaces", store.banned_count()),
+    ];
+    let mut out = String::new();
+    for (name, value) in counters {
+        let _ = writeln!(out, "# TYPE kitsune_bootstrap_{}_total counter", name);
+        let _ = writeln!(out, "kitsune_bootstrap_{}_total {}", name, value);
+    }
+    for (name, value) in gauges {
+        let _ = writeln!(out, "# TYPE kitsune_bootstrap_{} gauge", name);
+        let _ = writeln!(out, "kitsune_bootstrap_{} {}", name, value);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use fixt::prelude::*;
+    use kitsune_p2p::{agent_store::AgentInfoSigned, fixt::*, KitsuneSpace};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_metrics() {
+        let store = Store::new(vec![]);
+        let space: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace));
+        for _ in 0..3 {
+            let info = AgentInfoSigned::sign(
+                space.clone(),
+                Arc::new(fixt!(KitsuneAgent, Unpredictable)),
+                u32::MAX / 4,
+                fixt!(UrlList, Empty),
+                0,
+                std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 + 60_000_000,
+                |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+            )
+            .await
+            .unwrap();
+            store.put(info).await.unwrap();
+        }
+
+        let res = warp::test::request()
+            .method("GET")
+            .path("/metrics")
+            .reply(&super::metrics(store))
+            .await;
+        assert_eq!(res.status(), 200);
+        let body = String::from_utf8(res.body().to_vec()).unwrap();
+        assert!(body.contains("kitsune_bootstrap_spaces 1\n"));
+        assert!(body.contains("kitsune_bootstrap_agents 3\n"));
+        assert!(body.contains("kitsune_bootstrap_put_total "));
+    }
+}
@@ -0,0 +1,188 @@
+use std::{path::Path, sync::Arc};
+
+use kitsune_p2p_types::{agent_info::AgentInfoSigned, bin_types::KitsuneSpace};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS agent_info (
+    space           BLOB    NOT NULL,
+    agent           BLOB    NOT NULL,
+    signed_at_ms    INTEGER NOT NULL,
+    expires_at_ms   INTEGER NOT NULL,
+    encoded         BLOB    NOT NULL,
+    PRIMARY KEY (space, agent)
+);
+CREATE INDEX IF NOT EXISTS agent_info_expires_at_ms ON agent_info (expires_at_ms);
+CREATE TABLE IF NOT EXISTS banned_space (
+    space           BLOB    PRIMARY KEY
+);
+";
+
+/// Sqlite backing for the [`Store`](crate::store::Store) so agent infos
+/// and banned spaces survive a restart of the server.
+/// The in memory maps are still what requests are served from,
+/// every mutation is simply written through to here as well.
+#[derive(Debug)]
+pub(crate) struct Persist(Mutex<Connection>);
+
+impl Persist {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(path).map_err(|e| format!("{:?}", e))?;
+        conn.execute_batch(SCHEMA).map_err(|e| format!("{:?}", e))?;
+        Ok(Self(Mutex::new(conn)))
+    }
+
+    /// Load everything that was persisted. Agent infos that
+    /// no longer decode are skipped.
+    pub fn load(&self) -> Result<(Vec<AgentInfoSigned>, Vec<Arc<KitsuneSpace>>), String> {
+        let conn = self.0.lock();
+        let infos = conn
+            .prepare("SELECT encoded FROM agent_info")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get::<_, Vec<u8>>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .map_err(|e| format!("{:?}", e))?
+            .into_iter()
+            .filter_map(|bytes| AgentInfoSigned::decode(&bytes).ok())
+            .collect();
+        let banned = conn
+            .prepare("SELECT space FROM banned_space")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get::<_, Vec<u8>>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .map_err(|e| format!("{:?}", e))?
+            .into_iter()
+            .map(|bytes| Arc::new(KitsuneSpace(bytes)))
+            .collect();
+        Ok((infos, banned))
+    }
+
+    /// Store an agent info unless a newer one for the same agent
+    /// has already been written.
+    pub fn put(&self, info: &AgentInfoSigned) -> Result<(), String> {
+        let encoded = info.encode().map_err(|e| format!("{:?}", e))?;
+        self.0
+            .lock()
+            .execute(
+                "INSERT INTO agent_info
+                (space, agent, signed_at_ms, expires_at_ms, encoded)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT (space, agent) DO UPDATE SET
+                signed_at_ms = excluded.signed_at_ms,
+                expires_at_ms = excluded.expires_at_ms,
+                encoded = excluded.encoded
+                WHERE excluded.signed_at_ms > agent_info.signed_at_ms",
+                params![
+                    &info.space.0,
+                    &info.agent.0,
+                    info.signed_at_ms as i64,
+                    info.expires_at_ms as i64,
+                    &encoded[..],
+                ],
+            )
+            .map_err(|e| format!("{:?}", e))?;
+        Ok(())
+    }
+
+    pub fn prune(&self, now: u64) -> Result<(), String> {
+        self.0
+            .lock()
+            .execute(
+                "DELETE FROM agent_info WHERE expires_at_ms < ?1",
+                params![now as i64],
+            )
+            .map_err(|e| format!("{:?}", e))?;
+        Ok(())
+    }
+
+    /// Ban a space and drop every agent info stored for it.
+    pub fn ban(&self, space: &KitsuneSpace) -> Result<(), String> {
+        let mut conn = self.0.lock();
+        let txn = conn.transaction().map_err(|e| format!("{:?}", e))?;
+        txn.execute("DELETE FROM agent_info WHERE space = ?1", params![&space.0])
+            .map_err(|e| format!("{:?}", e))?;
+        txn.execute(
+            "INSERT OR IGNORE INTO banned_space (space) VALUES (?1)",
+            params![&space.0],
+        )
+        .map_err(|e| format!("{:?}", e))?;
+        txn.commit().map_err(|e| format!("{:?}", e))
+    }
+
+    pub fn unban(&self, space: &KitsuneSpace) -> Result<(), String> {
+        self.0
+            .lock()
+            .execute(
+                "DELETE FROM banned_space WHERE space = ?1",
+                params![&space.0],
+            )
+            .map_err(|e| format!("{:?}", e))?;
+        Ok(())
+    }
+
+    /// Drop all agent infos. Bans are kept.
+    pub fn clear(&self) -> Result<(), String> {
+        self.0
+            .lock()
+            .execute("DELETE FROM agent_info", [])
+            .map_err(|e| format!("{:?}", e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{store::Store, BootstrapConfig};
+    use fixt::prelude::*;
+    use kitsune_p2p::fixt::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_store_survives_restart() {
+        let path = std::env::temp_dir().join(format!(
+            "kitsune_bootstrap_{}.sqlite3",
+            rand::random::<u64>()
+        ));
+        let config = BootstrapConfig {
+            database: Some(path.clone()),
+            ..Default::default()
+        };
+        let space: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+        let banned: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+        let info = AgentInfoSigned::sign(
+            space.clone(),
+            Arc::new(fixt!(KitsuneAgent, Unpredictable)),
+            u32::MAX / 4,
+            fixt!(UrlList, Empty),
+            0,
+            std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 + 60_000_000,
+            |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+        )
+        .await
+        .unwrap();
+
+        {
+            let store = Store::with_config(&config).unwrap();
+            store.put(info.clone()).await.unwrap();
+            store.ban(banned.clone()).unwrap();
+        }
+
+        let store = Store::with_config(&config).unwrap();
+        assert_eq!(
+            *store
+                .all()
+                .get(space.as_ref())
+                .unwrap()
+                .get(info.agent.as_ref())
+                .unwrap(),
+            info
+        );
+        assert!(store.is_banned(&banned));
+
+        drop(store);
+        let _ = std::fs::remove_file(path);
+    }
+}
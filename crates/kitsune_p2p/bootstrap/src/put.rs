@@ -1,7 +1,15 @@
+use std::convert::TryInto;
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
 use crate::store::Store;
 
 use super::*;
 use kitsune_p2p_types::agent_info::AgentInfoSigned;
+use kitsune_p2p_types::bin_types::KitsuneBinType;
+use kitsune_p2p_types::dependencies::lair_keystore_api::prelude::BinDataSized;
+use warp::http::StatusCode;
 use warp::Filter;
 
 pub(crate) fn put(
@@ -11,38 +19,81 @@ pub(crate) fn put(
         .and(warp::header::exact("X-Op", "put"))
         .and(warp::body::content_length_limit(SIZE_LIMIT))
         .and(warp::body::bytes())
+        .and(warp::addr::remote())
         .and(with_store(store))
         .and_then(put_info)
 }
 
-async fn put_info(peer: Bytes, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+async fn put_info(
+    peer: Bytes,
+    remote: Option<SocketAddr>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
     let peer: AgentInfoSigned =
         rmp_decode(&mut AsRef::<[u8]>::as_ref(&peer)).map_err(|_| warp::reject())?;
-    // TODO: Return rejection if agent info was invalid?
-    if valid(&peer) {
-        store.put(peer);
+    PUT.fetch_add(1, Ordering::Relaxed);
+    if store.is_banned(&peer.space) {
+        return Ok(error(StatusCode::FORBIDDEN, "space is banned"));
+    }
+    if !store.quota().check_ip(remote.map(|addr| addr.ip())) {
+        PUT_RATE_LIMITED.fetch_add(1, Ordering::Relaxed);
+        return Ok(error(StatusCode::TOO_MANY_REQUESTS, "put quota exceeded"));
+    }
+    if !valid(&peer, store.verify_signatures()).await {
+        PUT_INVALID.fetch_add(1, Ordering::Relaxed);
+        return Ok(error(
+            StatusCode::BAD_REQUEST,
+            "agent info is expired or has an invalid signature",
+        ));
+    }
+    if !store.quota().check_space(&peer.space) {
+        PUT_RATE_LIMITED.fetch_add(1, Ordering::Relaxed);
+        return Ok(error(StatusCode::TOO_MANY_REQUESTS, "put quota exceeded"));
+    }
+    if let Err(e) = store.put(peer).await {
+        return Ok(error(StatusCode::INTERNAL_SERVER_ERROR, &e));
     }
-    PUT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
     let mut buf = Vec::with_capacity(1);
     rmp_encode(&mut buf, ()).map_err(|_| warp::reject())?;
-    Ok(buf)
+    Ok(warp::reply::with_status(buf, StatusCode::OK))
+}
+
+/// Error responses carry a plain text reason in the body.
+fn error(status: StatusCode, reason: &str) -> warp::reply::WithStatus<Vec<u8>> {
+    warp::reply::with_status(reason.as_bytes().to_vec(), status)
 }
 
-fn valid(peer: &AgentInfoSigned) -> bool {
-    // TODO: verify signature
+pub(crate) async fn valid(peer: &AgentInfoSigned, verify_signatures: bool) -> bool {
     // Verify time
-    peer.expires_at_ms as u128
+    let not_expired = peer.expires_at_ms as u128
         > std::time::UNIX_EPOCH
             .elapsed()
             .expect("Bootstrap system clock is set before the epoch")
-            .as_millis()
+            .as_millis();
+    not_expired && (!verify_signatures || verify_signature(peer).await)
+}
+
+/// The agent is the ed25519 public key the info was signed with.
+async fn verify_signature(peer: &AgentInfoSigned) -> bool {
+    let pub_key: [u8; 32] = match peer.agent.get_bytes().try_into() {
+        Ok(pub_key) => pub_key,
+        Err(_) => return false,
+    };
+    let sig: [u8; 64] = match peer.signature.0.as_slice().try_into() {
+        Ok(sig) => sig,
+        Err(_) => return false,
+    };
+    let data: Arc<[u8]> = peer.encoded_bytes.to_vec().into();
+    BinDataSized::<32>::from(pub_key)
+        .verify_detached(sig.into(), data)
+        .await
+        .unwrap_or(false)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
-
     use super::*;
+    use crate::test_util::signed_info;
     use fixt::prelude::*;
     use kitsune_p2p::fixt::*;
 
@@ -51,17 +102,12 @@ mod tests {
         let store = Store::new(vec![]);
         let filter = put(store.clone());
 
-        let info = AgentInfoSigned::sign(
+        let info = signed_info(
             Arc::new(fixt!(KitsuneSpace, Unpredictable)),
-            Arc::new(fixt!(KitsuneAgent, Unpredictable)),
-            u32::MAX / 4,
             fixt!(UrlList, Empty),
-            0,
             std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 + 60_000_000,
-            |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
         )
-        .await
-        .unwrap();
+        .await;
         let mut buf = Vec::new();
         rmp_encode(&mut buf, info.clone()).unwrap();
 
@@ -83,4 +129,134 @@ mod tests {
             info
         );
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_put_rejects_bad_signature() {
+        let store = Store::new(vec![]);
+        let filter = put(store.clone());
+
+        let info = AgentInfoSigned::sign(
+            Arc::new(fixt!(KitsuneSpace, Unpredictable)),
+            Arc::new(fixt!(KitsuneAgent, Unpredictable)),
+            u32::MAX / 4,
+            fixt!(UrlList, Empty),
+            0,
+            std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 + 60_000_000,
+            |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+        )
+        .await
+        .unwrap();
+        let mut buf = Vec::new();
+        rmp_encode(&mut buf, info).unwrap();
+
+        let res = warp::test::request()
+            .method("POST")
+            .header("Content-type", "application/octet")
+            .header("X-Op", "put")
+            .body(buf)
+            .reply(&filter)
+            .await;
+        assert_eq!(res.status(), 400);
+        assert!(store.all().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_forged_puts_dont_use_space_quota() {
+        let store = Store::with_config(&BootstrapConfig {
+            put_quota_per_space: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+        let filter = put(store.clone());
+        let space = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+        let expires_at_ms =
+            std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 + 60_000_000;
+
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let forged = AgentInfoSigned::sign(
+                space.clone(),
+                Arc::new(fixt!(KitsuneAgent, Unpredictable)),
+                u32::MAX / 4,
+                fixt!(UrlList, Empty),
+                0,
+                expires_at_ms,
+                |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+            )
+            .await
+            .unwrap();
+            let mut buf = Vec::new();
+            rmp_encode(&mut buf, forged).unwrap();
+            let res = warp::test::request()
+                .method("POST")
+                .header("Content-type", "application/octet")
+                .header("X-Op", "put")
+                .body(buf)
+                .reply(&filter)
+                .await;
+            statuses.push(res.status());
+        }
+        for _ in 0..2 {
+            let info = signed_info(space.clone(), fixt!(UrlList, Empty), expires_at_ms).await;
+            let mut buf = Vec::new();
+            rmp_encode(&mut buf, info).unwrap();
+            let res = warp::test::request()
+                .method("POST")
+                .header("Content-type", "application/octet")
+                .header("X-Op", "put")
+                .body(buf)
+                .reply(&filter)
+                .await;
+            statuses.push(res.status());
+        }
+        // The space's quota is only used by the first valid put.
+        assert_eq!(statuses, vec![400, 400, 400, 200, 429]);
+        assert_eq!(store.all().get(&space).unwrap().len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_put_quota_and_ban() {
+        let store = Store::with_config(&BootstrapConfig {
+            put_quota_per_ip: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+        let filter = put(store.clone());
+        let space = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+        let expires_at_ms =
+            std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 + 60_000_000;
+
+        let mut statuses = Vec::new();
+        for ip in [[10, 0, 0, 1], [10, 0, 0, 1], [10, 0, 0, 2]] {
+            let info = signed_info(space.clone(), fixt!(UrlList, Empty), expires_at_ms).await;
+            let mut buf = Vec::new();
+            rmp_encode(&mut buf, info).unwrap();
+            let res = warp::test::request()
+                .method("POST")
+                .header("Content-type", "application/octet")
+                .header("X-Op", "put")
+                .remote_addr((ip, 5000).into())
+                .body(buf)
+                .reply(&filter)
+                .await;
+            statuses.push(res.status());
+        }
+        assert_eq!(statuses, vec![200, 429, 200]);
+        assert_eq!(store.all().get(&space).unwrap().len(), 2);
+
+        store.ban(space.clone()).unwrap();
+        assert!(store.all().is_empty());
+        let info = signed_info(space.clone(), fixt!(UrlList, Empty), expires_at_ms).await;
+        let mut buf = Vec::new();
+        rmp_encode(&mut buf, info).unwrap();
+        let res = warp::test::request()
+            .method("POST")
+            .header("Content-type", "application/octet")
+            .header("X-Op", "put")
+            .remote_addr(([10, 0, 0, 3], 5000).into())
+            .body(buf)
+            .reply(&filter)
+            .await;
+        assert_eq!(res.status(), 403);
+    }
 }
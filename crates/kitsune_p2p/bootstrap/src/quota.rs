@@ -0,0 +1,134 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    net::IpAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use kitsune_p2p_types::bin_types::KitsuneSpace;
+use parking_lot::Mutex;
+
+/// The window the put quotas are counted over.
+pub const QUOTA_WINDOW: Duration = Duration::from_secs(60);
+
+/// Fixed window counters limiting how many puts are accepted
+/// per space and per remote ip in each [`QUOTA_WINDOW`].
+#[derive(Debug, Default)]
+pub(crate) struct PutQuota {
+    per_space: Option<u32>,
+    per_ip: Option<u32>,
+    spaces: Mutex<HashMap<Arc<KitsuneSpace>, Window>>,
+    ips: Mutex<HashMap<IpAddr, Window>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    started: Instant,
+    count: u32,
+}
+
+impl PutQuota {
+    pub fn new(per_space: Option<u32>, per_ip: Option<u32>) -> Self {
+        Self {
+            per_space,
+            per_ip,
+            ..Default::default()
+        }
+    }
+
+    /// Count a put from this ip against its quota. Returns false without
+    /// counting it if the ip is already at its limit.
+    /// This is checked before the put's signature so flooding the server
+    /// with bad signatures can't make it do unbounded crypto work.
+    pub fn check_ip(&self, ip: Option<IpAddr>) -> bool {
+        match (self.per_ip, ip) {
+            (Some(limit), Some(ip)) => check(&mut self.ips.lock(), ip, limit),
+            _ => true,
+        }
+    }
+
+    /// Count a put to this space against its quota. Returns false without
+    /// counting it if the space is already at its limit.
+    /// This is checked after the put's signature so puts with forged
+    /// signatures can't use up the quota of a space they aren't in.
+    pub fn check_space(&self, space: &Arc<KitsuneSpace>) -> bool {
+        match self.per_space {
+            Some(limit) => check(&mut self.spaces.lock(), space.clone(), limit),
+            None => true,
+        }
+    }
+
+    /// Forget windows that have already ended.
+    pub fn prune(&self) {
+        let now = Instant::now();
+        self.spaces
+            .lock()
+            .retain(|_, w| now.duration_since(w.started) < QUOTA_WINDOW);
+        self.ips
+            .lock()
+            .retain(|_, w| now.duration_since(w.started) < QUOTA_WINDOW);
+    }
+}
+
+/// Count against the window for this key if it's under the limit.
+fn check<K: Hash + Eq>(map: &mut HashMap<K, Window>, key: K, limit: u32) -> bool {
+    let now = Instant::now();
+    if current(map, &key, now) >= limit {
+        return false;
+    }
+    increment(map, key, now);
+    true
+}
+
+fn current<K: Hash + Eq>(map: &HashMap<K, Window>, key: &K, now: Instant) -> u32 {
+    match map.get(key) {
+        Some(w) if now.duration_since(w.started) < QUOTA_WINDOW => w.count,
+        _ => 0,
+    }
+}
+
+fn increment<K: Hash + Eq>(map: &mut HashMap<K, Window>, key: K, now: Instant) {
+    let w = map.entry(key).or_insert(Window {
+        started: now,
+        count: 0,
+    });
+    if now.duration_since(w.started) >= QUOTA_WINDOW {
+        *w = Window {
+            started: now,
+            count: 0,
+        };
+    }
+    w.count += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_quota() {
+        let quota = PutQuota::new(Some(3), Some(2));
+        let a = Arc::new(KitsuneSpace(vec![0; 36]));
+        let b = Arc::new(KitsuneSpace(vec![1; 36]));
+        let ip_1: IpAddr = [127, 0, 0, 1].into();
+        let ip_2: IpAddr = [127, 0, 0, 2].into();
+
+        assert!(quota.check_ip(Some(ip_1)));
+        assert!(quota.check_ip(Some(ip_1)));
+        // ip 1 is at its limit.
+        assert!(!quota.check_ip(Some(ip_1)));
+        assert!(quota.check_ip(Some(ip_2)));
+        // Puts without a remote address have no ip quota.
+        assert!(quota.check_ip(None));
+        assert!(quota.check_ip(None));
+        assert!(quota.check_ip(None));
+
+        assert!(quota.check_space(&a));
+        assert!(quota.check_space(&a));
+        assert!(quota.check_space(&a));
+        // space a is at its limit.
+        assert!(!quota.check_space(&a));
+        assert!(quota.check_space(&b));
+    }
+}
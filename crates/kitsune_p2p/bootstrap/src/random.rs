@@ -30,6 +30,7 @@ mod tests {
     use std::sync::Arc;
 
     use super::*;
+    use crate::test_util::signed_info;
     use fixt::prelude::*;
     use kitsune_p2p::{agent_store::AgentInfoSigned, fixt::*, KitsuneSpace};
     use kitsune_p2p_types::bootstrap::RandomLimit;
@@ -59,17 +60,12 @@ mod tests {
         let space: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace));
         let mut peers = Vec::new();
         for _ in 0..20 {
-            let info = AgentInfoSigned::sign(
+            let info = signed_info(
                 space.clone(),
-                Arc::new(fixt!(KitsuneAgent, Unpredictable)),
-                u32::MAX / 4,
                 vec!["fake:".into()],
-                0,
                 std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 + 60_000_000,
-                |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
             )
-            .await
-            .unwrap();
+            .await;
             peers.push(info);
         }
         put(store.clone(), peers.clone()).await;
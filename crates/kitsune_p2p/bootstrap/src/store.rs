@@ -1,4 +1,7 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use kitsune_p2p_types::{
     agent_info::AgentInfoSigned,
@@ -6,22 +9,102 @@ use kitsune_p2p_types::{
     bootstrap::RandomQuery,
     codec::rmp_encode,
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use rand::seq::IteratorRandom;
 
+use crate::{federate::MAX_FEDERATION_HOPS, persist::Persist, quota::PutQuota, BootstrapConfig};
+
 type AgentMap = HashMap<Arc<KitsuneAgent>, AgentInfoSigned>;
 type SpaceMap = HashMap<Arc<KitsuneSpace>, AgentMap>;
 
 #[derive(Clone, Debug)]
-pub(crate) struct Store(Arc<RwLock<SpaceMap>>, Arc<Vec<String>>);
+pub(crate) struct Store {
+    spaces: Arc<RwLock<SpaceMap>>,
+    banned: Arc<RwLock<HashSet<Arc<KitsuneSpace>>>>,
+    proxy_list: Arc<Vec<String>>,
+    persist: Option<Arc<Persist>>,
+    quota: Arc<PutQuota>,
+    admin_token: Option<Arc<str>>,
+    federation_secret: Option<Arc<str>>,
+    verify_signatures: bool,
+    /// Agent infos waiting to be pushed to the federation peers
+    /// with the number of hops they already travelled.
+    /// `None` if this server isn't federated.
+    outbox: Option<Arc<Mutex<Vec<(AgentInfoSigned, u8)>>>>,
+}
 
 impl Store {
     pub fn new(proxy_list: Vec<String>) -> Self {
-        Self(Arc::new(RwLock::new(HashMap::new())), Arc::new(proxy_list))
+        Self {
+            spaces: Arc::new(RwLock::new(HashMap::new())),
+            banned: Arc::new(RwLock::new(HashSet::new())),
+            proxy_list: Arc::new(proxy_list),
+            persist: None,
+            quota: Arc::new(PutQuota::default()),
+            admin_token: None,
+            federation_secret: None,
+            verify_signatures: true,
+            outbox: None,
+        }
+    }
+
+    /// Create a store from the server config, loading any
+    /// agent infos and bans persisted by a previous run.
+    pub fn with_config(config: &BootstrapConfig) -> Result<Self, String> {
+        let mut store = Self::new(config.proxy_list.clone());
+        store.quota = Arc::new(PutQuota::new(
+            config.put_quota_per_space,
+            config.put_quota_per_ip,
+        ));
+        store.admin_token = config.admin_token.as_deref().map(Arc::from);
+        store.federation_secret = config.federation_secret.as_deref().map(Arc::from);
+        store.verify_signatures = config.verify_signatures;
+        if !config.federation_peers.is_empty() {
+            if config.federation_secret.is_none() {
+                return Err("federation peers need a federation secret".to_string());
+            }
+            store.outbox = Some(Arc::new(Mutex::new(Vec::new())));
+        }
+        if let Some(path) = &config.database {
+            let persist = Persist::open(path)?;
+            let (infos, banned) = persist.load()?;
+            store.banned.write().extend(banned);
+            {
+                let mut lock = store.spaces.write();
+                for info in infos {
+                    lock.entry(info.space.clone())
+                        .or_insert_with(HashMap::new)
+                        .insert(info.agent.clone(), info);
+                }
+            }
+            store.persist = Some(Arc::new(persist));
+            store.prune();
+        }
+        Ok(store)
     }
 
     pub fn proxy_list(&self) -> Arc<Vec<String>> {
-        self.1.clone()
+        self.proxy_list.clone()
+    }
+
+    pub fn verify_signatures(&self) -> bool {
+        self.verify_signatures
+    }
+
+    pub fn quota(&self) -> &PutQuota {
+        &self.quota
+    }
+
+    /// Does this token match the configured admin token?
+    /// Always false if no admin token is configured.
+    pub fn is_admin(&self, token: Option<&str>) -> bool {
+        secret_matches(self.admin_token.as_deref(), token)
+    }
+
+    /// Does this secret match the configured federation secret?
+    /// Always false if no federation secret is configured.
+    pub fn is_federation_peer(&self, secret: Option<&str>) -> bool {
+        secret_matches(self.federation_secret.as_deref(), secret)
     }
 
     pub fn prune(&self) {
@@ -30,25 +113,86 @@ impl Store {
             .expect("Bootstrap server time set before epoch")
             .as_millis() as u64;
 
-        self.0.write().retain(|_, map| {
+        self.spaces.write().retain(|_, map| {
             map.retain(|_, info| info.expires_at_ms >= now);
             !map.is_empty()
         });
+        if let Some(persist) = &self.persist {
+            // The in memory store is already pruned and the next
+            // prune will try again.
+            let _ = persist.prune(now);
+        }
+        self.quota.prune();
     }
 
-    pub fn put(&self, info: AgentInfoSigned) {
-        let mut lock = self.0.write();
-        let space_map = lock.entry(info.space.clone()).or_insert_with(HashMap::new);
-        match space_map.entry(info.agent.clone()) {
-            std::collections::hash_map::Entry::Occupied(mut e) => {
-                if info.signed_at_ms > e.get().signed_at_ms {
-                    e.insert(info);
+    pub async fn put(&self, info: AgentInfoSigned) -> Result<(), String> {
+        self.insert(info, 0).await
+    }
+
+    /// Put an agent info received from a federation peer
+    /// after travelling `hops` hops.
+    pub async fn put_federated(&self, info: AgentInfoSigned, hops: u8) -> Result<(), String> {
+        self.insert(info, hops).await
+    }
+
+    /// Only agent infos newer than the one already stored are kept and
+    /// passed on to the federation peers, which stops them going round
+    /// in loops between servers.
+    /// The info is written to the database before the in memory map is
+    /// updated, without holding the store lock so other requests aren't
+    /// blocked on the disk. An info that fails to persist is never served.
+    async fn insert(&self, info: AgentInfoSigned, hops: u8) -> Result<(), String> {
+        if !self.is_newer(&info) {
+            return Ok(());
+        }
+        if let Some(persist) = &self.persist {
+            // The database also only keeps the newest info for an agent
+            // so it can't go back to an older one if puts race.
+            let persist = persist.clone();
+            let info = info.clone();
+            tokio::task::spawn_blocking(move || persist.put(&info))
+                .await
+                .map_err(|e| format!("{:?}", e))??;
+        }
+        {
+            let mut lock = self.spaces.write();
+            let space_map = lock.entry(info.space.clone()).or_insert_with(HashMap::new);
+            match space_map.entry(info.agent.clone()) {
+                std::collections::hash_map::Entry::Occupied(mut e) => {
+                    // A newer info may have been put while this one was persisted.
+                    if info.signed_at_ms <= e.get().signed_at_ms {
+                        return Ok(());
+                    }
+                    e.insert(info.clone());
+                }
+                std::collections::hash_map::Entry::Vacant(e) => {
+                    e.insert(info.clone());
                 }
             }
-            std::collections::hash_map::Entry::Vacant(e) => {
-                e.insert(info);
+        }
+        if let Some(outbox) = &self.outbox {
+            if hops < MAX_FEDERATION_HOPS {
+                outbox.lock().push((info, hops));
             }
         }
+        Ok(())
+    }
+
+    /// Is this info newer than the one stored for its agent?
+    fn is_newer(&self, info: &AgentInfoSigned) -> bool {
+        self.spaces
+            .read()
+            .get(&info.space)
+            .and_then(|space| space.get(&info.agent))
+            .map_or(true, |stored| info.signed_at_ms > stored.signed_at_ms)
+    }
+
+    /// Take all agent infos waiting to be pushed to the federation peers.
+    pub fn take_outbox(&self) -> Vec<(AgentInfoSigned, u8)> {
+        match &self.outbox {
+            Some(outbox) => std::mem::take(&mut *outbox.lock()),
+            None => Vec::new(),
+        }
     }
 
     pub fn random(&self, query: RandomQuery) -> Vec<Vec<u8>> {
@@ -59,7 +203,7 @@ impl Store {
             .elapsed()
             .expect("Bootstrap server time set before epoch")
             .as_millis() as u64;
-        self.0
+        self.spaces
             .read()
             .get(query.space.as_ref())
             .map(|space| {
@@ -83,12 +227,66 @@ impl Store {
             .unwrap_or_default()
     }
 
-    pub fn clear(&self) {
-        self.0.write().clear()
+    pub fn clear(&self) -> Result<(), String> {
+        if let Some(persist) = &self.persist {
+            persist.clear()?;
+        }
+        self.spaces.write().clear();
+        Ok(())
+    }
+
+    pub fn is_banned(&self, space: &KitsuneSpace) -> bool {
+        self.banned.read().contains(space)
+    }
+
+    /// Ban a space, dropping all its agent infos and refusing
+    /// any new ones until it is unbanned.
+    pub fn ban(&self, space: Arc<KitsuneSpace>) -> Result<(), String> {
+        if let Some(persist) = &self.persist {
+            persist.ban(&space)?;
+        }
+        self.spaces.write().remove(&space);
+        self.banned.write().insert(space);
+        Ok(())
+    }
+
+    pub fn unban(&self, space: &KitsuneSpace) -> Result<(), String> {
+        if let Some(persist) = &self.persist {
+            persist.unban(space)?;
+        }
+        self.banned.write().remove(space);
+        Ok(())
+    }
+
+    /// The number of spaces and agents currently stored.
+    pub fn counts(&self) -> (usize, usize) {
+        let lock = self.spaces.read();
+        (lock.len(), lock.values().map(|space| space.len()).sum())
+    }
+
+    pub fn banned_count(&self) -> usize {
+        self.banned.read().len()
     }
 
     #[cfg(test)]
     pub fn all(&self) -> HashMap<Arc<KitsuneSpace>, HashMap<Arc<KitsuneAgent>, AgentInfoSigned>> {
-        self.0.read().clone()
+        self.spaces.read().clone()
+    }
+}
+
+/// The comparison takes the same time wherever the secrets differ
+/// so a secret can't be guessed a byte at a time.
+fn secret_matches(expected: Option<&str>, secret: Option<&str>) -> bool {
+    match (expected, secret) {
+        (Some(expected), Some(secret)) => {
+            let (expected, secret) = (expected.as_bytes(), secret.as_bytes());
+            expected.len() == secret.len()
+                && expected
+                    .iter()
+                    .zip(secret)
+                    .fold(0, |diff, (a, b)| diff | (a ^ b))
+                    == 0
+        }
+        _ => false,
     }
 }
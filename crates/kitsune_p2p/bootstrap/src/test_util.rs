@@ -0,0 +1,41 @@
+//! Helpers shared by the unit tests and the integration tests.
+
+use std::sync::Arc;
+
+use kitsune_p2p_types::{
+    agent_info::{AgentInfoSigned, UrlList},
+    bin_types::{KitsuneAgent, KitsuneBinType, KitsuneSignature, KitsuneSpace},
+    dependencies::lair_keystore_api_0_0::internal::sign_ed25519::sign_ed25519_keypair_new_from_entropy,
+    KitsuneError,
+};
+
+/// Agent info for a fresh agent with a real signature, as the put
+/// endpoint rejects anything that doesn't verify.
+pub(crate) async fn signed_info(
+    space: Arc<KitsuneSpace>,
+    url_list: UrlList,
+    expires_at_ms: u64,
+) -> AgentInfoSigned {
+    let keypair = sign_ed25519_keypair_new_from_entropy().await.unwrap();
+    let agent = KitsuneAgent::new((*keypair.pub_key.0).clone());
+    AgentInfoSigned::sign(
+        space,
+        Arc::new(agent),
+        u32::MAX / 4,
+        url_list,
+        0,
+        expires_at_ms,
+        |d| {
+            let d = Arc::new(d.to_vec());
+            async move {
+                keypair
+                    .sign(d)
+                    .await
+                    .map(|s| Arc::new(KitsuneSignature(s.0.to_vec())))
+                    .map_err(KitsuneError::other)
+            }
+        },
+    )
+    .await
+    .unwrap()
+}
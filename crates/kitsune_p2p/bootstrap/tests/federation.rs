@@ -0,0 +1,110 @@
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use fixt::prelude::*;
+use kitsune_p2p::agent_store::AgentInfoSigned;
+use kitsune_p2p::fixt::*;
+use kitsune_p2p::KitsuneSpace;
+use kitsune_p2p_types::bootstrap::{RandomLimit, RandomQuery};
+use tokio::io::AsyncBufReadExt;
+use tokio::process::{Child, Command};
+
+#[path = "../src/test_util.rs"]
+mod test_util;
+use test_util::signed_info;
+
+/// Agents registering on two federated bootstrap servers,
+/// each running in its own process, discover each other.
+#[tokio::test(flavor = "multi_thread")]
+async fn federated_servers_share_agents() {
+    let port_a = free_port();
+    let port_b = free_port();
+    let _a = spawn_bootstrap(port_a, port_b).await;
+    let _b = spawn_bootstrap(port_b, port_a).await;
+    let url_a = format!("http://127.0.0.1:{}", port_a);
+    let url_b = format!("http://127.0.0.1:{}", port_b);
+
+    let client = reqwest::Client::new();
+    let space = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+    let expires_at_ms = std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 + 60_000_000;
+    let alice = signed_info(space.clone(), vec!["fake:".into()], expires_at_ms).await;
+    let bob = signed_info(space.clone(), vec!["fake:".into()], expires_at_ms).await;
+    let _: () = do_api(&url_a, "put", alice.clone(), &client).await;
+    let _: () = do_api(&url_b, "put", bob.clone(), &client).await;
+
+    let query = RandomQuery {
+        space,
+        limit: RandomLimit(10),
+    };
+    for url in [&url_a, &url_b] {
+        let mut found = Vec::new();
+        for _ in 0..100 {
+            let peers: Vec<serde_bytes::ByteBuf> =
+                do_api(url, "random", query.clone(), &client).await;
+            found = peers
+                .into_iter()
+                .map(|bytes| AgentInfoSigned::decode(&bytes).unwrap())
+                .collect();
+            if found.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(found.contains(&alice), "{} is missing alice", url);
+        assert!(found.contains(&bob), "{} is missing bob", url);
+    }
+}
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+async fn spawn_bootstrap(port: u16, peer_port: u16) -> Child {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_kitsune-bootstrap"))
+        .arg("--interface")
+        .arg(format!("127.0.0.1:{}", port))
+        .arg("--federation-peer")
+        .arg(format!("http://127.0.0.1:{}", peer_port))
+        .arg("--federation-secret")
+        .arg("secret")
+        .arg("--federation-interval-ms")
+        .arg("100")
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+    // The server prints its url once it's listening.
+    let stdout = child.stdout.take().unwrap();
+    tokio::io::BufReader::new(stdout)
+        .lines()
+        .next_line()
+        .await
+        .unwrap()
+        .unwrap();
+    child
+}
+
+async fn do_api<I: serde::Serialize, O: serde::de::DeserializeOwned>(
+    url: &str,
+    op: &str,
+    input: I,
+    client: &reqwest::Client,
+) -> O {
+    let mut body_data = Vec::new();
+    kitsune_p2p_types::codec::rmp_encode(&mut body_data, &input).unwrap();
+    let res = client
+        .post(url)
+        .body(body_data)
+        .header("X-Op", op)
+        .header(reqwest::header::CONTENT_TYPE, "application/octet")
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success(), "{} failed", op);
+    kitsune_p2p_types::codec::rmp_decode(&mut res.bytes().await.unwrap().as_ref()).unwrap()
+}
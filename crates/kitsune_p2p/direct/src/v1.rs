@@ -140,7 +140,7 @@ pub fn new_kitsune_direct_v1(
         let mut sub_config = KitsuneP2pConfig::default();
         sub_config.tuning_params = tuning_params.clone();
 
-        sub_config.bootstrap_service = Some(bootstrap.into());
+        sub_config.bootstrap_service = Some(url2::Url2::from(bootstrap).into());
 
         sub_config.transport_pool.push(TransportConfig::Proxy {
             sub_transport: Box::new(TransportConfig::Quic {
@@ -14,8 +14,14 @@ pub const BOOTSTRAP_SERVICE_DEV: &str = "https://bootstrap-dev.holohost.workers.
 
 pub(crate) enum KitsuneP2pTx2Backend {
     Mem,
-    Quic { bind_to: TxUrl },
-    Mock { mock_network: AdapterFactory },
+    Quic {
+        bind_to: TxUrl,
+        override_host: Option<String>,
+        override_port: Option<u16>,
+    },
+    Mock {
+        mock_network: AdapterFactory,
+    },
 }
 
 pub(crate) enum KitsuneP2pTx2ProxyConfig {
@@ -40,7 +46,8 @@ pub struct KitsuneP2pConfig {
     /// List of sub-transports to be included in this pool
     pub transport_pool: Vec<TransportConfig>,
     /// The service used for peers to discover each before they are peers.
-    pub bootstrap_service: Option<Url2>,
+    /// Either a single url or a list of urls to fail over between.
+    pub bootstrap_service: Option<BootstrapService>,
     /// Network tuning parameters. These are managed loosely,
     /// as they are subject to change. If you specify a tuning parameter
     /// that no longer exists, or a value that does not parse,
@@ -62,6 +69,76 @@ impl Default for KitsuneP2pConfig {
     }
 }
 
+/// One or more urls of a bootstrap service.
+///
+/// Requests go to the first url and fail over to the following ones, in
+/// order, if a service can't be reached or has an internal error.
+/// In the config this is either a single url or a list of urls.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BootstrapService(Vec<Url2>);
+
+impl BootstrapService {
+    /// Bootstrap service urls in the order they are tried.
+    /// There must be at least one url.
+    pub fn new(urls: Vec<Url2>) -> KitsuneResult<Self> {
+        if urls.is_empty() {
+            return Err("bootstrap_service needs at least one url".into());
+        }
+        Ok(Self(urls))
+    }
+
+    /// The urls in the order they are tried.
+    pub fn urls(&self) -> &[Url2] {
+        &self.0
+    }
+}
+
+impl From<Url2> for BootstrapService {
+    fn from(url: Url2) -> Self {
+        Self(vec![url])
+    }
+}
+
+impl TryFrom<Vec<Url2>> for BootstrapService {
+    type Error = KitsuneError;
+
+    fn try_from(urls: Vec<Url2>) -> KitsuneResult<Self> {
+        Self::new(urls)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum BootstrapServiceEncode {
+    Single(Url2),
+    List(Vec<Url2>),
+}
+
+impl serde::Serialize for BootstrapService {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.0.as_slice() {
+            [url] => BootstrapServiceEncode::Single(url.clone()),
+            urls => BootstrapServiceEncode::List(urls.to_vec()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for BootstrapService {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match BootstrapServiceEncode::deserialize(deserializer)? {
+            BootstrapServiceEncode::Single(url) => Ok(Self(vec![url])),
+            BootstrapServiceEncode::List(urls) => Self::new(urls).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
 fn cnv_bind_to(bind_to: &Option<url2::Url2>) -> TxUrl {
     match bind_to {
         Some(bind_to) => bind_to.clone().into(),
@@ -82,9 +159,17 @@ impl KitsuneP2pConfig {
             }) => {
                 let backend = match &**sub_transport {
                     TransportConfig::Mem {} => KitsuneP2pTx2Backend::Mem,
-                    TransportConfig::Quic { bind_to, .. } => {
+                    TransportConfig::Quic {
+                        bind_to,
+                        override_host,
+                        override_port,
+                    } => {
                         let bind_to = cnv_bind_to(bind_to);
-                        KitsuneP2pTx2Backend::Quic { bind_to }
+                        KitsuneP2pTx2Backend::Quic {
+                            bind_to,
+                            override_host: override_host.clone(),
+                            override_port: *override_port,
+                        }
                     }
                     _ => return Err("kitsune tx2 backend must be mem or quic".into()),
                 };
@@ -103,10 +188,18 @@ impl KitsuneP2pConfig {
                 };
                 Ok(KitsuneP2pTx2Config { backend, use_proxy })
             }
-            Some(TransportConfig::Quic { bind_to, .. }) => {
+            Some(TransportConfig::Quic {
+                bind_to,
+                override_host,
+                override_port,
+            }) => {
                 let bind_to = cnv_bind_to(bind_to);
                 Ok(KitsuneP2pTx2Config {
-                    backend: KitsuneP2pTx2Backend::Quic { bind_to },
+                    backend: KitsuneP2pTx2Backend::Quic {
+                        bind_to,
+                        override_host: override_host.clone(),
+                        override_port: *override_port,
+                    },
                     use_proxy: NoProxy,
                 })
             }
@@ -240,4 +333,20 @@ pub enum NetworkType {
     QuicBootstrap,
     /// Via MDNS to the LAN
     QuicMdns,
+    /// Via MDNS to the LAN and bootstrap server to the WAN at the same time.
+    /// Peers on the LAN are connected to directly even if they are
+    /// also reachable through a proxy.
+    QuicHybrid,
+}
+
+impl NetworkType {
+    /// Are agent infos published to and fetched from the bootstrap service?
+    pub fn uses_bootstrap(&self) -> bool {
+        matches!(self, NetworkType::QuicBootstrap | NetworkType::QuicHybrid)
+    }
+
+    /// Are agent infos broadcast and discovered via MDNS?
+    pub fn uses_mdns(&self) -> bool {
+        matches!(self, NetworkType::QuicMdns | NetworkType::QuicHybrid)
+    }
 }
@@ -2,5 +2,10 @@
 
 pub mod sharded_gossip;
 
+pub use crate::types::gossip::{
+    AsGossipModule, AsGossipModuleFactory, GossipModule, GossipModuleFactory, GossipStrategies,
+    NO_GOSSIP_STRATEGY, SHARDED_GOSSIP_STRATEGY,
+};
+
 mod common;
 pub use common::*;
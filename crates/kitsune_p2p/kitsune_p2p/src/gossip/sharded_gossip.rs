@@ -29,6 +29,7 @@ use tokio::time::Instant;
 pub use self::bandwidth::BandwidthThrottle;
 use self::ops::OpsBatchQueue;
 use self::state_map::RoundStateMap;
+use crate::bandwidth::BandwidthBudgets;
 use crate::metrics::MetricsSync;
 
 use super::{HowToConnect, MetaOpKey};
@@ -111,6 +112,8 @@ pub struct ShardedGossip {
     inner: Share<ShardedGossipState>,
     /// Bandwidth for incoming and outgoing gossip.
     bandwidth: Arc<BandwidthThrottle>,
+    /// The node's bandwidth budgets that gossip also counts against.
+    budgets: BandwidthBudgets,
 }
 
 /// Basic statistic for gossip loop processing performance.
@@ -154,9 +157,10 @@ impl ShardedGossip {
         evt_sender: EventSender,
         host: HostApi,
         gossip_type: GossipType,
-        bandwidth: Arc<BandwidthThrottle>,
+        budgets: BandwidthBudgets,
         metrics: MetricsSync,
     ) -> Arc<Self> {
+        let bandwidth = budgets.gossip().for_gossip_type(gossip_type);
         let this = Arc::new(Self {
             ep_hnd,
             inner: Share::new(Default::default()),
@@ -170,6 +174,7 @@ impl ShardedGossip {
                 closing: AtomicBool::new(false),
             },
             bandwidth,
+            budgets,
         });
         metric_task({
             let this = this.clone();
@@ -220,6 +225,10 @@ impl ShardedGossip {
 
         let timeout = self.gossip.tuning_params.implicit_timeout();
 
+        self.budgets
+            .outgoing_bytes(&self.gossip.space, &cert, bytes)
+            .await
+            .map_err(KitsuneError::other)?;
         self.bandwidth.outgoing_bytes(bytes).await;
 
         let con = match how.clone() {
@@ -241,25 +250,36 @@ impl ShardedGossip {
     async fn process_incoming_outgoing(&self) -> KitsuneResult<()> {
         let (incoming, outgoing) = self.pop_queues()?;
         if let Some((con, remote_url, msg, bytes)) = incoming {
-            self.bandwidth.incoming_bytes(bytes).await;
-            let outgoing = match self.gossip.process_incoming(con.peer_cert(), msg).await {
-                Ok(r) => r,
-                Err(e) => {
-                    tracing::error!("FAILED to process incoming gossip {:?}", e);
-                    self.gossip.remove_state(&con.peer_cert(), true)?;
-                    vec![ShardedGossipWire::error(e.to_string())]
-                }
-            };
-            self.inner.share_mut(|i, _| {
-                i.outgoing.extend(outgoing.into_iter().map(|msg| {
-                    (
-                        con.peer_cert(),
-                        HowToConnect::Con(con.clone(), remote_url.clone()),
-                        msg,
-                    )
-                }));
-                Ok(())
-            })?;
+            if let Err(err) = self
+                .budgets
+                .incoming_bytes(&self.gossip.space, &con.peer_cert(), bytes)
+                .await
+            {
+                // The daily cap has been reached so drop
+                // the message and end the round.
+                tracing::warn!("Dropping incoming gossip because of: {:?}", err);
+                self.gossip.remove_state(&con.peer_cert(), true)?;
+            } else {
+                self.bandwidth.incoming_bytes(bytes).await;
+                let outgoing = match self.gossip.process_incoming(con.peer_cert(), msg).await {
+                    Ok(r) => r,
+                    Err(e) => {
+                        tracing::error!("FAILED to process incoming gossip {:?}", e);
+                        self.gossip.remove_state(&con.peer_cert(), true)?;
+                        vec![ShardedGossipWire::error(e.to_string())]
+                    }
+                };
+                self.inner.share_mut(|i, _| {
+                    i.outgoing.extend(outgoing.into_iter().map(|msg| {
+                        (
+                            con.peer_cert(),
+                            HowToConnect::Con(con.clone(), remote_url.clone()),
+                            msg,
+                        )
+                    }));
+                    Ok(())
+                })?;
+            }
         }
         if let Some(outgoing) = outgoing {
             let cert = outgoing.0.clone();
@@ -1068,12 +1088,12 @@ impl AsGossipModule for ShardedGossip {
 }
 
 struct ShardedRecentGossipFactory {
-    bandwidth: Arc<BandwidthThrottle>,
+    budgets: BandwidthBudgets,
 }
 
 impl ShardedRecentGossipFactory {
-    fn new(bandwidth: Arc<BandwidthThrottle>) -> Self {
-        Self { bandwidth }
+    fn new(budgets: BandwidthBudgets) -> Self {
+        Self { budgets }
     }
 }
 
@@ -1094,19 +1114,19 @@ impl AsGossipModuleFactory for ShardedRecentGossipFactory {
             evt_sender,
             host,
             GossipType::Recent,
-            self.bandwidth.clone(),
+            self.budgets.clone(),
             metrics,
         ))
     }
 }
 
 struct ShardedHistoricalGossipFactory {
-    bandwidth: Arc<BandwidthThrottle>,
+    budgets: BandwidthBudgets,
 }
 
 impl ShardedHistoricalGossipFactory {
-    fn new(bandwidth: Arc<BandwidthThrottle>) -> Self {
-        Self { bandwidth }
+    fn new(budgets: BandwidthBudgets) -> Self {
+        Self { budgets }
     }
 }
 
@@ -1127,20 +1147,20 @@ impl AsGossipModuleFactory for ShardedHistoricalGossipFactory {
             evt_sender,
             host,
             GossipType::Historical,
-            self.bandwidth.clone(),
+            self.budgets.clone(),
             metrics,
         ))
     }
 }
 
 /// Create a recent `GossipModuleFactory`
-pub fn recent_factory(bandwidth: Arc<BandwidthThrottle>) -> GossipModuleFactory {
-    GossipModuleFactory(Arc::new(ShardedRecentGossipFactory::new(bandwidth)))
+pub fn recent_factory(budgets: BandwidthBudgets) -> GossipModuleFactory {
+    GossipModuleFactory(Arc::new(ShardedRecentGossipFactory::new(budgets)))
 }
 
 /// Create a historical `GossipModuleFactory`
-pub fn historical_factory(bandwidth: Arc<BandwidthThrottle>) -> GossipModuleFactory {
-    GossipModuleFactory(Arc::new(ShardedHistoricalGossipFactory::new(bandwidth)))
+pub fn historical_factory(budgets: BandwidthBudgets) -> GossipModuleFactory {
+    GossipModuleFactory(Arc::new(ShardedHistoricalGossipFactory::new(budgets)))
 }
 
 #[allow(dead_code)]
@@ -40,6 +40,14 @@ impl BandwidthThrottles {
     pub fn historical(&self) -> Arc<BandwidthThrottle> {
         self.historic.clone()
     }
+
+    /// Get the throttle for a gossip loop.
+    pub fn for_gossip_type(&self, gossip_type: GossipType) -> Arc<BandwidthThrottle> {
+        match gossip_type {
+            GossipType::Recent => self.recent(),
+            GossipType::Historical => self.historical(),
+        }
+    }
 }
 
 /// Manages incoming and outgoing bandwidth by providing methods which
@@ -50,8 +58,7 @@ where
     C: Clock,
 {
     clock: C,
-    inbound: Option<RateLimiter<NotKeyed, InMemoryState, C>>,
-    outbound: Option<RateLimiter<NotKeyed, InMemoryState, C>>,
+    limits: parking_lot::RwLock<Limiters<C>>,
     start_time: Instant,
     bits_inbound: AtomicUsize,
     peak_inbound: AtomicUsize,
@@ -61,6 +68,46 @@ where
     last_outbound_time: AtomicU64,
 }
 
+/// The current limits of a throttle.
+/// A limit of zero megabits per second has no limiter.
+struct Limiters<C>
+where
+    C: Clock,
+{
+    inbound_mbps: f64,
+    outbound_mbps: f64,
+    inbound: Option<Arc<RateLimiter<NotKeyed, InMemoryState, C>>>,
+    outbound: Option<Arc<RateLimiter<NotKeyed, InMemoryState, C>>>,
+}
+
+impl<C> Limiters<C>
+where
+    C: Clock,
+{
+    fn new(inbound_mbps: f64, outbound_mbps: f64, clock: &C) -> Self {
+        Self {
+            inbound_mbps,
+            outbound_mbps,
+            inbound: Self::limiter(inbound_mbps, clock),
+            outbound: Self::limiter(outbound_mbps, clock),
+        }
+    }
+
+    fn limiter(mbps: f64, clock: &C) -> Option<Arc<RateLimiter<NotKeyed, InMemoryState, C>>> {
+        // Convert to bits per second.
+        let bps = mbps * 1000.0 * 1000.0;
+        // Double the max message size to allow room for padding.
+        let max_burst_bits =
+            NonZeroU32::new(MAX_SEND_BUF_BYTES as u32 * 8 * 2).expect("This can't be zero");
+        NonZeroU32::new(bps as u32).map(|bps| {
+            Arc::new(RateLimiter::direct_with_clock(
+                Quota::per_second(bps).allow_burst(max_burst_bits),
+                clock,
+            ))
+        })
+    }
+}
+
 impl BandwidthThrottle {
     /// Set the inbound and outbound bandwidth limits in megabits per second.
     pub fn new(inbound_mbps: f64, outbound_mbps: f64) -> Self {
@@ -88,30 +135,10 @@ where
     C: Clock,
 {
     fn new_inner(inbound_mbps: f64, outbound_mbps: f64, clock: C) -> Self {
-        // Convert to bits per second.
-        let inbound_bps = inbound_mbps * 1000.0 * 1000.0;
-        let outbound_bps = outbound_mbps * 1000.0 * 1000.0;
-        // Double the max message size to allow room for padding.
-        let max_burst_bits =
-            NonZeroU32::new(MAX_SEND_BUF_BYTES as u32 * 8 * 2).expect("This can't be zero");
-
-        let inbound = NonZeroU32::new(inbound_bps as u32).map(|inbound_bps| {
-            RateLimiter::direct_with_clock(
-                Quota::per_second(inbound_bps).allow_burst(max_burst_bits),
-                &clock,
-            )
-        });
-
-        let outbound = NonZeroU32::new(outbound_bps as u32).map(|outbound_bps| {
-            RateLimiter::direct_with_clock(
-                Quota::per_second(outbound_bps).allow_burst(max_burst_bits),
-                &clock,
-            )
-        });
+        let limits = parking_lot::RwLock::new(Limiters::new(inbound_mbps, outbound_mbps, &clock));
         Self {
             clock,
-            inbound,
-            outbound,
+            limits,
             start_time: Instant::now(),
             bits_inbound: AtomicUsize::new(0),
             peak_inbound: AtomicUsize::new(0),
@@ -122,10 +149,36 @@ where
         }
     }
 
+    /// Change the inbound and outbound bandwidth limits in megabits per second.
+    /// Zero removes the limit.
+    pub fn set_limits(&self, inbound_mbps: f64, outbound_mbps: f64) {
+        *self.limits.write() = Limiters::new(inbound_mbps, outbound_mbps, &self.clock);
+    }
+
+    /// The inbound and outbound bandwidth limits in megabits per second.
+    pub fn limits(&self) -> (f64, f64) {
+        let limits = self.limits.read();
+        (limits.inbound_mbps, limits.outbound_mbps)
+    }
+
+    /// Total bytes received through this throttle.
+    pub fn bytes_inbound(&self) -> u64 {
+        (self.bits_inbound.load(std::sync::atomic::Ordering::Relaxed) / 8) as u64
+    }
+
+    /// Total bytes sent through this throttle.
+    pub fn bytes_outbound(&self) -> u64 {
+        (self
+            .bits_outbound
+            .load(std::sync::atomic::Ordering::Relaxed)
+            / 8) as u64
+    }
+
     /// Wait until there's enough bandwidth to send this many bytes.
     pub async fn outgoing_bytes(&self, bytes: usize) {
         if let Some(bits) = NonZeroU32::new(bytes as u32 * 8) {
-            if let Some(outbound) = &self.outbound {
+            let outbound = self.limits.read().outbound.clone();
+            if let Some(outbound) = outbound {
                 while let Err(e) = outbound.check_n(bits) {
                     match e {
                         governor::NegativeMultiDecision::BatchNonConforming(_, n) => {
@@ -182,7 +235,8 @@ where
     /// Wait until there's enough bandwidth to receive this many bytes.
     pub async fn incoming_bytes(&self, bytes: usize) {
         if let Some(bits) = NonZeroU32::new(bytes as u32 * 8) {
-            if let Some(inbound) = &self.inbound {
+            let inbound = self.limits.read().inbound.clone();
+            if let Some(inbound) = inbound {
                 while let Err(e) = inbound.check_n(bits) {
                     match e {
                         governor::NegativeMultiDecision::BatchNonConforming(_, n) => {
@@ -283,4 +337,25 @@ mod tests {
         // Allow for small rounding error.
         assert!(mbps < 0.11);
     }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn test_set_limits() {
+        let clock = governor::clock::FakeRelativeClock::default();
+        let bandwidth = BandwidthThrottle::test(0.1, 0.1, clock.clone());
+        let bytes = MAX_SEND_BUF_BYTES;
+        // Hit the burst limit.
+        bandwidth.outgoing_bytes(bytes).await;
+        bandwidth.outgoing_bytes(bytes).await;
+        let r =
+            tokio::time::timeout(Duration::from_secs(10), bandwidth.outgoing_bytes(bytes)).await;
+        assert!(r.is_err());
+
+        // Removing the limit lets the bytes through straight away.
+        bandwidth.set_limits(0.0, 0.0);
+        assert_eq!(bandwidth.limits(), (0.0, 0.0));
+        let n = tokio::time::Instant::now();
+        bandwidth.outgoing_bytes(bytes).await;
+        assert!(n.elapsed().is_zero());
+        assert_eq!(bandwidth.bytes_outbound(), 3 * bytes as u64);
+    }
 }
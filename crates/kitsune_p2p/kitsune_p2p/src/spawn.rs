@@ -1,5 +1,6 @@
 use crate::actor::*;
 use crate::event::*;
+use crate::gossip::GossipStrategies;
 use crate::HostApi;
 
 mod actor;
@@ -18,6 +19,20 @@ pub async fn spawn_kitsune_p2p(
 ) -> KitsuneP2pResult<(
     ghost_actor::GhostSender<KitsuneP2p>,
     KitsuneP2pEventReceiver,
+)> {
+    spawn_kitsune_p2p_with_gossip_strategies(config, tls_config, host, Default::default()).await
+}
+
+/// Spawn a new KitsuneP2p actor whose spaces can also run the
+/// application's own gossip strategies.
+pub async fn spawn_kitsune_p2p_with_gossip_strategies(
+    config: crate::KitsuneP2pConfig,
+    tls_config: kitsune_p2p_types::tls::TlsConfig,
+    host: HostApi,
+    gossip_strategies: GossipStrategies,
+) -> KitsuneP2pResult<(
+    ghost_actor::GhostSender<KitsuneP2p>,
+    KitsuneP2pEventReceiver,
 )> {
     let (evt_send, evt_recv) = futures::channel::mpsc::channel(10);
     let builder = ghost_actor::actor_builder::GhostActorBuilder::new();
@@ -37,6 +52,7 @@ pub async fn spawn_kitsune_p2p(
                 internal_sender,
                 evt_send,
                 host,
+                gossip_strategies,
             )
             .await?,
         ),
@@ -80,6 +96,7 @@ where
                 internal_sender,
                 evt_send,
                 host,
+                Default::default(),
             )
             .await?,
         ),
@@ -2,9 +2,9 @@
 
 use crate::actor;
 use crate::actor::*;
+use crate::bandwidth::BandwidthBudgets;
 use crate::event::*;
-use crate::gossip::sharded_gossip::BandwidthThrottles;
-use crate::types::gossip::GossipModuleType;
+use crate::types::gossip::{GossipModuleType, GossipStrategies};
 use crate::types::metrics::KitsuneMetrics;
 use crate::wire::MetricExchangeMsg;
 use crate::*;
@@ -89,8 +89,18 @@ pub(crate) struct KitsuneP2pActor {
             ghost_actor::GhostSender<space::SpaceInternal>,
         )>,
     >,
+    /// The gossip strategies spaces can run.
+    gossip_strategies: GossipStrategies,
+    /// The gossip strategy each space was spawned with.
+    space_gossip_strategies: HashMap<Arc<KitsuneSpace>, String>,
     config: Arc<KitsuneP2pConfig>,
-    bandwidth_throttles: BandwidthThrottles,
+    bandwidth: BandwidthBudgets,
+    /// The spaces we declare to our proxy, if enabled.
+    proxy_client_spaces: Option<kitsune_p2p_proxy::tx2::ProxyClientSpaces>,
+    /// The mdns responder shared by all spaces, if mdns is used.
+    mdns: Option<kitsune_p2p_mdns::Mdns>,
+    /// Direct addresses of peers found via mdns.
+    direct_addrs: Option<kitsune_p2p_proxy::tx2::ProxyDirectAddrs>,
     parallel_notify_permit: Arc<tokio::sync::Semaphore>,
 }
 
@@ -102,6 +112,7 @@ impl KitsuneP2pActor {
         internal_sender: ghost_actor::GhostSender<Internal>,
         evt_sender: futures::channel::mpsc::Sender<KitsuneP2pEvent>,
         host: HostApi,
+        gossip_strategies: GossipStrategies,
     ) -> KitsuneP2pResult<Self> {
         crate::types::metrics::init();
 
@@ -122,10 +133,16 @@ impl KitsuneP2pActor {
                     "none:".into(),
                 )
             }
-            KitsuneP2pTx2Backend::Quic { bind_to } => {
+            KitsuneP2pTx2Backend::Quic {
+                bind_to,
+                override_host,
+                override_port,
+            } => {
                 let mut conf = QuicConfig::default();
                 conf.tls = Some(tls_config.clone());
                 conf.tuning_params = Some(config.tuning_params.clone());
+                conf.override_host = override_host;
+                conf.override_port = override_port;
                 (
                     tx2_quic_adapter(conf)
                         .await
@@ -145,10 +162,47 @@ impl KitsuneP2pActor {
         // convert to frontend
         let f = tx2_pool_promote(f, config.tuning_params.clone());
 
+        let proxy_client_spaces = if config.tuning_params.proxy_declare_spaces {
+            Some(kitsune_p2p_proxy::tx2::ProxyClientSpaces::default())
+        } else {
+            None
+        };
+
+        let mdns = if config.network_type.uses_mdns() {
+            let mdns_config = kitsune_p2p_mdns::MdnsConfig {
+                interface: config
+                    .tuning_params
+                    .mdns_interface
+                    .parse()
+                    .map_err(KitsuneP2pError::other)?,
+                port: config.tuning_params.mdns_port,
+                ..Default::default()
+            };
+            match kitsune_p2p_mdns::Mdns::bind(mdns_config).await {
+                Ok(mdns) => Some(mdns),
+                Err(e) => {
+                    // e.g. no multicast capable interface, peers
+                    // can still be found via the bootstrap service
+                    tracing::warn!(?e, "failed to bind mdns, continuing without it");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let direct_addrs = if config.network_type.uses_mdns() {
+            Some(kitsune_p2p_proxy::tx2::ProxyDirectAddrs::default())
+        } else {
+            None
+        };
+
         // wrap in proxy
         let f = if !is_mock {
             let mut conf = kitsune_p2p_proxy::tx2::ProxyConfig::default();
             conf.tuning_params = Some(config.tuning_params.clone());
+            conf.client_spaces = proxy_client_spaces.clone();
+            conf.direct_addrs = direct_addrs.clone();
             match tx2_conf.use_proxy {
                 KitsuneP2pTx2ProxyConfig::NoProxy => (),
                 KitsuneP2pTx2ProxyConfig::Specific(proxy_url) => {
@@ -219,17 +273,21 @@ impl KitsuneP2pActor {
         // capture endpoint handle
         let ep_hnd = ep.handle().clone();
 
+        let bandwidth = BandwidthBudgets::new(&config.tuning_params);
+
         let i_s = internal_sender.clone();
         tokio::task::spawn({
             let evt_sender = evt_sender.clone();
             let host = host.clone();
             let tuning_params = config.tuning_params.clone();
+            let bandwidth = bandwidth.clone();
             async move {
                 ep.for_each_concurrent(tuning_params.concurrent_limit_per_thread, move |event| {
                     let evt_sender = evt_sender.clone();
                     let host = host.clone();
                     let tuning_params = tuning_params.clone();
                     let i_s = i_s.clone();
+                    let bandwidth = bandwidth.clone();
                     async move {
                         macro_rules! resp {
                             ($r:expr, $e:expr) => {
@@ -256,12 +314,14 @@ impl KitsuneP2pActor {
                                 let _ = i_s.new_con(url, con).await;
                             }
                             ConnectionClosed(Tx2EpConnectionClosed {
+                                con,
                                 url,
                                 ..
                             }) => {
+                                bandwidth.remove_peer(&con.peer_cert());
                                 let _ = i_s.del_con(url).await;
                             }
-                            IncomingRequest(Tx2EpIncomingRequest { data, respond, .. }) => {
+                            IncomingRequest(Tx2EpIncomingRequest { con, data, respond, .. }) => {
                                 match data {
                                     wire::Wire::Call(wire::Call {
                                         space,
@@ -269,6 +329,15 @@ impl KitsuneP2pActor {
                                         data,
                                         ..
                                     }) => {
+                                        if let Err(err) = bandwidth
+                                            .incoming_bytes(&space, &con.peer_cert(), data.len())
+                                            .await
+                                        {
+                                            let reason = format!("{:?}", err);
+                                            let fail = wire::Wire::failure(reason);
+                                            resp!(respond, fail);
+                                            return;
+                                        }
                                         let res = match evt_sender
                                             .call(space, to_agent, data.into())
                                             .await
@@ -334,6 +403,16 @@ impl KitsuneP2pActor {
                                         data,
                                         destination,
                                     }) => {
+                                        if let Err(err) = bandwidth
+                                            .incoming_bytes(&space, &con.peer_cert(), data.len())
+                                            .await
+                                        {
+                                            tracing::warn!(
+                                                ?err,
+                                                "dropping incoming delegate broadcast"
+                                            );
+                                            return;
+                                        }
                                         // one might be tempted to notify here
                                         // as in Broadcast below... but we
                                         // notify all relevent agents inside
@@ -363,7 +442,15 @@ impl KitsuneP2pActor {
                                         data,
                                         destination,
                                         ..
-                                    }) => match destination {
+                                    }) => {
+                                        if let Err(err) = bandwidth
+                                            .incoming_bytes(&space, &con.peer_cert(), data.len())
+                                            .await
+                                        {
+                                            tracing::warn!(?err, "dropping incoming broadcast");
+                                            return;
+                                        }
+                                        match destination {
                                         BroadcastTo::Notify => {
                                             // TODO: Should we check if the basis is
                                             // held before calling notify?
@@ -409,7 +496,8 @@ impl KitsuneP2pActor {
                                                 }
                                             }
                                         }
-                                    },
+                                        }
+                                    }
                                     wire::Wire::Gossip(wire::Gossip {
                                         space,
                                         data,
@@ -444,7 +532,6 @@ impl KitsuneP2pActor {
             }
         });
 
-        let bandwidth_throttles = BandwidthThrottles::new(&config.tuning_params);
         let parallel_notify_permit = Arc::new(tokio::sync::Semaphore::new(
             config.tuning_params.concurrent_limit_per_thread,
         ));
@@ -456,11 +543,89 @@ impl KitsuneP2pActor {
             ep_hnd,
             host,
             spaces: HashMap::new(),
+            gossip_strategies,
+            space_gossip_strategies: HashMap::new(),
             config: Arc::new(config),
-            bandwidth_throttles,
+            bandwidth,
+            proxy_client_spaces,
+            mdns,
+            direct_addrs,
             parallel_notify_permit,
         })
     }
+
+    /// Join an agent to a space, spawning the space with its
+    /// gossip strategy if this is the first agent to join.
+    fn join_space(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+        agent: Arc<KitsuneAgent>,
+        initial_arc: Option<crate::dht_arc::DhtArc>,
+        gossip_strategy: Option<String>,
+    ) -> KitsuneP2pHandlerResult<()> {
+        let space_sender = match self.spaces.entry(space.clone()) {
+            Entry::Occupied(entry) => {
+                if let Some(gossip_strategy) = gossip_strategy {
+                    let running = self.space_gossip_strategies.get(&space);
+                    if running != Some(&gossip_strategy) {
+                        return Err(format!(
+                            "space {:?} is already running gossip strategy {:?} not {}",
+                            space, running, gossip_strategy
+                        )
+                        .into());
+                    }
+                }
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => {
+                let gossip_strategy = gossip_strategy
+                    .unwrap_or_else(|| self.config.tuning_params.gossip_strategy.clone());
+                let gossip_modules = self
+                    .gossip_strategies
+                    .modules(&gossip_strategy, &self.bandwidth)?;
+                self.space_gossip_strategies
+                    .insert(space.clone(), gossip_strategy);
+                let internal_sender = self.internal_sender.clone();
+                let space2 = space.clone();
+                let ep_hnd = self.ep_hnd.clone();
+                let host = self.host.clone();
+                let config = Arc::clone(&self.config);
+                let bandwidth = self.bandwidth.clone();
+                let mdns = self.mdns.clone();
+                let direct_addrs = self.direct_addrs.clone();
+                let proxy_client_spaces = self.proxy_client_spaces.clone();
+                let parallel_notify_permit = self.parallel_notify_permit.clone();
+                entry.insert(AsyncLazy::new(async move {
+                    let (send, send_inner, evt_recv) = spawn_space(
+                        space2,
+                        ep_hnd,
+                        host,
+                        config,
+                        gossip_modules,
+                        bandwidth,
+                        mdns,
+                        direct_addrs,
+                        proxy_client_spaces,
+                        parallel_notify_permit,
+                    )
+                    .await
+                    .expect("cannot fail to create space");
+                    internal_sender
+                        .register_space_event_handler(evt_recv)
+                        .await
+                        .expect("FAIL");
+                    (send, send_inner)
+                }))
+            }
+        };
+        let space_sender = space_sender.get();
+        Ok(async move {
+            let (space_sender, _) = space_sender.await;
+            space_sender.join(space, agent, initial_arc).await
+        }
+        .boxed()
+        .into())
+    }
 }
 
 use ghost_actor::dependencies::must_future::MustBoxFuture;
@@ -703,40 +868,17 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
         agent: Arc<KitsuneAgent>,
         initial_arc: Option<crate::dht_arc::DhtArc>,
     ) -> KitsuneP2pHandlerResult<()> {
-        let internal_sender = self.internal_sender.clone();
-        let space2 = space.clone();
-        let ep_hnd = self.ep_hnd.clone();
-        let host = self.host.clone();
-        let config = Arc::clone(&self.config);
-        let bandwidth_throttles = self.bandwidth_throttles.clone();
-        let parallel_notify_permit = self.parallel_notify_permit.clone();
-        let space_sender = match self.spaces.entry(space.clone()) {
-            Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(entry) => entry.insert(AsyncLazy::new(async move {
-                let (send, send_inner, evt_recv) = spawn_space(
-                    space2,
-                    ep_hnd,
-                    host,
-                    config,
-                    bandwidth_throttles,
-                    parallel_notify_permit,
-                )
-                .await
-                .expect("cannot fail to create space");
-                internal_sender
-                    .register_space_event_handler(evt_recv)
-                    .await
-                    .expect("FAIL");
-                (send, send_inner)
-            })),
-        };
-        let space_sender = space_sender.get();
-        Ok(async move {
-            let (space_sender, _) = space_sender.await;
-            space_sender.join(space, agent, initial_arc).await
-        }
-        .boxed()
-        .into())
+        self.join_space(space, agent, initial_arc, None)
+    }
+
+    fn handle_join_with_gossip_strategy(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+        agent: Arc<KitsuneAgent>,
+        initial_arc: Option<crate::dht_arc::DhtArc>,
+        gossip_strategy: String,
+    ) -> KitsuneP2pHandlerResult<()> {
+        self.join_space(space, agent, initial_arc, Some(gossip_strategy))
     }
 
     fn handle_leave(
@@ -901,6 +1043,15 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
         .boxed()
         .into())
     }
+
+    fn handle_set_bandwidth_limits(
+        &mut self,
+        scope: crate::bandwidth::BandwidthScope,
+        limits: crate::bandwidth::BandwidthLimits,
+    ) -> KitsuneP2pHandlerResult<()> {
+        self.bandwidth.set_limits(scope, limits);
+        unit_ok_fut()
+    }
 }
 
 #[cfg(any(test, feature = "test_utils"))]
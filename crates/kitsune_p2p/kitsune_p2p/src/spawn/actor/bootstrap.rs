@@ -1,5 +1,6 @@
 use crate::types::actor::KitsuneP2pResult;
 use crate::types::agent_store::AgentInfoSigned;
+use crate::BootstrapService;
 use kitsune_p2p_types::bootstrap::RandomQuery;
 use once_cell::sync::Lazy;
 use once_cell::sync::OnceCell;
@@ -30,38 +31,59 @@ const OP_PROXY_LIST: &str = "proxy_list";
 
 /// Standard interface to the remote bootstrap service.
 ///
-/// - url: the url of the bootstrap service or None to short circuit and not send a request
+/// - bootstrap_service: the urls of the bootstrap service or None to short circuit and not send
+///                      a request. The urls are tried in order until one of them can be reached
+///                      and doesn't respond with a server error.
 /// - op: the header op for the remote service
 /// - input: op-specific struct that will be messagepack encoded and sent as binary data in the
 ///          body of the POST
 ///
 /// Output type O is op specific and needs to be messagepack decodeable.
 async fn do_api<I: serde::Serialize, O: serde::de::DeserializeOwned>(
-    url: Option<Url2>,
+    bootstrap_service: Option<BootstrapService>,
     op: &str,
     input: I,
 ) -> crate::types::actor::KitsuneP2pResult<Option<O>> {
     let mut body_data = Vec::new();
     kitsune_p2p_types::codec::rmp_encode(&mut body_data, &input)?;
-    match url {
-        Some(url) => {
-            let res = CLIENT
-                .post(url.as_str())
-                .body(body_data)
-                .header(OP_HEADER, op)
-                .header(reqwest::header::CONTENT_TYPE, "application/octet")
-                .send()
-                .await?;
-            if res.status().is_success() {
-                Ok(Some(kitsune_p2p_types::codec::rmp_decode(
-                    &mut res.bytes().await?.as_ref(),
-                )?))
-            } else {
-                Err(crate::KitsuneP2pError::Bootstrap(
-                    res.text().await?.into_boxed_str(),
-                ))
+    let bootstrap_service = match bootstrap_service {
+        Some(bootstrap_service) => bootstrap_service,
+        None => return Ok(None),
+    };
+    let mut last_err: Option<crate::KitsuneP2pError> = None;
+    for url in bootstrap_service.urls() {
+        let res = match CLIENT
+            .post(url.as_str())
+            .body(body_data.clone())
+            .header(OP_HEADER, op)
+            .header(reqwest::header::CONTENT_TYPE, "application/octet")
+            .send()
+            .await
+        {
+            Ok(res) => res,
+            Err(err) => {
+                tracing::warn!(%url, ?err, "bootstrap service unreachable, trying the next one");
+                last_err = Some(err.into());
+                continue;
             }
+        };
+        let status = res.status();
+        if status.is_success() {
+            return Ok(Some(kitsune_p2p_types::codec::rmp_decode(
+                &mut res.bytes().await?.as_ref(),
+            )?));
+        }
+        let err = crate::KitsuneP2pError::Bootstrap(res.text().await?.into_boxed_str());
+        if !status.is_server_error() {
+            // The service understood and refused the request,
+            // the other services would refuse it too.
+            return Err(err);
         }
+        tracing::warn!(%url, ?err, "bootstrap service failed, trying the next one");
+        last_err = Some(err);
+    }
+    match last_err {
+        Some(err) => Err(err),
         None => Ok(None),
     }
 }
@@ -71,10 +93,10 @@ async fn do_api<I: serde::Serialize, O: serde::de::DeserializeOwned>(
 /// Input must be an AgentInfoSigned with a valid siganture otherwise the remote service will not
 /// accept the data.
 pub async fn put(
-    url: Option<Url2>,
+    bootstrap_service: Option<BootstrapService>,
     agent_info_signed: crate::types::agent_store::AgentInfoSigned,
 ) -> crate::types::actor::KitsuneP2pResult<()> {
-    match do_api(url, OP_PUT, agent_info_signed).await {
+    match do_api(bootstrap_service, OP_PUT, agent_info_signed).await {
         Ok(Some(())) => Ok(()),
         Ok(None) => Ok(()),
         Err(e) => Err(e),
@@ -93,8 +115,10 @@ fn local_now() -> crate::types::actor::KitsuneP2pResult<u64> {
 ///
 /// There is no input to the `now` endpoint, just `()` to be encoded as nil in messagepack.
 #[allow(dead_code)]
-pub async fn now(url: Option<Url2>) -> crate::types::actor::KitsuneP2pResult<u64> {
-    match do_api(url, OP_NOW, ()).await {
+pub async fn now(
+    bootstrap_service: Option<BootstrapService>,
+) -> crate::types::actor::KitsuneP2pResult<u64> {
+    match do_api(bootstrap_service, OP_NOW, ()).await {
         // If the server gives us something useful we use it.
         Ok(Some(v)) => Ok(v),
         // If we don't have a server url we should trust ourselves.
@@ -109,11 +133,13 @@ pub async fn now(url: Option<Url2>) -> crate::types::actor::KitsuneP2pResult<u64
 ///
 /// Calculates the offset on the first call and caches it in the cell above.
 /// Only calls `now` once then keeps the offset for the static lifetime.
-pub async fn now_once(url: Option<Url2>) -> crate::types::actor::KitsuneP2pResult<u64> {
+pub async fn now_once(
+    bootstrap_service: Option<BootstrapService>,
+) -> crate::types::actor::KitsuneP2pResult<u64> {
     match NOW_OFFSET_MILLIS.get() {
         Some(offset) => Ok(u64::try_from(i64::try_from(local_now()?)? + offset)?),
         None => {
-            let offset: i64 = match now(url.clone()).await {
+            let offset: i64 = match now(bootstrap_service).await {
                 Ok(v) => {
                     let offset = v as i64 - local_now()? as i64;
                     match NOW_OFFSET_MILLIS.set(offset) {
@@ -149,14 +175,15 @@ pub async fn now_once(url: Option<Url2>) -> crate::types::actor::KitsuneP2pResul
 /// service to mitigate eclipse attacks by having a strong randomness implementation.
 #[allow(dead_code)]
 pub async fn random(
-    url: Option<Url2>,
+    bootstrap_service: Option<BootstrapService>,
     query: RandomQuery,
 ) -> crate::types::actor::KitsuneP2pResult<Vec<AgentInfoSigned>> {
-    let outer_vec: Vec<serde_bytes::ByteBuf> = match do_api(url, OP_RANDOM, query).await {
-        Ok(Some(v)) => v,
-        Ok(None) => Vec::new(),
-        Err(e) => return Err(e),
-    };
+    let outer_vec: Vec<serde_bytes::ByteBuf> =
+        match do_api(bootstrap_service, OP_RANDOM, query).await {
+            Ok(Some(v)) => v,
+            Ok(None) => Vec::new(),
+            Err(e) => return Err(e),
+        };
     let ret: Result<Vec<AgentInfoSigned>, _> = outer_vec
         .into_iter()
         .map(|bytes| kitsune_p2p_types::codec::rmp_decode(&mut AsRef::<[u8]>::as_ref(&bytes)))
@@ -169,12 +196,14 @@ pub async fn random(
 /// Fetches the list of proxy servers currently stored in the bootstrap service.
 #[allow(dead_code)]
 pub async fn proxy_list(url: Url2) -> KitsuneP2pResult<Vec<Url2>> {
-    Ok(do_api::<_, Vec<String>>(Some(url), OP_PROXY_LIST, ())
-        .await?
-        .unwrap_or_default()
-        .into_iter()
-        .map(Url2::parse)
-        .collect())
+    Ok(
+        do_api::<_, Vec<String>>(Some(url.into()), OP_PROXY_LIST, ())
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .map(Url2::parse)
+            .collect(),
+    )
 }
 
 #[cfg(test)]
@@ -231,7 +260,7 @@ mod tests {
 
         // Simply hitting the endpoint should be OK.
         super::put(
-            Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV)),
+            Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV).into()),
             agent_info_signed,
         )
         .await
@@ -239,7 +268,7 @@ mod tests {
 
         // We should get back an error if we don't have a good signature.
         assert!(super::put(
-            Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV)),
+            Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV).into()),
             fixt!(AgentInfoSigned)
         )
         .await
@@ -258,10 +287,9 @@ mod tests {
             .unwrap();
 
         // We should be able to get a milliseconds timestamp back.
-        let remote_now: u64 = super::now(Some(url2::url2!(
-            "{}",
-            crate::config::BOOTSTRAP_SERVICE_DEV
-        )))
+        let remote_now: u64 = super::now(Some(
+            url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV).into(),
+        ))
         .await
         .unwrap();
         let threshold = 5000;
@@ -270,10 +298,9 @@ mod tests {
 
         // Now once should return some number and the remote server offset should be set in the
         // NOW_OFFSET_MILLIS once cell.
-        let _: u64 = super::now_once(Some(url2::url2!(
-            "{}",
-            crate::config::BOOTSTRAP_SERVICE_DEV
-        )))
+        let _: u64 = super::now_once(Some(
+            url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV).into(),
+        ))
         .await
         .unwrap();
         assert!(super::NOW_OFFSET_MILLIS.get().is_some());
@@ -285,10 +312,9 @@ mod tests {
     // thread 'spawn::actor::bootstrap::tests::test_random' panicked at 'dispatch dropped without returning error', /rustc/d3fb005a39e62501b8b0b356166e515ae24e2e54/src/libstd/macros.rs:13:23
     async fn test_random() {
         let space = fixt!(KitsuneSpace, Unpredictable);
-        let now = super::now(Some(url2::url2!(
-            "{}",
-            crate::config::BOOTSTRAP_SERVICE_DEV
-        )))
+        let now = super::now(Some(
+            url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV).into(),
+        ))
         .await
         .unwrap();
 
@@ -322,7 +348,7 @@ mod tests {
             .unwrap();
 
             super::put(
-                Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV)),
+                Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV).into()),
                 agent_info_signed.clone(),
             )
             .await
@@ -332,7 +358,7 @@ mod tests {
         }
 
         let mut random = super::random(
-            Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV)),
+            Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV).into()),
             super::RandomQuery {
                 space: Arc::new(space.clone()),
                 ..Default::default()
@@ -348,7 +374,7 @@ mod tests {
         assert!(random == expected);
 
         let random_single = super::random(
-            Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV)),
+            Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV).into()),
             super::RandomQuery {
                 space: Arc::new(space.clone()),
                 limit: 1.into(),
@@ -360,4 +386,33 @@ mod tests {
         assert!(random_single.len() == 1);
         assert!(expected[0] == random_single[0] || expected[1] == random_single[0]);
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bootstrap_failover() {
+        // Nothing is listening on this port once the listener is dropped.
+        let dead = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let dead_url = url2::url2!("http://{}", dead.local_addr().unwrap());
+        drop(dead);
+
+        let (driver, addr, shutdown) = kitsune_p2p_bootstrap::run(([127, 0, 0, 1], 0), vec![])
+            .await
+            .unwrap();
+        tokio::task::spawn(driver);
+        let live_url = url2::url2!("http://{}", addr);
+
+        let now = super::now(Some(
+            BootstrapService::new(vec![dead_url.clone(), live_url]).unwrap(),
+        ))
+        .await
+        .unwrap();
+        assert!(now > 0);
+
+        // Without a service to fail over to the error is returned.
+        assert!(super::now(Some(dead_url.into())).await.is_err());
+
+        // A service needs at least one url.
+        assert!(BootstrapService::new(vec![]).is_err());
+
+        shutdown();
+    }
 }
@@ -1,15 +1,16 @@
 use super::*;
+use crate::bandwidth::BandwidthBudgets;
 use crate::metrics::*;
-use crate::types::gossip::GossipModule;
+use crate::types::gossip::{GossipModule, GossipModuleFactory};
 use ghost_actor::dependencies::tracing;
-use kitsune_p2p_mdns::*;
+use kitsune_p2p_mdns::Mdns;
+use kitsune_p2p_proxy::tx2::{ProxyClientSpaces, ProxyDirectAddrs};
 use kitsune_p2p_types::agent_info::AgentInfoSigned;
 use kitsune_p2p_types::codec::{rmp_decode, rmp_encode};
 use kitsune_p2p_types::dht_arc::{DhtArc, DhtArcRange, DhtArcSet};
 use kitsune_p2p_types::tx2::tx2_utils::TxUrl;
+use kitsune_p2p_types::Tx2Cert;
 use std::collections::{HashMap, HashSet};
-use std::sync::atomic::AtomicBool;
-use url2::Url2;
 
 /// How often to record historical metrics
 /// (currently once per hour)
@@ -82,7 +83,11 @@ pub(crate) async fn spawn_space(
     ep_hnd: Tx2EpHnd<wire::Wire>,
     host: HostApi,
     config: Arc<KitsuneP2pConfig>,
-    bandwidth_throttles: BandwidthThrottles,
+    gossip_modules: Vec<(GossipModuleType, GossipModuleFactory)>,
+    bandwidth: BandwidthBudgets,
+    mdns: Option<Mdns>,
+    direct_addrs: Option<ProxyDirectAddrs>,
+    proxy_client_spaces: Option<ProxyClientSpaces>,
     parallel_notify_permit: Arc<tokio::sync::Semaphore>,
 ) -> KitsuneP2pResult<(
     ghost_actor::GhostSender<KitsuneP2p>,
@@ -110,7 +115,11 @@ pub(crate) async fn spawn_space(
         host,
         ep_hnd,
         config,
-        bandwidth_throttles,
+        gossip_modules,
+        bandwidth,
+        mdns,
+        direct_addrs,
+        proxy_client_spaces,
         parallel_notify_permit,
     )));
 
@@ -145,7 +154,7 @@ impl SpaceInternalHandler for Space {
 
     fn handle_update_agent_info(&mut self) -> SpaceInternalHandlerResult<()> {
         let space = self.space.clone();
-        let mut mdns_handles = self.mdns_handles.clone();
+        let mdns = self.ro_inner.mdns.clone();
         let network_type = self.config.network_type.clone();
         let mut agent_list = Vec::with_capacity(self.local_joined_agents.len());
         for agent in self.local_joined_agents.iter().cloned() {
@@ -163,7 +172,7 @@ impl SpaceInternalHandler for Space {
             .gossip_single_storage_arc_per_space;
         let internal_sender = self.i_s.clone();
         Ok(async move {
-            let urls = vec![ep_hnd.local_addr()?];
+            let (urls, lan_urls) = agent_urls(&ep_hnd, &network_type)?;
             let mut peer_data = Vec::with_capacity(agent_list.len());
             for (agent, arc) in agent_list {
                 let input = UpdateAgentInfoInput {
@@ -172,10 +181,11 @@ impl SpaceInternalHandler for Space {
                     agent,
                     arc,
                     urls: &urls,
+                    lan_urls: &lan_urls,
                     evt_sender: &evt_sender,
                     internal_sender: &internal_sender,
                     network_type: network_type.clone(),
-                    mdns_handles: &mut mdns_handles,
+                    mdns: &mdns,
                     bootstrap_service: &bootstrap_service,
                     dynamic_arcs,
                     single_storage_arc_per_space,
@@ -199,7 +209,7 @@ impl SpaceInternalHandler for Space {
         agent: Arc<KitsuneAgent>,
     ) -> SpaceInternalHandlerResult<()> {
         let space = self.space.clone();
-        let mut mdns_handles = self.mdns_handles.clone();
+        let mdns = self.ro_inner.mdns.clone();
         let network_type = self.config.network_type.clone();
         let ep_hnd = self.ro_inner.ep_hnd.clone();
         let evt_sender = self.evt_sender.clone();
@@ -214,17 +224,18 @@ impl SpaceInternalHandler for Space {
         let arc = self.get_agent_arc(&agent);
 
         Ok(async move {
-            let urls = vec![ep_hnd.local_addr()?];
+            let (urls, lan_urls) = agent_urls(&ep_hnd, &network_type)?;
             let input = UpdateAgentInfoInput {
                 expires_after,
                 space: space.clone(),
                 agent,
                 arc,
                 urls: &urls,
+                lan_urls: &lan_urls,
                 evt_sender: &evt_sender,
                 internal_sender: &internal_sender,
                 network_type: network_type.clone(),
-                mdns_handles: &mut mdns_handles,
+                mdns: &mdns,
                 bootstrap_service: &bootstrap_service,
                 dynamic_arcs,
                 single_storage_arc_per_space,
@@ -366,6 +377,7 @@ impl SpaceInternalHandler for Space {
                 .filter(|info| info.agent.get_loc().as_u32() % mod_cnt == mod_idx)
             {
                 let ro_inner = ro_inner.clone();
+                let bandwidth = ro_inner.bandwidth.clone();
                 let space = space.clone();
                 let data = data.clone();
                 all.push(async move {
@@ -381,6 +393,14 @@ impl SpaceInternalHandler for Space {
                         }
                     };
 
+                    if let Err(err) = bandwidth
+                        .outgoing_bytes(&space, &con_hnd.peer_cert(), data.len())
+                        .await
+                    {
+                        tracing::warn!(?err, "broadcast error");
+                        return;
+                    }
+
                     // generate our broadcast payload
                     let payload =
                         wire::Wire::broadcast(space, info.agent.clone(), destination, data);
@@ -448,11 +468,12 @@ struct UpdateAgentInfoInput<'borrow> {
     agent: Arc<KitsuneAgent>,
     arc: DhtArc,
     urls: &'borrow Vec<TxUrl>,
+    lan_urls: &'borrow Vec<TxUrl>,
     evt_sender: &'borrow futures::channel::mpsc::Sender<KitsuneP2pEvent>,
     internal_sender: &'borrow ghost_actor::GhostSender<SpaceInternal>,
     network_type: NetworkType,
-    mdns_handles: &'borrow mut HashMap<Vec<u8>, Arc<AtomicBool>>,
-    bootstrap_service: &'borrow Option<Url2>,
+    mdns: &'borrow Option<Mdns>,
+    bootstrap_service: &'borrow Option<BootstrapService>,
     dynamic_arcs: bool,
     single_storage_arc_per_space: bool,
 }
@@ -476,10 +497,11 @@ async fn update_single_agent_info(
         agent,
         mut arc,
         urls,
+        lan_urls,
         evt_sender,
         internal_sender,
         network_type,
-        mdns_handles,
+        mdns,
         bootstrap_service,
         dynamic_arcs,
         single_storage_arc_per_space,
@@ -498,11 +520,85 @@ async fn update_single_agent_info(
     let signed_at_ms = crate::spawn::actor::bootstrap::now_once(None).await?;
     let expires_at_ms = signed_at_ms + expires_after;
 
-    let agent_info_signed = AgentInfoSigned::sign(
+    let sign = |urls: Vec<TxUrl>| SignAgentInfoInput {
+        space: space.clone(),
+        agent: agent.clone(),
+        half_length: arc.half_length(),
+        urls,
+        signed_at_ms,
+        expires_at_ms,
+        evt_sender,
+    };
+
+    let agent_info_signed = sign_agent_info(sign(urls.clone())).await?;
+
+    tracing::debug!(?agent_info_signed);
+
+    // Push to the network as well
+    if let Some(mdns) = mdns {
+        // Broadcast only valid AgentInfo
+        if !lan_urls.is_empty() {
+            // Broadcast by using Space as service type and Agent as service name,
+            // this replaces any previous broadcast for this space + agent
+            let (space_b64, agent_b64) = mdns_names(&space, &agent);
+            // Private LAN addresses are only ever broadcast over mdns,
+            // the published info carries the public urls.
+            let lan_info_signed = if lan_urls == urls {
+                agent_info_signed.clone()
+            } else {
+                sign_agent_info(sign(lan_urls.clone())).await?
+            };
+            // Broadcast rmp encoded agent_info_signed
+            let mut buffer = Vec::new();
+            rmp_encode(&mut buffer, &lan_info_signed)?;
+            tracing::trace!(?space_b64, ?agent_b64);
+            if let Err(e) = mdns.advertise(&space_b64, &agent_b64, buffer) {
+                tracing::error!(msg = "Failed to broadcast agent info over MDNS", ?e);
+            }
+        }
+    }
+    if network_type.uses_bootstrap() {
+        if let Err(e) = crate::spawn::actor::bootstrap::put(
+            bootstrap_service.clone(),
+            agent_info_signed.clone(),
+        )
+        .await
+        {
+            if !network_type.uses_mdns() {
+                return Err(e);
+            }
+            // LAN peers still find us via mdns while the bootstrap is unreachable
+            tracing::warn!(?e, "failed to publish agent info to bootstrap");
+        }
+    }
+    Ok(agent_info_signed)
+}
+
+struct SignAgentInfoInput<'borrow> {
+    space: Arc<KitsuneSpace>,
+    agent: Arc<KitsuneAgent>,
+    half_length: u32,
+    urls: Vec<TxUrl>,
+    signed_at_ms: u64,
+    expires_at_ms: u64,
+    evt_sender: &'borrow futures::channel::mpsc::Sender<KitsuneP2pEvent>,
+}
+
+async fn sign_agent_info(input: SignAgentInfoInput<'_>) -> KitsuneP2pResult<AgentInfoSigned> {
+    let SignAgentInfoInput {
+        space,
+        agent,
+        half_length,
+        urls,
+        signed_at_ms,
+        expires_at_ms,
+        evt_sender,
+    } = input;
+    Ok(AgentInfoSigned::sign(
         space.clone(),
         agent.clone(),
-        arc.half_length(),
-        urls.clone(),
+        half_length,
+        urls,
         signed_at_ms,
         expires_at_ms,
         |d| {
@@ -521,43 +617,113 @@ async fn update_single_agent_info(
             }
         },
     )
-    .await?;
+    .await?)
+}
 
-    tracing::debug!(?agent_info_signed);
+/// The urls our agents are published with, and the urls they are
+/// broadcast with over mdns. Only a pure mdns network publishes
+/// every local address, anything reaching the bootstrap service
+/// carries just the public one.
+fn agent_urls(
+    ep_hnd: &Tx2EpHnd<wire::Wire>,
+    network_type: &NetworkType,
+) -> KitsuneP2pResult<(Vec<TxUrl>, Vec<TxUrl>)> {
+    let urls = if network_type.uses_bootstrap() {
+        vec![ep_hnd.local_addr()?]
+    } else {
+        ep_hnd.local_addrs()?
+    };
+    let lan_urls = if network_type.uses_mdns() {
+        ep_hnd.local_addrs()?
+    } else {
+        urls.clone()
+    };
+    Ok((urls, lan_urls))
+}
 
-    // Push to the network as well
-    match network_type {
-        NetworkType::QuicMdns => {
-            // Broadcast only valid AgentInfo
-            if !urls.is_empty() {
-                // Kill previous broadcast for this space + agent
-                let key = [space.get_bytes(), agent.get_bytes()].concat();
-                if let Some(current_handle) = mdns_handles.get(&key) {
-                    mdns_kill_thread(current_handle.to_owned());
-                }
-                // Broadcast by using Space as service type and Agent as service name
-                let space_b64 = base64::encode_config(&space[..], base64::URL_SAFE_NO_PAD);
-                let agent_b64 = base64::encode_config(&agent[..], base64::URL_SAFE_NO_PAD);
-                //println!("(MDNS) - Broadcasting of Agent {:?} ({}) in space {:?} ({} ; {})",
-                // agent, agent.get_bytes().len(), space, space.get_bytes().len(), space_b64.len());
-                // Broadcast rmp encoded agent_info_signed
-                let mut buffer = Vec::new();
-                rmp_encode(&mut buffer, &agent_info_signed)?;
-                tracing::trace!(?space_b64, ?agent_b64);
-                let handle = mdns_create_broadcast_thread(space_b64, agent_b64, &buffer);
-                // store handle in self
-                mdns_handles.insert(key, handle);
+/// The MDNS service type and name an agent is broadcast under.
+fn mdns_names(space: &KitsuneSpace, agent: &KitsuneAgent) -> (String, String) {
+    (
+        base64::encode_config(&space[..], base64::URL_SAFE_NO_PAD),
+        base64::encode_config(&agent[..], base64::URL_SAFE_NO_PAD),
+    )
+}
+
+/// Direct addresses learned from the mdns services of one space.
+/// An address is forgotten when its service says goodbye
+/// or the agent info it came from expires.
+struct MdnsDirectAddrs {
+    direct_addrs: ProxyDirectAddrs,
+    /// service name -> (peer cert, agent info expiry)
+    services: HashMap<String, (Tx2Cert, u64)>,
+}
+
+impl MdnsDirectAddrs {
+    fn new(direct_addrs: ProxyDirectAddrs) -> Self {
+        Self {
+            direct_addrs,
+            services: HashMap::new(),
+        }
+    }
+
+    /// Remember the url of a peer found via mdns that points at the address
+    /// the mdns response came from, so we connect to it directly
+    /// instead of through its proxy.
+    fn record(&mut self, service_name: &str, info: &AgentInfoSigned, addr: std::net::IpAddr) {
+        for url in info.url_list.iter() {
+            let purl = match kitsune_p2p_proxy::ProxyUrl::from_full(url.as_str()) {
+                Ok(purl) => purl,
+                Err(_) => continue,
+            };
+            let host = purl
+                .as_base()
+                .host_str()
+                .map(|h| h.trim_start_matches('[').trim_end_matches(']'))
+                .and_then(|h| h.parse::<std::net::IpAddr>().ok());
+            if host == Some(addr) {
+                let cert: Tx2Cert = purl.digest().into();
+                self.direct_addrs
+                    .insert(cert.clone(), purl.as_base_str().into());
+                self.services
+                    .insert(service_name.to_string(), (cert, info.expires_at_ms));
+                return;
             }
         }
-        NetworkType::QuicBootstrap => {
-            crate::spawn::actor::bootstrap::put(
-                bootstrap_service.clone(),
-                agent_info_signed.clone(),
-            )
-            .await?;
+    }
+
+    /// Forget the direct address recorded for this service,
+    /// unless another service still points at the same peer.
+    fn remove(&mut self, service_name: &str) {
+        if let Some((cert, _)) = self.services.remove(service_name) {
+            self.forget_unused(&cert);
+        }
+    }
+
+    /// Forget the direct addresses of agent infos that have expired.
+    fn prune(&mut self) {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        let mut expired = Vec::new();
+        self.services.retain(|_, (cert, expires_at_ms)| {
+            if *expires_at_ms <= now_ms {
+                expired.push(cert.clone());
+                false
+            } else {
+                true
+            }
+        });
+        for cert in expired {
+            self.forget_unused(&cert);
+        }
+    }
+
+    fn forget_unused(&self, cert: &Tx2Cert) {
+        if !self.services.values().any(|(c, _)| c == cert) {
+            self.direct_addrs.remove(cert);
         }
     }
-    Ok(agent_info_signed)
 }
 
 use ghost_actor::dependencies::must_future::MustBoxFuture;
@@ -600,67 +766,99 @@ impl KitsuneP2pHandler for Space {
             self.agent_arcs.insert(agent.clone(), initial_arc);
         }
         self.local_joined_agents.insert(agent.clone());
+        if let Some(proxy_client_spaces) = &self.proxy_client_spaces {
+            proxy_client_spaces.add((*space).clone());
+        }
         for module in self.gossip_mod.values() {
             module.local_agent_join(agent.clone());
         }
         let fut = self.i_s.update_single_agent_info(agent);
         let evt_sender = self.evt_sender.clone();
-        match self.config.network_type {
-            NetworkType::QuicMdns => {
-                // Listen to MDNS service that has that space as service type
-                let space_b64 = base64::encode_config(&space[..], base64::URL_SAFE_NO_PAD);
-                if !self.mdns_listened_spaces.contains(&space_b64) {
-                    self.mdns_listened_spaces.insert(space_b64.clone());
-                    tokio::task::spawn(async move {
-                        let stream = mdns_listen(space_b64);
-                        tokio::pin!(stream);
-                        while let Some(maybe_response) = stream.next().await {
-                            match maybe_response {
-                                Ok(response) => {
-                                    tracing::trace!(msg = "Peer found via MDNS", ?response);
-                                    // Decode response
-                                    let maybe_agent_info_signed =
-                                        rmp_decode(&mut &*response.buffer);
-                                    if let Err(e) = maybe_agent_info_signed {
-                                        tracing::error!(msg = "Failed to decode MDNS peer", ?e);
-                                        continue;
+        let direct_addrs = self.ro_inner.direct_addrs.clone();
+        // quic bootstrap is managed for the whole space
+        // see the Space::new() constructor
+        if let Some(mdns) = &self.ro_inner.mdns {
+            // Listen to MDNS service that has that space as service type
+            let space_b64 = base64::encode_config(&space[..], base64::URL_SAFE_NO_PAD);
+            if !self.mdns_listened_spaces.contains(&space_b64) {
+                match mdns.listen(&space_b64) {
+                    Err(e) => tracing::error!(msg = "Failed to get peers from MDNS", ?e),
+                    Ok(stream) => {
+                        self.mdns_listened_spaces.insert(space_b64);
+                        tokio::task::spawn(async move {
+                            tokio::pin!(stream);
+                            let mut direct_addrs = direct_addrs.map(MdnsDirectAddrs::new);
+                            while let Some(response) = stream.next().await {
+                                tracing::trace!(msg = "Peer found via MDNS", ?response);
+                                if let Some(direct_addrs) = &mut direct_addrs {
+                                    direct_addrs.prune();
+                                }
+                                if response.removed {
+                                    // the agent info expires on its own,
+                                    // but stop connecting to the peer directly
+                                    if let Some(direct_addrs) = &mut direct_addrs {
+                                        direct_addrs.remove(&response.service_name);
                                     }
-                                    if let Ok(remote_agent_info_signed) = maybe_agent_info_signed {
-                                        // Add to local storage
-                                        if let Err(e) = evt_sender
-                                            .put_agent_info_signed(PutAgentInfoSignedEvt {
-                                                space: space.clone(),
-                                                peer_data: vec![remote_agent_info_signed],
-                                            })
-                                            .await
-                                        {
-                                            tracing::error!(msg = "Failed to store MDNS peer", ?e);
+                                    continue;
+                                }
+                                // Decode response
+                                let remote_agent_info_signed =
+                                    match rmp_decode(&mut &*response.buffer) {
+                                        Ok(info) => info,
+                                        Err(e) => {
+                                            tracing::error!(msg = "Failed to decode MDNS peer", ?e);
+                                            continue;
                                         }
-                                    }
+                                    };
+                                if let Some(direct_addrs) = &mut direct_addrs {
+                                    direct_addrs.record(
+                                        &response.service_name,
+                                        &remote_agent_info_signed,
+                                        response.addr,
+                                    );
                                 }
-                                Err(e) => {
-                                    tracing::error!(msg = "Failed to get peers from MDNS", ?e);
+                                // Add to local storage
+                                if let Err(e) = evt_sender
+                                    .put_agent_info_signed(PutAgentInfoSignedEvt {
+                                        space: space.clone(),
+                                        peer_data: vec![remote_agent_info_signed],
+                                    })
+                                    .await
+                                {
+                                    tracing::error!(msg = "Failed to store MDNS peer", ?e);
                                 }
                             }
-                        }
-                    });
+                        });
+                    }
                 }
             }
-            NetworkType::QuicBootstrap => {
-                // quic bootstrap is managed for the whole space
-                // see the Space::new() constructor
-            }
         }
 
         Ok(async move { fut.await }.boxed().into())
     }
 
+    fn handle_join_with_gossip_strategy(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+        agent: Arc<KitsuneAgent>,
+        initial_arc: Option<DhtArc>,
+        _gossip_strategy: String,
+    ) -> KitsuneP2pHandlerResult<()> {
+        // The gossip strategy is chosen when the space is spawned.
+        self.handle_join(space, agent, initial_arc)
+    }
+
     fn handle_leave(
         &mut self,
         _space: Arc<KitsuneSpace>,
         agent: Arc<KitsuneAgent>,
     ) -> KitsuneP2pHandlerResult<()> {
         self.local_joined_agents.remove(&agent);
+        if self.local_joined_agents.is_empty() {
+            if let Some(proxy_client_spaces) = &self.proxy_client_spaces {
+                proxy_client_spaces.remove(&self.space);
+            }
+        }
         self.agent_arcs.remove(&agent);
         self.update_metric_exchange_arcset();
         for module in self.gossip_mod.values() {
@@ -693,6 +891,7 @@ impl KitsuneP2pHandler for Space {
         );
 
         let metrics = self.ro_inner.metrics.clone();
+        let bandwidth = self.ro_inner.bandwidth.clone();
 
         Ok(async move {
             match discover_fut.await {
@@ -701,6 +900,10 @@ impl KitsuneP2pHandler for Space {
                     evt_sender.call(space, to_agent, payload).await
                 }
                 discover::PeerDiscoverResult::OkRemote { con_hnd, .. } => {
+                    let peer = con_hnd.peer_cert();
+                    bandwidth
+                        .outgoing_bytes(&space, &peer, payload.len())
+                        .await?;
                     let payload = wire::Wire::call(space.clone(), to_agent.clone(), payload.into());
                     let res = con_hnd.request(&payload, timeout).await?;
                     match res {
@@ -718,6 +921,7 @@ impl KitsuneP2pHandler for Space {
                             metrics
                                 .write()
                                 .record_latency_micros(start.elapsed().as_micros(), [&to_agent]);
+                            bandwidth.incoming_bytes(&space, &peer, data.len()).await?;
                             Ok(data.into())
                         }
                         r => {
@@ -888,8 +1092,12 @@ impl KitsuneP2pHandler for Space {
                 // they'll be responsible for forwarding the data to that node.
                 let mod_cnt = con_list.len();
                 for (mod_idx, (agent, con_hnd)) in con_list.into_iter().enumerate() {
+                    let bandwidth = ro_inner.bandwidth.clone();
+                    let bytes = payload.len();
+                    let space = space.clone();
+
                     // build our delegate message
-                    let payload = wire::Wire::delegate_broadcast(
+                    let delegate = wire::Wire::delegate_broadcast(
                         space.clone(),
                         basis.clone(),
                         agent,
@@ -901,7 +1109,14 @@ impl KitsuneP2pHandler for Space {
 
                     // notify the remote node
                     all.push(async move {
-                        if let Err(err) = con_hnd.notify(&payload, timeout).await {
+                        if let Err(err) = bandwidth
+                            .outgoing_bytes(&space, &con_hnd.peer_cert(), bytes)
+                            .await
+                        {
+                            tracing::warn!(?err, "delegate broadcast error");
+                            return;
+                        }
+                        if let Err(err) = con_hnd.notify(&delegate, timeout).await {
                             tracing::warn!(?err, "delegate broadcast error");
                         }
                     });
@@ -978,6 +1193,17 @@ impl KitsuneP2pHandler for Space {
                                 .await;
                         }
                         discover::PeerDiscoverResult::OkRemote { con_hnd, .. } => {
+                            if let Err(e) = ro_inner
+                                .bandwidth
+                                .outgoing_bytes(&space, &con_hnd.peer_cert(), payload.len())
+                                .await
+                            {
+                                tracing::info!(
+                                    "Failed to broadcast to remote agent because: {:?}",
+                                    e
+                                );
+                                return;
+                            }
                             let payload = wire::Wire::broadcast(
                                 space,
                                 agent,
@@ -1043,19 +1269,32 @@ impl KitsuneP2pHandler for Space {
     ) -> KitsuneP2pHandlerResult<serde_json::Value> {
         let space = self.ro_inner.space.clone();
         let metrics = self.ro_inner.metrics.read().dump();
+        let bandwidth = self.ro_inner.bandwidth.dump(&space);
         Ok(async move {
             Ok(serde_json::json!({
                 "space": space.to_string(),
                 "metrics": metrics,
+                "bandwidth": bandwidth,
             }))
         }
         .boxed()
         .into())
     }
+
+    fn handle_set_bandwidth_limits(
+        &mut self,
+        _scope: crate::bandwidth::BandwidthScope,
+        _limits: crate::bandwidth::BandwidthLimits,
+    ) -> KitsuneP2pHandlerResult<()> {
+        unreachable!(
+            "These requests are handled at the to actor level and are never propagated down to the space."
+        )
+    }
 }
 
 pub(crate) struct SpaceReadOnlyInner {
     pub(crate) space: Arc<KitsuneSpace>,
+    pub(crate) bandwidth: BandwidthBudgets,
     #[allow(dead_code)]
     pub(crate) i_s: ghost_actor::GhostSender<SpaceInternal>,
     pub(crate) evt_sender: futures::channel::mpsc::Sender<KitsuneP2pEvent>,
@@ -1066,6 +1305,8 @@ pub(crate) struct SpaceReadOnlyInner {
     pub(crate) parallel_notify_permit: Arc<tokio::sync::Semaphore>,
     pub(crate) metrics: MetricsSync,
     pub(crate) metric_exchange: MetricExchangeSync,
+    pub(crate) mdns: Option<Mdns>,
+    pub(crate) direct_addrs: Option<ProxyDirectAddrs>,
 }
 
 /// A Kitsune P2p Node can track multiple "spaces" -- Non-interacting namespaced
@@ -1079,9 +1320,10 @@ pub(crate) struct Space {
     pub(crate) local_joined_agents: HashSet<Arc<KitsuneAgent>>,
     pub(crate) agent_arcs: HashMap<Arc<KitsuneAgent>, DhtArc>,
     pub(crate) config: Arc<KitsuneP2pConfig>,
-    mdns_handles: HashMap<Vec<u8>, Arc<AtomicBool>>,
     mdns_listened_spaces: HashSet<String>,
     gossip_mod: HashMap<GossipModuleType, GossipModule>,
+    /// The spaces we declare to our proxy, if enabled.
+    proxy_client_spaces: Option<ProxyClientSpaces>,
 }
 
 impl Space {
@@ -1094,7 +1336,11 @@ impl Space {
         host_api: HostApi,
         ep_hnd: Tx2EpHnd<wire::Wire>,
         config: Arc<KitsuneP2pConfig>,
-        bandwidth_throttles: BandwidthThrottles,
+        gossip_modules: Vec<(GossipModuleType, GossipModuleFactory)>,
+        bandwidth: BandwidthBudgets,
+        mdns: Option<Mdns>,
+        direct_addrs: Option<ProxyDirectAddrs>,
+        proxy_client_spaces: Option<ProxyClientSpaces>,
         parallel_notify_permit: Arc<tokio::sync::Semaphore>,
     ) -> Self {
         let metrics = MetricsSync::default();
@@ -1124,28 +1370,8 @@ impl Space {
             metrics.clone(),
         );
 
-        let gossip_mod = config
-            .tuning_params
-            .gossip_strategy
-            .split(',')
-            .flat_map(|module| match module {
-                "sharded-gossip" => vec![
-                    (
-                        GossipModuleType::ShardedRecent,
-                        crate::gossip::sharded_gossip::recent_factory(bandwidth_throttles.recent()),
-                    ),
-                    (
-                        GossipModuleType::ShardedHistorical,
-                        crate::gossip::sharded_gossip::historical_factory(
-                            bandwidth_throttles.historical(),
-                        ),
-                    ),
-                ],
-                "none" => vec![],
-                _ => {
-                    panic!("unknown gossip strategy: {}", module);
-                }
-            })
+        let gossip_mod = gossip_modules
+            .into_iter()
             .map(|(module, factory)| {
                 (
                     module,
@@ -1171,7 +1397,7 @@ impl Space {
             }
         });
 
-        if let NetworkType::QuicBootstrap = &config.network_type {
+        if config.network_type.uses_bootstrap() {
             // spawn the periodic bootstrap pull
             let i_s_c = i_s.clone();
             let evt_s_c = evt_sender.clone();
@@ -1247,8 +1473,11 @@ impl Space {
             ep_hnd,
             config: config.clone(),
             parallel_notify_permit,
+            bandwidth,
             metrics,
             metric_exchange,
+            mdns,
+            direct_addrs,
         });
 
         Self {
@@ -1260,9 +1489,9 @@ impl Space {
             local_joined_agents: HashSet::new(),
             agent_arcs: HashMap::new(),
             config,
-            mdns_handles: HashMap::new(),
             mdns_listened_spaces: HashSet::new(),
             gossip_mod,
+            proxy_client_spaces,
         }
     }
 
@@ -1284,6 +1513,13 @@ impl Space {
         let evt_sender = self.evt_sender.clone();
         let bootstrap_service = self.config.bootstrap_service.clone();
         let expires_after = self.config.tuning_params.agent_info_expires_after_ms as u64;
+        // Stop broadcasting this agent to the local network
+        if let Some(mdns) = &self.ro_inner.mdns {
+            let (space_b64, agent_b64) = mdns_names(&space, &agent);
+            if let Err(e) = mdns.remove(&space_b64, &agent_b64) {
+                tracing::error!(msg = "Failed to stop MDNS broadcast", ?e);
+            }
+        }
         Ok(async move {
             let signed_at_ms = crate::spawn::actor::bootstrap::now_once(None).await?;
             let expires_at_ms = signed_at_ms + expires_after;
@@ -1322,15 +1558,9 @@ impl Space {
                 .await?;
 
             // Push to the network as well
-            match network_type {
-                NetworkType::QuicMdns => tracing::warn!("NOT publishing leaves to mdns"),
-                NetworkType::QuicBootstrap => {
-                    crate::spawn::actor::bootstrap::put(
-                        bootstrap_service.clone(),
-                        agent_info_signed,
-                    )
+            if network_type.uses_bootstrap() {
+                crate::spawn::actor::bootstrap::put(bootstrap_service.clone(), agent_info_signed)
                     .await?;
-                }
             }
 
             Ok(())
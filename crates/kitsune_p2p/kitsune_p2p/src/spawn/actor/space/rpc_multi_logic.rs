@@ -358,6 +358,16 @@ impl Outer {
                             PeerDiscoverResult::OkRemote { con_hnd, .. } => con_hnd,
                         };
 
+                    if let Err(err) = ro_inner
+                        .bandwidth
+                        .outgoing_bytes(&space, &con_hnd.peer_cert(), payload.len())
+                        .await
+                    {
+                        tracing::warn!(?err, "remote call error");
+                        permit.close();
+                        return;
+                    }
+
                     let msg = wire::Wire::call(space, info.agent.clone(), payload.into());
 
                     let start = tokio::time::Instant::now();
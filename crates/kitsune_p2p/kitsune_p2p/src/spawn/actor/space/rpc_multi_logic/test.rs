@@ -248,6 +248,7 @@ async fn test_rpc_multi_logic_mocked() {
         parallel_notify_permit: Arc::new(tokio::sync::Semaphore::new(
             config.tuning_params.concurrent_limit_per_thread,
         )),
+        bandwidth: BandwidthBudgets::new(&config.tuning_params),
         config,
         metrics,
         metric_exchange,
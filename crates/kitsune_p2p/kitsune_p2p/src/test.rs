@@ -320,6 +320,81 @@ mod tests {
         Ok(())
     }
 
+    /// Test that a hybrid node stores agents found via mdns
+    /// as well as agents found via the bootstrap service.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_hybrid_discovery() -> Result<(), KitsuneP2pError> {
+        observability::test_run().ok();
+
+        let (driver, addr, shutdown) = kitsune_p2p_bootstrap::run_with_config(
+            ([127, 0, 0, 1], 0),
+            kitsune_p2p_bootstrap::BootstrapConfig {
+                verify_signatures: false,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        tokio::task::spawn(driver);
+        let bootstrap_service: Option<BootstrapService> =
+            Some(url2::url2!("http://{}", addr).into());
+
+        // Keep the mdns traffic of this test on loopback and off the real mdns port.
+        let mdns_port = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut tuning_params =
+            kitsune_p2p_types::config::tuning_params_struct::KitsuneP2pTuningParams::default();
+        tuning_params.mdns_interface = "127.0.0.1".to_string();
+        tuning_params.mdns_port = mdns_port;
+        let tuning_params = Arc::new(tuning_params);
+        let config = |network_type, bootstrap_service| KitsuneP2pConfig {
+            network_type,
+            bootstrap_service,
+            tuning_params: tuning_params.clone(),
+            ..Default::default()
+        };
+
+        let (harness, _evt) = spawn_test_harness_quic().await?;
+        harness.add_space().await?;
+
+        let (hybrid, _) = harness
+            .add_direct_agent_with_config(
+                "HYBRID".into(),
+                config(NetworkType::QuicHybrid, bootstrap_service.clone()),
+            )
+            .await?;
+        let (mdns_only, _) = harness
+            .add_direct_agent_with_config("MDNS".into(), config(NetworkType::QuicMdns, None))
+            .await?;
+        let (bootstrap_only, _) = harness
+            .add_direct_agent_with_config(
+                "BOOTSTRAP".into(),
+                config(NetworkType::QuicBootstrap, bootstrap_service),
+            )
+            .await?;
+
+        let mut found = false;
+        for _ in 0..60 {
+            let res = harness.dump_local_peer_data(hybrid.clone()).await?;
+            if res.contains_key(&mdns_only) && res.contains_key(&bootstrap_only) {
+                found = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+        assert!(
+            found,
+            "hybrid node did not find both the mdns and the bootstrap agent"
+        );
+
+        harness.ghost_actor_shutdown().await?;
+        shutdown();
+        Ok(())
+    }
+
     /// Test that we can publish agent info.
     #[tokio::test(flavor = "multi_thread")]
     // @freesig Can anyone think of a better way to do this?
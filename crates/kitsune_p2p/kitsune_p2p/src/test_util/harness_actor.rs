@@ -24,6 +24,14 @@ ghost_actor::ghost_chan! {
             ghost_actor::GhostSender<KitsuneP2p>,
         );
 
+        /// Create a new directly addressable agent that will
+        /// reject any proxy requests, using the network type,
+        /// bootstrap service and tuning params from `config`.
+        fn add_direct_agent_with_config(nick: String, config: KitsuneP2pConfig) -> (
+            Arc<KitsuneAgent>,
+            ghost_actor::GhostSender<KitsuneP2p>,
+        );
+
         /// Create a new directly addressable agent that will
         /// reject any proxy requests.
         fn add_publish_only_agent(nick: String) -> (
@@ -277,6 +285,32 @@ impl HarnessControlApiHandler for HarnessActor {
         .into())
     }
 
+    fn handle_add_direct_agent_with_config(
+        &mut self,
+        nick: String,
+        mut config: KitsuneP2pConfig,
+    ) -> HarnessControlApiHandlerResult<(Arc<KitsuneAgent>, ghost_actor::GhostSender<KitsuneP2p>)>
+    {
+        config.transport_pool.push(TransportConfig::Proxy {
+            sub_transport: Box::new(self.sub_config.clone()),
+            proxy_config: ProxyConfig::LocalProxyServer {
+                proxy_accept_config: Some(ProxyAcceptConfig::RejectAll),
+            },
+        });
+
+        let sub_harness = self.harness_chan.sub_clone(nick);
+        let i_s = self.i_s.clone();
+        Ok(async move {
+            let (agent, p2p, ctrl) = spawn_test_agent(sub_harness, config).await?;
+
+            i_s.finish_agent(agent.clone(), p2p.clone(), ctrl).await?;
+
+            Ok((agent, p2p))
+        }
+        .boxed()
+        .into())
+    }
+
     fn handle_add_publish_only_agent(
         &mut self,
         nick: String,
@@ -1,6 +1,7 @@
 //! An in-memory network for sharded kitsune tests.
 
-use crate::gossip::sharded_gossip::{BandwidthThrottle, GossipType, ShardedGossip};
+use crate::bandwidth::{BandwidthBudgets, BandwidthLimits, BandwidthScope};
+use crate::gossip::sharded_gossip::{GossipType, ShardedGossip};
 use crate::test_util::spawn_handler;
 use crate::types::gossip::*;
 use crate::types::wire;
@@ -124,7 +125,15 @@ impl Switchboard {
         let host = HostStub::new();
         let (evt_sender, handler_task) = spawn_handler(evt_handler.clone()).await;
 
-        let bandwidth = Arc::new(BandwidthThrottle::new(1000.0, 1000.0));
+        let bandwidth = BandwidthBudgets::new(&tuning_params);
+        bandwidth.set_limits(
+            BandwidthScope::Gossip(self.gossip_type),
+            BandwidthLimits {
+                inbound_mbps: 1000.0,
+                outbound_mbps: 1000.0,
+                daily_byte_cap: 0,
+            },
+        );
 
         let gossip = ShardedGossip::new(
             tuning_params,
@@ -44,6 +44,10 @@ pub enum KitsuneP2pError {
     #[error(transparent)]
     TryFromInt(#[from] std::num::TryFromIntError),
 
+    /// The daily byte cap of a bandwidth budget has been reached.
+    #[error("Daily bandwidth cap of {0} bytes reached")]
+    BandwidthCapReached(u64),
+
     /// Other
     #[error("Other: {0}")]
     Other(Box<dyn std::error::Error + Send + Sync>),
@@ -91,6 +95,7 @@ pub mod agent_store {
 }
 
 pub mod actor;
+pub mod bandwidth;
 pub mod event;
 pub(crate) mod gossip;
 #[allow(missing_docs)]
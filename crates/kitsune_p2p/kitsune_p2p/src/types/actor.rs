@@ -87,6 +87,12 @@ ghost_actor::ghost_chan! {
         /// Announce a space/agent pair on this network.
         fn join(space: KSpace, agent: KAgent, initial_arc: OptArc) -> ();
 
+        /// Announce a space/agent pair on this network.
+        /// If this is the first agent to join the space it will run the
+        /// named gossip strategy instead of the `gossip_strategy` tuning param.
+        /// Joining a running space with a different strategy is an error.
+        fn join_with_gossip_strategy(space: KSpace, agent: KAgent, initial_arc: OptArc, gossip_strategy: String) -> ();
+
         /// Withdraw this space/agent pair from this network.
         fn leave(space: KSpace, agent: KAgent) -> ();
 
@@ -141,5 +147,11 @@ ghost_actor::ghost_chan! {
         fn dump_network_metrics(
             space: KSpaceOpt,
         ) -> serde_json::Value;
+
+        /// Change the limits of a bandwidth budget at runtime.
+        fn set_bandwidth_limits(
+            scope: crate::bandwidth::BandwidthScope,
+            limits: crate::bandwidth::BandwidthLimits,
+        ) -> ();
     }
 }
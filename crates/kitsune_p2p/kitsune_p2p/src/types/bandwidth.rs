@@ -0,0 +1,465 @@
+//! Bandwidth budgets covering all the traffic of a kitsune node.
+//!
+//! Every message is throttled by the node's total budget, the budget
+//! of the space it belongs to and the budget of the peer it is
+//! exchanged with. Gossip is additionally throttled by its loop.
+
+use crate::gossip::sharded_gossip::{BandwidthThrottle, BandwidthThrottles, GossipType};
+use crate::types::*;
+use kitsune_p2p_types::config::KitsuneP2pTuningParams;
+use kitsune_p2p_types::Tx2Cert;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// The number of seconds in a day.
+const DAY_S: u64 = 60 * 60 * 24;
+
+/// Peer budgets that haven't been used for this many seconds are dropped.
+const PEER_IDLE_S: u64 = 60 * 5;
+
+/// The limits of a bandwidth budget.
+/// A limit of zero is no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BandwidthLimits {
+    /// Inbound rate limit in megabits per second.
+    pub inbound_mbps: f64,
+    /// Outbound rate limit in megabits per second.
+    pub outbound_mbps: f64,
+    /// Hard cap on the bytes sent and received each day (UTC).
+    pub daily_byte_cap: u64,
+}
+
+/// The budget a set of [`BandwidthLimits`] applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BandwidthScope {
+    /// All traffic of this node.
+    Total,
+    /// All traffic of a single space.
+    Space(Arc<KitsuneSpace>),
+    /// The traffic exchanged with each peer.
+    Peers,
+    /// Gossip sent and received by one of the sharded gossip loops.
+    Gossip(GossipType),
+}
+
+/// Current usage of a bandwidth budget.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BandwidthUsage {
+    /// The limits of the budget.
+    pub limits: BandwidthLimits,
+    /// Total bytes received.
+    pub bytes_inbound: u64,
+    /// Total bytes sent.
+    pub bytes_outbound: u64,
+    /// Bytes sent and received today (UTC).
+    pub bytes_today: u64,
+}
+
+/// A rate limited budget with an optional daily byte cap.
+pub struct BandwidthBudget {
+    throttle: BandwidthThrottle,
+    daily_byte_cap: AtomicU64,
+    day: AtomicU64,
+    bytes_today: AtomicU64,
+    last_used_s: AtomicU64,
+}
+
+impl BandwidthBudget {
+    /// Create a budget with these limits.
+    pub fn new(limits: BandwidthLimits) -> Self {
+        Self {
+            throttle: BandwidthThrottle::new(limits.inbound_mbps, limits.outbound_mbps),
+            daily_byte_cap: AtomicU64::new(limits.daily_byte_cap),
+            day: AtomicU64::new(today()),
+            bytes_today: AtomicU64::new(0),
+            last_used_s: AtomicU64::new(now_s()),
+        }
+    }
+
+    /// Change the limits of this budget.
+    pub fn set_limits(&self, limits: BandwidthLimits) {
+        self.throttle
+            .set_limits(limits.inbound_mbps, limits.outbound_mbps);
+        self.daily_byte_cap
+            .store(limits.daily_byte_cap, Ordering::Relaxed);
+    }
+
+    /// The current limits of this budget.
+    pub fn limits(&self) -> BandwidthLimits {
+        let (inbound_mbps, outbound_mbps) = self.throttle.limits();
+        BandwidthLimits {
+            inbound_mbps,
+            outbound_mbps,
+            daily_byte_cap: self.daily_byte_cap.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The current usage of this budget.
+    pub fn usage(&self) -> BandwidthUsage {
+        self.roll_day();
+        BandwidthUsage {
+            limits: self.limits(),
+            bytes_inbound: self.throttle.bytes_inbound(),
+            bytes_outbound: self.throttle.bytes_outbound(),
+            bytes_today: self.bytes_today.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Wait until there's enough bandwidth to send this many bytes.
+    /// Fails without waiting if the daily byte cap has been reached.
+    pub async fn outgoing_bytes(&self, bytes: usize) -> KitsuneP2pResult<()> {
+        spend_all(&[self], bytes)?;
+        self.throttle.outgoing_bytes(bytes).await;
+        Ok(())
+    }
+
+    /// Wait until there's enough bandwidth to receive this many bytes.
+    /// Fails without waiting if the daily byte cap has been reached.
+    pub async fn incoming_bytes(&self, bytes: usize) -> KitsuneP2pResult<()> {
+        spend_all(&[self], bytes)?;
+        self.throttle.incoming_bytes(bytes).await;
+        Ok(())
+    }
+
+    /// Check these bytes fit under the daily cap without counting them.
+    fn check(&self, bytes: usize) -> KitsuneP2pResult<()> {
+        self.roll_day();
+        let cap = self.daily_byte_cap.load(Ordering::Relaxed);
+        if cap != 0 && self.bytes_today.load(Ordering::Relaxed) + bytes as u64 > cap {
+            return Err(KitsuneP2pError::BandwidthCapReached(cap));
+        }
+        Ok(())
+    }
+
+    /// Count bytes against the daily cap.
+    fn spend(&self, bytes: usize) -> KitsuneP2pResult<()> {
+        self.roll_day();
+        self.last_used_s.store(now_s(), Ordering::Relaxed);
+        let cap = self.daily_byte_cap.load(Ordering::Relaxed);
+        let bytes = bytes as u64;
+        let spent = self.bytes_today.fetch_add(bytes, Ordering::Relaxed);
+        if cap != 0 && spent + bytes > cap {
+            self.refund(bytes as usize);
+            return Err(KitsuneP2pError::BandwidthCapReached(cap));
+        }
+        Ok(())
+    }
+
+    /// Give back bytes that were counted but never sent or received.
+    fn refund(&self, bytes: usize) {
+        self.bytes_today.fetch_sub(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Has this budget been unused for long enough that dropping it
+    /// loses nothing that counts against today's cap.
+    fn is_idle(&self) -> bool {
+        self.roll_day();
+        let idle = now_s().saturating_sub(self.last_used_s.load(Ordering::Relaxed)) >= PEER_IDLE_S;
+        let counts_today = self.daily_byte_cap.load(Ordering::Relaxed) != 0
+            && self.bytes_today.load(Ordering::Relaxed) != 0;
+        idle && !counts_today
+    }
+
+    /// Reset the daily count when the day changes.
+    fn roll_day(&self) {
+        let today = today();
+        if self.day.swap(today, Ordering::Relaxed) != today {
+            self.bytes_today.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The bandwidth budgets of a kitsune node.
+#[derive(Clone)]
+pub struct BandwidthBudgets(Arc<BandwidthBudgetsInner>);
+
+struct BandwidthBudgetsInner {
+    total: BandwidthBudget,
+    gossip: BandwidthThrottles,
+    space_limits: parking_lot::RwLock<BandwidthLimits>,
+    peer_limits: parking_lot::RwLock<BandwidthLimits>,
+    spaces: parking_lot::RwLock<HashMap<Arc<KitsuneSpace>, Arc<BandwidthBudget>>>,
+    peers: parking_lot::RwLock<HashMap<Tx2Cert, Arc<BandwidthBudget>>>,
+}
+
+impl BandwidthBudgets {
+    /// Create the budgets from the configuration.
+    pub fn new(tuning_params: &KitsuneP2pTuningParams) -> Self {
+        let total = BandwidthLimits {
+            inbound_mbps: tuning_params.bandwidth_total_inbound_target_mbps,
+            outbound_mbps: tuning_params.bandwidth_total_outbound_target_mbps,
+            daily_byte_cap: tuning_params.bandwidth_daily_byte_cap,
+        };
+        let space_limits = BandwidthLimits {
+            inbound_mbps: tuning_params.bandwidth_space_inbound_target_mbps,
+            outbound_mbps: tuning_params.bandwidth_space_outbound_target_mbps,
+            daily_byte_cap: 0,
+        };
+        let peer_limits = BandwidthLimits {
+            inbound_mbps: tuning_params.bandwidth_peer_inbound_target_mbps,
+            outbound_mbps: tuning_params.bandwidth_peer_outbound_target_mbps,
+            daily_byte_cap: 0,
+        };
+        Self(Arc::new(BandwidthBudgetsInner {
+            total: BandwidthBudget::new(total),
+            gossip: BandwidthThrottles::new(tuning_params),
+            space_limits: parking_lot::RwLock::new(space_limits),
+            peer_limits: parking_lot::RwLock::new(peer_limits),
+            spaces: parking_lot::RwLock::new(HashMap::new()),
+            peers: parking_lot::RwLock::new(HashMap::new()),
+        }))
+    }
+
+    /// The throttles of the sharded gossip loops.
+    pub fn gossip(&self) -> &BandwidthThrottles {
+        &self.0.gossip
+    }
+
+    /// The budget of a space.
+    pub fn space(&self, space: &Arc<KitsuneSpace>) -> Arc<BandwidthBudget> {
+        if let Some(budget) = self.0.spaces.read().get(space) {
+            return budget.clone();
+        }
+        let limits = *self.0.space_limits.read();
+        self.0
+            .spaces
+            .write()
+            .entry(space.clone())
+            .or_insert_with(|| Arc::new(BandwidthBudget::new(limits)))
+            .clone()
+    }
+
+    /// The budget of a peer.
+    /// Adding a new peer drops the budgets of peers that have gone idle.
+    pub fn peer(&self, peer: &Tx2Cert) -> Arc<BandwidthBudget> {
+        if let Some(budget) = self.0.peers.read().get(peer) {
+            return budget.clone();
+        }
+        let limits = *self.0.peer_limits.read();
+        let mut peers = self.0.peers.write();
+        peers.retain(|_, budget| Arc::strong_count(budget) > 1 || !budget.is_idle());
+        peers
+            .entry(peer.clone())
+            .or_insert_with(|| Arc::new(BandwidthBudget::new(limits)))
+            .clone()
+    }
+
+    /// Drop the budget of a peer we are no longer connected to,
+    /// unless it still counts bytes against today's cap.
+    pub fn remove_peer(&self, peer: &Tx2Cert) {
+        let mut peers = self.0.peers.write();
+        if let Some(budget) = peers.get(peer) {
+            let counts_today = budget.daily_byte_cap.load(Ordering::Relaxed) != 0
+                && budget.usage().bytes_today != 0;
+            if Arc::strong_count(budget) == 1 && !counts_today {
+                peers.remove(peer);
+            }
+        }
+    }
+
+    /// Change the limits of a budget.
+    /// Changing the limits of [`BandwidthScope::Peers`] applies
+    /// to every current peer and all future peers.
+    pub fn set_limits(&self, scope: BandwidthScope, limits: BandwidthLimits) {
+        match scope {
+            BandwidthScope::Total => self.0.total.set_limits(limits),
+            BandwidthScope::Space(space) => self.space(&space).set_limits(limits),
+            BandwidthScope::Peers => {
+                *self.0.peer_limits.write() = limits;
+                for budget in self.0.peers.read().values() {
+                    budget.set_limits(limits);
+                }
+            }
+            BandwidthScope::Gossip(gossip_type) => self
+                .0
+                .gossip
+                .for_gossip_type(gossip_type)
+                .set_limits(limits.inbound_mbps, limits.outbound_mbps),
+        }
+    }
+
+    /// Wait until the total, space and peer budgets
+    /// have enough bandwidth to send this many bytes.
+    pub async fn outgoing_bytes(
+        &self,
+        space: &Arc<KitsuneSpace>,
+        peer: &Tx2Cert,
+        bytes: usize,
+    ) -> KitsuneP2pResult<()> {
+        let space = self.space(space);
+        let peer = self.peer(peer);
+        let budgets = [&self.0.total, &*space, &*peer];
+        spend_all(&budgets, bytes)?;
+        for budget in budgets {
+            budget.throttle.outgoing_bytes(bytes).await;
+        }
+        Ok(())
+    }
+
+    /// Wait until the total, space and peer budgets
+    /// have enough bandwidth to receive this many bytes.
+    pub async fn incoming_bytes(
+        &self,
+        space: &Arc<KitsuneSpace>,
+        peer: &Tx2Cert,
+        bytes: usize,
+    ) -> KitsuneP2pResult<()> {
+        let space = self.space(space);
+        let peer = self.peer(peer);
+        let budgets = [&self.0.total, &*space, &*peer];
+        spend_all(&budgets, bytes)?;
+        for budget in budgets {
+            budget.throttle.incoming_bytes(bytes).await;
+        }
+        Ok(())
+    }
+
+    /// Dump the usage of the budgets that cover a space.
+    pub fn dump(&self, space: &Arc<KitsuneSpace>) -> serde_json::Value {
+        let gossip_usage = |throttle: Arc<BandwidthThrottle>| {
+            let (inbound_mbps, outbound_mbps) = throttle.limits();
+            serde_json::json!({
+                "inbound_mbps": inbound_mbps,
+                "outbound_mbps": outbound_mbps,
+                "bytes_inbound": throttle.bytes_inbound(),
+                "bytes_outbound": throttle.bytes_outbound(),
+            })
+        };
+        let peers = self
+            .0
+            .peers
+            .read()
+            .iter()
+            .map(|(peer, budget)| (peer.as_str().to_string(), budget.usage()))
+            .collect::<HashMap<_, _>>();
+        serde_json::json!({
+            "total": self.0.total.usage(),
+            "space": self.space(space).usage(),
+            "peers": peers,
+            "gossip_recent": gossip_usage(self.0.gossip.recent()),
+            "gossip_historical": gossip_usage(self.0.gossip.historical()),
+        })
+    }
+}
+
+/// Count bytes against every budget's daily cap, or against none of
+/// them if any budget would go over its cap.
+fn spend_all(budgets: &[&BandwidthBudget], bytes: usize) -> KitsuneP2pResult<()> {
+    for budget in budgets {
+        budget.check(bytes)?;
+    }
+    for (i, budget) in budgets.iter().enumerate() {
+        if let Err(e) = budget.spend(bytes) {
+            // Another task spent the last of the cap since we checked.
+            for spent in &budgets[..i] {
+                spent.refund(bytes);
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// The current time as seconds since the UNIX epoch.
+fn now_s() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// The current day (UTC) as days since the UNIX epoch.
+fn today() -> u64 {
+    now_s() / DAY_S
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn daily_byte_cap_is_enforced() {
+        let budget = BandwidthBudget::new(BandwidthLimits {
+            daily_byte_cap: 100,
+            ..Default::default()
+        });
+        budget.outgoing_bytes(60).await.unwrap();
+        budget.incoming_bytes(40).await.unwrap();
+        assert!(matches!(
+            budget.outgoing_bytes(1).await,
+            Err(KitsuneP2pError::BandwidthCapReached(100))
+        ));
+        let usage = budget.usage();
+        assert_eq!(usage.bytes_today, 100);
+        assert_eq!(usage.bytes_outbound, 60);
+        assert_eq!(usage.bytes_inbound, 40);
+
+        // Raising the cap at runtime lets traffic through again.
+        budget.set_limits(BandwidthLimits {
+            daily_byte_cap: 200,
+            ..Default::default()
+        });
+        budget.outgoing_bytes(1).await.unwrap();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn capped_budget_charges_no_other_budget() {
+        let budgets = BandwidthBudgets::new(&Default::default());
+        let space = Arc::new(KitsuneSpace(vec![0; 36]));
+        let peer = Tx2Cert::from(vec![1; 32]);
+        budgets.set_limits(
+            BandwidthScope::Peers,
+            BandwidthLimits {
+                daily_byte_cap: 100,
+                ..Default::default()
+            },
+        );
+        budgets.outgoing_bytes(&space, &peer, 100).await.unwrap();
+        assert!(matches!(
+            budgets.outgoing_bytes(&space, &peer, 1).await,
+            Err(KitsuneP2pError::BandwidthCapReached(100))
+        ));
+        // The rejected bytes were not counted against the total or space.
+        assert_eq!(budgets.0.total.usage().bytes_today, 100);
+        assert_eq!(budgets.space(&space).usage().bytes_today, 100);
+    }
+
+    #[test]
+    fn idle_and_disconnected_peers_are_dropped() {
+        let budgets = BandwidthBudgets::new(&Default::default());
+        let peer = Tx2Cert::from(vec![1; 32]);
+        let other = Tx2Cert::from(vec![2; 32]);
+
+        // A budget in use is kept.
+        let budget = budgets.peer(&peer);
+        budgets.remove_peer(&peer);
+        assert_eq!(budgets.0.peers.read().len(), 1);
+        drop(budget);
+        budgets.remove_peer(&peer);
+        assert!(budgets.0.peers.read().is_empty());
+
+        budgets.peer(&peer).last_used_s.store(0, Ordering::Relaxed);
+        budgets.peer(&other);
+        let peers = budgets.0.peers.read();
+        assert_eq!(peers.len(), 1);
+        assert!(peers.contains_key(&other));
+    }
+
+    #[test]
+    fn peer_limits_apply_to_current_and_future_peers() {
+        let budgets = BandwidthBudgets::new(&Default::default());
+        let peer = Tx2Cert::from(vec![1; 32]);
+        assert_eq!(budgets.peer(&peer).limits(), BandwidthLimits::default());
+
+        let limits = BandwidthLimits {
+            inbound_mbps: 1.0,
+            outbound_mbps: 2.0,
+            daily_byte_cap: 3,
+        };
+        budgets.set_limits(BandwidthScope::Peers, limits);
+        assert_eq!(budgets.peer(&peer).limits(), limits);
+        let other = Tx2Cert::from(vec![2; 32]);
+        assert_eq!(budgets.peer(&other).limits(), limits);
+    }
+}
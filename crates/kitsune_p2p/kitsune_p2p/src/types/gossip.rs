@@ -1,3 +1,4 @@
+use crate::bandwidth::BandwidthBudgets;
 use crate::metrics::*;
 use crate::types::*;
 use crate::HostApi;
@@ -5,8 +6,16 @@ use kitsune_p2p_types::config::*;
 use kitsune_p2p_types::tx2::tx2_api::*;
 use kitsune_p2p_types::tx2::tx2_utils::TxUrl;
 use kitsune_p2p_types::*;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
 
+/// The built in sharded gossip strategy.
+pub const SHARDED_GOSSIP_STRATEGY: &str = "sharded-gossip";
+
+/// The strategy that runs no gossip at all.
+pub const NO_GOSSIP_STRATEGY: &str = "none";
+
 #[derive(Clone, Debug, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq, Hash)]
 /// The type of gossip module running this gossip.
 pub enum GossipModuleType {
@@ -16,30 +25,41 @@ pub enum GossipModuleType {
     ShardedRecent,
     /// Historical sharded gossip.
     ShardedHistorical,
+    /// A gossip module registered by the application.
+    /// The id must be the same on every node running the module.
+    Custom(u16),
 }
 
 /// Represents an interchangeable gossip strategy module
 pub trait AsGossipModule: 'static + Send + Sync {
+    /// Stop the module's gossip loop.
     fn close(&self);
+    /// Handle gossip sent to this module by a remote node.
     fn incoming_gossip(
         &self,
         con: Tx2ConHnd<wire::Wire>,
         remote_url: TxUrl,
         gossip_data: Box<[u8]>,
     ) -> KitsuneResult<()>;
+    /// A local agent has joined the space.
     fn local_agent_join(&self, a: Arc<KitsuneAgent>);
+    /// A local agent has left the space.
     fn local_agent_leave(&self, a: Arc<KitsuneAgent>);
+    /// New data has been integrated and is ready for gossiping.
     fn new_integrated_data(&self) {}
 }
 
+/// A running gossip module.
 #[derive(Clone)]
 pub struct GossipModule(pub Arc<dyn AsGossipModule>);
 
 impl GossipModule {
+    /// Stop the module's gossip loop.
     pub fn close(&self) {
         self.0.close()
     }
 
+    /// Handle gossip sent to this module by a remote node.
     pub fn incoming_gossip(
         &self,
         con: Tx2ConHnd<wire::Wire>,
@@ -49,10 +69,12 @@ impl GossipModule {
         self.0.incoming_gossip(con, remote_url, gossip_data)
     }
 
+    /// A local agent has joined the space.
     pub fn local_agent_join(&self, a: Arc<KitsuneAgent>) {
         self.0.local_agent_join(a);
     }
 
+    /// A local agent has left the space.
     pub fn local_agent_leave(&self, a: Arc<KitsuneAgent>) {
         self.0.local_agent_leave(a);
     }
@@ -71,6 +93,7 @@ impl std::fmt::Debug for GossipModule {
 
 /// Represents an interchangeable gossip strategy module factory
 pub trait AsGossipModuleFactory: 'static + Send + Sync {
+    /// Spawn the gossip module for a space.
     fn spawn_gossip_task(
         &self,
         tuning_params: KitsuneP2pTuningParams,
@@ -82,9 +105,12 @@ pub trait AsGossipModuleFactory: 'static + Send + Sync {
     ) -> GossipModule;
 }
 
+/// Spawns a gossip module for each space that runs it.
+#[derive(Clone)]
 pub struct GossipModuleFactory(pub Arc<dyn AsGossipModuleFactory>);
 
 impl GossipModuleFactory {
+    /// Spawn the gossip module for a space.
     pub fn spawn_gossip_task(
         &self,
         tuning_params: KitsuneP2pTuningParams,
@@ -98,3 +124,169 @@ impl GossipModuleFactory {
             .spawn_gossip_task(tuning_params, space, ep_hnd, evt_sender, host, metrics)
     }
 }
+
+/// Gossip strategies registered by the application embedding kitsune,
+/// passed to [`spawn_kitsune_p2p_with_gossip_strategies`](crate::spawn_kitsune_p2p_with_gossip_strategies).
+#[derive(Clone, Default)]
+pub struct GossipStrategies(HashMap<String, Vec<(GossipModuleType, GossipModuleFactory)>>);
+
+impl std::fmt::Debug for GossipStrategies {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+impl GossipStrategies {
+    /// Register a gossip strategy under a name so spaces can run it by
+    /// setting the `gossip_strategy` tuning param or joining with
+    /// [`join_with_gossip_strategy`](crate::actor::KitsuneP2pSender::join_with_gossip_strategy).
+    /// Every module must use a [`GossipModuleType::Custom`] type so
+    /// its gossip can be routed alongside the built in modules.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        modules: Vec<(GossipModuleType, GossipModuleFactory)>,
+    ) -> KitsuneP2pResult<()> {
+        let name = name.into();
+        if name.contains(',') || name == SHARDED_GOSSIP_STRATEGY || name == NO_GOSSIP_STRATEGY {
+            return Err(format!("invalid gossip strategy name: {}", name).into());
+        }
+        if let Some((module, _)) = modules
+            .iter()
+            .find(|(module, _)| !matches!(module, GossipModuleType::Custom(_)))
+        {
+            return Err(format!(
+                "gossip strategy {} can't register built in module {:?}",
+                name, module
+            )
+            .into());
+        }
+        match self.0.entry(name) {
+            std::collections::hash_map::Entry::Occupied(e) => {
+                Err(format!("gossip strategy {} is already registered", e.key()).into())
+            }
+            std::collections::hash_map::Entry::Vacant(e) => {
+                e.insert(modules);
+                Ok(())
+            }
+        }
+    }
+
+    /// Resolve a comma separated list of gossip strategies
+    /// to the gossip modules a space should run.
+    pub(crate) fn modules(
+        &self,
+        gossip_strategy: &str,
+        bandwidth: &BandwidthBudgets,
+    ) -> KitsuneP2pResult<Vec<(GossipModuleType, GossipModuleFactory)>> {
+        let mut out = Vec::new();
+        for strategy in gossip_strategy.split(',') {
+            match strategy {
+                SHARDED_GOSSIP_STRATEGY => {
+                    out.push((
+                        GossipModuleType::ShardedRecent,
+                        crate::gossip::sharded_gossip::recent_factory(bandwidth.clone()),
+                    ));
+                    out.push((
+                        GossipModuleType::ShardedHistorical,
+                        crate::gossip::sharded_gossip::historical_factory(bandwidth.clone()),
+                    ));
+                }
+                NO_GOSSIP_STRATEGY => (),
+                _ => match self.0.get(strategy) {
+                    Some(modules) => out.extend(modules.iter().cloned()),
+                    None => return Err(format!("unknown gossip strategy: {}", strategy).into()),
+                },
+            }
+        }
+        let mut seen = HashSet::new();
+        if let Some((module, _)) = out.iter().find(|(module, _)| !seen.insert(*module)) {
+            return Err(format!(
+                "gossip module {:?} is run by more than one strategy in {}",
+                module, gossip_strategy
+            )
+            .into());
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoopGossipFactory;
+
+    impl AsGossipModuleFactory for NoopGossipFactory {
+        fn spawn_gossip_task(
+            &self,
+            _tuning_params: KitsuneP2pTuningParams,
+            _space: Arc<KitsuneSpace>,
+            _ep_hnd: Tx2EpHnd<wire::Wire>,
+            _evt_sender: futures::channel::mpsc::Sender<event::KitsuneP2pEvent>,
+            _host: HostApi,
+            _metrics: MetricsSync,
+        ) -> GossipModule {
+            unreachable!("the registry tests never spawn gossip")
+        }
+    }
+
+    fn noop_factory() -> GossipModuleFactory {
+        GossipModuleFactory(Arc::new(NoopGossipFactory))
+    }
+
+    #[test]
+    fn registered_gossip_strategies_resolve() {
+        let bandwidth = BandwidthBudgets::new(&Default::default());
+        let mut strategies = GossipStrategies::default();
+
+        strategies
+            .register(
+                "test-push-only",
+                vec![(GossipModuleType::Custom(1), noop_factory())],
+            )
+            .unwrap();
+        // Names can only be registered once.
+        assert!(strategies
+            .register(
+                "test-push-only",
+                vec![(GossipModuleType::Custom(2), noop_factory())],
+            )
+            .is_err());
+        // Built in names and modules are reserved.
+        assert!(strategies
+            .register(
+                SHARDED_GOSSIP_STRATEGY,
+                vec![(GossipModuleType::Custom(3), noop_factory())],
+            )
+            .is_err());
+        assert!(strategies
+            .register(
+                "test-recent-only",
+                vec![(GossipModuleType::ShardedRecent, noop_factory())],
+            )
+            .is_err());
+
+        let modules = strategies.modules("test-push-only", &bandwidth).unwrap();
+        assert_eq!(
+            modules.iter().map(|(m, _)| *m).collect::<Vec<_>>(),
+            vec![GossipModuleType::Custom(1)]
+        );
+        let modules = strategies
+            .modules("sharded-gossip,test-push-only", &bandwidth)
+            .unwrap();
+        assert_eq!(
+            modules.iter().map(|(m, _)| *m).collect::<Vec<_>>(),
+            vec![
+                GossipModuleType::ShardedRecent,
+                GossipModuleType::ShardedHistorical,
+                GossipModuleType::Custom(1)
+            ]
+        );
+        assert!(strategies.modules("none", &bandwidth).unwrap().is_empty());
+        assert!(strategies.modules("test-unknown", &bandwidth).is_err());
+        assert!(strategies
+            .modules("test-push-only,test-push-only", &bandwidth)
+            .is_err());
+    }
+}
@@ -57,7 +57,7 @@ async fn test_integrated_proxy_list() {
     let k_tls = TlsConfig::new_ephemeral().await.unwrap();
     let mut kconf = KitsuneP2pConfig::default();
     kconf.tuning_params = tuning_params.clone();
-    kconf.bootstrap_service = Some(bootstrap_url.clone());
+    kconf.bootstrap_service = Some(bootstrap_url.clone().into());
     kconf.transport_pool = vec![TransportConfig::Proxy {
         sub_transport: Box::new(TransportConfig::Quic {
             bind_to: Some(url2::Url2::parse("kitsune-quic://127.0.0.1:0")),
@@ -4,18 +4,13 @@ use kitsune_p2p_mdns::*;
 #[tokio::main]
 async fn main() {
     println!("Starting discovery");
-    let service_name = "bobby".to_owned();
+    let mdns = Mdns::bind(MdnsConfig::default())
+        .await
+        .expect("failed to bind mdns socket");
     // Start Stream
-    let stream = mdns_listen(service_name);
+    let stream = mdns.listen("bobby").expect("invalid service type");
     pin_mut!(stream);
-    while let Some(maybe_response) = stream.next().await {
-        match maybe_response {
-            Ok(response) => {
-                println!("Discovered: {:?}", response);
-            }
-            Err(e) => {
-                println!("!!! Discovery Error: {:?}", e);
-            }
-        }
+    while let Some(response) = stream.next().await {
+        println!("Discovered: {:?}", response);
     }
 }
@@ -0,0 +1,307 @@
+//! Just enough of the DNS wire format (RFC 1035) to send and receive
+//! the PTR and TXT records mdns service discovery is built on.
+//! Other record types are skipped when decoding.
+
+pub(crate) const TYPE_PTR: u16 = 12;
+pub(crate) const TYPE_TXT: u16 = 16;
+pub(crate) const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+/// The top bit of the class is the mdns cache flush / unicast response bit.
+const CLASS_MASK: u16 = 0x7fff;
+const FLAG_RESPONSE: u16 = 0x8000;
+const FLAG_AUTHORITATIVE: u16 = 0x0400;
+const MAX_LABEL_LEN: usize = 63;
+const MAX_TXT_STRING_LEN: usize = 255;
+/// Guards against compression pointer loops in malicious packets.
+const MAX_POINTER_JUMPS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Question {
+    pub name: String,
+    pub qtype: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RData {
+    Ptr(String),
+    /// The character strings of the record.
+    Txt(Vec<Vec<u8>>),
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Record {
+    pub name: String,
+    pub ttl: u32,
+    pub data: RData,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct Packet {
+    pub is_response: bool,
+    pub questions: Vec<Question>,
+    /// Answer, authority and additional records all end up in here.
+    pub records: Vec<Record>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DecodeError;
+
+/// Can `name` be encoded as a DNS name?
+pub(crate) fn is_valid_name(name: &str) -> bool {
+    name.split('.')
+        .all(|label| !label.is_empty() && label.len() <= MAX_LABEL_LEN)
+}
+
+impl Packet {
+    /// Encode without name compression, names must be valid.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        // mdns ids are always zero
+        out.extend_from_slice(&0u16.to_be_bytes());
+        let flags = if self.is_response {
+            FLAG_RESPONSE | FLAG_AUTHORITATIVE
+        } else {
+            0
+        };
+        out.extend_from_slice(&flags.to_be_bytes());
+        out.extend_from_slice(&(self.questions.len() as u16).to_be_bytes());
+        out.extend_from_slice(&(self.records.len() as u16).to_be_bytes());
+        // no authority or additional records
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        for question in &self.questions {
+            encode_name(&mut out, &question.name);
+            out.extend_from_slice(&question.qtype.to_be_bytes());
+            out.extend_from_slice(&CLASS_IN.to_be_bytes());
+        }
+        for record in &self.records {
+            encode_name(&mut out, &record.name);
+            let rtype = match record.data {
+                RData::Ptr(_) => TYPE_PTR,
+                RData::Txt(_) => TYPE_TXT,
+                RData::Other => unreachable!("only PTR and TXT records are encoded"),
+            };
+            out.extend_from_slice(&rtype.to_be_bytes());
+            out.extend_from_slice(&CLASS_IN.to_be_bytes());
+            out.extend_from_slice(&record.ttl.to_be_bytes());
+            let mut rdata = Vec::new();
+            match &record.data {
+                RData::Ptr(target) => encode_name(&mut rdata, target),
+                RData::Txt(strings) => {
+                    for s in strings {
+                        debug_assert!(s.len() <= MAX_TXT_STRING_LEN);
+                        rdata.push(s.len() as u8);
+                        rdata.extend_from_slice(s);
+                    }
+                }
+                RData::Other => (),
+            }
+            out.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+            out.extend_from_slice(&rdata);
+        }
+        out
+    }
+
+    pub fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader { buf, pos: 0 };
+        let _id = r.u16()?;
+        let flags = r.u16()?;
+        let question_count = r.u16()?;
+        let record_count = r.u16()? as usize + r.u16()? as usize + r.u16()? as usize;
+        let mut packet = Packet {
+            is_response: flags & FLAG_RESPONSE != 0,
+            ..Default::default()
+        };
+        for _ in 0..question_count {
+            let name = r.name()?;
+            let qtype = r.u16()?;
+            let _class = r.u16()? & CLASS_MASK;
+            packet.questions.push(Question { name, qtype });
+        }
+        for _ in 0..record_count {
+            let name = r.name()?;
+            let rtype = r.u16()?;
+            let _class = r.u16()? & CLASS_MASK;
+            let ttl = r.u32()?;
+            let len = r.u16()? as usize;
+            let end = r.pos.checked_add(len).ok_or(DecodeError)?;
+            if end > buf.len() {
+                return Err(DecodeError);
+            }
+            let data = match rtype {
+                TYPE_PTR => RData::Ptr(r.name()?),
+                TYPE_TXT => {
+                    let mut strings = Vec::new();
+                    while r.pos < end {
+                        let len = r.u8()? as usize;
+                        strings.push(r.bytes(len)?.to_vec());
+                    }
+                    RData::Txt(strings)
+                }
+                _ => RData::Other,
+            };
+            r.pos = end;
+            packet.records.push(Record { name, ttl, data });
+        }
+        Ok(packet)
+    }
+}
+
+/// Split `data` into TXT character strings.
+pub(crate) fn txt_strings(data: &[u8]) -> Vec<Vec<u8>> {
+    if data.is_empty() {
+        // a TXT record always has at least one (possibly empty) string
+        return vec![Vec::new()];
+    }
+    data.chunks(MAX_TXT_STRING_LEN)
+        .map(<[u8]>::to_vec)
+        .collect()
+}
+
+fn encode_name(out: &mut Vec<u8>, name: &str) {
+    for label in name.split('.') {
+        debug_assert!(!label.is_empty() && label.len() <= MAX_LABEL_LEN);
+        out.push(label.len() as u8);
+        out.extend_from_slice(label.as_bytes());
+    }
+    out.push(0);
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self.pos.checked_add(len).ok_or(DecodeError)?;
+        let out = self.buf.get(self.pos..end).ok_or(DecodeError)?;
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, DecodeError> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Read a possibly compressed name, leaving `pos` after it.
+    fn name(&mut self) -> Result<String, DecodeError> {
+        let mut labels: Vec<String> = Vec::new();
+        let mut pos = self.pos;
+        let mut resume_at = None;
+        let mut jumps = 0;
+        loop {
+            let len = *self.buf.get(pos).ok_or(DecodeError)? as usize;
+            if len == 0 {
+                pos += 1;
+                break;
+            }
+            if len & 0xc0 == 0xc0 {
+                let low = *self.buf.get(pos + 1).ok_or(DecodeError)? as usize;
+                jumps += 1;
+                if jumps > MAX_POINTER_JUMPS {
+                    return Err(DecodeError);
+                }
+                if resume_at.is_none() {
+                    resume_at = Some(pos + 2);
+                }
+                pos = ((len & 0x3f) << 8) | low;
+                continue;
+            }
+            if len > MAX_LABEL_LEN {
+                return Err(DecodeError);
+            }
+            let label = self.buf.get(pos + 1..pos + 1 + len).ok_or(DecodeError)?;
+            labels.push(String::from_utf8_lossy(label).into_owned());
+            pos += 1 + len;
+        }
+        self.pos = resume_at.unwrap_or(pos);
+        Ok(labels.join("."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packet_round_trip() {
+        let data = vec![7; 600];
+        let packet = Packet {
+            is_response: true,
+            questions: vec![Question {
+                name: "_space._udp.local".into(),
+                qtype: TYPE_PTR,
+            }],
+            records: vec![
+                Record {
+                    name: "_space._udp.local".into(),
+                    ttl: 120,
+                    data: RData::Ptr("agent._space._udp.local".into()),
+                },
+                Record {
+                    name: "agent._space._udp.local".into(),
+                    ttl: 120,
+                    data: RData::Txt(txt_strings(&data)),
+                },
+            ],
+        };
+        let decoded = Packet::decode(&packet.encode()).unwrap();
+        assert_eq!(packet, decoded);
+        match &decoded.records[1].data {
+            RData::Txt(strings) => {
+                assert_eq!(strings.len(), 3);
+                assert_eq!(strings.concat(), data);
+            }
+            _ => panic!("expected a TXT record"),
+        }
+    }
+
+    #[test]
+    fn test_decode_compressed_name() {
+        let packet = Packet {
+            is_response: true,
+            questions: vec![],
+            records: vec![Record {
+                name: "_space._udp.local".into(),
+                ttl: 120,
+                data: RData::Ptr("agent._space._udp.local".into()),
+            }],
+        };
+        let mut buf = packet.encode();
+        // replace the uncompressed target with "agent" + a pointer to the
+        // record name, which starts right after the 12 byte header
+        let rdata_start = buf.len() - "agent._space._udp.local".len() - 2;
+        buf.truncate(rdata_start - 2);
+        buf.extend_from_slice(&8u16.to_be_bytes());
+        buf.push(5);
+        buf.extend_from_slice(b"agent");
+        buf.extend_from_slice(&[0xc0, 12]);
+        let decoded = Packet::decode(&buf).unwrap();
+        assert_eq!(decoded, packet);
+    }
+
+    #[test]
+    fn test_decode_rejects_pointer_loop() {
+        let mut buf = Packet {
+            is_response: false,
+            questions: vec![],
+            records: vec![],
+        }
+        .encode();
+        // one question whose name points at itself
+        buf[5] = 1;
+        buf.extend_from_slice(&[0xc0, 12, 0, 12, 0, 1]);
+        assert_eq!(Packet::decode(&buf), Err(DecodeError));
+    }
+}
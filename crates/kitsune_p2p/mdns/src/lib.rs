@@ -1,91 +1,100 @@
-///! Crate for discovering Holochain peers over MDNS
-///! Works by broadcasting a service named `HC_SERVICE_NAME`
-///! and adding base64 encoded data in a TXT record
-///!
-///! Uses libmdns crate for broadcasting
-///! Uses mdns crate for discovery
+//! Crate for discovering Holochain peers over MDNS.
+//!
+//! A single [`Mdns`] handle owns one multicast socket which both answers
+//! queries for, and periodically announces, every service advertised
+//! through it, and browses for the service types it is asked to listen to.
+//! Services are published as a PTR record pointing at a TXT record
+//! carrying the raw service data, split into TXT strings. Data larger
+//! than [`CHUNK_SIZE`] is spread over several packets which listeners
+//! put back together.
 use err_derive::Error;
-use mdns::RecordKind;
-use std::time::Duration;
-use tokio_stream::{Stream, StreamExt};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::Stream;
 
-use std::sync::atomic::{AtomicBool, Ordering};
+mod dns;
+use dns::*;
 
-const HC_SERVICE_PROTOCOL: &str = "._udp";
-const BROADCAST_INTERVAL_SEC: u64 = 8;
-const QUERY_INTERVAL_SEC: u64 = 5;
-const MAX_TXT_SIZE: usize = 192;
+/// The standard mdns multicast group.
+pub const MDNS_ADDR: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+/// The standard mdns port.
+pub const MDNS_PORT: u16 = 5353;
+/// The largest mdns packet accepted, RFC 6762 allows up to 9000 bytes.
+pub const MAX_PACKET_SIZE: usize = 9000;
+/// Service data is sent in chunks of at most this many bytes, one per
+/// packet, so packets stay below a 1500 byte MTU and aren't fragmented.
+pub const CHUNK_SIZE: usize = 1000;
+/// The most chunks the data of a service is split into.
+pub const MAX_CHUNKS: usize = 64;
+/// The largest service data that can be advertised.
+pub const MAX_DATA_SIZE: usize = CHUNK_SIZE * MAX_CHUNKS;
+
+const HC_SERVICE_PROTOCOL: &str = "._udp.local";
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(8);
+const QUERY_INTERVAL: Duration = Duration::from_secs(5);
+/// Queries are not answered with a record multicast within this,
+/// see RFC 6762 section 6.
+const MIN_ANSWER_INTERVAL: Duration = Duration::from_secs(1);
+/// Records are announced well within this.
+const RECORD_TTL: u32 = 120;
+/// Prefix of the first TXT string, saying which chunk a packet carries.
+const CHUNK_HEADER: &str = "hc-chunk=";
+/// Partly received service data is dropped after this.
+const PARTIAL_TIMEOUT: Duration = Duration::from_secs(60);
+/// The most services partly received at the same time.
+const MAX_PARTIALS: usize = 1024;
 
 #[derive(Debug, Error)]
 pub enum MdnsError {
-    #[error(display = "Regular Mdns error {}", _0)]
-    Mdns(#[error(source)] mdns::Error),
-    #[error(display = "Base64 decoding error {}", _0)]
-    Base64(#[error(source)] base64::DecodeError),
+    #[error(display = "Mdns io error {}", _0)]
+    Io(#[error(source)] std::io::Error),
+    #[error(display = "Invalid mdns service type or name {}", _0)]
+    InvalidName(String),
+    #[error(display = "Mdns service data too large, {} bytes", _0)]
+    TooLarge(usize),
+    #[error(display = "Mdns responder is shut down")]
+    Closed,
 }
 
-/// Stop thread created by `mdns_create_broadcast_thread()`
-pub fn mdns_kill_thread(can_run: ::std::sync::Arc<AtomicBool>) {
-    can_run.store(false, Ordering::Relaxed);
+impl From<std::io::Error> for MdnsError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
 }
 
-/// Create a thread that will broadcast a holochain service over mdns
-/// Returns Sender for sending thread termination command
-pub fn mdns_create_broadcast_thread(
-    service_type: String,
-    service_name: String,
-    buffer: &[u8],
-) -> ::std::sync::Arc<AtomicBool> {
-    let svc_type = format!("_{}{}", service_type, HC_SERVICE_PROTOCOL);
-    // Constraints in libmdns
-    assert!(
-        svc_type.len() < 63,
-        "len = {} ({}) ; {}",
-        svc_type.len(),
-        service_type.len(),
-        service_type
-    );
-    assert!(service_name.len() < 63);
-    // Create Termination command variable
-    let can_run = ::std::sync::Arc::new(AtomicBool::new(true));
-    let can_run_clone = can_run.clone();
-    // Change buffer to base64 string
-    let mut b64 = format!(
-        "u{}",
-        base64::encode_config(buffer, base64::URL_SAFE_NO_PAD)
-    );
-    //println!(
-    //    "Broadcasting service type '{}', named '{}' over mdns ({})",
-    //    svc_type,
-    //    service_name,
-    //    b64.len()
-    //);
-    // Create thread
-    let _handle = tokio::task::spawn(async move {
-        // Split buffer to fix TXT max size
-        let mut substrs = Vec::new();
-        while b64.len() > MAX_TXT_SIZE {
-            let start: String = b64.drain(..MAX_TXT_SIZE).collect();
-            substrs.push(start);
-        }
-        substrs.push(b64);
-        let txts: Vec<_> = substrs.iter().map(AsRef::as_ref).collect();
-        //println!("Entering mdns broadcasting thread...");
-        // Create mdns responder
-
-        let responder = libmdns::Responder::new().unwrap();
-        let _svc = responder.register(svc_type, service_name, 0, &txts);
-        // Loop forever unless termination command received
-        loop {
-            tokio::time::sleep(::std::time::Duration::from_secs(BROADCAST_INTERVAL_SEC)).await;
-            if !can_run_clone.load(Ordering::Relaxed) {
-                //println!("Terminating.");
-                break;
-            }
+/// Where the mdns socket sends and listens.
+#[derive(Debug, Clone)]
+pub struct MdnsConfig {
+    /// The local interface to join the multicast group on.
+    /// With [`Ipv4Addr::UNSPECIFIED`] the OS picks a single interface,
+    /// usually the one of the default route.
+    /// [Default: [`Ipv4Addr::UNSPECIFIED`]]
+    pub interface: Ipv4Addr,
+    /// [Default: [`MDNS_ADDR`]]
+    pub multicast_addr: Ipv4Addr,
+    /// [Default: [`MDNS_PORT`]]
+    pub port: u16,
+    /// How often advertised services are announced unasked.
+    /// [Default: 8 seconds]
+    pub announce_interval: Duration,
+    /// How often the listened to service types are queried.
+    /// [Default: 5 seconds]
+    pub query_interval: Duration,
+}
+
+impl Default for MdnsConfig {
+    fn default() -> Self {
+        Self {
+            interface: Ipv4Addr::UNSPECIFIED,
+            multicast_addr: MDNS_ADDR,
+            port: MDNS_PORT,
+            announce_interval: ANNOUNCE_INTERVAL,
+            query_interval: QUERY_INTERVAL,
         }
-    });
-    // Done
-    can_run
+    }
 }
 
 ///
@@ -99,77 +108,563 @@ pub struct MdnsResponse {
     pub addr: std::net::IpAddr,
     /// Data contained in the TXT record
     pub buffer: Vec<u8>,
+    /// The service was withdrawn, `buffer` is empty
+    pub removed: bool,
 }
 
-/// Queries the network for the holochain service.
-/// Returns an iterator over all responses received.
-#[allow(clippy::let_and_return)]
-pub fn mdns_listen(service_type: String) -> impl Stream<Item = Result<MdnsResponse, MdnsError>> {
-    //let service_name = format!("{}.local", HC_SERVICE_TYPE);
-    let svc_type = format!("_{}{}.local", service_type, HC_SERVICE_PROTOCOL);
-    //println!("MDNS query for service type '{}'", svc_type);
-    let query = mdns::discover::all(svc_type, Duration::from_secs(QUERY_INTERVAL_SEC))
-        .expect("mdns Discover failed");
-    // Get Mdns Response stream
-    let response_stream = query.listen();
-    // Change it into a MdnsResponse stream
-    let mdns_stream = response_stream
-        // Filtering out Empty responses
-        .filter(move |res| {
-            match res {
-                Ok(response) => !response.is_empty() && response.ip_addr().is_some(),
-                Err(_) => true, // Keep errors
-            }
+enum Cmd {
+    Advertise(Service),
+    Remove { svc_type: String, name: String },
+    Listen(String, mpsc::UnboundedSender<MdnsResponse>),
+    Shutdown(oneshot::Sender<()>),
+}
+
+/// Handle to the mdns responder and browser. Clones share the same socket.
+/// The socket is closed, and all advertised services withdrawn, when
+/// [`Mdns::shutdown`] is called or the last handle is dropped.
+#[derive(Clone)]
+pub struct Mdns(mpsc::UnboundedSender<Cmd>);
+
+impl Mdns {
+    /// Bind the mdns socket and spawn the task driving it.
+    pub async fn bind(config: MdnsConfig) -> Result<Self, MdnsError> {
+        let socket = bind_socket(&config)?;
+        let (send, recv) = mpsc::unbounded_channel();
+        tokio::task::spawn(run(socket, config, recv));
+        Ok(Self(send))
+    }
+
+    /// Advertise `data` as service `service_name` of `service_type`,
+    /// replacing what was advertised under that name before.
+    pub fn advertise(
+        &self,
+        service_type: &str,
+        service_name: &str,
+        data: Vec<u8>,
+    ) -> Result<(), MdnsError> {
+        let service = Service::new(service_type, service_name, data)?;
+        self.send(Cmd::Advertise(service))
+    }
+
+    /// Stop advertising a service, telling listeners it is gone
+    /// with a response that is marked as `removed`.
+    pub fn remove(&self, service_type: &str, service_name: &str) -> Result<(), MdnsError> {
+        self.send(Cmd::Remove {
+            svc_type: full_service_type(service_type)?,
+            name: service_name.to_string(),
         })
-        .map(|maybe_response| {
-            if let Err(e) = maybe_response {
-                return Err(MdnsError::Mdns(e));
+    }
+
+    /// Query the network for services of `service_type`.
+    /// Every announcement of a service is yielded, not just the first,
+    /// as well as a `removed` response when a service is withdrawn.
+    /// The stream ends when the responder is shut down.
+    pub fn listen(
+        &self,
+        service_type: &str,
+    ) -> Result<impl Stream<Item = MdnsResponse>, MdnsError> {
+        let (send, recv) = mpsc::unbounded_channel();
+        self.send(Cmd::Listen(full_service_type(service_type)?, send))?;
+        Ok(tokio_stream::wrappers::UnboundedReceiverStream::new(recv))
+    }
+
+    /// Withdraw all advertised services and close the socket.
+    pub async fn shutdown(&self) {
+        let (send, recv) = oneshot::channel();
+        if self.send(Cmd::Shutdown(send)).is_ok() {
+            let _ = recv.await;
+        }
+    }
+
+    fn send(&self, cmd: Cmd) -> Result<(), MdnsError> {
+        self.0.send(cmd).map_err(|_| MdnsError::Closed)
+    }
+}
+
+fn full_service_type(service_type: &str) -> Result<String, MdnsError> {
+    let svc_type = format!("_{}{}", service_type, HC_SERVICE_PROTOCOL);
+    if !is_valid_name(&svc_type) {
+        return Err(MdnsError::InvalidName(service_type.to_string()));
+    }
+    Ok(svc_type)
+}
+
+/// An advertised service.
+struct Service {
+    svc_type: String,
+    name: String,
+    /// Identifies the data, so listeners don't mix up
+    /// chunks of data advertised at different times.
+    id: u64,
+    chunks: Vec<Vec<u8>>,
+    last_announced: Option<Instant>,
+}
+
+impl Service {
+    fn new(service_type: &str, service_name: &str, data: Vec<u8>) -> Result<Self, MdnsError> {
+        use std::hash::{Hash, Hasher};
+        let svc_type = full_service_type(service_type)?;
+        if service_name.contains('.') || !is_valid_name(service_name) {
+            return Err(MdnsError::InvalidName(service_name.to_string()));
+        }
+        if data.len() > MAX_DATA_SIZE {
+            return Err(MdnsError::TooLarge(data.len()));
+        }
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        data.hash(&mut hasher);
+        let chunks = if data.is_empty() {
+            vec![Vec::new()]
+        } else {
+            data.chunks(CHUNK_SIZE).map(<[u8]>::to_vec).collect()
+        };
+        Ok(Self {
+            svc_type,
+            name: service_name.to_string(),
+            id: hasher.finish(),
+            chunks,
+            last_announced: None,
+        })
+    }
+
+    fn instance(&self) -> String {
+        format!("{}.{}", self.name, self.svc_type)
+    }
+
+    fn ptr_record(&self, ttl: u32) -> Record {
+        Record {
+            name: self.svc_type.clone(),
+            ttl,
+            data: RData::Ptr(self.instance()),
+        }
+    }
+
+    /// The responses announcing this service, one per chunk of its data.
+    fn packets(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        let count = self.chunks.len();
+        self.chunks.iter().enumerate().map(move |(index, chunk)| {
+            let mut strings = vec![chunk_header(self.id, index, count)];
+            strings.extend(txt_strings(chunk));
+            Packet {
+                is_response: true,
+                questions: Vec::new(),
+                records: vec![
+                    self.ptr_record(RECORD_TTL),
+                    Record {
+                        name: self.instance(),
+                        ttl: RECORD_TTL,
+                        data: RData::Txt(strings),
+                    },
+                ],
             }
-            let response = maybe_response.unwrap();
-            // NOTE: if response.ip_addr() is not te right address,
-            // we should give all A/AAA records found in the answers instead
-            let addr = response.ip_addr().unwrap(); // should have already been filtered out
-            let mut buffer = Vec::new();
-            let mut service_name = String::new();
-            let mut service_type = String::new();
-            //println!("Response Answer count = {}", response.answers.len());
-            for answer in response.answers {
-                match answer.kind {
-                    RecordKind::TXT(txts) => {
-                        //println!("TXT count = {}", txts.len());
-                        let mut b64 = String::new();
-                        for txt in txts {
-                            //println!("Response TXT = {:?}", txt);
-                            b64.push_str(&txt);
-                        }
-                        buffer = match base64::decode_config(&b64[1..], base64::URL_SAFE_NO_PAD) {
-                            Err(e) => return Err(MdnsError::Base64(e)),
-                            Ok(s) => s,
-                        };
+            .encode()
+        })
+    }
+
+    /// The response withdrawing this service.
+    fn goodbye_packet(&self) -> Vec<u8> {
+        Packet {
+            is_response: true,
+            questions: Vec::new(),
+            records: vec![self.ptr_record(0)],
+        }
+        .encode()
+    }
+
+    async fn announce(&mut self, socket: &UdpSocket, dest: SocketAddr) {
+        self.last_announced = Some(Instant::now());
+        for packet in self.packets() {
+            send(socket, dest, &packet).await;
+        }
+    }
+
+    /// Announce this service in answer to a query,
+    /// unless it was announced less than a second ago.
+    async fn answer(&mut self, socket: &UdpSocket, dest: SocketAddr) {
+        let recently = self
+            .last_announced
+            .map_or(false, |at| at.elapsed() < MIN_ANSWER_INTERVAL);
+        if !recently {
+            self.announce(socket, dest).await;
+        }
+    }
+}
+
+fn chunk_header(id: u64, index: usize, count: usize) -> Vec<u8> {
+    format!("{}{:x}/{}/{}", CHUNK_HEADER, id, index, count).into_bytes()
+}
+
+fn parse_chunk_header(header: &[u8]) -> Option<(u64, usize, usize)> {
+    let header = std::str::from_utf8(header)
+        .ok()?
+        .strip_prefix(CHUNK_HEADER)?;
+    let mut parts = header.split('/');
+    let id = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let index = parts.next()?.parse().ok()?;
+    let count = parts.next()?.parse().ok()?;
+    Some((id, index, count))
+}
+
+/// Service data of which only some chunks were received so far.
+struct Partial {
+    id: u64,
+    chunks: Vec<Option<Vec<u8>>>,
+    since: Instant,
+}
+
+impl Partial {
+    fn new(id: u64, count: usize) -> Self {
+        Self {
+            id,
+            chunks: vec![None; count],
+            since: Instant::now(),
+        }
+    }
+}
+
+/// Partly received service data by sender and service instance.
+type Partials = HashMap<(IpAddr, String), Partial>;
+
+/// Add a received chunk, returning the service data once all of its chunks are in.
+fn add_chunk(partials: &mut Partials, key: (IpAddr, String), txt: &[Vec<u8>]) -> Option<Vec<u8>> {
+    let (header, data) = txt.split_first()?;
+    let (id, index, count) = parse_chunk_header(header)?;
+    if index >= count || count > MAX_CHUNKS {
+        return None;
+    }
+    if !partials.contains_key(&key) && partials.len() >= MAX_PARTIALS {
+        return None;
+    }
+    let partial = partials
+        .entry(key.clone())
+        .or_insert_with(|| Partial::new(id, count));
+    if partial.id != id || partial.chunks.len() != count {
+        // the service is advertised with new data now
+        *partial = Partial::new(id, count);
+    }
+    partial.chunks[index] = Some(data.concat());
+    if partial.chunks.iter().any(Option::is_none) {
+        return None;
+    }
+    let partial = partials.remove(&key)?;
+    Some(partial.chunks.into_iter().flatten().flatten().collect())
+}
+
+fn bind_socket(config: &MdnsConfig) -> Result<UdpSocket, MdnsError> {
+    use socket2::{Domain, Protocol, Socket, Type};
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    // other mdns responders on this host share the port
+    socket.set_reuse_address(true)?;
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    socket.set_reuse_port(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, config.port).into())?;
+    socket.join_multicast_v4(&config.multicast_addr, &config.interface)?;
+    socket.set_multicast_if_v4(&config.interface)?;
+    // so nodes on the same host find each other
+    socket.set_multicast_loop_v4(true)?;
+    socket.set_multicast_ttl_v4(255)?;
+    Ok(UdpSocket::from_std(socket.into())?)
+}
+
+#[derive(Default)]
+struct State {
+    /// Advertised services by type, then name.
+    services: HashMap<String, HashMap<String, Service>>,
+    /// Listeners by service type.
+    listeners: HashMap<String, Vec<mpsc::UnboundedSender<MdnsResponse>>>,
+    partials: Partials,
+}
+
+async fn run(socket: UdpSocket, config: MdnsConfig, mut cmd_recv: mpsc::UnboundedReceiver<Cmd>) {
+    let dest = SocketAddr::from((config.multicast_addr, config.port));
+    let mut state = State::default();
+    let mut announce = tokio::time::interval(config.announce_interval);
+    let mut query = tokio::time::interval(config.query_interval);
+    let mut buf = vec![0; MAX_PACKET_SIZE];
+    loop {
+        tokio::select! {
+            cmd = cmd_recv.recv() => match cmd {
+                Some(Cmd::Advertise(mut service)) => {
+                    service.announce(&socket, dest).await;
+                    state
+                        .services
+                        .entry(service.svc_type.clone())
+                        .or_default()
+                        .insert(service.name.clone(), service);
+                }
+                Some(Cmd::Remove { svc_type, name }) => {
+                    let removed = state
+                        .services
+                        .get_mut(&svc_type)
+                        .and_then(|services| services.remove(&name));
+                    if let Some(service) = removed {
+                        send(&socket, dest, &service.goodbye_packet()).await;
                     }
-                    // Retrieve service name stored in PTR record
-                    RecordKind::PTR(ptr) => {
-                        //println!("PTR = {}", ptr);
-                        service_name = ptr
-                            .split('.')
-                            .into_iter()
-                            .next()
-                            .expect("Found service without a name")
-                            .to_string();
-                        let names: Vec<&str> = answer.name.split("._").collect();
-                        //println!("answer.name = {}", answer.name);
-                        service_type = names[0][1..].to_string();
+                }
+                Some(Cmd::Listen(svc_type, sender)) => {
+                    send(&socket, dest, &query_packet(&svc_type)).await;
+                    state.listeners.entry(svc_type).or_default().push(sender);
+                }
+                Some(Cmd::Shutdown(done)) => {
+                    // refuse new commands before reporting we're done
+                    cmd_recv.close();
+                    goodbye(&socket, dest, &state).await;
+                    let _ = done.send(());
+                    break;
+                }
+                // all handles are dropped
+                None => {
+                    goodbye(&socket, dest, &state).await;
+                    break;
+                }
+            },
+            _ = announce.tick() => {
+                for service in state.services.values_mut().flat_map(HashMap::values_mut) {
+                    service.announce(&socket, dest).await;
+                }
+            }
+            _ = query.tick() => {
+                state.listeners.retain(|_, senders| {
+                    senders.retain(|s| !s.is_closed());
+                    !senders.is_empty()
+                });
+                state
+                    .partials
+                    .retain(|_, partial| partial.since.elapsed() < PARTIAL_TIMEOUT);
+                for svc_type in state.listeners.keys() {
+                    send(&socket, dest, &query_packet(svc_type)).await;
+                }
+            }
+            res = socket.recv_from(&mut buf) => {
+                if let Ok((len, from)) = res {
+                    if let Ok(packet) = Packet::decode(&buf[..len]) {
+                        handle_packet(&socket, dest, &mut state, packet, from).await;
                     }
-                    _ => {}
                 }
             }
-            Ok(MdnsResponse {
-                service_type,
-                service_name,
-                addr,
-                buffer,
-            })
-        });
-    // Done
-    mdns_stream
+        }
+    }
+}
+
+async fn send(socket: &UdpSocket, dest: SocketAddr, packet: &[u8]) {
+    // mdns is best effort, the next announcement or query retries
+    let _ = socket.send_to(packet, dest).await;
+}
+
+async fn goodbye(socket: &UdpSocket, dest: SocketAddr, state: &State) {
+    for service in state.services.values().flat_map(HashMap::values) {
+        send(socket, dest, &service.goodbye_packet()).await;
+    }
+}
+
+fn query_packet(svc_type: &str) -> Vec<u8> {
+    Packet {
+        is_response: false,
+        questions: vec![Question {
+            name: svc_type.to_string(),
+            qtype: TYPE_PTR,
+        }],
+        records: Vec::new(),
+    }
+    .encode()
+}
+
+async fn handle_packet(
+    socket: &UdpSocket,
+    dest: SocketAddr,
+    state: &mut State,
+    packet: Packet,
+    from: SocketAddr,
+) {
+    // Names are compared case sensitively, unlike DNS in general,
+    // because the base64 service types and names we use are.
+    if !packet.is_response {
+        for question in packet.questions {
+            if question.qtype != TYPE_PTR && question.qtype != TYPE_ANY {
+                continue;
+            }
+            if let Some(services) = state.services.get_mut(&question.name) {
+                for service in services.values_mut() {
+                    service.answer(socket, dest).await;
+                }
+            }
+        }
+        return;
+    }
+    for record in packet.records.iter() {
+        let instance = match &record.data {
+            RData::Ptr(instance) => instance,
+            _ => continue,
+        };
+        if !state.listeners.contains_key(&record.name) {
+            continue;
+        }
+        let service_name = match instance.strip_suffix(&format!(".{}", record.name)) {
+            Some(service_name) => service_name,
+            None => continue,
+        };
+        let key = (from.ip(), instance.clone());
+        let buffer = if record.ttl == 0 {
+            // a goodbye
+            state.partials.remove(&key);
+            None
+        } else {
+            let txt = packet.records.iter().find_map(|r| match &r.data {
+                RData::Txt(strings) if &r.name == instance => Some(strings),
+                _ => None,
+            });
+            match txt.and_then(|txt| add_chunk(&mut state.partials, key, txt)) {
+                Some(buffer) => Some(buffer),
+                None => continue,
+            }
+        };
+        let response = MdnsResponse {
+            service_type: record.name[1..record.name.len() - HC_SERVICE_PROTOCOL.len()].to_string(),
+            service_name: service_name.to_string(),
+            addr: from.ip(),
+            removed: buffer.is_none(),
+            buffer: buffer.unwrap_or_default(),
+        };
+        if let Some(listeners) = state.listeners.get_mut(&record.name) {
+            listeners.retain(|l| l.send(response.clone()).is_ok());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::StreamExt;
+
+    fn loopback_config(port: u16) -> MdnsConfig {
+        MdnsConfig {
+            interface: Ipv4Addr::LOCALHOST,
+            port,
+            announce_interval: Duration::from_millis(100),
+            query_interval: Duration::from_millis(100),
+            ..Default::default()
+        }
+    }
+
+    /// A port no other test uses, both responders share it.
+    fn free_port() -> u16 {
+        std::net::UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_advertise_and_listen_on_loopback() {
+        let port = free_port();
+        let a = Mdns::bind(loopback_config(port)).await.unwrap();
+        let b = Mdns::bind(loopback_config(port)).await.unwrap();
+
+        // spread over several chunks
+        let data: Vec<u8> = (0..(CHUNK_SIZE * 5 + 10)).map(|i| i as u8).collect();
+        a.advertise("space", "agent", data.clone()).unwrap();
+        a.advertise("other", "agent", vec![1]).unwrap();
+
+        let stream = b.listen("space").unwrap();
+        tokio::pin!(stream);
+        let response = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.service_type, "space");
+        assert_eq!(response.service_name, "agent");
+        assert_eq!(response.buffer, data);
+        assert!(!response.removed);
+
+        // data that doesn't fit the chunks is refused up front
+        assert!(matches!(
+            a.advertise("space", "big", vec![0; MAX_DATA_SIZE + 1]),
+            Err(MdnsError::TooLarge(_))
+        ));
+
+        // listeners are told about withdrawn services
+        a.remove("space", "agent").unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let response = stream.next().await.unwrap();
+                if response.removed {
+                    assert_eq!(response.service_name, "agent");
+                    assert!(response.buffer.is_empty());
+                    break;
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        a.shutdown().await;
+        assert!(matches!(
+            a.advertise("space", "agent", vec![]),
+            Err(MdnsError::Closed)
+        ));
+        b.shutdown().await;
+        // listeners end with the responder
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while stream.next().await.is_some() {}
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_answers_are_rate_limited() {
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let recv = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let dest = recv.local_addr().unwrap();
+        let mut service = Service::new("space", "agent", vec![1]).unwrap();
+        let mut buf = vec![0; MAX_PACKET_SIZE];
+
+        // a flood of queries is answered once
+        for _ in 0..10 {
+            service.answer(&socket, dest).await;
+        }
+        recv.recv_from(&mut buf).await.unwrap();
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), recv.recv_from(&mut buf))
+                .await
+                .is_err()
+        );
+
+        // and answered again a second later
+        tokio::time::sleep(MIN_ANSWER_INTERVAL).await;
+        service.answer(&socket, dest).await;
+        recv.recv_from(&mut buf).await.unwrap();
+    }
+
+    fn txt_of(packet: &[u8]) -> Vec<Vec<u8>> {
+        match &Packet::decode(packet).unwrap().records[1].data {
+            RData::Txt(strings) => strings.clone(),
+            _ => panic!("expected a TXT record"),
+        }
+    }
+
+    #[test]
+    fn test_chunks_are_put_back_together() {
+        let data: Vec<u8> = (0..2500).map(|i| i as u8).collect();
+        let service = Service::new("space", "agent", data.clone()).unwrap();
+        let mut chunks: Vec<_> = service.packets().map(|p| txt_of(&p)).collect();
+        assert_eq!(chunks.len(), 3);
+
+        let mut partials = Partials::new();
+        let key = (IpAddr::from(Ipv4Addr::LOCALHOST), service.instance());
+
+        // a chunk of older data is replaced by the newer data
+        let old = Service::new("space", "agent", vec![1; 2500]).unwrap();
+        let old_chunk = txt_of(&old.packets().next().unwrap());
+        assert_eq!(add_chunk(&mut partials, key.clone(), &old_chunk), None);
+
+        // chunks can arrive in any order
+        let last = chunks.remove(0);
+        for chunk in chunks {
+            assert_eq!(add_chunk(&mut partials, key.clone(), &chunk), None);
+        }
+        assert_eq!(add_chunk(&mut partials, key.clone(), &last), Some(data));
+        assert!(partials.is_empty());
+    }
 }
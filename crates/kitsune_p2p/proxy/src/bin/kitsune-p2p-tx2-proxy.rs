@@ -1,6 +1,7 @@
 use futures::stream::StreamExt;
 use kitsune_p2p_proxy::tx2::*;
 use kitsune_p2p_transport_quic::tx2::*;
+use kitsune_p2p_types::bin_types::KitsuneSpace;
 use kitsune_p2p_types::config::KitsuneP2pTuningParams;
 use kitsune_p2p_types::dependencies::{ghost_actor::dependencies::tracing, serde_json};
 use kitsune_p2p_types::metrics::*;
@@ -26,6 +27,55 @@ pub struct Opt {
     /// To which network interface / port should we bind?
     #[structopt(short = "b", long, default_value = "kitsune-quic://0.0.0.0:0")]
     pub bind_to: String,
+
+    /// Only relay for clients with this base64 tls cert digest.
+    /// Can be given multiple times.
+    #[structopt(long, parse(try_from_str = parse_cert))]
+    pub allow_cert: Vec<Tx2Cert>,
+
+    /// Only relay for clients that declare this space, as base64 space
+    /// bytes or a holochain dna hash. Can be given multiple times.
+    /// Clients matching either an allowed cert or space are relayed for.
+    #[structopt(long, parse(try_from_str = parse_space))]
+    pub allow_space: Vec<KitsuneSpace>,
+
+    /// Maximum bytes per second relayed to or from a single client.
+    #[structopt(long)]
+    pub client_bytes_per_second: Option<u64>,
+
+    /// Maximum number of clients connected at the same time.
+    #[structopt(long)]
+    pub max_clients: Option<usize>,
+
+    /// Maximum number of peers a single client can be relaying with.
+    #[structopt(long)]
+    pub max_connections_per_client: Option<usize>,
+
+    /// Close the connections of clients that haven't relayed or
+    /// declared their spaces for this many milliseconds.
+    #[structopt(long)]
+    pub evict_idle_clients_ms: Option<u64>,
+}
+
+fn parse_cert(s: &str) -> Result<Tx2Cert, base64::DecodeError> {
+    let digest = base64::decode_config(s, base64::URL_SAFE_NO_PAD)?;
+    Ok(digest.into())
+}
+
+fn parse_space(s: &str) -> Result<KitsuneSpace, String> {
+    // holochain hashes are 'u' prefixed and carry a 3 byte hash type prefix
+    let (s, prefix_len) = match s.strip_prefix('u') {
+        Some(s) if s.len() == 52 => (s, 3),
+        _ => (s, 0),
+    };
+    let bytes = base64::decode_config(s, base64::URL_SAFE_NO_PAD).map_err(|e| e.to_string())?;
+    if bytes.len() != prefix_len + 36 {
+        return Err(format!(
+            "expected a 36 byte space, got {} bytes",
+            bytes.len()
+        ));
+    }
+    Ok(KitsuneSpace(bytes[prefix_len..].to_vec()))
 }
 
 #[tokio::main(flavor = "multi_thread")]
@@ -114,6 +164,18 @@ async fn inner() -> KitsuneResult<()> {
     let mut conf = ProxyConfig::default();
     conf.tuning_params = Some(tuning_params.clone());
     conf.allow_proxy_fwd = true;
+    if !opt.allow_cert.is_empty() {
+        conf.access.allowed_certs = Some(opt.allow_cert.iter().cloned().collect());
+    }
+    if !opt.allow_space.is_empty() {
+        conf.access.allowed_spaces = Some(opt.allow_space.iter().cloned().collect());
+    }
+    conf.access.client_bytes_per_second = opt.client_bytes_per_second;
+    conf.access.max_clients = opt.max_clients;
+    conf.access.max_connections_per_client = opt.max_connections_per_client;
+    conf.access.evict_idle_clients_after = opt
+        .evict_idle_clients_ms
+        .map(std::time::Duration::from_millis);
     let f = tx2_proxy(f, conf)?;
 
     let ep = f
@@ -6,14 +6,16 @@ use crate::*;
 use futures::future::BoxFuture;
 use futures::stream::{Stream, StreamExt};
 use ghost_actor::dependencies::tracing;
+use kitsune_p2p_types::bin_types::KitsuneSpace;
 use kitsune_p2p_types::config::KitsuneP2pTuningParams;
 use kitsune_p2p_types::dependencies::serde_json;
 use kitsune_p2p_types::tx2::tx2_adapter::*;
 use kitsune_p2p_types::tx2::tx2_pool::*;
 use kitsune_p2p_types::tx2::tx2_utils::*;
 use kitsune_p2p_types::tx2::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::Notify;
 
 /// Configuration for the remote connection portion
@@ -78,6 +80,115 @@ fn stub_proxy_from_bootstrap_cb(_: TxUrl) -> BoxFuture<'static, Option<TxUrl>> {
     Box::pin(async move { None })
 }
 
+/// Restrictions a proxy server applies to the clients it relays for.
+/// The default relays for anyone without limits.
+#[derive(Debug, Clone, Default)]
+pub struct ProxyAccessConfig {
+    /// Only relay for clients with one of these cert digests.
+    /// If both this and `allowed_spaces` are set a client
+    /// matching either of them is allowed.
+    pub allowed_certs: Option<HashSet<Tx2Cert>>,
+
+    /// Only relay for clients that declared one of these spaces
+    /// (see [`ProxyClientSpaces`]).
+    pub allowed_spaces: Option<HashSet<KitsuneSpace>>,
+
+    /// Maximum number of clients connected at the same time.
+    pub max_clients: Option<usize>,
+
+    /// Maximum number of distinct peers a single client
+    /// can be relaying with at the same time.
+    pub max_connections_per_client: Option<usize>,
+
+    /// Maximum bytes per second relayed to or from a single client.
+    pub client_bytes_per_second: Option<u64>,
+
+    /// Close the connections of clients that haven't relayed or declared
+    /// their spaces for this long. Clients without traffic only stay
+    /// active by declaring their spaces (see [`ProxyClientSpaces`]),
+    /// so only set this if your clients do.
+    /// Default: None, clients are never evicted.
+    pub evict_idle_clients_after: Option<Duration>,
+}
+
+impl ProxyAccessConfig {
+    fn allows(&self, cert: &Tx2Cert, spaces: &[KitsuneSpace]) -> bool {
+        if self.allowed_certs.is_none() && self.allowed_spaces.is_none() {
+            return true;
+        }
+        self.allowed_certs
+            .as_ref()
+            .map_or(false, |certs| certs.contains(cert))
+            || self
+                .allowed_spaces
+                .as_ref()
+                .map_or(false, |allowed| spaces.iter().any(|s| allowed.contains(s)))
+    }
+}
+
+/// The spaces a node declares to the proxy servers it connects to,
+/// so proxies that only relay for some spaces will relay for it.
+/// The declaration is refreshed every `proxy_keepalive_ms`,
+/// which also keeps proxies with
+/// [`evict_idle_clients_after`](ProxyAccessConfig::evict_idle_clients_after)
+/// set from evicting us as idle.
+/// Clones share the same set of spaces.
+#[derive(Clone, Default)]
+pub struct ProxyClientSpaces(Arc<ProxyClientSpacesInner>);
+
+#[derive(Default)]
+struct ProxyClientSpacesInner {
+    spaces: parking_lot::RwLock<HashSet<KitsuneSpace>>,
+    changed: Notify,
+}
+
+impl ProxyClientSpaces {
+    /// Declare a space, proxies are told right away.
+    pub fn add(&self, space: KitsuneSpace) {
+        if self.0.spaces.write().insert(space) {
+            self.0.changed.notify_waiters();
+        }
+    }
+
+    /// Stop declaring a space.
+    pub fn remove(&self, space: &KitsuneSpace) {
+        if self.0.spaces.write().remove(space) {
+            self.0.changed.notify_waiters();
+        }
+    }
+
+    fn list(&self) -> Vec<KitsuneSpace> {
+        self.0.spaces.read().iter().cloned().collect()
+    }
+}
+
+/// A direct connection attempt may use at most this fraction
+/// (1 / DIRECT_CON_TIMEOUT_DIVISOR) of the connection timeout.
+const DIRECT_CON_TIMEOUT_DIVISOR: u32 = 2;
+
+/// Direct addresses of peers we would otherwise reach through their
+/// proxy, e.g. peers found on the local network. Connections to these
+/// peers are made to the direct address first, falling back to the proxy.
+/// Clones share the same addresses.
+#[derive(Clone, Default)]
+pub struct ProxyDirectAddrs(Arc<parking_lot::RwLock<HashMap<Tx2Cert, TxUrl>>>);
+
+impl ProxyDirectAddrs {
+    /// Reach the peer with this cert at `base_url` directly.
+    pub fn insert(&self, cert: Tx2Cert, base_url: TxUrl) {
+        self.0.write().insert(cert, base_url);
+    }
+
+    /// Forget the direct address of a peer.
+    pub fn remove(&self, cert: &Tx2Cert) {
+        self.0.write().remove(cert);
+    }
+
+    fn get(&self, cert: &Tx2Cert) -> Option<TxUrl> {
+        self.0.read().get(cert).cloned()
+    }
+}
+
 /// Configuration for tx2 proxy wrapper
 #[non_exhaustive]
 pub struct ProxyConfig {
@@ -99,6 +210,20 @@ pub struct ProxyConfig {
 
     /// Logic for dynamically fetching a proxy url from the bootstrap service
     pub proxy_from_bootstrap_cb: ProxyFromBootstrapCb,
+
+    /// If you are a proxy server, who to relay for.
+    /// Default: relay for anyone.
+    pub access: ProxyAccessConfig,
+
+    /// If Some, declare these spaces to the proxy servers we connect to.
+    /// Only enable this if all proxies in the network understand the
+    /// declaration, older proxies drop connections that send it.
+    /// Default: None.
+    pub client_spaces: Option<ProxyClientSpaces>,
+
+    /// If Some, prefer these direct addresses over proxied ones.
+    /// Default: None.
+    pub direct_addrs: Option<ProxyDirectAddrs>,
 }
 
 impl Default for ProxyConfig {
@@ -108,6 +233,9 @@ impl Default for ProxyConfig {
             allow_proxy_fwd: false,
             client_of_remote_proxy: ProxyRemoteType::default(),
             proxy_from_bootstrap_cb: Arc::new(stub_proxy_from_bootstrap_cb),
+            access: ProxyAccessConfig::default(),
+            client_spaces: None,
+            direct_addrs: None,
         }
     }
 }
@@ -121,12 +249,18 @@ impl ProxyConfig {
         bool,
         ProxyRemoteType,
         ProxyFromBootstrapCb,
+        ProxyAccessConfig,
+        Option<ProxyClientSpaces>,
+        Option<ProxyDirectAddrs>,
     )> {
         let ProxyConfig {
             tuning_params,
             allow_proxy_fwd,
             client_of_remote_proxy,
             proxy_from_bootstrap_cb,
+            access,
+            client_spaces,
+            direct_addrs,
         } = self;
 
         let tuning_params = tuning_params.unwrap_or_default();
@@ -136,6 +270,9 @@ impl ProxyConfig {
             allow_proxy_fwd,
             client_of_remote_proxy,
             proxy_from_bootstrap_cb,
+            access,
+            client_spaces,
+            direct_addrs,
         ))
     }
 }
@@ -151,6 +288,7 @@ const PROXY_TYPE_BYTES: usize = 1;
 const DIGEST_BYTES: usize = 32;
 
 const PROXY_FWD_MSG: u8 = 0x30;
+const PROXY_ACCESS_MSG: u8 = 0x31;
 const PROXY_ROUTE_ERR: u8 = 0xc0;
 
 struct ProxyConHnd {
@@ -300,9 +438,151 @@ struct ProxyEpInner {
     direct_to_final_peer_con_map: HashMap<Uniq, HashMap<Tx2Cert, ConHnd>>,
 
     backoff: Backoff,
+
+    access: ProxyAccessConfig,
+
+    // stats and quota state of the peers we relay for,
+    // keyed the same as digest_to_sub_con_map
+    clients: HashMap<Tx2Cert, ProxyClient>,
+}
+
+struct ProxyClient {
+    connected_at: Instant,
+    last_active: Instant,
+    spaces: Vec<KitsuneSpace>,
+    bytes_sent: u64,
+    bytes_received: u64,
+    frames_relayed: u64,
+    frames_rejected: u64,
+    // peers we relayed between this client and, with the last relay time
+    peers: HashMap<Tx2Cert, Instant>,
+    window_start: Instant,
+    window_bytes: u64,
+}
+
+impl ProxyClient {
+    fn new(now: Instant) -> Self {
+        Self {
+            connected_at: now,
+            last_active: now,
+            spaces: Vec::new(),
+            bytes_sent: 0,
+            bytes_received: 0,
+            frames_relayed: 0,
+            frames_rejected: 0,
+            peers: HashMap::new(),
+            window_start: now,
+            window_bytes: 0,
+        }
+    }
+
+    // can this client relay `len` more bytes in the current one second window
+    fn has_bandwidth(&mut self, limit: Option<u64>, len: u64, now: Instant) -> bool {
+        if now.duration_since(self.window_start) >= Duration::from_secs(1) {
+            self.window_start = now;
+            self.window_bytes = 0;
+        }
+        match limit {
+            // always let one frame through per window,
+            // frames bigger than the limit would starve otherwise
+            Some(limit) => self.window_bytes == 0 || self.window_bytes + len <= limit,
+            None => true,
+        }
+    }
+
+    fn debug(&self, now: Instant) -> serde_json::Value {
+        serde_json::json!({
+            "connected_s": now.duration_since(self.connected_at).as_secs(),
+            "idle_ms": now.duration_since(self.last_active).as_millis() as u64,
+            "space_count": self.spaces.len(),
+            "peer_count": self.peers.len(),
+            "bytes_sent": self.bytes_sent,
+            "bytes_received": self.bytes_received,
+            "frames_relayed": self.frames_relayed,
+            "frames_rejected": self.frames_rejected,
+        })
+    }
 }
 
 impl ProxyEpInner {
+    /// Check the access rules and quotas for relaying `len` bytes
+    /// from `src` to `dest`, and account for it if allowed.
+    pub fn check_relay(
+        &mut self,
+        src: &Tx2Cert,
+        dest: &Tx2Cert,
+        len: usize,
+    ) -> KitsuneResult<Option<ConHnd>> {
+        let d_sub_con = match self.digest_to_sub_con_map.get(dest) {
+            Some(d_sub_con) => d_sub_con.clone(),
+            None => return Ok(None),
+        };
+        let now = Instant::now();
+        let len = len as u64;
+        let access = &self.access;
+        let mut src_client = self
+            .clients
+            .remove(src)
+            .unwrap_or_else(|| ProxyClient::new(now));
+        let dest_client = self
+            .clients
+            .entry(dest.clone())
+            .or_insert_with(|| ProxyClient::new(now));
+        let res: KitsuneResult<Option<ConHnd>> = if !access.allows(src, &src_client.spaces) {
+            Err("proxy access denied".into())
+        } else if !access.allows(dest, &dest_client.spaces) {
+            Err("proxy access denied for target".into())
+        } else if !src_client.peers.contains_key(dest)
+            && access
+                .max_connections_per_client
+                .map_or(false, |max| src_client.peers.len() >= max)
+        {
+            Err("proxy connection quota exceeded".into())
+        } else if !src_client.has_bandwidth(access.client_bytes_per_second, len, now)
+            || !dest_client.has_bandwidth(access.client_bytes_per_second, len, now)
+        {
+            Err("proxy bandwidth quota exceeded".into())
+        } else {
+            src_client.peers.insert(dest.clone(), now);
+            src_client.last_active = now;
+            src_client.bytes_sent += len;
+            src_client.window_bytes += len;
+            src_client.frames_relayed += 1;
+            dest_client.peers.insert(src.clone(), now);
+            dest_client.last_active = now;
+            dest_client.bytes_received += len;
+            dest_client.window_bytes += len;
+            Ok(Some(d_sub_con))
+        };
+        if res.is_err() {
+            src_client.frames_rejected += 1;
+        }
+        self.clients.insert(src.clone(), src_client);
+        res
+    }
+
+    /// Forget peers not relayed with for longer than `expire` and return
+    /// the connections of clients that have been idle for longer than
+    /// [`ProxyAccessConfig::evict_idle_clients_after`].
+    pub fn idle_clients(&mut self, expire: Duration) -> Vec<ConHnd> {
+        let now = Instant::now();
+        let evict_after = self.access.evict_idle_clients_after;
+        let mut idle = Vec::new();
+        for (cert, client) in self.clients.iter_mut() {
+            client
+                .peers
+                .retain(|_, last| now.duration_since(*last) < expire);
+            if evict_after.map_or(false, |after| {
+                now.duration_since(client.last_active) >= after
+            }) {
+                if let Some(sub_con) = self.digest_to_sub_con_map.get(cert) {
+                    idle.push(sub_con.clone());
+                }
+            }
+        }
+        idle
+    }
+
     pub fn get_con_hnd(
         &mut self,
         sub_con: ConHnd,
@@ -335,6 +615,8 @@ struct ProxyEpHnd {
     logic_hnd: LogicChanHandle<EpEvent>,
     inner: Share<ProxyEpInner>,
     cur_proxy_url: Share<Option<ProxyUrl>>,
+    client_spaces: Option<ProxyClientSpaces>,
+    direct_addrs: Option<ProxyDirectAddrs>,
 }
 
 async fn get_con_hnd(
@@ -366,6 +648,9 @@ impl ProxyEpHnd {
         logic_hnd: LogicChanHandle<EpEvent>,
         backoff: Backoff,
         cur_proxy_url: Share<Option<ProxyUrl>>,
+        access: ProxyAccessConfig,
+        client_spaces: Option<ProxyClientSpaces>,
+        direct_addrs: Option<ProxyDirectAddrs>,
     ) -> KitsuneResult<Arc<ProxyEpHnd>> {
         let local_cert = sub_ep_hnd.local_cert();
         Ok(Arc::new(ProxyEpHnd {
@@ -376,8 +661,12 @@ impl ProxyEpHnd {
                 digest_to_sub_con_map: HashMap::new(),
                 direct_to_final_peer_con_map: HashMap::new(),
                 backoff,
+                access,
+                clients: HashMap::new(),
             }),
             cur_proxy_url,
+            client_spaces,
+            direct_addrs,
         }))
     }
 }
@@ -391,12 +680,19 @@ impl AsEpHnd for ProxyEpHnd {
                 .keys()
                 .map(|k| format!("{:?}", k))
                 .collect::<Vec<_>>();
+            let now = Instant::now();
+            let clients = i
+                .clients
+                .iter()
+                .map(|(k, c)| (k.as_str().to_string(), c.debug(now)))
+                .collect::<serde_json::Map<_, _>>();
             Ok(serde_json::json!({
                 "type": "tx2_proxy",
                 "state": "open",
                 "addr": addr?,
                 "proxy_count": i.digest_to_sub_con_map.len(),
                 "proxy_list": proxy_list,
+                "clients": clients,
                 "sub": self.sub_ep_hnd.debug(),
             }))
         }) {
@@ -433,6 +729,21 @@ impl AsEpHnd for ProxyEpHnd {
         }
     }
 
+    fn local_addrs(&self) -> KitsuneResult<Vec<TxUrl>> {
+        let local_addr = self.sub_ep_hnd.local_addr()?;
+        let direct_addr: TxUrl =
+            ProxyUrl::new(local_addr.as_str(), self.local_cert.as_digest().clone())
+                .map_err(KitsuneError::other)?
+                .as_str()
+                .into();
+        let addr = self.local_addr()?;
+        if addr == direct_addr {
+            Ok(vec![addr])
+        } else {
+            Ok(vec![addr, direct_addr])
+        }
+    }
+
     fn local_cert(&self) -> Tx2Cert {
         self.sub_ep_hnd.local_cert()
     }
@@ -474,12 +785,39 @@ impl AsEpHnd for ProxyEpHnd {
 
         let base_url: TxUrl = purl.as_base().as_str().into();
 
+        let direct_url = self
+            .direct_addrs
+            .as_ref()
+            .and_then(|d| d.get(&peer_cert))
+            .filter(|direct_url| direct_url != &base_url);
+
         let local_cert = self.local_cert.clone();
         let logic_hnd = self.logic_hnd.clone();
-        let con_fut = self.sub_ep_hnd.get_connection(base_url, timeout);
+        let sub_ep_hnd = self.sub_ep_hnd.clone();
         let inner = self.inner.clone();
         async move {
-            let sub_con = con_fut.await?;
+            let sub_con = match direct_url {
+                Some(direct_url) => {
+                    // leave the rest of the timeout for the proxy fallback
+                    let direct_timeout =
+                        KitsuneTimeout::new(timeout.time_remaining() / DIRECT_CON_TIMEOUT_DIVISOR);
+                    let direct_con = direct_timeout
+                        .mix(sub_ep_hnd.get_connection(direct_url, direct_timeout))
+                        .await;
+                    match direct_con {
+                        Ok(sub_con) => sub_con,
+                        Err(e) => {
+                            tracing::debug!(
+                                ?peer_cert,
+                                ?e,
+                                "direct connection failed, using proxy"
+                            );
+                            sub_ep_hnd.get_connection(base_url, timeout).await?
+                        }
+                    }
+                }
+                None => sub_ep_hnd.get_connection(base_url, timeout).await?,
+            };
             get_con_hnd(&inner, logic_hnd, sub_con, local_cert, peer_cert, true).await
         }
         .boxed()
@@ -558,20 +896,50 @@ async fn ensure_proxy_register(
         return Ok(());
     }
 
-    let _ = inner.share_mut(move |i, _| {
-        match i.digest_to_sub_con_map.entry(peer_cert.clone()) {
-            std::collections::hash_map::Entry::Occupied(mut e) => {
-                if e.get().uniq() != sub_con.uniq() {
-                    tracing::warn!(?peer_cert, "REPLACE EXISTING CONNECTION!");
-                    e.insert(sub_con);
-                }
+    let over_limit = inner
+        .share_mut(|i, _| {
+            let over_limit = !i.digest_to_sub_con_map.contains_key(&peer_cert)
+                && i.access
+                    .max_clients
+                    .map_or(false, |max| i.digest_to_sub_con_map.len() >= max);
+            if over_limit {
+                return Ok(true);
             }
-            std::collections::hash_map::Entry::Vacant(e) => {
-                e.insert(sub_con);
+            match i.digest_to_sub_con_map.entry(peer_cert.clone()) {
+                std::collections::hash_map::Entry::Occupied(mut e) => {
+                    if e.get().uniq() != sub_con.uniq() {
+                        tracing::warn!(?peer_cert, "REPLACE EXISTING CONNECTION!");
+                        e.insert(sub_con.clone());
+                    }
+                }
+                std::collections::hash_map::Entry::Vacant(e) => {
+                    e.insert(sub_con.clone());
+                }
             }
-        }
-        Ok(())
-    });
+            let now = Instant::now();
+            i.clients
+                .entry(peer_cert.clone())
+                .or_insert_with(|| ProxyClient::new(now))
+                .last_active = now;
+            Ok(false)
+        })
+        .unwrap_or(false);
+    if over_limit {
+        tracing::warn!(
+            ?peer_cert,
+            "refusing connection, proxy client limit reached"
+        );
+        close_connection(
+            inner,
+            logic_hnd,
+            sub_con,
+            500,
+            "proxy client limit reached",
+            cur_proxy_url,
+        )
+        .await;
+        return Err(().into());
+    }
     Ok(())
 }
 
@@ -588,9 +956,23 @@ async fn incoming_evt_handle(
     use EpEvent::*;
     match evt {
         OutgoingConnection(EpConnection { con: sub_con, .. }) => {
-            let _ =
-                ensure_proxy_register(&hnd.inner, logic_hnd, &local_cert, sub_con, cur_proxy_url)
-                    .await;
+            if ensure_proxy_register(
+                &hnd.inner,
+                logic_hnd,
+                &local_cert,
+                sub_con.clone(),
+                cur_proxy_url,
+            )
+            .await
+            .is_err()
+            {
+                return;
+            }
+            if let Some(client_spaces) = &hnd.client_spaces {
+                if let Err(e) = declare_spaces(tuning_params, &sub_con, client_spaces).await {
+                    tracing::debug!("failed to declare proxy client spaces: {:?}", e);
+                }
+            }
         }
         IncomingConnection(EpConnection { con: sub_con, .. }) => {
             let _ =
@@ -658,9 +1040,12 @@ async fn incoming_evt_handle(
                             tracing::error!("received fwd request on, but proxy fwd is disallowed");
                             Err("proxy fwd disallowed".into())
                         } else {
-                            hnd.inner.share_mut(|i, _| {
-                                Ok(i.digest_to_sub_con_map.get(&dest_cert).cloned())
-                            })
+                            // check against the cert the sender authenticated
+                            // with, not the one it wrote in the frame
+                            let sender_cert = sub_con.peer_cert();
+                            let len = data.len();
+                            hnd.inner
+                                .share_mut(|i, _| i.check_relay(&sender_cert, &dest_cert, len))
                         };
                         if let Err(e) = match dest {
                             Ok(Some(d_sub_con)) => {
@@ -703,6 +1088,31 @@ async fn incoming_evt_handle(
                         }
                     }
                 }
+                PROXY_ACCESS_MSG => {
+                    // only proxy servers care which spaces their clients are in
+                    if !allow_proxy_fwd {
+                        return;
+                    }
+                    let mut bytes: &[u8] = &data[PROXY_TYPE_BYTES..];
+                    let spaces: Vec<KitsuneSpace> =
+                        match kitsune_p2p_types::codec::rmp_decode(&mut bytes) {
+                            Ok(spaces) => spaces,
+                            Err(e) => {
+                                tracing::warn!("Invalid proxy access declaration: {:?}", e);
+                                return;
+                            }
+                        };
+                    let peer_cert = sub_con.peer_cert();
+                    let _ = hnd.inner.share_mut(move |i, _| {
+                        let client = i
+                            .clients
+                            .entry(peer_cert)
+                            .or_insert_with(|| ProxyClient::new(Instant::now()));
+                        client.spaces = spaces;
+                        client.last_active = Instant::now();
+                        Ok(())
+                    });
+                }
                 PROXY_ROUTE_ERR => {
                     const SRC_START: usize = PROXY_TYPE_BYTES;
                     const SRC_END: usize = SRC_START + DIGEST_BYTES;
@@ -755,6 +1165,22 @@ async fn incoming_evt_handle(
     }
 }
 
+/// Tell the node on the other end of `sub_con` which spaces we are in,
+/// in case it is a proxy restricting who it relays for.
+async fn declare_spaces(
+    tuning_params: &KitsuneP2pTuningParams,
+    sub_con: &ConHnd,
+    client_spaces: &ProxyClientSpaces,
+) -> KitsuneResult<()> {
+    let mut data = PoolBuf::new();
+    kitsune_p2p_types::codec::rmp_encode(&mut data, client_spaces.list())
+        .map_err(KitsuneError::other)?;
+    data.prepend_from_slice(&[PROXY_ACCESS_MSG]);
+    sub_con
+        .write(MsgId::new_notify(), data, tuning_params.implicit_timeout())
+        .await
+}
+
 async fn write_to_sub_con(
     tuning_params: &KitsuneP2pTuningParams,
     inner: &Share<ProxyEpInner>,
@@ -802,6 +1228,7 @@ async fn close_connection_inner(
         // if this is an INCOMING connection, remove it from our proxy list
         if let Tx2ConDir::Incoming = peer_dir {
             i.digest_to_sub_con_map.remove(&peer_cert);
+            i.clients.remove(&peer_cert);
         }
 
         // remove all out cons associated with this exact connection
@@ -857,11 +1284,14 @@ impl ProxyEp {
         allow_proxy_fwd: bool,
         client_of_remote_proxy: ProxyRemoteType,
         proxy_from_bootstrap_cb: ProxyFromBootstrapCb,
+        access: ProxyAccessConfig,
+        client_spaces: Option<ProxyClientSpaces>,
+        direct_addrs: Option<ProxyDirectAddrs>,
     ) -> KitsuneResult<Ep> {
         // this isn't something that needs to be configurable,
         // because it's entirely dependent on the code written here
-        // we only ever capture two logic closures
-        // so technically, it only really would need to be 2.
+        // we only ever capture four logic closures
+        // so technically, it only really would need to be 4.
         const LOGIC_CHAN_LIMIT: usize = 32;
 
         let cur_proxy_url = Share::new(None);
@@ -876,6 +1306,9 @@ impl ProxyEp {
             logic_hnd.clone(),
             backoff.clone(),
             cur_proxy_url.clone(),
+            access,
+            client_spaces.clone(),
+            direct_addrs,
         )?;
 
         let logic = incoming_evt_logic(
@@ -883,13 +1316,87 @@ impl ProxyEp {
             allow_proxy_fwd,
             sub_ep,
             hnd.clone(),
-            logic_hnd,
+            logic_hnd.clone(),
             cur_proxy_url.clone(),
         );
 
         let l_hnd = logic_chan.handle().clone();
         l_hnd.capture_logic(logic).await?;
 
+        if allow_proxy_fwd {
+            // set up the logic loop that forgets stale peers
+            // and evicts idle proxy clients if enabled
+            let expire = Duration::from_millis(tuning_params.proxy_to_expire_ms as u64);
+            let tick = hnd
+                .inner
+                .share_ref(|i| Ok(i.access.evict_idle_clients_after))?
+                .map_or(expire, |after| after.min(expire))
+                / 2;
+            let hnd = hnd.clone();
+            let logic_hnd = logic_hnd.clone();
+            let cur_proxy_url = cur_proxy_url.clone();
+            l_hnd
+                .capture_logic(async move {
+                    loop {
+                        tokio::time::sleep(tick).await;
+                        let idle = match hnd.inner.share_mut(|i, _| Ok(i.idle_clients(expire))) {
+                            Ok(idle) => idle,
+                            Err(_) => break,
+                        };
+                        for sub_con in idle {
+                            tracing::debug!(peer_cert = ?sub_con.peer_cert(), "evicting idle proxy client");
+                            close_connection(
+                                &hnd.inner,
+                                &logic_hnd,
+                                sub_con,
+                                0,
+                                "proxy client idle",
+                                &cur_proxy_url,
+                            )
+                            .await;
+                        }
+                    }
+                })
+                .await?;
+        }
+
+        if let Some(client_spaces) = client_spaces {
+            // set up the logic loop that keeps our proxy
+            // up to date with the spaces we are in
+            let keepalive = Duration::from_millis(tuning_params.proxy_keepalive_ms as u64);
+            let tuning_params = tuning_params.clone();
+            let hnd = hnd.clone();
+            let cur_proxy_url = cur_proxy_url.clone();
+            l_hnd
+                .capture_logic(async move {
+                    loop {
+                        tokio::select! {
+                            _ = tokio::time::sleep(keepalive) => (),
+                            _ = client_spaces.0.changed.notified() => (),
+                        }
+                        if hnd.inner.is_closed() {
+                            break;
+                        }
+                        let proxy_url = match cur_proxy_url.share_ref(|r| Ok(r.clone())) {
+                            Ok(Some(proxy_url)) => proxy_url,
+                            _ => continue,
+                        };
+                        let base_url: TxUrl = proxy_url.as_base().as_str().into();
+                        let timeout = tuning_params.implicit_timeout();
+                        let sub_con = match hnd.sub_ep_hnd.get_connection(base_url, timeout).await {
+                            Ok(sub_con) => sub_con,
+                            Err(_) => continue,
+                        };
+                        if let Err(e) =
+                            declare_spaces(&tuning_params, &sub_con, &client_spaces).await
+                        {
+                            tracing::debug!("failed to declare proxy client spaces: {:?}", e);
+                        }
+                    }
+                })
+                .await?;
+        }
+
         {
             // try to get our proxy addy inline, but fail silently
             if let Some(proxy_url) = client_of_remote_proxy
@@ -961,18 +1468,31 @@ struct ProxyEpFactory {
     allow_proxy_fwd: bool,
     client_of_remote_proxy: ProxyRemoteType,
     proxy_from_bootstrap_cb: ProxyFromBootstrapCb,
+    access: ProxyAccessConfig,
+    client_spaces: Option<ProxyClientSpaces>,
+    direct_addrs: Option<ProxyDirectAddrs>,
     sub_fact: EpFactory,
 }
 
 impl ProxyEpFactory {
     pub fn new(sub_fact: EpFactory, config: ProxyConfig) -> KitsuneResult<EpFactory> {
-        let (tuning_params, allow_proxy_fwd, client_of_remote_proxy, proxy_from_bootstrap_cb) =
-            config.split()?;
+        let (
+            tuning_params,
+            allow_proxy_fwd,
+            client_of_remote_proxy,
+            proxy_from_bootstrap_cb,
+            access,
+            client_spaces,
+            direct_addrs,
+        ) = config.split()?;
         let fact: EpFactory = Arc::new(ProxyEpFactory {
             tuning_params,
             allow_proxy_fwd,
             client_of_remote_proxy,
             proxy_from_bootstrap_cb,
+            access,
+            client_spaces,
+            direct_addrs,
             sub_fact,
         });
         Ok(fact)
@@ -990,6 +1510,9 @@ impl AsEpFactory for ProxyEpFactory {
         let allow_proxy_fwd = self.allow_proxy_fwd;
         let client_of_remote_proxy = self.client_of_remote_proxy.clone();
         let proxy_from_bootstrap_cb = self.proxy_from_bootstrap_cb.clone();
+        let access = self.access.clone();
+        let client_spaces = self.client_spaces.clone();
+        let direct_addrs = self.direct_addrs.clone();
         async move {
             let sub_ep = fut.await?;
             ProxyEp::new(
@@ -998,6 +1521,9 @@ impl AsEpFactory for ProxyEpFactory {
                 allow_proxy_fwd,
                 client_of_remote_proxy,
                 proxy_from_bootstrap_cb,
+                access,
+                client_spaces,
+                direct_addrs,
             )
             .await
         }
@@ -1011,15 +1537,22 @@ mod tests {
     use kitsune_p2p_types::tx2::tx2_pool_promote::*;
 
     async fn build_node(
+        s_done: Option<tokio::sync::oneshot::Sender<()>>,
+        expect_err: bool,
+    ) -> (tokio::task::JoinHandle<KitsuneResult<()>>, TxUrl, EpHnd) {
+        build_node_with_conf(s_done, expect_err, Default::default()).await
+    }
+
+    async fn build_node_with_conf(
         mut s_done: Option<tokio::sync::oneshot::Sender<()>>,
         expect_err: bool,
+        mut conf: ProxyConfig,
     ) -> (tokio::task::JoinHandle<KitsuneResult<()>>, TxUrl, EpHnd) {
         let t = KitsuneTimeout::from_millis(5000);
 
         let f = tx2_mem_adapter(MemConfig::default()).await.unwrap();
         let f = tx2_pool_promote(f, Default::default());
 
-        let mut conf = super::ProxyConfig::default();
         conf.allow_proxy_fwd = true;
         let f = tx2_proxy(f, conf).unwrap();
 
@@ -1111,6 +1644,187 @@ mod tests {
         futures::future::try_join_all(all_tasks).await.unwrap();
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tx2_proxy_access_denied() {
+        observability::test_run().ok();
+        let t = KitsuneTimeout::from_millis(5000);
+        let mut all_tasks = Vec::new();
+
+        let allowed: Tx2Cert = vec![0xdb; 32].into();
+        let mut conf = ProxyConfig::default();
+        conf.access = ProxyAccessConfig {
+            allowed_certs: Some(std::iter::once(allowed).collect()),
+            ..Default::default()
+        };
+        let (p_join, p_addr, p_ep) = build_node_with_conf(None, false, conf).await;
+        all_tasks.push(p_join);
+
+        let (t_join, t_addr, t_ep) = build_node(None, false).await;
+        all_tasks.push(t_join);
+
+        // establish proxy connection
+        let _ = t_ep.get_connection(p_addr.clone(), t).await.unwrap();
+        let t_addr_proxy = proxify_addr(&p_addr, &t_addr);
+
+        // the sender is not in the allow list
+        let (s_done, r_done) = tokio::sync::oneshot::channel();
+        let (n_join, _n_addr, n_ep) = build_node(Some(s_done), true).await;
+
+        let mut data = PoolBuf::new();
+        data.extend_from_slice(b"hello");
+        n_ep.write(t_addr_proxy, 0.into(), data, t).await.unwrap();
+        r_done.await.unwrap();
+
+        let debug = p_ep.debug();
+        let n_stats = &debug["clients"][n_ep.local_cert().as_str()];
+        assert_eq!(n_stats["frames_relayed"], 0);
+        assert_eq!(n_stats["frames_rejected"], 1);
+
+        n_ep.close(0, "").await;
+        n_join.await.unwrap().unwrap();
+
+        t_ep.close(0, "").await;
+        p_ep.close(0, "").await;
+
+        futures::future::try_join_all(all_tasks).await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tx2_proxy_direct_addr() {
+        observability::test_run().ok();
+        let t = KitsuneTimeout::from_millis(5000);
+        let mut all_tasks = Vec::new();
+
+        let (p_join, p_addr, p_ep) = build_node(None, false).await;
+        all_tasks.push(p_join);
+
+        // the target never connects to the proxy,
+        // so it can only be reached at its direct address
+        let (t_join, t_addr, t_ep) = build_node(None, false).await;
+        all_tasks.push(t_join);
+        let t_addr_proxy = proxify_addr(&p_addr, &t_addr);
+
+        let direct_addrs = ProxyDirectAddrs::default();
+        direct_addrs.insert(
+            t_ep.local_cert(),
+            ProxyUrl::from(t_addr.as_str()).as_base_str().into(),
+        );
+        let mut conf = ProxyConfig::default();
+        conf.direct_addrs = Some(direct_addrs);
+        let (s_done, r_done) = tokio::sync::oneshot::channel();
+        let (n_join, _n_addr, n_ep) = build_node_with_conf(Some(s_done), false, conf).await;
+
+        let mut data = PoolBuf::new();
+        data.extend_from_slice(b"hello");
+        n_ep.write(t_addr_proxy, 0.into(), data, t).await.unwrap();
+        r_done.await.unwrap();
+
+        n_ep.close(0, "").await;
+        n_join.await.unwrap().unwrap();
+
+        t_ep.close(0, "").await;
+        p_ep.close(0, "").await;
+
+        futures::future::try_join_all(all_tasks).await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tx2_proxy_dead_direct_addr() {
+        observability::test_run().ok();
+        let t = KitsuneTimeout::from_millis(5000);
+        let mut all_tasks = Vec::new();
+
+        let (p_join, p_addr, p_ep) = build_node(None, false).await;
+        all_tasks.push(p_join);
+
+        let (t_join, t_addr, t_ep) = build_node(None, false).await;
+        all_tasks.push(t_join);
+
+        // establish proxy connection
+        let _ = t_ep.get_connection(p_addr.clone(), t).await.unwrap();
+        let t_addr_proxy = proxify_addr(&p_addr, &t_addr);
+
+        // the direct address points at an endpoint that is gone
+        let (d_join, d_addr, d_ep) = build_node(None, false).await;
+        d_ep.close(0, "").await;
+        d_join.await.unwrap().unwrap();
+
+        let direct_addrs = ProxyDirectAddrs::default();
+        direct_addrs.insert(
+            t_ep.local_cert(),
+            ProxyUrl::from(d_addr.as_str()).as_base_str().into(),
+        );
+        let mut conf = ProxyConfig::default();
+        conf.direct_addrs = Some(direct_addrs);
+        let (s_done, r_done) = tokio::sync::oneshot::channel();
+        let (n_join, _n_addr, n_ep) = build_node_with_conf(Some(s_done), false, conf).await;
+
+        let mut data = PoolBuf::new();
+        data.extend_from_slice(b"hello");
+        n_ep.write(t_addr_proxy, 0.into(), data, t).await.unwrap();
+        r_done.await.unwrap();
+
+        // the message went through the proxy
+        let debug = p_ep.debug();
+        let n_stats = &debug["clients"][n_ep.local_cert().as_str()];
+        assert!(n_stats["frames_relayed"].as_u64().unwrap() > 0);
+
+        n_ep.close(0, "").await;
+        n_join.await.unwrap().unwrap();
+
+        t_ep.close(0, "").await;
+        p_ep.close(0, "").await;
+
+        futures::future::try_join_all(all_tasks).await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tx2_proxy_idle_eviction() {
+        observability::test_run().ok();
+        let t = KitsuneTimeout::from_millis(5000);
+        let mut all_tasks = Vec::new();
+
+        // only the first proxy evicts idle clients
+        let mut conf = ProxyConfig::default();
+        conf.access.evict_idle_clients_after = Some(Duration::from_millis(200));
+        let (e_join, e_addr, e_ep) = build_node_with_conf(None, false, conf).await;
+        all_tasks.push(e_join);
+        let (k_join, k_addr, k_ep) = build_node(None, false).await;
+        all_tasks.push(k_join);
+
+        let (c_join, _c_addr, c_ep) = build_node(None, false).await;
+        all_tasks.push(c_join);
+        let c_cert = c_ep.local_cert();
+        let _ = c_ep.get_connection(e_addr, t).await.unwrap();
+        let _ = c_ep.get_connection(k_addr, t).await.unwrap();
+        for p_ep in [&e_ep, &k_ep] {
+            assert!(p_ep.debug()["clients"].get(c_cert.as_str()).is_some());
+        }
+
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+        assert!(e_ep.debug()["clients"].get(c_cert.as_str()).is_none());
+        assert!(k_ep.debug()["clients"].get(c_cert.as_str()).is_some());
+
+        c_ep.close(0, "").await;
+        e_ep.close(0, "").await;
+        k_ep.close(0, "").await;
+
+        futures::future::try_join_all(all_tasks).await.unwrap();
+    }
+
+    #[test]
+    fn test_proxy_client_bandwidth() {
+        let now = Instant::now();
+        let mut client = ProxyClient::new(now);
+        // the first frame in a window is let through regardless of size
+        assert!(client.has_bandwidth(Some(10), 100, now));
+        client.window_bytes += 100;
+        assert!(!client.has_bandwidth(Some(10), 1, now));
+        // a new window starts after a second
+        assert!(client.has_bandwidth(Some(10), 5, now + Duration::from_secs(1)));
+        assert!(client.has_bandwidth(None, 1_000_000, now + Duration::from_secs(1)));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_tx2_proxy() {
         observability::test_run().ok();
@@ -23,12 +23,23 @@ pub struct QuicConfig {
     /// Tuning Params
     /// Default: None = default.
     pub tuning_params: Option<KitsuneP2pTuningParams>,
+
+    /// Advertise this host instead of the bound one,
+    /// e.g. when port-forwarding or relaying.
+    /// Default: None = use the bound host.
+    pub override_host: Option<String>,
+
+    /// Advertise this port instead of the bound one.
+    /// Default: None = use the bound port.
+    pub override_port: Option<u16>,
 }
 
 impl QuicConfig {
     /// into inner contents with default application
     pub async fn split(self) -> KitsuneResult<(TlsConfig, KitsuneP2pTuningParams)> {
-        let QuicConfig { tls, tuning_params } = self;
+        let QuicConfig {
+            tls, tuning_params, ..
+        } = self;
 
         let tls = match tls {
             None => TlsConfig::new_ephemeral().await?,
@@ -264,16 +275,25 @@ impl ConRecvAdapt for QuicConRecvAdapt {}
 struct QuicEndpointAdaptInner {
     ep: quinn::Endpoint,
     local_cert: Tx2Cert,
+    override_host: Option<String>,
+    override_port: Option<u16>,
 }
 
 struct QuicEndpointAdapt(Share<QuicEndpointAdaptInner>, Uniq, Tx2Cert);
 
 impl QuicEndpointAdapt {
-    pub fn new(ep: quinn::Endpoint, local_cert: Tx2Cert) -> Self {
+    pub fn new(
+        ep: quinn::Endpoint,
+        local_cert: Tx2Cert,
+        override_host: Option<String>,
+        override_port: Option<u16>,
+    ) -> Self {
         Self(
             Share::new(QuicEndpointAdaptInner {
                 ep,
                 local_cert: local_cert.clone(),
+                override_host,
+                override_port,
             }),
             Uniq::default(),
             local_cert,
@@ -301,9 +321,10 @@ impl EndpointAdapt for QuicEndpointAdapt {
     }
 
     fn local_addr(&self) -> KitsuneResult<TxUrl> {
-        let addr = self
-            .0
-            .share_mut(|i, _| i.ep.local_addr().map_err(KitsuneError::other))?;
+        let (addr, override_host, override_port) = self.0.share_mut(|i, _| {
+            let addr = i.ep.local_addr().map_err(KitsuneError::other)?;
+            Ok((addr, i.override_host.clone(), i.override_port))
+        })?;
 
         use kitsune_p2p_types::dependencies::url2;
         let mut url = url2::url2!("{}://{}", crate::SCHEME, addr);
@@ -326,6 +347,14 @@ impl EndpointAdapt for QuicEndpointAdapt {
             }
         }
 
+        if let Some(host) = override_host {
+            url.set_host(Some(&host)).map_err(KitsuneError::other)?;
+        }
+        if let Some(port) = override_port {
+            url.set_port(Some(port))
+                .map_err(|_| KitsuneError::from("cannot override quic port"))?;
+        }
+
         Ok(url.into())
     }
 
@@ -378,11 +407,15 @@ pub struct QuicBackendAdapt {
     local_cert: Tx2Cert,
     quic_srv: quinn::ServerConfig,
     quic_cli: quinn::ClientConfig,
+    override_host: Option<String>,
+    override_port: Option<u16>,
 }
 
 impl QuicBackendAdapt {
     /// Construct a new quic tx2 backend bind adapter
     pub async fn new(config: QuicConfig) -> KitsuneResult<AdapterFactory> {
+        let override_host = config.override_host.clone();
+        let override_port = config.override_port;
         let (tls, tuning_params) = config.split().await?;
 
         let local_cert = tls.cert_digest.clone().into();
@@ -424,6 +457,8 @@ impl QuicBackendAdapt {
             local_cert,
             quic_srv,
             quic_cli,
+            override_host,
+            override_port,
         });
 
         Ok(out)
@@ -435,6 +470,8 @@ impl BindAdapt for QuicBackendAdapt {
         let local_cert = self.local_cert.clone();
         let quic_srv = self.quic_srv.clone();
         let quic_cli = self.quic_cli.clone();
+        let override_host = self.override_host.clone();
+        let override_port = self.override_port;
         timeout
             .mix(async move {
                 let addr = crate::url_to_addr(url.as_url2(), crate::SCHEME)
@@ -446,8 +483,12 @@ impl BindAdapt for QuicBackendAdapt {
 
                 ep.set_default_client_config(quic_cli);
 
-                let ep: Arc<dyn EndpointAdapt> =
-                    Arc::new(QuicEndpointAdapt::new(ep, local_cert.clone()));
+                let ep: Arc<dyn EndpointAdapt> = Arc::new(QuicEndpointAdapt::new(
+                    ep,
+                    local_cert.clone(),
+                    override_host,
+                    override_port,
+                ));
                 let con_recv: Box<dyn ConRecvAdapt> =
                     Box::new(QuicConRecvAdapt::new(inc, local_cert.clone(), ep.clone()));
 
@@ -525,4 +566,24 @@ mod tests {
 
         rt.await.unwrap().unwrap();
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_quic_tx2_override_addr() {
+        let t = KitsuneTimeout::from_millis(5000);
+
+        let mut config = QuicConfig::default();
+        config.override_host = Some("example.com".to_string());
+        config.override_port = Some(4242);
+        let factory = QuicBackendAdapt::new(config).await.unwrap();
+        let (ep, _con_recv) = factory
+            .bind("kitsune-quic://127.0.0.1:0".into(), t)
+            .await
+            .unwrap();
+
+        let addr = ep.local_addr().unwrap();
+        assert_eq!(Some("example.com"), addr.host_str());
+        assert_eq!(Some(4242), addr.port());
+
+        ep.close(0, "").await;
+    }
 }
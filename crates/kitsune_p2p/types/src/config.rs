@@ -101,6 +101,41 @@ pub mod tuning_params_struct {
         /// to this count mega bits per second. [Default: 0.1]
         gossip_historic_inbound_target_mbps: f64 = 0.1,
 
+        /// Each space will attempt to rate-limit all of its inbound
+        /// traffic to this count mega bits per second.
+        /// Zero means no limit. [Default: 0.0]
+        bandwidth_space_inbound_target_mbps: f64 = 0.0,
+
+        /// Each space will attempt to rate-limit all of its outbound
+        /// traffic to this count mega bits per second.
+        /// Zero means no limit. [Default: 0.0]
+        bandwidth_space_outbound_target_mbps: f64 = 0.0,
+
+        /// Traffic received from each peer will be rate-limited
+        /// to this count mega bits per second.
+        /// Zero means no limit. [Default: 0.0]
+        bandwidth_peer_inbound_target_mbps: f64 = 0.0,
+
+        /// Traffic sent to each peer will be rate-limited
+        /// to this count mega bits per second.
+        /// Zero means no limit. [Default: 0.0]
+        bandwidth_peer_outbound_target_mbps: f64 = 0.0,
+
+        /// All inbound traffic of this node, gossip and rpc alike,
+        /// will be rate-limited to this count mega bits per second.
+        /// Zero means no limit. [Default: 0.0]
+        bandwidth_total_inbound_target_mbps: f64 = 0.0,
+
+        /// All outbound traffic of this node, gossip and rpc alike,
+        /// will be rate-limited to this count mega bits per second.
+        /// Zero means no limit. [Default: 0.0]
+        bandwidth_total_outbound_target_mbps: f64 = 0.0,
+
+        /// Hard cap on the bytes this node sends and receives each
+        /// day (UTC), useful on metered connections. Traffic over the
+        /// cap is dropped. Zero means no cap. [Default: 0]
+        bandwidth_daily_byte_cap: u64 = 0,
+
         /// How long should we hold off talking to a peer
         /// we've previously spoken successfully to.
         /// [Default: 1 minute]
@@ -155,6 +190,14 @@ pub mod tuning_params_struct {
         /// [Default: 5 minutes]
         proxy_to_expire_ms: u32 = 1000 * 60 * 5,
 
+        /// Should we tell the proxies we connect to which spaces we
+        /// are in, so proxies that only relay for some spaces will
+        /// relay for us? Proxies that don't support this close the
+        /// connection when receiving the declaration, so only enable
+        /// it if all proxies in your network do.
+        /// [Default: false]
+        proxy_declare_spaces: bool = false,
+
         /// Mainly used as the for_each_concurrent limit,
         /// this restricts the number of active polled futures
         /// on a single thread.
@@ -195,6 +238,14 @@ pub mod tuning_params_struct {
 
         /// Don't publish ops, only rely on gossip. Useful for testing the efficacy of gossip.
         disable_publish: bool = false,
+
+        /// The local interface mdns joins the multicast group on.
+        /// [Default: "0.0.0.0", the interface chosen by the OS]
+        mdns_interface: String = "0.0.0.0".to_string(),
+
+        /// The port mdns sends and listens on.
+        /// [Default: 5353]
+        mdns_port: u16 = 5353,
     }
 
     impl KitsuneP2pTuningParams {
@@ -431,6 +431,12 @@ impl<C: Codec + 'static + Send + Unpin> Tx2EpHnd<C> {
         self.0.local_addr()
     }
 
+    /// Get all the addresses this endpoint can be reached at,
+    /// starting with [`Tx2EpHnd::local_addr`].
+    pub fn local_addrs(&self) -> KitsuneResult<Vec<TxUrl>> {
+        self.0.local_addrs()
+    }
+
     /// Get the local certificate digest.
     pub fn local_cert(&self) -> Tx2Cert {
         self.0.local_cert()
@@ -50,6 +50,12 @@ pub trait AsEpHnd: 'static + Send + Sync + Unpin {
     /// Get the bound local address of this endpoint.
     fn local_addr(&self) -> KitsuneResult<TxUrl>;
 
+    /// Get all the addresses this endpoint can be reached at,
+    /// starting with [`AsEpHnd::local_addr`].
+    fn local_addrs(&self) -> KitsuneResult<Vec<TxUrl>> {
+        Ok(vec![self.local_addr()?])
+    }
+
     /// Get the local certificate digest.
     fn local_cert(&self) -> Tx2Cert;
 
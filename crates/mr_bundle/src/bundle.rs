@@ -8,11 +8,13 @@ use holochain_util::ffs;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
     borrow::Cow,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     path::{Path, PathBuf},
 };
 
-pub type ResourceMap = HashMap<PathBuf, ResourceBytes>;
+/// The resources included in a bundle, by their bundled path.
+/// Kept sorted by path so that encoding a bundle always gives the same bytes.
+pub type ResourceMap = BTreeMap<PathBuf, ResourceBytes>;
 
 /// A Manifest bundled together, optionally, with the Resources that it describes.
 /// This is meant to be serialized for standalone distribution, and deserialized
@@ -174,7 +176,20 @@ where
         &self.resources
     }
 
-    /// An arbitrary and opaque encoding of the bundle data into a byte array
+    /// The Locations specified by the manifest which are not bundled, i.e.
+    /// whose resources will be fetched from the filesystem or the network
+    /// when resolved.
+    pub fn unbundled_locations(&self) -> Vec<Location> {
+        self.manifest
+            .locations()
+            .into_iter()
+            .filter(|loc| !matches!(loc, Location::Bundled(_)))
+            .collect()
+    }
+
+    /// An arbitrary and opaque encoding of the bundle data into a byte array.
+    /// Bundles with the same manifest and resources always encode to the
+    /// same bytes.
     pub fn encode(&self) -> MrBundleResult<Vec<u8>> {
         crate::encode(self)
     }
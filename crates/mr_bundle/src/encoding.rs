@@ -2,10 +2,25 @@ use super::error::MrBundleResult;
 use std::io::Read;
 use std::io::Write;
 
-/// Get compressed bytes from some serializable data
+/// The gzip compression level used for all encoded data.
+const COMPRESSION_LEVEL: u32 = 6;
+
+/// The "unknown" operating system, as defined by the gzip header format.
+const GZIP_OS_UNKNOWN: u8 = 255;
+
+/// Get compressed bytes from some serializable data.
+///
+/// The gzip header carries no timestamp, file name or host operating system.
+/// These are flate2's defaults, they are set explicitly so the output can't
+/// change with the flate2 version. The bytes being reproducible depends on
+/// the data being serialized in a stable order, e.g. the `ResourceMap` of a
+/// bundle being sorted by path.
 pub fn encode<T: serde::ser::Serialize>(data: &T) -> MrBundleResult<Vec<u8>> {
     let bytes = rmp_serde::to_vec_named(data)?;
-    let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut enc = flate2::GzBuilder::new()
+        .mtime(0)
+        .operating_system(GZIP_OS_UNKNOWN)
+        .write(Vec::new(), flate2::Compression::new(COMPRESSION_LEVEL));
     enc.write_all(&bytes)?;
     Ok(enc.finish()?)
 }
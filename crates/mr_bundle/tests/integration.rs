@@ -180,3 +180,42 @@ async fn unpack_roundtrip() {
 
     assert_eq!(bundle, reconstructed);
 }
+
+#[test]
+fn encoding_is_deterministic() {
+    let paths: Vec<PathBuf> = (0..20)
+        .map(|i| PathBuf::from(format!("things/{}.thing", i)))
+        .collect();
+    let local_location = Location::Path(PathBuf::from("/somewhere/local.thing"));
+    let mut things: Vec<_> = paths
+        .iter()
+        .map(|path| ThingManifest {
+            location: Location::Bundled(path.clone()),
+        })
+        .collect();
+    things.push(ThingManifest {
+        location: local_location.clone(),
+    });
+    let manifest = TestManifest::V1(ManifestV1 {
+        name: "name".to_string(),
+        things,
+    });
+    let resources: Vec<_> = paths
+        .iter()
+        .map(|path| {
+            let thing = Thing(path.to_string_lossy().to_string());
+            (path.clone(), mr_bundle::encode(&thing).unwrap())
+        })
+        .collect();
+
+    // The order in which resources are added to a bundle does not matter
+    let bundle = Bundle::new_unchecked(manifest.clone(), resources.clone()).unwrap();
+    let reversed = Bundle::new_unchecked(manifest, resources.into_iter().rev()).unwrap();
+    assert_eq!(bundle.encode().unwrap(), reversed.encode().unwrap());
+
+    // Encoding the same bundle again gives the same bytes
+    let decoded: Bundle<TestManifest> = Bundle::decode(&bundle.encode().unwrap()).unwrap();
+    assert_eq!(bundle.encode().unwrap(), decoded.encode().unwrap());
+
+    assert_eq!(bundle.unbundled_locations(), vec![local_location]);
+}
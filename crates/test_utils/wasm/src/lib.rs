@@ -256,10 +256,14 @@ impl From<TestWasm> for ZomeDef {
     fn from(test_wasm: TestWasm) -> Self {
         tokio_helper::block_forever_on(async move {
             let dna_wasm: DnaWasm = test_wasm.into();
-            let (_, wasm_hash) = holochain_types::dna::wasm::DnaWasmHashed::from_content(dna_wasm)
-                .await
-                .into_inner();
-            ZomeDef::Wasm(WasmZome { wasm_hash })
+            let (dna_wasm, wasm_hash) =
+                holochain_types::dna::wasm::DnaWasmHashed::from_content(dna_wasm)
+                    .await
+                    .into_inner();
+            ZomeDef::Wasm(WasmZome {
+                wasm_hash,
+                read_only_fns: dna_wasm.read_only_fns(),
+            })
         })
     }
 }
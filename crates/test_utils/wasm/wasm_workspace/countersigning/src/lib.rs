@@ -15,7 +15,11 @@ impl From<Thing> for ValidateCallbackResult {
     }
 }
 
-entry_defs![Thing::entry_def()];
+/// Committed after a thing in a multi entry session.
+#[hdk_entry(id = "note")]
+struct Note(String);
+
+entry_defs![Thing::entry_def(), Note::entry_def()];
 
 #[hdk_extern]
 fn validate(op: Op) -> ExternResult<ValidateCallbackResult> {
@@ -45,10 +49,11 @@ fn create_an_invalid_thing(_: ()) -> ExternResult<HeaderHash> {
     create_entry(&Thing::Invalid)
 }
 
-fn create_countersigned(
-    responses: Vec<PreflightResponse>,
-    thing: Thing,
-) -> ExternResult<HeaderHash> {
+fn create_countersigned<T>(responses: Vec<PreflightResponse>, thing: T) -> ExternResult<HeaderHash>
+where
+    EntryDefId: for<'a> From<&'a T>,
+    AppEntryBytes: TryFrom<T, Error = WasmError>,
+{
     HDK.with(|h| {
         h.borrow().create(CreateInput::new(
             (&thing).into(),
@@ -78,14 +83,25 @@ fn create_a_countersigned_thing(responses: Vec<PreflightResponse>) -> ExternResu
     create_countersigned(responses, Thing::Valid)
 }
 
+#[hdk_extern]
+fn create_a_countersigned_thing_and_note(
+    responses: Vec<PreflightResponse>,
+) -> ExternResult<Vec<HeaderHash>> {
+    Ok(vec![
+        create_countersigned(responses.clone(), Thing::Valid)?,
+        create_countersigned(responses, Note("countersigned".into()))?,
+    ])
+}
+
 fn generate_preflight_request(
     agents: Vec<(AgentPubKey, Vec<Role>)>,
+    enzyme_index: Option<u8>,
     thing: Thing,
 ) -> ExternResult<PreflightRequest> {
     PreflightRequest::try_new(
         hash_entry(thing)?,
         agents,
-        None,
+        enzyme_index,
         session_times_from_millis(5000)?,
         HeaderBase::Create(CreateBase::new(entry_type!(Thing)?)),
         PreflightBytes(vec![]),
@@ -97,14 +113,35 @@ fn generate_preflight_request(
 fn generate_countersigning_preflight_request(
     agents: Vec<(AgentPubKey, Vec<Role>)>,
 ) -> ExternResult<PreflightRequest> {
-    generate_preflight_request(agents, Thing::Valid)
+    generate_preflight_request(agents, None, Thing::Valid)
+}
+
+/// The first agent is the enzyme for the session.
+#[hdk_extern]
+fn generate_enzymatic_countersigning_preflight_request(
+    agents: Vec<(AgentPubKey, Vec<Role>)>,
+) -> ExternResult<PreflightRequest> {
+    generate_preflight_request(agents, Some(0), Thing::Valid)
+}
+
+/// Every agent commits a thing and then a note.
+#[hdk_extern]
+fn generate_multi_entry_countersigning_preflight_request(
+    agents: Vec<(AgentPubKey, Vec<Role>)>,
+) -> ExternResult<PreflightRequest> {
+    generate_preflight_request(agents, None, Thing::Valid)?
+        .try_with_additional_entries(vec![PreflightEntry::new(
+            hash_entry(Note("countersigned".into()))?,
+            HeaderBase::Create(CreateBase::new(entry_type!(Note)?)),
+        )])
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))
 }
 
 #[hdk_extern]
 fn generate_invalid_countersigning_preflight_request(
     agents: Vec<(AgentPubKey, Vec<Role>)>,
 ) -> ExternResult<PreflightRequest> {
-    generate_preflight_request(agents, Thing::Invalid)
+    generate_preflight_request(agents, None, Thing::Invalid)
 }
 
 #[hdk_extern]
@@ -84,6 +84,32 @@ fn get_post(hash: HeaderHash) -> ExternResult<Option<Element>> {
     get(hash, GetOptions::content())
 }
 
+#[hdk_extern(read_only)]
+fn read_only_get_entry(_: ()) -> ExternResult<Option<Element>> {
+    get(hash_entry(&post())?, GetOptions::content())
+}
+
+#[hdk_extern(read_only)]
+fn read_only_create_entry(_: ()) -> ExternResult<HeaderHash> {
+    hdk::prelude::create_entry(&post())
+}
+
+#[hdk_extern(read_only)]
+fn read_only_call_create_entry(_: ()) -> ExternResult<ZomeCallResponse> {
+    call(
+        CallTargetCell::Local,
+        "create_entry".to_string().into(),
+        "create_entry".to_string().into(),
+        None,
+        &(),
+    )
+}
+
+#[hdk_extern(read_only)]
+fn read_only_emit_signal(_: ()) -> ExternResult<()> {
+    emit_signal(&())
+}
+
 #[hdk_extern]
 fn create_msg(_: ()) -> ExternResult<HeaderHash> {
     hdk::prelude::create_entry(&msg())
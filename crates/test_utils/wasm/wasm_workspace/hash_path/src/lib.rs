@@ -20,6 +20,11 @@ fn ensure(path_string: String) -> ExternResult<()> {
     Path::from(path_string).ensure()
 }
 
+#[hdk_extern]
+fn ensure_relinked(path_string: String) -> ExternResult<()> {
+    Path::from(path_string).ensure_relinked()
+}
+
 #[hdk_extern]
 fn delete_link(delete_link: HeaderHash) -> ExternResult<HeaderHash> {
     hdk::prelude::delete_link(delete_link)
@@ -34,3 +39,20 @@ fn children(path_string: String) -> ExternResult<Vec<Link>> {
 fn children_details(path_string: String) -> ExternResult<LinkDetails> {
     Path::from(path_string).children_details()
 }
+
+#[hdk_extern]
+fn leaves(path_string: String) -> ExternResult<Vec<Path>> {
+    Path::from(path_string).leaves().collect()
+}
+
+#[hdk_extern]
+fn leaves_page(input: (PathLeaves, u32)) -> ExternResult<(Vec<Path>, PathLeaves)> {
+    let (mut leaves, limit) = input;
+    let page = leaves.next_page(limit as usize)?;
+    Ok((page, leaves))
+}
+
+#[hdk_extern]
+fn prune(path_string: String) -> ExternResult<Vec<Path>> {
+    Path::from(path_string).prune()
+}
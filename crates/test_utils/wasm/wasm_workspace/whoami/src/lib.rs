@@ -77,3 +77,20 @@ fn call_create_entry(cell_id: CellId) -> ExternResult<HeaderHash> {
         _ => unreachable!(),
     }
 }
+
+/// Call the create entry zome in the cell with this role in our app.
+#[hdk_extern]
+fn call_create_entry_in_role(role_id: String) -> ExternResult<HeaderHash> {
+    let zome_call_response: ZomeCallResponse = call(
+        CallTargetCell::OtherRole(role_id),
+        "create_entry".to_string().into(),
+        "create_entry".to_string().into(),
+        None,
+        &(),
+    )?;
+    match zome_call_response {
+        ZomeCallResponse::Ok(v) => Ok(v.decode().map_err(|e| wasm_error!(e.into()))?),
+        // This should be handled in real code.
+        _ => unreachable!(),
+    }
+}